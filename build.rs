@@ -1,4 +1,9 @@
 fn main() {
+    // the icon and the manifest are Windows resources, the headless renderer builds anywhere
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() != Ok("windows") {
+        return;
+    }
+
     let mut res = winres::WindowsResource::new();
 
     res.set_icon("icon.ico");
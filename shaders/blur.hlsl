@@ -0,0 +1,66 @@
+#include "common.hlsl"
+
+cbuffer Params : register(b0) {
+    uint4 Rect;
+    uint Radius;
+    uint Gaussian;
+};
+
+Texture2D<float4> Src : register(t1);
+RWTexture2D<float4> Dst : register(u0);
+
+float Weight(int offset)
+{
+    if (Gaussian == 0) {
+        return 1.f;
+    }
+
+    // the kernel ends at three sigma
+    float sigma = max(float(Radius) / 3.f, 0.5f);
+    return exp(-float(offset * offset) / (2.f * sigma * sigma));
+}
+
+// samples outside the view repeat its edge
+uint2 Clamp(int2 position)
+{
+    return uint2(clamp(position, int2(Rect.xy), int2(Rect.zw) - 1));
+}
+
+#define THREADS 8
+[numthreads(THREADS, THREADS, 1)]
+void BlurHorizontalCs(uint2 id: SV_DispatchThreadID)
+{
+    int2 position = int2(Rect.xy + id);
+
+    if (all(uint2(position) < Rect.zw)) {
+        float3 sum = 0.f;
+        float weight = 0.f;
+
+        for (int i = -int(Radius); i <= int(Radius); i++) {
+            float w = Weight(i);
+            sum += w * Tex[Clamp(position + int2(i, 0))].rgb;
+            weight += w;
+        }
+
+        Dst[position] = float4(sum / weight, 1.f);
+    }
+}
+
+[numthreads(THREADS, THREADS, 1)]
+void BlurVerticalCs(uint2 id: SV_DispatchThreadID)
+{
+    int2 position = int2(Rect.xy + id);
+
+    if (all(uint2(position) < Rect.zw)) {
+        float3 sum = 0.f;
+        float weight = 0.f;
+
+        for (int i = -int(Radius); i <= int(Radius); i++) {
+            float w = Weight(i);
+            sum += w * Src[Clamp(position + int2(0, i))].rgb;
+            weight += w;
+        }
+
+        Dst[position] = float4(sum / weight, 1.f);
+    }
+}
@@ -0,0 +1,143 @@
+#include "common.hlsl"
+
+// bins per axis of the density plot, x fastest
+static const uint ChromaticityBins = 128;
+
+// CIE 1931 xy, or CIE 1976 u'v' for Diagram 1. black has no chromaticity
+float2 ToChromaticity(uint diagram, float3 rgb)
+{
+    static const float3x3 RgbToXyz = {
+        0.4124f, 0.3576f, 0.1805f,
+        0.2126f, 0.7152f, 0.0722f,
+        0.0193f, 0.1192f, 0.9505f
+    };
+
+    float3 rgb_linear = float3(SrgbToLinear(rgb.r), SrgbToLinear(rgb.g), SrgbToLinear(rgb.b));
+    float3 xyz = mul(RgbToXyz, rgb_linear);
+    float2 xy = xyz.xy / (xyz.x + xyz.y + xyz.z);
+
+    if (diagram == 1) {
+        return float2(4.f * xy.x, 9.f * xy.y) / (-2.f * xy.x + 12.f * xy.y + 3.f);
+    }
+    return xy;
+}
+
+#ifdef CREATE
+
+cbuffer Params : register(b0) {
+    uint4 Rect;
+    uint Diagram;
+    float Range;
+};
+
+RWBuffer<uint> DensityBuf : register(u0);
+
+#define THREADS 8
+[numthreads(THREADS, THREADS, 1)]
+void CreateCs(uint2 id: SV_DispatchThreadID)
+{
+    uint2 position = Rect.xy + id;
+
+    if (all(position < Rect.zw)) {
+        float3 color = Tex[position].rgb;
+        float2 t = ToChromaticity(Diagram, color) / Range;
+
+        if (any(color > 0.f) && all(t >= 0.f && t < 1.f)) {
+            uint2 bin = min(uint2(ChromaticityBins * t), ChromaticityBins - 1);
+            InterlockedAdd(DensityBuf[bin.y * ChromaticityBins + bin.x], 1);
+        }
+    }
+}
+
+#endif // CREATE
+
+#ifdef DRAW
+
+cbuffer Params : register(b0) {
+    float2 Scale;
+    float InvPixelCount;
+    uint Diagram;
+    float Range;
+};
+
+Buffer<uint> DensityBuf : register(t1);
+
+struct VertexOut {
+    float4 position : SV_Position;
+    float4 color : COLOR;
+};
+
+// the square of 0 ~ Range on both axes, with room for the ticks
+float4 PlotPosition(float2 position)
+{
+    return float4(0.9f * Scale * (2.f * position / Range - 1.f), 0.f, 1.f);
+}
+
+// the brightest sRGB color of a chromaticity, its hue where it is out of gamut
+float3 ChromaticityToRgb(float2 position)
+{
+    static const float3x3 XyzToRgb = {
+        3.2406f, -1.5372f, -0.4986f,
+        -0.9689f, 1.8758f, 0.0415f,
+        0.0557f, -0.2040f, 1.0570f
+    };
+
+    float2 xy = position;
+    if (Diagram == 1) {
+        xy = float2(9.f * position.x, 4.f * position.y) / (6.f * position.x - 16.f * position.y + 12.f);
+    }
+
+    float y = max(xy.y, 1e-4f);
+    float3 rgb_linear = max(mul(XyzToRgb, float3(xy.x / y, 1.f, (1.f - xy.x - xy.y) / y)), 0.f);
+    rgb_linear /= max(Max3(rgb_linear.r, rgb_linear.g, rgb_linear.b), 1e-4f);
+
+    return float3(LinearToSrgb(rgb_linear.r), LinearToSrgb(rgb_linear.g), LinearToSrgb(rgb_linear.b));
+}
+
+float DensityAlpha(uint count)
+{
+    float density = InvPixelCount * count * ChromaticityBins * ChromaticityBins;
+    return count != 0 ? 0.3f + 0.7f * saturate(log2(1.f + density) / 8.f) : 0.f;
+}
+
+static const uint2 Corners[6] = {
+    uint2(0, 0),
+    uint2(1, 0),
+    uint2(0, 1),
+    uint2(1, 0),
+    uint2(1, 1),
+    uint2(0, 1),
+};
+
+// a square per bin in the color of its chromaticity
+VertexOut DensityVs(uint vid: SV_VertexID)
+{
+    uint index = vid / 6;
+    uint2 bin = uint2(index % ChromaticityBins, index / ChromaticityBins);
+    uint2 corner = bin + Corners[vid % 6];
+    uint count = DensityBuf[index];
+
+    float2 center = Range * (bin + 0.5f) / ChromaticityBins;
+
+    VertexOut output;
+    output.position = PlotPosition(Range * float2(corner) / ChromaticityBins);
+    output.color = float4(ChromaticityToRgb(center), DensityAlpha(count));
+
+    return output;
+}
+
+VertexOut GuideVs(float2 position: POSITION, float4 color: COLOR)
+{
+    VertexOut output;
+    output.position = PlotPosition(position);
+    output.color = color;
+
+    return output;
+}
+
+float4 DrawPs(VertexOut input) : SV_Target
+{
+    return input.color;
+}
+
+#endif // DRAW
@@ -9,6 +9,19 @@ float3 ColorIdToRgb(uint color_index)
     return float3(color_index & 0xff, (color_index & 0xff00) >> 8, (color_index & 0xff0000) >> 16) / float(0xff);
 }
 
+// colors quantized to `bits` per channel, 8 bits is the same as the color id
+uint FlattenBinId(uint3 id, uint bits)
+{
+    return id.z << (2 * bits) | id.y << bits | id.x;
+}
+
+// below 8 bits a bin is the count and the count weighted sums of the
+// offsets of its colors in the cell, 4 words
+uint BinStride(uint bits)
+{
+    return bits < 8 ? 4 : 1;
+}
+
 float3 RgbToPosition(float3 rgb)
 {
     return 2.f * rgb - 1.f;
@@ -62,6 +75,26 @@ float3 YuvToPosition(float3 rgb)
     return float3(dot(uv, float2(c, -s)), yuv.x, dot(uv, float2(s, c)));
 }
 
+// weighted blended order independent transparency (McGuire and Bavoil 2013),
+// the nearer and the more opaque count more in the average of the colors
+struct TransparencyOut {
+    float4 accum : SV_Target0;
+    float revealage : SV_Target1;
+};
+
+// `color` is premultiplied, `depth` the one of SV_Position
+TransparencyOut Accumulate(float4 color, float depth)
+{
+    float d = 1.f - depth;
+    float weight = color.a * clamp(3e3f * d * d * d, 1e-2f, 3e3f);
+
+    TransparencyOut output;
+    output.accum = weight * color;
+    output.revealage = color.a;
+
+    return output;
+}
+
 #ifdef COUNT
 
 cbuffer Params : register(b0) {
@@ -108,6 +141,101 @@ void CountCs(uint2 group_id: SV_GroupID, uint2 id: SV_DispatchThreadID, uint gro
 
 #endif // COUNT
 
+#ifdef BIN
+
+cbuffer Params : register(b0) {
+    uint Bits;
+};
+
+Buffer<uint> CountBuf : register(t1);
+RWBuffer<uint> BinBuf : register(u0);
+
+#define THREAD 8
+
+// once per color of the 256^3 cube, the offsets in the cell keep the sums in 32 bits
+[numthreads(THREAD, THREAD, THREAD)]
+void BinCs(uint3 id: SV_DispatchThreadID)
+{
+    uint count = CountBuf[FlattenColorId(id)];
+    if (count != 0) {
+        uint index = BinStride(Bits) * FlattenBinId(id >> (8 - Bits), Bits);
+        uint3 offset = id & ((1 << (8 - Bits)) - 1);
+        InterlockedAdd(BinBuf[index + 0], count);
+        InterlockedAdd(BinBuf[index + 1], count * offset.x);
+        InterlockedAdd(BinBuf[index + 2], count * offset.y);
+        InterlockedAdd(BinBuf[index + 3], count * offset.z);
+    }
+}
+
+#endif // BIN
+
+#ifdef SELECT
+
+cbuffer Params : register(b0) {
+    float4x4 Projection;
+    float4 Shape;
+    uint Kind;
+    uint ColorSpace;
+    uint Channel;
+    uint SaturationMeasure;
+    uint BrightnessMeasure;
+};
+
+RWBuffer<uint> MaskBuf : register(u0);
+
+float ChannelValue(float3 rgb)
+{
+    switch (Channel) {
+    case 2: return ToHsv(rgb).x;
+    case 3: return ToSaturation(SaturationMeasure, rgb);
+    default: return ToBrightness(BrightnessMeasure, rgb);
+    }
+}
+
+float3 ToPosition(float3 rgb)
+{
+    switch (ColorSpace) {
+    case 1: return HsvToPosition(rgb);
+    case 2: return HslToPosition(rgb);
+    case 3: return YuvToPosition(rgb);
+    default: return RgbToPosition(rgb);
+    }
+}
+
+// Shape is the bin range, the view space box or the view space sphere
+bool IsSelected(float3 rgb)
+{
+    if (Kind == 1) {
+        float bin = floor(255.f * ChannelValue(rgb));
+        return Shape.x <= bin && bin <= Shape.y;
+    }
+
+    float4 clip = mul(Projection, float4(ToPosition(rgb), 1.f));
+    float3 position = clip.xyz / clip.w;
+    if (Kind == 2) {
+        return all(Shape.xy <= position.xy && position.xy <= Shape.zw);
+    }
+    return distance(position, Shape.xyz) <= Shape.w;
+}
+
+#define THREAD 8
+
+// each thread packs 32 colors along red into one word
+[numthreads(THREAD, THREAD, THREAD)]
+void SelectCs(uint3 id: SV_DispatchThreadID)
+{
+    uint bits = 0;
+    for (uint i = 0; i < 32; ++i) {
+        uint3 color_id = uint3(32 * id.x + i, id.y, id.z);
+        if (IsSelected(color_id / 255.f)) {
+            bits |= 1u << i;
+        }
+    }
+    MaskBuf[FlattenColorId(uint3(32 * id.x, id.y, id.z)) / 32] = bits;
+}
+
+#endif // SELECT
+
 #ifdef COMPACT
 
 struct IndirectCommand
@@ -118,6 +246,10 @@ struct IndirectCommand
     uint instance_offset;
 };
 
+cbuffer Params : register(b0) {
+    uint Bits;
+};
+
 Buffer<uint> CountBuf : register(t1);
 RWBuffer<uint> PackedBuf : register(u0);
 AppendStructuredBuffer<IndirectCommand> CommandBuf : register(u1);
@@ -139,10 +271,11 @@ void CompactCs(uint3 group_id: SV_GroupID, uint group_index: SV_GroupIndex, uint
     uint indices[BLOCK * BLOCK * BLOCK];
     uint num_indices = 0;
 
-    uint color_index0 = group_id.z << 21 | group_id.y << 18 | group_id.x << 15;
+    uint num_bins = 1 << (3 * Bits);
+    uint color_index0 = group_id.x << 15;
     for (uint i = 0; i < BLOCK * BLOCK * BLOCK; ++i) {
         uint color_index = color_index0 | i << 9 | group_index;
-        uint count = CountBuf[color_index];
+        uint count = color_index < num_bins ? CountBuf[BinStride(Bits) * color_index] : 0;
         if (count != 0) {
             indices[num_indices] = color_index;
             ++num_indices;
@@ -182,26 +315,108 @@ void CompactCs(uint3 group_id: SV_GroupID, uint group_index: SV_GroupIndex, uint
 
 cbuffer Params : register(b0) {
     float4x4 Projection;
+    float4 ClipParams;
     float2 Scale;
     uint NumPixels;
     uint ColorSpace;
+    uint SizeMapping;
+    float MinSize;
+    float MaxSize;
+    float SizeScale;
+    uint WeightedOpacity;
+    uint Bits;
+    uint ClipMode;
 };
 
 Buffer<uint> CountBuf : register(t1);
 
+uint BinCount(uint bin_index)
+{
+    return CountBuf[BinStride(Bits) * bin_index];
+}
+
+// centroid of the colors merged into the bin, the color itself at 8 bits
+float3 BinCentroid(uint bin_index)
+{
+    uint mask = (1 << Bits) - 1;
+    uint3 id = uint3(bin_index & mask, (bin_index >> Bits) & mask, bin_index >> (2 * Bits));
+    float3 offset = 0.f;
+    if (Bits < 8) {
+        uint index = 4 * bin_index;
+        offset = float3(CountBuf[index + 1], CountBuf[index + 2], CountBuf[index + 3]) / float(CountBuf[index]);
+    }
+    return (float(1 << (8 - Bits)) * float3(id) + offset) / float(0xff);
+}
+
+// share of the pixels at which a color reaches the max size
+static const float MaxRate = 0.04f;
+static const float MinOpacity = 0.15f;
+
 struct VertexOut {
     float4 position : SV_Position;
     float4 color : COLOR;
     float2 uv : TEXCOORD;
 };
 
+// 0 ~ 1 on a log scale, from a single pixel to MaxRate
+float LogWeight(uint count)
+{
+    return saturate(log2(1.f + float(count)) / log2(1.f + MaxRate * float(NumPixels)));
+}
+
 float CalcSize(uint count)
 {
-    float rate = min(float(count) / float(NumPixels), 0.04f);
-    return max(1.f * pow(rate, 1.f / 2.f), 0.25f / 256.f);
+    float rate = min(float(count) / float(NumPixels), MaxRate);
+
+    float weight;
+    switch (SizeMapping) {
+    case 0: weight = rate / MaxRate; break;
+    case 1: weight = sqrt(rate / MaxRate); break;
+    case 2: weight = LogWeight(count); break;
+    default: weight = 0.f; break;
+    }
+
+    return SizeScale * max(MaxSize * weight, MinSize);
+}
+
+float CalcOpacity(uint count)
+{
+    return WeightedOpacity ? lerp(MinOpacity, 1.f, LogWeight(count)) : 1.f;
+}
+
+float Lightness(float3 rgb)
+{
+    switch (ColorSpace) {
+    case 1: return ToHsv(rgb).z;
+    case 2: return ToHsl(rgb).z;
+    case 3: return ToYuv(rgb).x;
+    default: return (rgb.r + rgb.g + rgb.b) / 3.f;
+    }
+}
+
+// ClipParams is the lightness range, the hue start and width, or the plane
+// normal and offset with the points on its negative side kept
+bool IsClipped(float3 rgb, float3 position)
+{
+    switch (ClipMode) {
+    case 1: {
+        float l = Lightness(rgb);
+        return l < ClipParams.x || ClipParams.y < l;
+    }
+    case 2: {
+        float3 hsv = ToHsv(rgb);
+        // the neutral axis has no hue
+        return hsv.y > 0.f && frac(hsv.x - ClipParams.x) > ClipParams.y;
+    }
+    case 3:
+        return dot(position, ClipParams.xyz) > ClipParams.w;
+    default:
+        return false;
+    }
 }
 
-VertexOut GetVertexAttribute(uint index, float3 color, float3 center, float size)
+// `center` is in clip space of the camera, w is 1 unless seen in perspective
+VertexOut GetVertexAttribute(uint index, float3 color, float4 center, float size, float opacity)
 {
     static const float2 Uvs[3] = {
         float2(-1.f, +3.f),
@@ -209,14 +424,16 @@ VertexOut GetVertexAttribute(uint index, float3 color, float3 center, float size
         float2(-1.f, -1.f),
     };
 
-    float3 position = center;
+    float4 position = center;
     position.xy += size * Uvs[index];
     position.xy *= 0.95f * Scale;
-    position.z = 0.25f * position.z + 0.5f - 0.0001f * color.g;
+    // the orthographic depth, kept through the perspective divide
+    position.z = (0.25f * center.z + 0.5f - 0.0001f * color.g) * center.w;
 
     VertexOut vert;
-    vert.position = float4(position, 1.0);
-    vert.color = float4(color, 1.0);
+    vert.position = position;
+    // premultiplied for the blended pipeline
+    vert.color = float4(opacity * color, opacity);
     vert.uv = Uvs[index];
 
     return vert;
@@ -247,10 +464,11 @@ void DrawAs(uint3 gid: SV_GroupID, uint gindex: SV_GroupIndex)
     uint indices[STEPS * STEPS * STEPS];
     uint num_indices = 0;
 
-    uint color_index0 = gid.z << 19 | gid.y << 14 | gid.x << 9;
+    uint num_groups = (1 << Bits) / GRID;
+    uint color_index0 = ((gid.z * num_groups + gid.y) * num_groups + gid.x) << 9;
     for (uint i = 0; i < STEPS * STEPS * STEPS; ++i) {
         uint color_index = color_index0 | i << 6 | gindex;
-        uint count = CountBuf[color_index];
+        uint count = BinCount(color_index);
 
         if (count > 0) {
             indices[num_indices] = color_index;
@@ -290,10 +508,11 @@ void DrawMs(uint id: SV_DispatchThreadID, uint gid: SV_GroupID, uint tid: SV_Gro
 
     if (id < payload.num ) {
         uint color_index = payload.indices[id];
-        uint count = CountBuf[color_index];
+        uint count = BinCount(color_index);
 
-        float3 color = ColorIdToRgb(color_index);
+        float3 color = BinCentroid(color_index);
         float size = CalcSize(count);
+        float opacity = CalcOpacity(count);
 
         float3 center;
         switch (ColorSpace) {
@@ -302,12 +521,16 @@ void DrawMs(uint id: SV_DispatchThreadID, uint gid: SV_GroupID, uint tid: SV_Gro
         case 2: center = HslToPosition(color); break;
         case 3: center = YuvToPosition(color); break;
         }
-        center = mul(Projection, float4(center, 1.f)).xyz;
+        // clipped points collapse to nothing
+        if (IsClipped(color, center)) {
+            size = 0.f;
+        }
+        float4 position = mul(Projection, float4(center, 1.f));
 
         uint vindex = 3 * tid;
-        vertes[vindex + 0] = GetVertexAttribute(0, color, center, size);
-        vertes[vindex + 1] = GetVertexAttribute(1, color, center, size);
-        vertes[vindex + 2] = GetVertexAttribute(2, color, center, size);
+        vertes[vindex + 0] = GetVertexAttribute(0, color, position, size, opacity);
+        vertes[vindex + 1] = GetVertexAttribute(1, color, position, size, opacity);
+        vertes[vindex + 2] = GetVertexAttribute(2, color, position, size, opacity);
 
         uint pindex = 1 * tid;
         tris[pindex + 0] = uint3(vindex + 0, vindex + 1, vindex + 2);
@@ -320,8 +543,11 @@ void DrawMs(uint id: SV_DispatchThreadID, uint gid: SV_GroupID, uint tid: SV_Gro
 
 VertexOut DrawVs(uint vertex_id: SV_VertexID, uint instance_id: SV_InstanceID, uint color_index: COLOR_INDEX)
 {
-    float3 color = ColorIdToRgb(color_index);
-    float size = CalcSize(CountBuf[color_index]);
+    uint count = BinCount(color_index);
+
+    float3 color = BinCentroid(color_index);
+    float size = CalcSize(count);
+    float opacity = CalcOpacity(count);
 
     float3 center;
     switch (ColorSpace) {
@@ -330,9 +556,12 @@ VertexOut DrawVs(uint vertex_id: SV_VertexID, uint instance_id: SV_InstanceID, u
     case 2: center = HslToPosition(color); break;
     case 3: center = YuvToPosition(color); break;
     }
-    center = mul(Projection, float4(center, 1.f)).xyz;
+    if (IsClipped(color, center)) {
+        size = 0.f;
+    }
+    float4 position = mul(Projection, float4(center, 1.f));
 
-    return GetVertexAttribute(vertex_id, color, center, size);
+    return GetVertexAttribute(vertex_id, color, position, size, opacity);
 }
 
 #endif // INDIRECT
@@ -349,4 +578,82 @@ float4 DrawPs(PsInput input) : SV_Target
     return input.color;
 }
 
+TransparencyOut DrawTransparentPs(PsInput input)
+{
+    clip(1.f - dot(input.uv, input.uv));
+    return Accumulate(input.color, input.position.z);
+}
+
 #endif // DRAW
+
+#ifdef GUIDE
+
+cbuffer Params : register(b0) {
+    float4x4 Projection;
+    float2 Scale;
+};
+
+struct VertexOut {
+    float4 position : SV_Position;
+    float4 color : COLOR;
+};
+
+VertexOut GuideVs(float3 position: POSITION, float4 color: COLOR)
+{
+    float4 center = mul(Projection, float4(position, 1.f));
+
+    VertexOut vert;
+    vert.position = center;
+    vert.position.xy *= 0.95f * Scale;
+    // the depth of the points, kept through the perspective divide
+    vert.position.z = (0.25f * center.z + 0.5f) * center.w;
+    // premultiplied for the blended pipeline
+    vert.color = float4(color.a * color.rgb, color.a);
+
+    return vert;
+}
+
+float4 GuidePs(VertexOut vert) : SV_Target
+{
+    return vert.color;
+}
+
+TransparencyOut GuideTransparentPs(VertexOut vert)
+{
+    return Accumulate(vert.color, vert.position.z);
+}
+
+#endif // GUIDE
+
+#ifdef RESOLVE
+
+Texture2D<float4> Accum : register(t1);
+Texture2D<float> Revealage : register(t2);
+
+float4 ResolveVs(uint id: SV_VertexID) : SV_Position
+{
+    static const float2 Positions[3] = {
+        float2(-1.f, +3.f),
+        float2(+3.f, -1.f),
+        float2(-1.f, -1.f),
+    };
+
+    return float4(Positions[id], 0.f, 1.f);
+}
+
+// the weighted average of the colors covering 1 - revealage, premultiplied
+float4 ResolvePs(float4 position : SV_Position) : SV_Target
+{
+    uint2 p = uint2(position.xy);
+    float revealage = Revealage[p];
+    if (revealage >= 1.f) {
+        discard;
+    }
+
+    float4 accum = Accum[p];
+    float3 color = accum.rgb / clamp(accum.a, 1e-5f, 5e4f);
+
+    return float4(color, 1.f) * (1.f - revealage);
+}
+
+#endif // RESOLVE
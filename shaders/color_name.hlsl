@@ -0,0 +1,18 @@
+Buffer<uint> CountBuf : register(t1);
+
+RWBuffer<uint> NameCountBuf : register(u0);
+RWBuffer<uint> NameLut : register(u1);
+
+#define THREAD 8
+#define LUT_BITS 6
+
+[numthreads(THREAD, THREAD, THREAD)]
+void CountNamesCs(uint3 id: SV_DispatchThreadID)
+{
+    uint count = CountBuf[256 * 256 * id.z + 256 * id.y + id.x];
+    if (count != 0) {
+        uint3 cell = id >> (8 - LUT_BITS);
+        uint name = NameLut[(cell.z << (2 * LUT_BITS)) | (cell.y << LUT_BITS) | cell.x];
+        InterlockedAdd(NameCountBuf[name], count);
+    }
+}
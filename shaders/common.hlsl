@@ -150,4 +150,265 @@ float3 HslToRgb(float hue, float saturation, float luminance)
     } else {
         return float3(ma, mi, mi + mm*(360.f - h) / 60.f);
     }
-}
\ No newline at end of file
+}
+float HueDistance(float a, float b)
+{
+    float d = frac(a - b);
+    return min(d, 1.f - d);
+}
+
+bool InHarmony(float hue, float3 centers, float3 half_widths, uint num_sectors)
+{
+    for (uint i = 0; i < num_sectors; ++i) {
+        if (HueDistance(hue, centers[i]) <= half_widths[i]) {
+            return true;
+        }
+    }
+    return false;
+}
+
+float SrgbToLinear(float c)
+{
+    return c <= 0.04045f ? c / 12.92f : pow((c + 0.055f) / 1.055f, 2.4f);
+}
+
+float LinearToSrgb(float c)
+{
+    return c <= 0.0031308f ? 12.92f * c : 1.055f * pow(c, 1.f / 2.4f) - 0.055f;
+}
+
+float LabF(float t)
+{
+    static const float Delta = 6.f / 29.f;
+    return t > Delta * Delta * Delta ? pow(t, 1.f / 3.f) : t / (3.f * Delta * Delta) + 4.f / 29.f;
+}
+
+// CIE 1976 L*a*b*, D65
+float3 ToLab(float3 rgb)
+{
+    static const float3x3 RgbToXyz = {
+        0.4124f, 0.3576f, 0.1805f,
+        0.2126f, 0.7152f, 0.0722f,
+        0.0193f, 0.1192f, 0.9505f
+    };
+    static const float3 White = { 0.95047f, 1.f, 1.08883f };
+
+    float3 rgb_linear = float3(SrgbToLinear(rgb.r), SrgbToLinear(rgb.g), SrgbToLinear(rgb.b));
+    float3 xyz = mul(RgbToXyz, rgb_linear) / White;
+    float3 f = float3(LabF(xyz.x), LabF(xyz.y), LabF(xyz.z));
+
+    return float3(116.f * f.y - 16.f, 500.f * (f.x - f.y), 200.f * (f.y - f.z));
+}
+
+float DeltaE76(float3 lab1, float3 lab2)
+{
+    return distance(lab1, lab2);
+}
+
+float DeltaE94(float3 lab1, float3 lab2)
+{
+    float c1 = length(lab1.yz);
+    float c2 = length(lab2.yz);
+
+    float dl = lab1.x - lab2.x;
+    float dc = c1 - c2;
+    float2 dab = lab1.yz - lab2.yz;
+    float dh2 = max(dot(dab, dab) - dc * dc, 0.f);
+
+    float sc = 1.f + 0.045f * c1;
+    float sh = 1.f + 0.015f * c1;
+
+    return sqrt(dl * dl + (dc / sc) * (dc / sc) + dh2 / (sh * sh));
+}
+
+float HueAngle(float a, float b)
+{
+    return (a == 0.f && b == 0.f) ? 0.f : frac(atan2(b, a) / (2.f * Pi) + 1.f) * 360.f;
+}
+
+float DeltaE2000(float3 lab1, float3 lab2)
+{
+    static const float Pow25_7 = 6103515625.f;
+
+    float c_mean = 0.5f * (length(lab1.yz) + length(lab2.yz));
+    float c_mean7 = pow(c_mean, 7.f);
+    float g = 0.5f * (1.f - sqrt(c_mean7 / (c_mean7 + Pow25_7)));
+
+    float a1 = (1.f + g) * lab1.y;
+    float a2 = (1.f + g) * lab2.y;
+    float c1 = length(float2(a1, lab1.z));
+    float c2 = length(float2(a2, lab2.z));
+    float h1 = HueAngle(a1, lab1.z);
+    float h2 = HueAngle(a2, lab2.z);
+
+    float dl = lab2.x - lab1.x;
+    float dc = c2 - c1;
+    float dh = h2 - h1;
+    if (c1 * c2 == 0.f) {
+        dh = 0.f;
+    }
+    else if (dh > 180.f) {
+        dh -= 360.f;
+    }
+    else if (dh < -180.f) {
+        dh += 360.f;
+    }
+    dh = 2.f * sqrt(c1 * c2) * sin(radians(0.5f * dh));
+
+    float l_mean = 0.5f * (lab1.x + lab2.x);
+    c_mean = 0.5f * (c1 + c2);
+    float h_mean = h1 + h2;
+    if (c1 * c2 != 0.f) {
+        if (abs(h1 - h2) <= 180.f) {
+            h_mean = 0.5f * (h1 + h2);
+        }
+        else if (h1 + h2 < 360.f) {
+            h_mean = 0.5f * (h1 + h2 + 360.f);
+        }
+        else {
+            h_mean = 0.5f * (h1 + h2 - 360.f);
+        }
+    }
+
+    float t = 1.f
+        - 0.17f * cos(radians(h_mean - 30.f))
+        + 0.24f * cos(radians(2.f * h_mean))
+        + 0.32f * cos(radians(3.f * h_mean + 6.f))
+        - 0.20f * cos(radians(4.f * h_mean - 63.f));
+
+    float d_theta = 30.f * exp(-pow((h_mean - 275.f) / 25.f, 2.f));
+    c_mean7 = pow(c_mean, 7.f);
+    float rc = 2.f * sqrt(c_mean7 / (c_mean7 + Pow25_7));
+    float rt = -sin(radians(2.f * d_theta)) * rc;
+
+    float l50 = (l_mean - 50.f) * (l_mean - 50.f);
+    float sl = 1.f + 0.015f * l50 / sqrt(20.f + l50);
+    float sc = 1.f + 0.045f * c_mean;
+    float sh = 1.f + 0.015f * c_mean * t;
+
+    float l = dl / sl;
+    float c = dc / sc;
+    float h = dh / sh;
+
+    return sqrt(max(l * l + c * c + h * h + rt * c * h, 0.f));
+}
+
+// 0: CIE76, 1: CIE94, 2: CIEDE2000
+float DeltaE(uint formula, float3 reference, float3 lab)
+{
+    switch (formula) {
+    case 0: return DeltaE76(reference, lab);
+    case 1: return DeltaE94(reference, lab);
+    default: return DeltaE2000(reference, lab);
+    }
+}
+
+// largest C* of the sRGB gamut, reached by blue
+static const float MaxLabChroma = 134.f;
+
+// measure follows SaturationMeasure
+float ToSaturation(uint measure, float3 rgb)
+{
+    float ma = Max3(rgb.r, rgb.g, rgb.b);
+    float mi = Min3(rgb.r, rgb.g, rgb.b);
+    float l = (ma + mi) / 2.f;
+
+    switch (measure) {
+    case 1: return ma > 0.f ? (ma - mi) / ma : 0.f;
+    case 2: return l > 0.f && l < 1.f ? saturate((ma - mi) / (1.f - abs(2.f * l - 1.f))) : 0.f;
+    case 3: return saturate(length(ToLab(rgb).yz) / MaxLabChroma);
+    default: return ma - mi;
+    }
+}
+
+float ToRelativeLuminance(float3 rgb)
+{
+    float3 rgb_linear = float3(SrgbToLinear(rgb.r), SrgbToLinear(rgb.g), SrgbToLinear(rgb.b));
+    return dot(rgb_linear, float3(0.2126f, 0.7152f, 0.0722f));
+}
+
+// measure follows BrightnessMeasure
+float ToBrightness(uint measure, float3 rgb)
+{
+    switch (measure) {
+    case 1: return ToRelativeLuminance(rgb);
+    case 2: return Max3(rgb.r, rgb.g, rgb.b);
+    case 3: return (Max3(rgb.r, rgb.g, rgb.b) + Min3(rgb.r, rgb.g, rgb.b)) / 2.f;
+    case 4: return saturate(ToLab(rgb).x / 100.f);
+    default: return ToLuma(rgb);
+    }
+}
+
+// 0xRRGGBB
+float3 UnpackRgb(uint rgb)
+{
+    return float3((rgb >> 16) & 0xff, (rgb >> 8) & 0xff, rgb & 0xff) / 255.f;
+}
+
+// cell of the color lookup tables built by Dictionary::lut, red fastest
+static const uint LutBits = 6;
+
+uint LutCellIndex(uint3 cell)
+{
+    return (cell.z << (2 * LutBits)) | (cell.y << LutBits) | cell.x;
+}
+
+uint LutIndex(uint3 rgb)
+{
+    return LutCellIndex(rgb >> (8 - LutBits));
+}
+
+// palettes up to this size are searched whole, larger ones at the lookup table
+// entries of 5x5x5 cells around the color, matches color/palette.rs
+static const uint FullSearchColors = 128;
+
+// cells between the probes, about 2.5 probes per palette spacing
+int ProbeStride(uint num_colors)
+{
+    return max(1, int(round(float(1 << LutBits) / (2.5f * pow(float(num_colors), 1.f / 3.f)))));
+}
+
+// index of the palette color closest to `color` under `formula`, the lookup
+// table only holds the Oklab nearest color at the cell centers
+uint ClosestPaletteColor(
+    RWBuffer<uint> lut,
+    RWBuffer<uint> colors,
+    uint num_colors,
+    uint formula,
+    float3 color,
+    out float delta_e)
+{
+    float3 lab = ToLab(color);
+    uint closest = 0;
+    delta_e = 1e30f;
+
+    if (num_colors <= FullSearchColors) {
+        for (uint i = 0; i < num_colors; ++i) {
+            float d = DeltaE(formula, ToLab(UnpackRgb(colors[i])), lab);
+            if (d < delta_e) {
+                delta_e = d;
+                closest = i;
+            }
+        }
+        return closest;
+    }
+
+    static const int Cells = 1 << LutBits;
+    int stride = ProbeStride(num_colors);
+    int3 cell = int3(uint3(255.f * color) >> (8 - LutBits));
+    for (int z = -2; z <= 2; ++z) {
+        for (int y = -2; y <= 2; ++y) {
+            for (int x = -2; x <= 2; ++x) {
+                int3 neighbor = clamp(cell + stride * int3(x, y, z), 0, Cells - 1);
+
+                uint i = lut[LutCellIndex(uint3(neighbor))];
+                float d = DeltaE(formula, ToLab(UnpackRgb(colors[i])), lab);
+                if (d < delta_e) {
+                    delta_e = d;
+                    closest = i;
+                }
+            }
+        }
+    }
+    return closest;
+}
@@ -0,0 +1,46 @@
+#include "common.hlsl"
+
+cbuffer Params : register(b0) {
+    uint4 Rect;
+    uint2 Size;
+    float MaxDeltaE;
+};
+
+// the compared image from the top left of the view area, Size.x wide
+Buffer<float4> Reference : register(t1);
+
+struct PsInput {
+    float4 position : SV_Position;
+};
+
+PsInput CompareVs(uint id: SV_VertexID) {
+    static const float2 Positions[6] = {
+        float2(-1, +1),
+        float2(+1, +1),
+        float2(-1, -1),
+        float2(+1, +1),
+        float2(+1, -1),
+        float2(-1, -1),
+    };
+
+    PsInput output;
+
+    output.position = float4(Positions[id], 0.999999f, 1.f);
+
+    return output;
+}
+
+// blue where the frame matches the compared image through green and yellow to
+// red at MaxDeltaE, gray beyond the image
+float4 ComparePs(PsInput input) : SV_Target {
+    uint2 offset = uint2(input.position.xy);
+    float3 color = Tex[Rect.xy + offset].rgb;
+
+    if (any(offset >= Size)) {
+        return float4(0.5f * ToLuma(color).xxx, 1.f);
+    }
+
+    float3 reference = Reference[offset.y * Size.x + offset.x].rgb;
+    float t = saturate(DeltaE2000(ToLab(color), ToLab(reference)) / MaxDeltaE);
+    return float4(HsvToRgb((1.f - t) * 2.f / 3.f, 0.9f, 0.9f), 1.f);
+}
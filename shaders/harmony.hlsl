@@ -0,0 +1,50 @@
+#include "common.hlsl"
+
+cbuffer Params : register(b0) {
+    float4 Area;
+    float3 Centers;
+    uint NumSectors;
+    float3 HalfWidths;
+};
+
+struct PsInput {
+    float4 position : SV_Position;
+    float2 uv : TEXCOORD;
+};
+
+PsInput WheelVs(uint id: SV_VertexID) {
+    static const float2 Uvs[6] = {
+        float2(-1, +1),
+        float2(+1, +1),
+        float2(-1, -1),
+        float2(+1, +1),
+        float2(+1, -1),
+        float2(-1, -1),
+    };
+
+    float2 uv = Uvs[id];
+
+    PsInput output;
+    output.position = float4(lerp(Area.xy, Area.zw, 0.5f * uv + 0.5f), 0.f, 1.f);
+    output.uv = uv;
+
+    return output;
+}
+
+float4 WheelPs(PsInput input) : SV_Target {
+    float r = length(input.uv);
+    clip(1.f - r);
+
+    float hue = frac(atan2(input.uv.y, input.uv.x) / (2.f * Pi) + 1.f);
+    bool inside = InHarmony(hue, Centers, HalfWidths, NumSectors);
+
+    if (r > 0.75f) {
+        return float4(HslToRgb(hue, 0.8f, 0.6f), inside ? 1.f : 0.3f);
+    }
+    else if (r > 0.7f) {
+        return float4(0.f, 0.f, 0.f, 0.f);
+    }
+    else {
+        return inside ? float4(0.8f, 0.8f, 0.8f, 0.4f) : float4(0.f, 0.f, 0.f, 0.2f);
+    }
+}
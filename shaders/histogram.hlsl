@@ -5,9 +5,13 @@
 cbuffer Params : register(b0) {
     uint4 Rect;
     uint Mode;
+    uint Weighted;
+    uint SaturationMeasure;
+    uint BrightnessMeasure;
 };
 
 RWBuffer<uint> HistogramBuf[3] : register(u0);
+RWBuffer<uint> WeightedHueBuf : register(u3);
 
 void CreateRgbHistogram(float3 color)
 {
@@ -25,14 +29,20 @@ void CreateHueHistogram(float3 color)
 
 void CreateSaturationHistogram(float3 color)
 {
-    uint3 hsv = 255.f * ToHsv(color);
-    InterlockedAdd(HistogramBuf[0][hsv.y], 1);
+    uint saturation = 255.f * ToSaturation(SaturationMeasure, color);
+    InterlockedAdd(HistogramBuf[0][saturation], 1);
 }
 
 void CreateBrightnessHistogram(float3 color)
 {
-    uint3 hsv = 255.f * ToHsv(color);
-    InterlockedAdd(HistogramBuf[0][hsv.z], 1);
+    uint brightness = 255.f * ToBrightness(BrightnessMeasure, color);
+    InterlockedAdd(HistogramBuf[0][brightness], 1);
+}
+
+void CreateWeightedHueHistogram(float3 color)
+{
+    float3 hsv = ToHsv(color);
+    InterlockedAdd(WeightedHueBuf[uint(255.f * hsv.x)], uint(255.f * hsv.y));
 }
 
 #define THREADS 8
@@ -50,11 +60,51 @@ void CreateCs(uint2 id: SV_DispatchThreadID, uint gindex: SV_GroupIndex)
         case 3: CreateSaturationHistogram(color); break;
         case 4: CreateBrightnessHistogram(color); break;
         }
+
+        if (Weighted != 0) {
+            CreateWeightedHueHistogram(color);
+        }
     }
 }
 
 #endif // CREATE
 
+// bins per axis of the joint histogram, x fastest
+static const uint JointBins = 64;
+
+#ifdef CREATE_JOINT
+
+cbuffer Params : register(b0) {
+    uint4 Rect;
+    uint Axes;
+    uint SaturationMeasure;
+    uint BrightnessMeasure;
+};
+
+RWBuffer<uint> JointBuf : register(u0);
+
+// axes follow JointAxes, x is hue or chroma and y saturation or brightness
+uint JointBin(float3 color)
+{
+    float x = Axes == 2 ? ToSaturation(0, color) : ToHsv(color).x;
+    float y = Axes == 0 ? ToSaturation(SaturationMeasure, color) : ToBrightness(BrightnessMeasure, color);
+    uint2 bin = min(uint2(JointBins * float2(x, y)), JointBins - 1);
+    return bin.y * JointBins + bin.x;
+}
+
+#define THREADS 8
+[numthreads(THREADS, THREADS, 1)]
+void CreateJointCs(uint2 id: SV_DispatchThreadID)
+{
+    uint2 position = Rect.xy + id;
+
+    if (all(position < Rect.zw)) {
+        InterlockedAdd(JointBuf[JointBin(Tex[position].rgb)], 1);
+    }
+}
+
+#endif // CREATE_JOINT
+
 #ifdef DRAW
 
 cbuffer Params : register(b0) {
@@ -62,6 +112,8 @@ cbuffer Params : register(b0) {
     float2 Scale;
     float InvPixelCount;
     uint Mode;
+    uint LogScale;
+    float4 Thresholds;
 };
 
 Buffer<uint> HistogramBuf : register(t1);
@@ -112,9 +164,111 @@ VertexOut LineVs(uint vid: SV_VertexID)
     return output;
 }
 
+// hue runs clockwise from red at the top
+float2 WheelPosition(float hue, float radius)
+{
+    float angle = 2.f * Pi * hue;
+    return float2(Scale.x * radius * sin(angle), radius * cos(angle));
+}
+
+// a bar as tall as the linear strip reaches the rim
+float WheelRadius(uint count)
+{
+    return min(InvPixelCount * count, 2.f) / 2.f;
+}
+
+// fans out from the center, the last bin closes the wheel
+VertexOut WheelFillVs(uint vid: SV_VertexID)
+{
+    uint index = (vid / 2) % 256;
+    uint count = HistogramBuf[index];
+    bool center = vid % 2 == 0;
+
+    VertexOut output;
+    output.position = float4(WheelPosition(float(index) / 256.f, center ? 0.f : WheelRadius(count)), 0.f, 1.f);
+    output.color = float4(HslToRgb((float)index / 256.f, 0.8f, 0.8f), Color.a);
+
+    return output;
+}
+
+VertexOut WheelLineVs(uint vid: SV_VertexID)
+{
+    uint index = vid % 256;
+    uint count = HistogramBuf[index];
+
+    VertexOut output;
+    output.position = float4(WheelPosition(float(index) / 256.f, WheelRadius(count)), 0.f, 1.f);
+    output.color = float4(HslToRgb((float)index / 256.f, 0.8f, 0.6f), Color.a);
+
+    return output;
+}
+
+static const uint2 Corners[6] = {
+    uint2(0, 0),
+    uint2(1, 0),
+    uint2(0, 1),
+    uint2(1, 0),
+    uint2(1, 1),
+    uint2(0, 1),
+};
+
+// 0 ~ 1, reaches 1 at 16 times the density of a flat joint histogram
+float JointDensity(uint count)
+{
+    float density = InvPixelCount * count * JointBins * JointBins / 4.f;
+    return LogScale != 0 ? saturate(log2(1.f + density) / 4.f) : saturate(density / 16.f);
+}
+
+// one annular sector per hue x saturation bin
+VertexOut DiscVs(uint vid: SV_VertexID)
+{
+    uint index = vid / 6;
+    uint2 bin = uint2(index % JointBins, index / JointBins);
+    uint2 corner = bin + Corners[vid % 6];
+    uint count = HistogramBuf[index];
+
+    float hue = (bin.x + 0.5f) / JointBins;
+    float saturation = (bin.y + 0.5f) / JointBins;
+
+    VertexOut output;
+    output.position = float4(WheelPosition(float(corner.x) / JointBins, float(corner.y) / JointBins), 0.f, 1.f);
+    output.color = float4(HsvToRgb(hue, saturation, 0.9f), Color.a * JointDensity(count));
+
+    return output;
+}
+
+// heat map square, x to the right and y upwards
+VertexOut JointVs(uint vid: SV_VertexID)
+{
+    uint index = vid / 6;
+    uint2 corner = uint2(index % JointBins, index / JointBins) + Corners[vid % 6];
+    uint count = HistogramBuf[index];
+
+    float t = JointDensity(count);
+    float2 position = 2.f * float2(corner) / JointBins - 1.f;
+
+    VertexOut output;
+    output.position = float4(Scale.x * position.x, position.y, 0.f, 1.f);
+    output.color = float4(HsvToRgb((1.f - t) * 2.f / 3.f, 0.9f, 0.9f), count != 0 ? Color.a : 0.f);
+
+    return output;
+}
+
+// vertical notan threshold lines, the unused ones fall right of the strip
+VertexOut ThresholdVs(uint vid: SV_VertexID)
+{
+    float x = 2.f * Thresholds[vid / 2] - 1.f;
+
+    VertexOut output;
+    output.position = float4(x, vid % 2 == 0 ? -1.f : 1.f, 0.f, 1.f);
+    output.color = Color;
+
+    return output;
+}
+
 float4 DrawPs(VertexOut input) : SV_Target
 {
     return input.color;
 }
 
-#endif // DRAW
\ No newline at end of file
+#endif // DRAW
\ No newline at end of file
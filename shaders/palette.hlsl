@@ -0,0 +1,29 @@
+#include "common.hlsl"
+
+cbuffer Params : register(b0) {
+    float Tolerance;
+    uint DeltaEFormula;
+    uint OffPalette;
+    uint NumColors;
+};
+
+Buffer<uint> CountBuf : register(t1);
+
+RWBuffer<uint> CoverageBuf : register(u0);
+RWBuffer<uint> PaletteLut : register(u1);
+RWBuffer<uint> PaletteColors : register(u2);
+
+#define THREAD 8
+
+// once per color of the 256^3 cube instead of once per pixel
+[numthreads(THREAD, THREAD, THREAD)]
+void CountPaletteCs(uint3 id: SV_DispatchThreadID)
+{
+    uint count = CountBuf[256 * 256 * id.z + 256 * id.y + id.x];
+    if (count != 0) {
+        float delta_e;
+        uint index = ClosestPaletteColor(
+            PaletteLut, PaletteColors, NumColors, DeltaEFormula, id / 255.f, delta_e);
+        InterlockedAdd(CoverageBuf[delta_e <= Tolerance ? index : OffPalette], count);
+    }
+}
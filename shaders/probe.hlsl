@@ -0,0 +1,38 @@
+#include "common.hlsl"
+
+cbuffer Params : register(b0)
+{
+    uint4 Rect;
+    uint2 Cursor;
+    uint HasCursor;
+};
+
+// [cursor r, g, b, found, sum r, g, b, pixel count]
+RWBuffer<uint> ProbeBuf : register(u0);
+
+#define THREAD 8
+
+[numthreads(THREAD, THREAD, 1)]
+void ProbeCs(uint2 id: SV_DispatchThreadID)
+{
+    uint2 position = Rect.xy + id;
+    if (all(position < Rect.zw)) {
+        uint3 color = 0xff * saturate(Tex[position].rgb);
+
+        if (HasCursor != 0 && all(position == Cursor)) {
+            ProbeBuf[0] = color.r;
+            ProbeBuf[1] = color.g;
+            ProbeBuf[2] = color.b;
+            ProbeBuf[3] = 1;
+        }
+
+        uint3 sum = WaveActiveSum(color);
+        uint count = WaveActiveCountBits(true);
+        if (WaveIsFirstLane()) {
+            InterlockedAdd(ProbeBuf[4], sum.r);
+            InterlockedAdd(ProbeBuf[5], sum.g);
+            InterlockedAdd(ProbeBuf[6], sum.b);
+            InterlockedAdd(ProbeBuf[7], count);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+#include "common.hlsl"
+
+cbuffer Params : register(b0)
+{
+    uint4 Rect;
+};
+
+// 0xRRGGBB per pixel of the rect, rows from the top
+RWBuffer<uint> SnapshotBuf : register(u0);
+
+#define THREAD 8
+
+[numthreads(THREAD, THREAD, 1)]
+void SnapshotCs(uint2 id: SV_DispatchThreadID)
+{
+    uint2 position = Rect.xy + id;
+    if (all(position < Rect.zw)) {
+        uint3 color = 255.f * saturate(Tex[position].rgb) + 0.5f;
+        SnapshotBuf[id.y * (Rect.z - Rect.x) + id.x] = color.r << 16 | color.g << 8 | color.b;
+    }
+}
@@ -0,0 +1,112 @@
+#include "common.hlsl"
+
+// layout of StatsBuf, matches analysis/stats.rs
+// the sums of 0 ~ 255 per pixel take two slots, low word first, as a frame of
+// more than 2^24 pixels wraps a single one
+static const uint SumLuma = 0;
+static const uint SumSaturation = 2;
+static const uint ShadowClip = 4;
+static const uint HighlightClip = 5;
+static const uint PixelCount = 6;
+static const uint UniqueColors = 7;
+static const uint HueBins = 8;
+static const uint NumHueBins = 36;
+
+#ifdef STATS
+
+cbuffer Params : register(b0)
+{
+    uint4 Rect;
+    uint SaturationMeasure;
+};
+
+RWBuffer<uint> StatsBuf : register(u0);
+// a bit per 24 bit color
+RWBuffer<uint> ColorBits : register(u1);
+
+// carries into the high word when the low word wraps
+void InterlockedAddWide(uint index, uint value)
+{
+    uint low;
+    InterlockedAdd(StatsBuf[index], value, low);
+    if (low + value < low) {
+        InterlockedAdd(StatsBuf[index + 1], 1);
+    }
+}
+
+#define THREADS 8
+[numthreads(THREADS, THREADS, 1)]
+void StatsCs(uint2 id: SV_DispatchThreadID)
+{
+    uint2 position = Rect.xy + id;
+    if (all(position < Rect.zw)) {
+        float3 color = saturate(Tex[position].rgb);
+        uint3 rgb = uint3(255.f * color + 0.5f);
+
+        uint color_id = rgb.b << 16 | rgb.g << 8 | rgb.r;
+        InterlockedOr(ColorBits[color_id >> 5], 1u << (color_id & 31));
+
+        // saturation weighted, greys have no hue
+        float3 hsv = ToHsv(color);
+        uint weight = uint(255.f * hsv.y + 0.5f);
+        if (weight != 0) {
+            InterlockedAddWide(HueBins + 2 * min(uint(NumHueBins * hsv.x), NumHueBins - 1), weight);
+        }
+
+        uint luma = uint(255.f * ToLuma(color) + 0.5f);
+        uint saturation = uint(255.f * ToSaturation(SaturationMeasure, color) + 0.5f);
+
+        uint4 sum = WaveActiveSum(uint4(luma, saturation, any(rgb == 0), any(rgb == 255)));
+        uint count = WaveActiveCountBits(true);
+        if (WaveIsFirstLane()) {
+            InterlockedAddWide(SumLuma, sum.x);
+            InterlockedAddWide(SumSaturation, sum.y);
+            InterlockedAdd(StatsBuf[ShadowClip], sum.z);
+            InterlockedAdd(StatsBuf[HighlightClip], sum.w);
+            InterlockedAdd(StatsBuf[PixelCount], count);
+        }
+    }
+}
+
+#endif // STATS
+
+#ifdef COUNT
+
+RWBuffer<uint> StatsBuf : register(u0);
+Buffer<uint> ColorBits : register(t1);
+
+#define THREADS 64
+[numthreads(THREADS, 1, 1)]
+void CountCs(uint id: SV_DispatchThreadID)
+{
+    uint count = WaveActiveSum(countbits(ColorBits[id]));
+    if (WaveIsFirstLane()) {
+        InterlockedAdd(StatsBuf[UniqueColors], count);
+    }
+}
+
+#endif // COUNT
+
+#ifdef CHART
+
+struct VertexOut {
+    float4 position : SV_Position;
+    float4 color : COLOR;
+};
+
+// already in clip space
+VertexOut ChartVs(float2 position: POSITION, float4 color: COLOR)
+{
+    VertexOut output;
+    output.position = float4(position, 0.f, 1.f);
+    output.color = color;
+
+    return output;
+}
+
+float4 ChartPs(VertexOut input) : SV_Target
+{
+    return input.color;
+}
+
+#endif // CHART
@@ -0,0 +1,17 @@
+cbuffer Params : register(b0) {
+    uint4 Rect;
+};
+
+// the pixels of the frame within Rect, row by row
+Buffer<float4> Pixels : register(t1);
+RWTexture2D<float4> Frame : register(u0);
+
+#define THREADS 8
+[numthreads(THREADS, THREADS, 1)]
+void VideoCs(uint2 id: SV_DispatchThreadID)
+{
+    uint2 position = Rect.xy + id;
+    if (all(position < Rect.zw)) {
+        Frame[position] = float4(Pixels[id.y * (Rect.z - Rect.x) + id.x].rgb, 1.f);
+    }
+}
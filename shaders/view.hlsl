@@ -4,8 +4,25 @@ cbuffer Params : register(b0) {
     uint4 Rect;
     float4 Mask;
     uint Mode;
+    float3 HarmonyCenters;
+    float3 HarmonyHalfWidths;
+    uint NumHarmonySectors;
+    float3 TargetLab;
+    uint DeltaEFormula;
+    float MaxDeltaE;
+    uint SaturationMeasure;
+    uint BrightnessMeasure;
+    uint PosterizeLevels;
+    float4 NotanThresholds;
+    uint PosterizeLightness;
+    uint NumPaletteColors;
 };
 
+Buffer<uint> SelectionMask : register(t1);
+
+RWBuffer<uint> PaletteLut : register(u1);
+RWBuffer<uint> PaletteColors : register(u2);
+
 struct PsInput {
     float4 position : SV_Position;
 };
@@ -40,23 +57,140 @@ float4 ViewHue(float3 color)
 
 float4 ViewSaturation(float3 color)
 {
-    float saturation = ToHsl(color).y;
+    float saturation = ToSaturation(SaturationMeasure, color);
     return float4(HsvToRgb(lerp(-120.f, 60.f, saturation) / 360.f, 0.8f, saturation), 1.f);
 }
 
 float4 ViewBrightness(float3 color)
 {
-    return float4(ToLuma(color).xxx, 1.f);
+    return float4(ToBrightness(BrightnessMeasure, color).xxx, 1.f);
+}
+
+float4 ViewHarmony(float3 color)
+{
+    float3 hsv = ToHsv(color);
+    if (NumHarmonySectors == 0) {
+        return float4(color, 1.f);
+    }
+    else if (hsv.y < 0.1f || InHarmony(hsv.x, HarmonyCenters, HarmonyHalfWidths, NumHarmonySectors)) {
+        return float4(0.5f * ToLuma(color).xxx, 1.f);
+    }
+    else {
+        return float4(color, 1.f);
+    }
+}
+
+// blue where the pixel matches the target, through green and yellow to red at MaxDeltaE
+float4 ViewDeltaE(float3 color)
+{
+    float t = saturate(DeltaE(DeltaEFormula, TargetLab, ToLab(color)) / MaxDeltaE);
+    return float4(HsvToRgb((1.f - t) * 2.f / 3.f, 0.9f, 0.9f), 1.f);
+}
+
+// gray steps from black for the darkest group to white for the brightest
+float4 ViewNotan(float3 color)
+{
+    float brightness = ToBrightness(BrightnessMeasure, color);
+
+    // unused thresholds are above 1
+    uint num_thresholds = 0;
+    uint group = 0;
+    for (uint i = 0; i < 4; i++) {
+        num_thresholds += NotanThresholds[i] <= 1.f ? 1 : 0;
+        group += brightness >= NotanThresholds[i] ? 1 : 0;
+    }
+
+    return float4((float(group) / max(num_thresholds, 1)).xxx, 1.f);
+}
+
+float3 Posterize(float3 value)
+{
+    float n = float(PosterizeLevels);
+    return min(floor(n * value), n - 1.f) / max(n - 1.f, 1.f);
+}
+
+// per lightness keeps the chroma and shifts every channel by the brightness step
+float4 ViewPosterize(float3 color)
+{
+    if (PosterizeLightness != 0) {
+        float brightness = ToBrightness(BrightnessMeasure, color);
+        return float4(saturate(color + Posterize(brightness.xxx).x - brightness), 1.f);
+    }
+    else {
+        return float4(Posterize(color), 1.f);
+    }
+}
+
+// Sobel gradient magnitude of the brightness
+float4 ViewLocalContrast(uint2 position)
+{
+    float b[3][3];
+    for (int y = 0; y < 3; y++) {
+        for (int x = 0; x < 3; x++) {
+            int2 p = clamp(int2(position) + int2(x - 1, y - 1), int2(Rect.xy), int2(Rect.zw) - 1);
+            b[y][x] = ToBrightness(BrightnessMeasure, Tex[p].rgb);
+        }
+    }
+
+    float gx = (b[0][2] + 2.f * b[1][2] + b[2][2]) - (b[0][0] + 2.f * b[1][0] + b[2][0]);
+    float gy = (b[2][0] + 2.f * b[2][1] + b[2][2]) - (b[0][0] + 2.f * b[0][1] + b[0][2]);
+
+    return float4(saturate(length(float2(gx, gy)) / 4.f).xxx, 1.f);
+}
+
+float3 NearestPaletteColor(float3 color)
+{
+    return UnpackRgb(PaletteColors[PaletteLut[LutIndex(uint3(255.f * color))]]);
+}
+
+float4 ViewPalette(float3 color)
+{
+    return float4(NearestPaletteColor(color), 1.f);
+}
+
+// magenta where the closest palette color is more than MaxDeltaE away
+float4 ViewPaletteCompliance(float3 color)
+{
+    float delta_e;
+    ClosestPaletteColor(
+        PaletteLut, PaletteColors, NumPaletteColors, DeltaEFormula, color, delta_e);
+    return delta_e <= MaxDeltaE ? float4(color, 1.f) : float4(1.f, 0.f, 1.f, 1.f);
+}
+
+// one bit per 24 bit color
+bool IsSelected(float3 color)
+{
+    uint3 id = uint3(255.f * color);
+    uint index = 256 * 256 * id.z + 256 * id.y + id.x;
+    return (SelectionMask[index / 32] >> (index % 32)) & 1;
+}
+
+float4 ViewSelection(float3 color, bool grey)
+{
+    if (IsSelected(color)) {
+        return float4(color, 1.f);
+    }
+    return float4(grey ? ToLuma(color).xxx : 0.25f * color, 1.f);
 }
 
 float4 ViewPs(PsInput input) : SV_Target {
-    float3 color = Tex[Rect.xy + uint2(input.position.xy)].rgb;
+    uint2 position = Rect.xy + uint2(input.position.xy);
+    float3 color = Tex[position].rgb;
 
     switch (Mode) {
     case 1: return ViewRgb(color);
     case 2: return ViewHue(color);
     case 3: return ViewSaturation(color);
     case 4: return ViewBrightness(color);
+    case 5: return ViewHarmony(color);
+    case 6: return ViewDeltaE(color);
+    case 7: return ViewNotan(color);
+    case 8: return ViewPosterize(color);
+    case 9: return ViewLocalContrast(position);
+    case 10: return ViewPalette(color);
+    case 11: return ViewPaletteCompliance(color);
+    case 12: return ViewSelection(color, false);
+    case 13: return ViewSelection(color, true);
     default: return float4(color, 1.f);
     }
 }
@@ -0,0 +1,6 @@
+pub mod barcode;
+pub mod compare;
+pub mod harmony;
+pub mod joint;
+pub mod probe;
+pub mod stats;
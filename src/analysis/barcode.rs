@@ -0,0 +1,296 @@
+use crate::color::difference::delta_e76;
+use crate::color::*;
+use crate::render::{color_bin, Image};
+use glam::Vec3;
+
+// quantization of the color histograms compared between frames, as the cloud bins
+pub const BARCODE_BITS: u32 = 4;
+const NUM_BINS: usize = 1 << (3 * BARCODE_BITS);
+
+// the half L1 distance of the histograms of two frames, 0 ~ 1, above which a shot starts
+pub const DEFAULT_CUT_THRESHOLD: f32 = 0.4;
+pub const PALETTE_SIZE: usize = 5;
+// palette colors closer than this are taken as one
+const MIN_PALETTE_DELTA_E: f32 = 15.0;
+
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum BarcodeColor {
+    // the mean of all pixels
+    #[default]
+    Average,
+    // the mean of the most populated histogram bin
+    Dominant,
+}
+
+#[derive(Clone, Debug)]
+pub struct Shot {
+    // frames start..end
+    pub start: usize,
+    pub end: usize,
+    // colors and their share of the pixels of the shot, largest first
+    pub palette: Vec<([u8; 3], f32)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Barcode {
+    // a color per frame
+    pub columns: Vec<[u8; 3]>,
+    pub shots: Vec<Shot>,
+}
+
+// pixel counts and 8 bit color sums of the histogram bins, integers so that
+// long shots add up exactly
+#[derive(Clone)]
+struct Bins {
+    counts: Vec<u64>,
+    sums: Vec<[u64; 3]>,
+    total: u64,
+}
+
+impl Bins {
+    fn new() -> Self {
+        Self {
+            counts: vec![0; NUM_BINS],
+            sums: vec![[0; 3]; NUM_BINS],
+            total: 0,
+        }
+    }
+
+    fn of(image: &Image) -> Self {
+        let mut bins = Self::new();
+
+        for &rgb in &image.pixels {
+            let bin = color_bin(rgb, BARCODE_BITS) as usize;
+            bins.counts[bin] += 1;
+            for (sum, c) in bins.sums[bin].iter_mut().zip(rgb) {
+                *sum += c as u64;
+            }
+        }
+        bins.total = image.pixels.len() as u64;
+
+        bins
+    }
+
+    fn add(&mut self, other: &Self) {
+        for bin in 0..NUM_BINS {
+            self.counts[bin] += other.counts[bin];
+            for channel in 0..3 {
+                self.sums[bin][channel] += other.sums[bin][channel];
+            }
+        }
+        self.total += other.total;
+    }
+
+    fn mean_of(sum: [u64; 3], count: u64) -> Vec3 {
+        Vec3::from(sum.map(|c| c as f32)) / (255.0 * count.max(1) as f32)
+    }
+
+    fn mean(&self, bin: usize) -> Vec3 {
+        Self::mean_of(self.sums[bin], self.counts[bin])
+    }
+
+    fn color(&self, color: BarcodeColor) -> [u8; 3] {
+        let rgb = match color {
+            BarcodeColor::Average => {
+                let mut sum = [0; 3];
+                for bin in &self.sums {
+                    for channel in 0..3 {
+                        sum[channel] += bin[channel];
+                    }
+                }
+                Self::mean_of(sum, self.total)
+            }
+            BarcodeColor::Dominant => {
+                let bin = (0..NUM_BINS).max_by_key(|&bin| self.counts[bin]).unwrap();
+                self.mean(bin)
+            }
+        };
+        to_rgb8(rgb)
+    }
+
+    // half the L1 distance of the normalized histograms
+    fn distance(&self, other: &Self) -> f32 {
+        let (a, b) = (self.total.max(1) as f32, other.total.max(1) as f32);
+        let sum: f32 = self
+            .counts
+            .iter()
+            .zip(&other.counts)
+            .map(|(&p, &q)| (p as f32 / a - q as f32 / b).abs())
+            .sum();
+        0.5 * sum
+    }
+
+    // the most populated bins apart by MIN_PALETTE_DELTA_E, each pixel counted
+    // for the nearest of them
+    fn palette(&self) -> Vec<([u8; 3], f32)> {
+        let mut order = (0..NUM_BINS)
+            .filter(|&bin| self.counts[bin] > 0)
+            .collect::<Vec<_>>();
+        order.sort_by_key(|&bin| std::cmp::Reverse(self.counts[bin]));
+
+        let labs = |bin: usize| rgb_to_lab(self.mean(bin));
+
+        let mut picked: Vec<(usize, Vec3)> = vec![];
+        for &bin in &order {
+            let lab = labs(bin);
+            if picked
+                .iter()
+                .all(|&(_, other)| delta_e76(lab, other) >= MIN_PALETTE_DELTA_E)
+            {
+                picked.push((bin, lab));
+                if picked.len() == PALETTE_SIZE {
+                    break;
+                }
+            }
+        }
+
+        let mut shares = vec![0; picked.len()];
+        for &bin in &order {
+            let lab = labs(bin);
+            let nearest = (0..picked.len())
+                .min_by(|&i, &j| {
+                    delta_e76(lab, picked[i].1).total_cmp(&delta_e76(lab, picked[j].1))
+                })
+                .unwrap();
+            shares[nearest] += self.counts[bin];
+        }
+
+        let mut palette = picked
+            .iter()
+            .zip(shares)
+            .map(|(&(bin, _), share)| {
+                (
+                    to_rgb8(self.mean(bin)),
+                    share as f32 / self.total.max(1) as f32,
+                )
+            })
+            .collect::<Vec<_>>();
+        palette.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        palette
+    }
+}
+
+// takes the frames of a sequence one by one, so only the current shot is kept
+pub struct BarcodeBuilder {
+    color: BarcodeColor,
+    threshold: f32,
+
+    columns: Vec<[u8; 3]>,
+    shots: Vec<Shot>,
+    previous: Option<Bins>,
+    // the start and the pixels of the running shot
+    shot: (usize, Bins),
+}
+
+impl BarcodeBuilder {
+    pub fn new(color: BarcodeColor, threshold: f32) -> Self {
+        Self {
+            color,
+            threshold,
+            columns: vec![],
+            shots: vec![],
+            previous: None,
+            shot: (0, Bins::new()),
+        }
+    }
+
+    pub fn push(&mut self, image: &Image) {
+        let bins = Bins::of(image);
+        let frame = self.columns.len();
+
+        let cut = self
+            .previous
+            .as_ref()
+            .is_some_and(|previous| previous.distance(&bins) > self.threshold);
+        if cut {
+            self.end_shot(frame);
+        }
+
+        self.columns.push(bins.color(self.color));
+        self.shot.1.add(&bins);
+        self.previous = Some(bins);
+    }
+
+    fn end_shot(&mut self, end: usize) {
+        let (start, bins) = std::mem::replace(&mut self.shot, (end, Bins::new()));
+        self.shots.push(Shot {
+            start,
+            end,
+            palette: bins.palette(),
+        });
+    }
+
+    pub fn finish(mut self) -> Barcode {
+        if !self.columns.is_empty() {
+            self.end_shot(self.columns.len());
+        }
+
+        Barcode {
+            columns: self.columns,
+            shots: self.shots,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(rgb: [u8; 3]) -> Image {
+        Image {
+            width: 4,
+            height: 4,
+            pixels: vec![rgb; 16],
+        }
+    }
+
+    #[test]
+    fn cuts_between_shots() {
+        let mut builder = BarcodeBuilder::new(BarcodeColor::Average, DEFAULT_CUT_THRESHOLD);
+        // a slow fade within the histogram bins stays in the shot, the jump to blue starts the next
+        for rgb in [
+            [196, 40, 40],
+            [200, 40, 40],
+            [204, 44, 40],
+            [30, 60, 220],
+            [30, 60, 216],
+        ] {
+            builder.push(&frame(rgb));
+        }
+        let barcode = builder.finish();
+
+        assert_eq!(barcode.columns.len(), 5);
+        assert_eq!(barcode.columns[3], [30, 60, 220]);
+
+        let shots: Vec<_> = barcode
+            .shots
+            .iter()
+            .map(|shot| (shot.start, shot.end))
+            .collect();
+        assert_eq!(shots, [(0, 3), (3, 5)]);
+
+        // each shot a single color that takes all of it
+        let [red, blue] = [&barcode.shots[0], &barcode.shots[1]].map(|shot| &shot.palette);
+        assert_eq!(red.len(), 1);
+        assert_eq!(red[0].1, 1.0);
+        assert_eq!(red[0].0, [200, 41, 40]);
+        assert_eq!(blue[0].0, [30, 60, 218]);
+    }
+
+    #[test]
+    fn keeps_a_single_shot_without_cuts() {
+        let mut builder = BarcodeBuilder::new(BarcodeColor::Dominant, DEFAULT_CUT_THRESHOLD);
+        for _ in 0..3 {
+            builder.push(&frame([90, 90, 90]));
+        }
+        let barcode = builder.finish();
+
+        assert_eq!(barcode.shots.len(), 1);
+        assert_eq!((barcode.shots[0].start, barcode.shots[0].end), (0, 3));
+        assert_eq!(barcode.columns, vec![[90, 90, 90]; 3]);
+
+        let empty = BarcodeBuilder::new(BarcodeColor::Average, DEFAULT_CUT_THRESHOLD).finish();
+        assert!(empty.shots.is_empty());
+    }
+}
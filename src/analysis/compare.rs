@@ -0,0 +1,173 @@
+use crate::color::difference::delta_e2000;
+use crate::color::*;
+use crate::render::Image;
+use glam::Vec3;
+use std::io::{Error, ErrorKind, Result};
+
+// the live frame or first image and the reference, in histograms and the dual cloud
+pub const COMPARE_COLORS: [Vec3; 2] = [Vec3::new(1.0, 0.55, 0.1), Vec3::new(0.1, 0.75, 1.0)];
+// red end of the heat map
+pub const MAX_HEAT_DELTA_E: f32 = 10.0;
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Comparison {
+    // CIEDE2000 per pixel
+    pub mean_delta_e: f32,
+    pub max_delta_e: f32,
+    // earth mover's distance of the R, G and B histograms in 0 ~ 1, the share
+    // of the pixels times how far along the axis they move
+    pub emd: [f32; 3],
+}
+
+impl Comparison {
+    // `a` and `b` are of the same size, Image::crop takes the area they share
+    pub fn new(a: &Image, b: &Image) -> Result<Self> {
+        let delta_e = delta_e_map(a, b)?;
+        let mean_delta_e = delta_e.iter().sum::<f32>() / delta_e.len().max(1) as f32;
+        let max_delta_e = delta_e.iter().copied().fold(0.0, f32::max);
+
+        let (ha, hb) = (rgb_histograms(a), rgb_histograms(b));
+        let emd = [0, 1, 2].map(|channel| emd(&ha[channel], &hb[channel]));
+
+        Ok(Self {
+            mean_delta_e,
+            max_delta_e,
+            emd,
+        })
+    }
+
+    pub fn mean_emd(&self) -> f32 {
+        self.emd.iter().sum::<f32>() / 3.0
+    }
+}
+
+// CIEDE2000 per pixel of two images of the same size, row by row
+pub fn delta_e_map(a: &Image, b: &Image) -> Result<Vec<f32>> {
+    if (a.width, a.height) != (b.width, b.height) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "the images differ in size, {}x{} and {}x{}",
+                a.width, a.height, b.width, b.height
+            ),
+        ));
+    }
+
+    Ok(a.pixels
+        .iter()
+        .zip(&b.pixels)
+        .map(|(&pa, &pb)| delta_e2000(rgb_to_lab(from_rgb8(pa)), rgb_to_lab(from_rgb8(pb))))
+        .collect())
+}
+
+// blue for a match through green and yellow to red at MAX_HEAT_DELTA_E, as
+// ViewDeltaE shades the difference from the target
+pub fn heat(delta_e: f32) -> Vec3 {
+    let t = (delta_e / MAX_HEAT_DELTA_E).clamp(0.0, 1.0);
+    hsv_to_rgb((1.0 - t) * 2.0 / 3.0, 0.9, 0.9)
+}
+
+pub fn rgb_histograms(image: &Image) -> [[u32; 256]; 3] {
+    let mut histograms = [[0; 256]; 3];
+    for rgb in &image.pixels {
+        for channel in 0..3 {
+            histograms[channel][rgb[channel] as usize] += 1;
+        }
+    }
+    histograms
+}
+
+// between two 1D histograms the distance of their cumulative distributions
+pub fn emd(a: &[u32; 256], b: &[u32; 256]) -> f32 {
+    let na = a.iter().sum::<u32>().max(1) as f32;
+    let nb = b.iter().sum::<u32>().max(1) as f32;
+
+    let (mut ca, mut cb, mut distance) = (0.0, 0.0, 0.0);
+    for (&a, &b) in a.iter().zip(b) {
+        ca += a as f32 / na;
+        cb += b as f32 / nb;
+        distance += (ca - cb).abs();
+    }
+    distance / 255.0
+}
+
+// the color of a cloud bin by which image has more of it, grey where they agree
+pub fn divergence_color(share_a: f32, share_b: f32) -> Vec3 {
+    const AGREE: Vec3 = Vec3::splat(0.8);
+
+    let total = share_a + share_b;
+    if total <= 0.0 {
+        return AGREE;
+    }
+
+    let d = (share_a - share_b) / total;
+    if d >= 0.0 {
+        AGREE.lerp(COMPARE_COLORS[0], d)
+    } else {
+        AGREE.lerp(COMPARE_COLORS[1], -d)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32, pixels: &[[u8; 3]]) -> Image {
+        Image {
+            width,
+            height,
+            pixels: pixels.to_vec(),
+        }
+    }
+
+    #[test]
+    fn maps_the_delta_e_per_pixel() {
+        let a = image(2, 1, &[[0, 0, 0], [128, 64, 32]]);
+        let b = image(2, 1, &[[255, 255, 255], [128, 64, 32]]);
+
+        // black and white are 100 apart in L* with no weighting at L* 50
+        let map = delta_e_map(&a, &b).unwrap();
+        assert!((map[0] - 100.0).abs() < 0.01);
+        assert_eq!(map[1], 0.0);
+
+        let comparison = Comparison::new(&a, &b).unwrap();
+        assert!((comparison.mean_delta_e - 50.0).abs() < 0.01);
+        assert!((comparison.max_delta_e - 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn rejects_images_of_different_sizes() {
+        let a = image(2, 1, &[[0; 3]; 2]);
+        let b = image(1, 2, &[[0; 3]; 2]);
+
+        assert_eq!(
+            delta_e_map(&a, &b).unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+        assert!(Comparison::new(&a, &b).is_err());
+        assert!(Comparison::new(&a.crop(1, 1), &b.crop(1, 1)).is_ok());
+    }
+
+    #[test]
+    fn moves_the_histograms_along_the_axis() {
+        let mut a = [0; 256];
+        let mut b = [0; 256];
+        assert_eq!(emd(&a, &a), 0.0);
+
+        // every pixel from one end to the other
+        a[0] = 10;
+        b[255] = 10;
+        assert_eq!(emd(&a, &b), 1.0);
+
+        // a single bin, counts scaled to the share of the pixels
+        b = [0; 256];
+        b[1] = 40;
+        assert_eq!(emd(&a, &b), 1.0 / 255.0);
+
+        // half of the pixels across the whole axis
+        b = [0; 256];
+        b[0] = 5;
+        b[255] = 5;
+        assert!((emd(&a, &b) - 0.5).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,231 @@
+// Hue harmony templates after Matsuda, fitted as in Cohen-Or et al. "Color Harmonization" (2006).
+
+pub const MAX_SECTORS: usize = 3;
+
+const NUM_BINS: usize = 256;
+
+// wider templates cover more of the wheel and always fit at least as well as the narrow ones,
+// so they pay this many degrees of mean distance per full turn of coverage when picking the best.
+const COVERAGE_PENALTY: f32 = 10.0;
+
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum HarmonyTemplate {
+    #[default]
+    SmallI,
+    V,
+    L,
+    I,
+    T,
+    Y,
+    X,
+    Complementary,
+    Analogous,
+    Triadic,
+    SplitComplementary,
+}
+
+impl HarmonyTemplate {
+    pub const ALL: [Self; 11] = [
+        Self::SmallI,
+        Self::V,
+        Self::L,
+        Self::I,
+        Self::T,
+        Self::Y,
+        Self::X,
+        Self::Complementary,
+        Self::Analogous,
+        Self::Triadic,
+        Self::SplitComplementary,
+    ];
+
+    // (center, width) in degrees
+    pub fn sectors(&self) -> &'static [(f32, f32)] {
+        match *self {
+            Self::SmallI => &[(0.0, 18.0)],
+            Self::V => &[(0.0, 93.6)],
+            Self::L => &[(0.0, 18.0), (90.0, 79.2)],
+            Self::I => &[(0.0, 18.0), (180.0, 18.0)],
+            Self::T => &[(0.0, 180.0)],
+            Self::Y => &[(0.0, 93.6), (180.0, 18.0)],
+            Self::X => &[(0.0, 93.6), (180.0, 93.6)],
+            Self::Complementary => &[(0.0, 36.0), (180.0, 36.0)],
+            Self::Analogous => &[(0.0, 72.0)],
+            Self::Triadic => &[(0.0, 36.0), (120.0, 36.0), (240.0, 36.0)],
+            Self::SplitComplementary => &[(0.0, 36.0), (150.0, 36.0), (210.0, 36.0)],
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Self::SmallI => "i",
+            Self::V => "V",
+            Self::L => "L",
+            Self::I => "I",
+            Self::T => "T",
+            Self::Y => "Y",
+            Self::X => "X",
+            Self::Complementary => "Compl.",
+            Self::Analogous => "Analog.",
+            Self::Triadic => "Triad",
+            Self::SplitComplementary => "Split",
+        }
+    }
+
+    fn coverage(&self) -> f32 {
+        self.sectors().iter().map(|(_, width)| width).sum::<f32>() / 360.0
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Harmony {
+    pub template: HarmonyTemplate,
+    pub rotation: f32,      // degrees
+    pub mean_distance: f32, // degrees, weighted by saturation
+    pub score: f32,         // 0 ~ 1
+}
+
+impl Harmony {
+    // (center, half width) in degrees, rotated
+    pub fn sectors(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.template
+            .sectors()
+            .iter()
+            .map(|&(center, width)| ((center + self.rotation).rem_euclid(360.0), 0.5 * width))
+    }
+
+    pub fn contains(&self, hue: f32) -> bool {
+        distance(self.template, self.rotation, hue) == 0.0
+    }
+
+    // centers and half widths in 0 ~ 1 hue units, as the shaders take them
+    pub fn shader_sectors(&self) -> ([f32; MAX_SECTORS], [f32; MAX_SECTORS], u32) {
+        let mut centers = [0.0; MAX_SECTORS];
+        let mut half_widths = [0.0; MAX_SECTORS];
+        let mut num = 0;
+
+        for (i, (center, half_width)) in self.sectors().enumerate() {
+            centers[i] = center / 360.0;
+            half_widths[i] = half_width / 360.0;
+            num += 1;
+        }
+
+        (centers, half_widths, num)
+    }
+}
+
+// `histogram` is the saturation weighted hue histogram, 256 bins over 0 ~ 360 degrees.
+// Fits `template` or, if none is given, every template and returns the best one.
+// None when there are no hues to fit, an empty or all grey image.
+pub fn fit(histogram: &[u32], template: Option<HarmonyTemplate>) -> Option<Harmony> {
+    let bins = histogram
+        .iter()
+        .take(NUM_BINS)
+        .enumerate()
+        .filter(|(_, &weight)| weight != 0)
+        .map(|(i, &weight)| (bin_hue(i), weight as f32))
+        .collect::<Vec<_>>();
+
+    let total = bins.iter().map(|(_, weight)| weight).sum::<f32>();
+    if total <= 0.0 {
+        return None;
+    }
+
+    match template {
+        Some(template) => Some(fit_template(&bins, total, template)),
+        None => HarmonyTemplate::ALL
+            .iter()
+            .map(|&template| fit_template(&bins, total, template))
+            .min_by(|a, b| penalized(a).total_cmp(&penalized(b))),
+    }
+}
+
+fn fit_template(bins: &[(f32, f32)], total: f32, template: HarmonyTemplate) -> Harmony {
+    let mut best = Harmony {
+        template,
+        ..Default::default()
+    };
+
+    let mut best_cost = f32::MAX;
+    for degree in 0..360 {
+        let rotation = degree as f32;
+        let cost = bins
+            .iter()
+            .map(|&(hue, weight)| weight * distance(template, rotation, hue))
+            .sum::<f32>();
+
+        if cost < best_cost {
+            best_cost = cost;
+            best.rotation = rotation;
+        }
+    }
+
+    best.mean_distance = best_cost / total;
+    best.score = (1.0 - best.mean_distance / 90.0).clamp(0.0, 1.0);
+    best
+}
+
+fn penalized(harmony: &Harmony) -> f32 {
+    harmony.mean_distance + COVERAGE_PENALTY * harmony.template.coverage()
+}
+
+// distance in degrees from `hue` to the nearest sector border, 0 inside a sector
+fn distance(template: HarmonyTemplate, rotation: f32, hue: f32) -> f32 {
+    template
+        .sectors()
+        .iter()
+        .map(|&(center, width)| (hue_distance(hue, center + rotation) - 0.5 * width).max(0.0))
+        .fold(f32::MAX, f32::min)
+}
+
+pub fn hue_distance(a: f32, b: f32) -> f32 {
+    let d = (a - b).rem_euclid(360.0);
+    d.min(360.0 - d)
+}
+
+fn bin_hue(index: usize) -> f32 {
+    360.0 * (index as f32 + 0.5) / 255.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_to_fit_without_hues() {
+        assert_eq!(fit(&[], None), None);
+        assert_eq!(fit(&[0; NUM_BINS], Some(HarmonyTemplate::V)), None);
+    }
+
+    #[test]
+    fn a_single_hue_fits_every_template() {
+        let mut histogram = [0; NUM_BINS];
+        histogram[40] = 1000;
+        let hue = bin_hue(40);
+
+        for template in HarmonyTemplate::ALL {
+            let harmony = fit(&histogram, Some(template)).unwrap();
+            assert_eq!(harmony.template, template);
+            assert_eq!((harmony.mean_distance, harmony.score), (0.0, 1.0));
+            assert!(harmony.contains(hue));
+        }
+
+        // the narrowest template wins once the coverage is paid for
+        let best = fit(&histogram, None).unwrap();
+        assert_eq!(best.template, HarmonyTemplate::SmallI);
+        assert!(hue_distance(best.rotation, hue) <= 9.0);
+    }
+
+    #[test]
+    fn opposite_hues_fit_a_two_sector_template() {
+        let mut histogram = [0; NUM_BINS];
+        histogram[0] = 500;
+        histogram[128] = 500;
+
+        let best = fit(&histogram, None).unwrap();
+        assert_eq!(best.template, HarmonyTemplate::I);
+        assert_eq!(best.score, 1.0);
+        let narrow = fit(&histogram, Some(HarmonyTemplate::SmallI)).unwrap();
+        assert!(narrow.score < 1.0);
+    }
+}
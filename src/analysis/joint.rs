@@ -0,0 +1,112 @@
+use crate::color::{measure::*, *};
+
+// bins per axis of the joint histogram, x fastest
+pub const JOINT_BINS: usize = 64;
+
+// x axis by y axis, the shaders switch on the discriminant
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum JointAxes {
+    #[default]
+    HueSaturation,
+    HueBrightness,
+    ChromaBrightness,
+}
+
+impl JointAxes {
+    pub const ALL: [Self; 3] = [
+        Self::HueSaturation,
+        Self::HueBrightness,
+        Self::ChromaBrightness,
+    ];
+
+    // matches JointBin in histogram.hlsl
+    pub fn bin(
+        &self,
+        saturation: SaturationMeasure,
+        brightness: BrightnessMeasure,
+        rgb: [u8; 3],
+    ) -> usize {
+        let rgb = from_rgb8(rgb);
+
+        let x = match *self {
+            Self::ChromaBrightness => SaturationMeasure::Chroma.of(rgb),
+            _ => hue(rgb),
+        };
+        let y = match *self {
+            Self::HueSaturation => saturation.of(rgb),
+            _ => brightness.of(rgb),
+        };
+
+        let index = |t: f32| ((JOINT_BINS as f32 * t) as usize).min(JOINT_BINS - 1);
+        index(y) * JOINT_BINS + index(x)
+    }
+}
+
+// the same counts the GPU accumulates, for exports and the automation API
+pub fn histogram(
+    pixels: impl IntoIterator<Item = [u8; 3]>,
+    axes: JointAxes,
+    saturation: SaturationMeasure,
+    brightness: BrightnessMeasure,
+) -> Vec<u32> {
+    let mut counts = vec![0; JOINT_BINS * JOINT_BINS];
+    for rgb in pixels {
+        counts[axes.bin(saturation, brightness, rgb)] += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bin(axes: JointAxes, rgb: [u8; 3]) -> (usize, usize) {
+        let index = axes.bin(SaturationMeasure::HsvS, BrightnessMeasure::HsvV, rgb);
+        (index % JOINT_BINS, index / JOINT_BINS)
+    }
+
+    #[test]
+    fn places_known_colors() {
+        let last = JOINT_BINS - 1;
+
+        // x is the hue, y the saturation or the brightness
+        assert_eq!(bin(JointAxes::HueSaturation, [255, 0, 0]), (0, last));
+        assert_eq!(
+            bin(JointAxes::HueSaturation, [0, 255, 0]),
+            (JOINT_BINS / 3, last)
+        );
+        assert_eq!(
+            bin(JointAxes::HueSaturation, [0, 0, 255]),
+            (2 * JOINT_BINS / 3, last)
+        );
+        assert_eq!(bin(JointAxes::HueSaturation, [128, 128, 128]), (0, 0));
+
+        assert_eq!(
+            bin(JointAxes::HueBrightness, [0, 0, 128]),
+            (2 * JOINT_BINS / 3, 32)
+        );
+        assert_eq!(bin(JointAxes::HueBrightness, [0, 0, 0]), (0, 0));
+
+        // x is the chroma
+        assert_eq!(bin(JointAxes::ChromaBrightness, [255, 255, 255]), (0, last));
+        assert_eq!(
+            bin(JointAxes::ChromaBrightness, [255, 0, 255]),
+            (last, last)
+        );
+    }
+
+    #[test]
+    fn counts_every_pixel_once() {
+        let pixels = [[255, 0, 0], [255, 0, 0], [0, 0, 0]];
+        let counts = histogram(
+            pixels,
+            JointAxes::HueSaturation,
+            SaturationMeasure::HsvS,
+            BrightnessMeasure::HsvV,
+        );
+
+        assert_eq!(counts.iter().sum::<u32>(), 3);
+        assert_eq!(counts[(JOINT_BINS - 1) * JOINT_BINS], 2);
+        assert_eq!(counts[0], 1);
+    }
+}
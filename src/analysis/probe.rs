@@ -0,0 +1,23 @@
+// layout of the probe buffer written by shaders/probe.hlsl
+pub const PROBE_LEN: usize = 8;
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Probe {
+    pub color: Option<[u8; 3]>, // under the cursor
+    pub mean: [u8; 3],          // of the whole view
+}
+
+impl Probe {
+    // [cursor r, g, b, found, sum r, g, b, pixel count]
+    pub fn from_buffer(data: &[u32]) -> Self {
+        let color = (data[3] != 0).then(|| [data[0] as u8, data[1] as u8, data[2] as u8]);
+
+        let count = data[7].max(1) as u64;
+        let mean = |sum: u32| ((sum as u64 + count / 2) / count) as u8;
+
+        Self {
+            color,
+            mean: [mean(data[4]), mean(data[5]), mean(data[6])],
+        }
+    }
+}
@@ -0,0 +1,46 @@
+// layout of the stats buffer written by shaders/stats.hlsl
+// [sum luma, sum saturation, shadow clip, highlight clip, pixel count, unique colors, hue bins ...]
+// the sums and the hue bins are 64 bit, two slots each with the low word first
+pub const NUM_HUE_BINS: usize = 36;
+pub const STATS_LEN: usize = 8 + 2 * NUM_HUE_BINS;
+
+// a bit per 24 bit color
+pub const COLOR_BITS_LEN: usize = (1 << 24) / 32;
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Stats {
+    // 0 ~ 1
+    pub mean_luma: f32,
+    pub mean_saturation: f32,
+    // degrees at the middle of the 10 degree bin holding the most saturation,
+    // None for a grey frame
+    pub dominant_hue: Option<f32>,
+    // fraction of the pixels with a channel at 0 or 255
+    pub shadow_clip: f32,
+    pub highlight_clip: f32,
+    pub unique_colors: u32,
+}
+
+impl Stats {
+    pub fn from_buffer(data: &[u32]) -> Self {
+        let wide = |i: usize| data[i] as u64 | (data[i + 1] as u64) << 32;
+        let count = data[6].max(1) as f64;
+
+        let dominant_hue = (0..NUM_HUE_BINS)
+            .map(|i| wide(8 + 2 * i))
+            .enumerate()
+            .filter(|&(_, weight)| weight > 0)
+            // the first of equal bins
+            .max_by_key(|&(i, weight)| (weight, std::cmp::Reverse(i)))
+            .map(|(i, _)| (i as f32 + 0.5) * 360.0 / NUM_HUE_BINS as f32);
+
+        Self {
+            mean_luma: (wide(0) as f64 / 255.0 / count) as f32,
+            mean_saturation: (wide(2) as f64 / 255.0 / count) as f32,
+            dominant_hue,
+            shadow_clip: (data[4] as f64 / count) as f32,
+            highlight_clip: (data[5] as f64 / count) as f32,
+            unique_colors: data[7],
+        }
+    }
+}
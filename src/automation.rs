@@ -0,0 +1,538 @@
+// Local automation API, a JSON-RPC 2.0 server on the loopback for scripts and
+// pipeline tools. `iromiru --listen [PORT]` serves it on 127.0.0.1:PORT, 7410
+// by default. Requests and responses are one JSON object per line:
+//
+//   -> {"jsonrpc": "2.0", "id": 1, "method": "set_view_mode", "params": {"mode": "hue"}}
+//   <- {"jsonrpc":"2.0","id":1,"result":null}
+//   <- {"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"unknown view mode: hu"}}
+//
+// A request without an id is a notification and gets no response.
+//
+// Methods, their params and results:
+//   get_state                         {"view_mode", "histogram_mode", "color_cloud_mode",
+//                                     "rect", "frozen"}
+//   set_view_mode {"mode"}            original, rgb, rgb:rg ..., hue, saturation, brightness,
+//                                     harmony, delta-e, notan, posterize, squint, contrast,
+//                                     palette, compliance, selection, compare
+//   set_histogram_mode {"mode"}       disable, rgb, hue, hue-wheel, hue-disc, saturation,
+//                                     brightness, joint-hue-saturation,
+//                                     joint-hue-brightness, joint-chroma-brightness
+//   set_color_cloud_mode {"mode"}     disable, rgb, hsv, hsl, yuv
+//   set_rect {"rect"}                 [left, top, right, bottom] screen pixels analyzed in
+//                                     place of the area under the window, null to follow it
+//   freeze {"frozen"}                 true holds the current frame, false releases it
+//   get_histogram {"mode"?}           {"mode", "pixels", "channels": [[256 counts], ...]}
+//                                     of rgb, hue, hue-wheel, saturation and brightness,
+//                                     {"mode", "pixels", "bins": 64, "counts": [64 x 64]}
+//                                     of hue-disc and the joint modes, x fastest, the disc
+//                                     as hue and saturation; the current mode by default
+//   get_stats                         {"mean_luma", "mean_saturation", "saturation_measure",
+//                                     "dominant_hue", "shadow_clip", "highlight_clip",
+//                                     "unique_colors"}, saturation_measure names the
+//                                     measure of mean_saturation: chroma, hsv-s, hsl-s
+//                                     or lab-c
+//
+// get_histogram and get_stats take the next frame and fail with -32000 if none
+// comes within 2 seconds. The panel keeps its radio buttons as they were.
+
+mod json;
+
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Result, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use windows::Win32::Foundation::RECT;
+
+use crate::analysis::joint::{self, JointAxes, JOINT_BINS};
+use crate::render::{histogram, Image};
+use crate::state::*;
+
+pub use self::json::Json;
+
+pub const DEFAULT_PORT: u16 = 7410;
+// longest wait for the executor to take a frame
+const FRAME_TIMEOUT: Duration = Duration::from_secs(2);
+// longest request line, a longer one is answered with an error and ends the connection
+const MAX_LINE: u64 = 1 << 20;
+
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const NO_FRAME: i32 = -32000;
+
+type RpcResult = std::result::Result<Json, (i32, String)>;
+
+// binds at once so that a taken port fails here, then serves each client on a
+// thread of its own. Port 0 takes any free one, the address tells which
+pub fn listen(state: RefState, port: u16) -> Result<SocketAddr> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    let address = listener.local_addr()?;
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let state = RefState::clone(&state);
+            std::thread::spawn(move || {
+                _ = serve(&state, stream);
+            });
+        }
+    });
+
+    Ok(address)
+}
+
+fn serve(state: &RefState, stream: TcpStream) -> Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut line = vec![];
+    loop {
+        line.clear();
+        let len = reader
+            .by_ref()
+            .take(MAX_LINE)
+            .read_until(b'\n', &mut line)?;
+        if len == 0 {
+            return Ok(());
+        }
+        if len as u64 == MAX_LINE && line.last() != Some(&b'\n') {
+            let error = error(INVALID_REQUEST, "request line too long");
+            writeln!(writer, "{}", response(Json::Null, Err(error)))?;
+            return Ok(());
+        }
+
+        let line = std::str::from_utf8(&line).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = respond(state, line) {
+            writeln!(writer, "{}", response)?;
+        }
+    }
+}
+
+// the response to a request line, None for a notification
+pub fn respond(state: &RefState, line: &str) -> Option<Json> {
+    let Some(request) = Json::parse(line) else {
+        return Some(response(Json::Null, Err(error(PARSE_ERROR, "parse error"))));
+    };
+
+    let id = request.get("id").cloned();
+    let Some(method) = request.get("method").and_then(Json::as_str) else {
+        return Some(response(
+            id.unwrap_or(Json::Null),
+            Err(error(INVALID_REQUEST, "method must be a string")),
+        ));
+    };
+
+    let params = request.get("params").unwrap_or(&Json::Null);
+    let result = call(state, method, params);
+    Some(response(id?, result))
+}
+
+fn response(id: Json, result: RpcResult) -> Json {
+    let outcome = match result {
+        Ok(result) => ("result", result),
+        Err((code, message)) => (
+            "error",
+            Json::object([("code", code.into()), ("message", message.into())]),
+        ),
+    };
+    Json::object([("jsonrpc", "2.0".into()), ("id", id), outcome])
+}
+
+fn error(code: i32, message: impl Into<String>) -> (i32, String) {
+    (code, message.into())
+}
+
+fn call(state: &RefState, method: &str, params: &Json) -> RpcResult {
+    let mode = || {
+        params
+            .get("mode")
+            .and_then(Json::as_str)
+            .ok_or_else(|| error(INVALID_PARAMS, "mode must be a string"))
+    };
+    let unknown =
+        |what: &str, name: &str| error(INVALID_PARAMS, format!("unknown {}: {}", what, name));
+
+    match method {
+        "get_state" => Ok(get_state(&state.read())),
+        "set_view_mode" => {
+            let name = mode()?;
+            let mode = ViewMode::from_name(name).ok_or_else(|| unknown("view mode", name))?;
+            state.set_view_mode(mode);
+            Ok(Json::Null)
+        }
+        "set_histogram_mode" => {
+            let name = mode()?;
+            let mode =
+                HistogramMode::from_name(name).ok_or_else(|| unknown("histogram mode", name))?;
+            state.set_histogram_mode(mode);
+            Ok(Json::Null)
+        }
+        "set_color_cloud_mode" => {
+            let name = mode()?;
+            let mode =
+                ColorCloudMode::from_name(name).ok_or_else(|| unknown("color cloud mode", name))?;
+            state.set_color_cloud_mode(mode);
+            Ok(Json::Null)
+        }
+        "set_rect" => {
+            let rect = match params.get("rect") {
+                None | Some(Json::Null) => None,
+                Some(rect) => Some(parse_rect(rect)?),
+            };
+            state.set_capture_rect(rect);
+            Ok(Json::Null)
+        }
+        "freeze" => {
+            let frozen = params
+                .get("frozen")
+                .and_then(Json::as_bool)
+                .ok_or_else(|| error(INVALID_PARAMS, "frozen must be a boolean"))?;
+            state.set_frozen(frozen);
+            Ok(Json::Null)
+        }
+        "get_histogram" => {
+            let mode = match params.get("mode") {
+                None | Some(Json::Null) => state.histogram_mode(),
+                Some(_) => {
+                    let name = mode()?;
+                    HistogramMode::from_name(name).ok_or_else(|| unknown("histogram mode", name))?
+                }
+            };
+            get_histogram(state, mode)
+        }
+        "get_stats" => get_stats(state),
+        _ => Err(error(
+            METHOD_NOT_FOUND,
+            format!("unknown method: {}", method),
+        )),
+    }
+}
+
+fn get_state(state: &State) -> Json {
+    let rect = state
+        .capture_rect
+        .map(|rect| vec![rect.left, rect.top, rect.right, rect.bottom]);
+
+    Json::object([
+        ("view_mode", state.view_mode.name().into()),
+        ("histogram_mode", state.histogram_mode.name().into()),
+        ("color_cloud_mode", state.color_cloud_mode.name().into()),
+        ("rect", rect.into()),
+        ("frozen", state.frozen.into()),
+    ])
+}
+
+fn parse_rect(rect: &Json) -> std::result::Result<RECT, (i32, String)> {
+    let invalid = || {
+        error(
+            INVALID_PARAMS,
+            "rect must be [left, top, right, bottom] or null",
+        )
+    };
+
+    let values = rect
+        .as_array()
+        .filter(|values| values.len() == 4)
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|value| {
+            value
+                .as_f64()
+                .filter(|v| v.fract() == 0.0 && v.abs() <= i32::MAX as f64)
+                .map(|v| v as i32)
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(invalid)?;
+
+    let rect = RECT {
+        left: values[0],
+        top: values[1],
+        right: values[2],
+        bottom: values[3],
+    };
+    if rect.right <= rect.left || rect.bottom <= rect.top {
+        return Err(error(INVALID_PARAMS, "rect is empty"));
+    }
+    Ok(rect)
+}
+
+fn get_histogram(state: &RefState, mode: HistogramMode) -> RpcResult {
+    if !mode.is_enable() {
+        return Err(error(INVALID_PARAMS, "no histogram mode"));
+    }
+
+    let image = next_frame(state)?;
+    let mut state = state.read();
+    let pixels = image.pixels.len() as u32;
+
+    let axes = match mode {
+        HistogramMode::Joint(axes) => Some(axes),
+        HistogramMode::Hue(HueShape::Disc) => Some(JointAxes::HueSaturation),
+        _ => None,
+    };
+
+    if let Some(axes) = axes {
+        let counts = joint::histogram(
+            image.pixels.iter().copied(),
+            axes,
+            state.saturation_measure,
+            state.brightness_measure,
+        );
+        return Ok(Json::object([
+            ("mode", mode.name().into()),
+            ("pixels", pixels.into()),
+            ("bins", (JOINT_BINS as u32).into()),
+            ("counts", counts.into()),
+        ]));
+    }
+
+    // the wheel bins hue as the strip does
+    state.histogram_mode = match mode {
+        HistogramMode::Hue(_) => HistogramMode::Hue(HueShape::Linear),
+        mode => mode,
+    };
+    let channels = histogram::bins(&state, &image).unwrap_or_default();
+
+    Ok(Json::object([
+        ("mode", mode.name().into()),
+        ("pixels", pixels.into()),
+        (
+            "channels",
+            channels
+                .iter()
+                .map(|bins| bins.to_vec())
+                .collect::<Vec<_>>()
+                .into(),
+        ),
+    ]))
+}
+
+fn get_stats(state: &RefState) -> RpcResult {
+    state.set_stats_request(true);
+    wait(state, RefState::is_stats_request)?;
+
+    let stats = state
+        .stats()
+        .ok_or_else(|| error(NO_FRAME, "no statistics"))?;
+    let saturation_measure = state.read().saturation_measure;
+
+    Ok(Json::object([
+        ("mean_luma", stats.mean_luma.into()),
+        ("mean_saturation", stats.mean_saturation.into()),
+        ("saturation_measure", saturation_measure.name().into()),
+        ("dominant_hue", stats.dominant_hue.into()),
+        ("shadow_clip", stats.shadow_clip.into()),
+        ("highlight_clip", stats.highlight_clip.into()),
+        ("unique_colors", stats.unique_colors.into()),
+    ]))
+}
+
+// asks the executor for a snapshot of the view area and waits for it
+fn next_frame(state: &RefState) -> std::result::Result<Arc<Image>, (i32, String)> {
+    state.set_frame_request(true);
+    wait(state, RefState::is_frame_request)?;
+
+    state.frame().ok_or_else(|| error(NO_FRAME, "no frame"))
+}
+
+fn wait(
+    state: &RefState,
+    pending: fn(&RefState) -> bool,
+) -> std::result::Result<(), (i32, String)> {
+    let start = Instant::now();
+    while pending(state) {
+        if start.elapsed() > FRAME_TIMEOUT {
+            return Err(error(NO_FRAME, "no frame in time, is the viewer running?"));
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::stats::Stats;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Client {
+        fn connect(state: &RefState) -> Self {
+            let address = listen(RefState::clone(state), 0).unwrap();
+            let writer = TcpStream::connect(address).unwrap();
+            writer.set_read_timeout(Some(2 * FRAME_TIMEOUT)).unwrap();
+            Self {
+                reader: BufReader::new(writer.try_clone().unwrap()),
+                writer,
+            }
+        }
+
+        fn send(&mut self, line: &str) {
+            writeln!(self.writer, "{}", line).unwrap();
+        }
+
+        fn receive(&mut self) -> Json {
+            let mut line = String::new();
+            self.reader.read_line(&mut line).unwrap();
+            Json::parse(&line).unwrap()
+        }
+
+        fn call(&mut self, id: i32, method: &str, params: &str) -> Json {
+            self.send(&format!(
+                r#"{{"jsonrpc": "2.0", "id": {}, "method": "{}", "params": {}}}"#,
+                id, method, params
+            ));
+            let response = self.receive();
+            assert_eq!(response.get("id"), Some(&Json::from(id)));
+            response
+        }
+    }
+
+    fn error_code(response: &Json) -> Option<f64> {
+        response.get("error")?.get("code")?.as_f64()
+    }
+
+    // answers the frame and statistics requests as the executor does, until dropped
+    struct Executor(Arc<AtomicBool>);
+
+    impl Executor {
+        fn spawn(state: &RefState, image: Image, stats: Stats) -> Self {
+            let running = Arc::new(AtomicBool::new(true));
+            let (state, flag) = (RefState::clone(state), Arc::clone(&running));
+            std::thread::spawn(move || {
+                while flag.load(Ordering::Relaxed) {
+                    if state.is_frame_request() {
+                        state.set_frame(Some(Arc::new(image.clone())));
+                        state.set_frame_request(false);
+                    }
+                    if state.is_stats_request() {
+                        state.set_stats(Some(stats));
+                        state.set_stats_request(false);
+                    }
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+            });
+            Self(running)
+        }
+    }
+
+    impl Drop for Executor {
+        fn drop(&mut self) {
+            self.0.store(false, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn sets_the_modes_and_reads_them_back() {
+        let state = RefState::new();
+        let mut client = Client::connect(&state);
+
+        let response = client.call(1, "set_view_mode", r#"{"mode": "hue"}"#);
+        assert_eq!(response.get("result"), Some(&Json::Null));
+        let response = client.call(2, "set_histogram_mode", r#"{"mode": "hue-wheel"}"#);
+        assert_eq!(response.get("result"), Some(&Json::Null));
+        let response = client.call(3, "set_rect", "{\"rect\": [10, 20, 110, 70]}");
+        assert_eq!(response.get("result"), Some(&Json::Null));
+
+        assert_eq!(state.view_mode(), ViewMode::Hue);
+        let result = client.call(4, "get_state", "{}");
+        let result = result.get("result").unwrap();
+        assert_eq!(result.get("view_mode").and_then(Json::as_str), Some("hue"));
+        assert_eq!(
+            result.get("histogram_mode").and_then(Json::as_str),
+            Some("hue-wheel")
+        );
+        assert_eq!(result.get("rect"), Some(&Json::from(vec![10, 20, 110, 70])));
+    }
+
+    #[test]
+    fn reports_the_error_codes() {
+        let state = RefState::new();
+        let mut client = Client::connect(&state);
+
+        client.send("{not json");
+        assert_eq!(error_code(&client.receive()), Some(PARSE_ERROR as f64));
+
+        let response = client.call(1, "set_view_mode", r#"{"mode": "hu"}"#);
+        assert_eq!(error_code(&response), Some(INVALID_PARAMS as f64));
+        let response = client.call(2, "set_rect", r#"{"rect": [0, 0, 0, 0]}"#);
+        assert_eq!(error_code(&response), Some(INVALID_PARAMS as f64));
+        let response = client.call(3, "load", r#"{"path": "frame.ppm"}"#);
+        assert_eq!(error_code(&response), Some(METHOD_NOT_FOUND as f64));
+
+        // a notification is carried out without a response
+        client.send(r#"{"jsonrpc": "2.0", "method": "freeze", "params": {"frozen": true}}"#);
+        let response = client.call(4, "get_state", "null");
+        let result = response.get("result").unwrap();
+        assert_eq!(result.get("frozen"), Some(&Json::Bool(true)));
+    }
+
+    #[test]
+    fn pulls_the_results_of_the_next_frame() {
+        let state = RefState::new();
+        let image = Image {
+            width: 2,
+            height: 1,
+            pixels: vec![[255, 0, 0], [0, 0, 0]],
+        };
+        let stats = Stats {
+            mean_luma: 0.25,
+            mean_saturation: 0.5,
+            dominant_hue: Some(5.0),
+            shadow_clip: 0.5,
+            highlight_clip: 0.5,
+            unique_colors: 2,
+        };
+        let _executor = Executor::spawn(&state, image, stats);
+        let mut client = Client::connect(&state);
+
+        let response = client.call(1, "get_stats", "{}");
+        let result = response.get("result").unwrap();
+        assert_eq!(result.get("mean_luma").and_then(Json::as_f64), Some(0.25));
+        assert_eq!(
+            result.get("saturation_measure").and_then(Json::as_str),
+            Some("chroma")
+        );
+        assert_eq!(
+            result.get("unique_colors").and_then(Json::as_f64),
+            Some(2.0)
+        );
+
+        let response = client.call(2, "get_histogram", r#"{"mode": "rgb"}"#);
+        let result = response.get("result").unwrap();
+        assert_eq!(result.get("pixels").and_then(Json::as_f64), Some(2.0));
+        let red = &result.get("channels").unwrap().as_array().unwrap()[0];
+        let red = red.as_array().unwrap();
+        assert_eq!((red[0].as_f64(), red[255].as_f64()), (Some(1.0), Some(1.0)));
+    }
+
+    #[test]
+    fn times_out_without_a_viewer() {
+        let state = RefState::new();
+        let mut client = Client::connect(&state);
+
+        let response = client.call(1, "get_stats", "{}");
+        assert_eq!(error_code(&response), Some(NO_FRAME as f64));
+    }
+
+    #[test]
+    fn ends_the_connection_on_an_overlong_line() {
+        let state = RefState::new();
+        let mut client = Client::connect(&state);
+
+        // exactly the longest line and no newline, nothing is left unread
+        let line = " ".repeat(MAX_LINE as usize);
+        client.writer.write_all(line.as_bytes()).unwrap();
+        assert_eq!(error_code(&client.receive()), Some(INVALID_REQUEST as f64));
+
+        let mut rest = String::new();
+        assert_eq!(client.reader.read_line(&mut rest).unwrap(), 0);
+    }
+}
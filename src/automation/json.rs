@@ -0,0 +1,393 @@
+use std::fmt::{self, Display, Formatter, Write};
+
+// nesting beyond this is refused rather than recursed into
+const MAX_DEPTH: usize = 64;
+
+// a JSON value, objects keep the order of their keys
+#[derive(Clone, PartialEq, Debug)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    // a whole document, None for anything malformed or trailing
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parser = Parser {
+            chars: text.chars().peekable(),
+        };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        parser.chars.peek().is_none().then_some(value)
+    }
+
+    pub fn object<'a>(fields: impl IntoIterator<Item = (&'a str, Json)>) -> Self {
+        Self::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Self::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Self::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f32> for Json {
+    fn from(value: f32) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<i32> for Json {
+    fn from(value: i32) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<u32> for Json {
+    fn from(value: u32) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(values: Vec<T>) -> Self {
+        Self::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+// compact, on a single line
+impl Display for Json {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(b) => write!(f, "{}", b),
+            // no NaN or infinity in JSON
+            Self::Number(n) if !n.is_finite() => f.write_str("null"),
+            Self::Number(n) => write!(f, "{}", n),
+            Self::String(s) => write_string(f, s),
+            Self::Array(values) => {
+                f.write_char('[')?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_char(']')
+            }
+            Self::Object(fields) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut Formatter, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self
+            .chars
+            .next_if(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+            .is_some()
+        {}
+    }
+
+    fn value(&mut self, depth: usize) -> Option<Json> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        self.skip_whitespace();
+        match *self.chars.peek()? {
+            'n' => self.literal("null", Json::Null),
+            't' => self.literal("true", Json::Bool(true)),
+            'f' => self.literal("false", Json::Bool(false)),
+            '"' => self.string().map(Json::String),
+            '[' => self.array(depth),
+            '{' => self.object(depth),
+            _ => self.number(),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Option<Json> {
+        for expected in word.chars() {
+            self.chars.next_if_eq(&expected)?;
+        }
+        Some(value)
+    }
+
+    // -? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?
+    fn number(&mut self) -> Option<Json> {
+        let mut text = String::new();
+        text.extend(self.chars.next_if_eq(&'-'));
+
+        match self.chars.next_if(|c| c.is_ascii_digit())? {
+            '0' => text.push('0'),
+            c => {
+                text.push(c);
+                self.digits(&mut text);
+            }
+        }
+
+        if let Some(c) = self.chars.next_if_eq(&'.') {
+            text.push(c);
+            if self.digits(&mut text) == 0 {
+                return None;
+            }
+        }
+
+        if let Some(c) = self.chars.next_if(|&c| c == 'e' || c == 'E') {
+            text.push(c);
+            text.extend(self.chars.next_if(|&c| c == '+' || c == '-'));
+            if self.digits(&mut text) == 0 {
+                return None;
+            }
+        }
+
+        text.parse().ok().map(Json::Number)
+    }
+
+    fn digits(&mut self, text: &mut String) -> usize {
+        let len = text.len();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit()) {
+            text.push(c);
+        }
+        text.len() - len
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.chars.next_if_eq(&'"')?;
+
+        let mut string = String::new();
+        loop {
+            match self.chars.next()? {
+                '"' => return Some(string),
+                '\\' => match self.chars.next()? {
+                    '"' => string.push('"'),
+                    '\\' => string.push('\\'),
+                    '/' => string.push('/'),
+                    'b' => string.push('\u{8}'),
+                    'f' => string.push('\u{c}'),
+                    'n' => string.push('\n'),
+                    'r' => string.push('\r'),
+                    't' => string.push('\t'),
+                    'u' => {
+                        let unit = self.hex4()?;
+                        // a surrogate pair spells one code point
+                        let code = if (0xd800..0xdc00).contains(&unit) {
+                            self.chars.next_if_eq(&'\\')?;
+                            self.chars.next_if_eq(&'u')?;
+                            let low = self.hex4().filter(|low| (0xdc00..0xe000).contains(low))?;
+                            0x10000 + ((unit - 0xd800) << 10) + (low - 0xdc00)
+                        } else {
+                            unit
+                        };
+                        string.push(char::from_u32(code)?);
+                    }
+                    _ => return None,
+                },
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        (0..4).try_fold(0, |code, _| {
+            Some(code << 4 | self.chars.next()?.to_digit(16)?)
+        })
+    }
+
+    fn array(&mut self, depth: usize) -> Option<Json> {
+        self.chars.next_if_eq(&'[')?;
+
+        let mut values = vec![];
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&']').is_some() {
+            return Some(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.chars.next()? {
+                ',' => continue,
+                ']' => return Some(Json::Array(values)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Option<Json> {
+        self.chars.next_if_eq(&'{')?;
+
+        let mut fields = vec![];
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'}').is_some() {
+            return Some(Json::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.chars.next_if_eq(&':')?;
+            fields.push((key, self.value(depth + 1)?));
+            self.skip_whitespace();
+            match self.chars.next()? {
+                ',' => continue,
+                '}' => return Some(Json::Object(fields)),
+                _ => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_follow_the_grammar() {
+        for (text, value) in [
+            ("0", 0.0),
+            ("-0", 0.0),
+            ("12", 12.0),
+            ("-3.25", -3.25),
+            ("1e3", 1000.0),
+            ("2.5E-1", 0.25),
+            ("1e+2", 100.0),
+        ] {
+            assert_eq!(Json::parse(text), Some(Json::Number(value)), "{}", text);
+        }
+
+        for text in [
+            "+1", ".5", "1.", "01", "-", "1e", "1e+", "--1", "1.e3", "0x10",
+        ] {
+            assert_eq!(Json::parse(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let nested = |depth: usize| "[".repeat(depth) + &"]".repeat(depth);
+        assert!(Json::parse(&nested(MAX_DEPTH + 1)).is_some());
+        assert!(Json::parse(&nested(MAX_DEPTH + 2)).is_none());
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        assert_eq!(
+            Json::parse(r#""\ud83c\udfa8""#),
+            Some(Json::String("\u{1f3a8}".to_string()))
+        );
+        assert_eq!(
+            Json::parse(r#""\u00e9""#),
+            Some(Json::String("\u{e9}".to_string()))
+        );
+        // lone or reversed halves
+        for text in [
+            r#""\ud83c""#,
+            r#""\ud83cx""#,
+            r#""\udfa8""#,
+            r#""\ud83c\u0041""#,
+        ] {
+            assert_eq!(Json::parse(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = r#"{"method":"set_rect","params":{"rect":[0,0,640,480]},"id":null}"#;
+        assert_eq!(Json::parse(text).unwrap().to_string(), text);
+    }
+}
@@ -0,0 +1,276 @@
+// Camera of the IroSphere. The cloud is seen from the -z side in view space,
+// zoom and pan apply on the screen plane, and the perspective keeps the
+// orthographic size of the plane through the middle of the cloud.
+
+use glam::{Mat4, Quat, Vec2, Vec3, Vec4};
+
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 8.0;
+
+// distance from the eye to the middle of the cloud, in units of its radius
+const EYE_DISTANCE: f32 = 3.0;
+
+const TRANSITION_SECONDS: f32 = 0.4;
+const TURNTABLE_DEGREES_PER_SECOND: f32 = 20.0;
+
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum CameraPreset {
+    // looking along the lightness axis onto the hue wheel
+    Top,
+    // lightness axis up
+    #[default]
+    Side,
+    // looking along the grey diagonal of the RGB cube from white
+    Diagonal,
+}
+
+impl CameraPreset {
+    pub const ALL: [Self; 3] = [Self::Top, Self::Side, Self::Diagonal];
+
+    // rotation of the cloud into view space
+    fn view(&self) -> Quat {
+        match *self {
+            Self::Top => Quat::from_rotation_x(-90f32.to_radians()),
+            Self::Side => Quat::IDENTITY,
+            Self::Diagonal => Quat::from_rotation_arc(Vec3::ONE.normalize(), Vec3::NEG_Z),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Pose {
+    rotation: Quat,
+    zoom: f32,
+    pan: Vec2,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Transition {
+    from: Pose,
+    to: Pose,
+    time: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Camera {
+    // rotation of the view around the cloud, the cloud is seen through its inverse
+    pub rotation: Quat,
+    pub zoom: f32,
+    // offset on the screen plane
+    pub pan: Vec2,
+    pub perspective: bool,
+    pub turntable: bool,
+    transition: Option<Transition>,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            rotation: Quat::IDENTITY,
+            zoom: 1.0,
+            pan: Vec2::ZERO,
+            perspective: false,
+            turntable: false,
+            transition: None,
+        }
+    }
+}
+
+impl Camera {
+    // the cloud into clip space before the aspect scale of the passes, the
+    // perspective divisor is 1 on the plane through the middle of the cloud
+    pub fn projection(&self) -> Mat4 {
+        let k = if self.perspective {
+            1.0 / EYE_DISTANCE
+        } else {
+            0.0
+        };
+
+        let lens = Mat4::from_cols(
+            Vec4::new(self.zoom, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, self.zoom, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, 1.0, k),
+            Vec4::W,
+        );
+        let pan = Mat4::from_cols(Vec4::X, Vec4::Y, Vec4::Z, self.pan.extend(0.0).extend(1.0));
+
+        pan * lens * self.view()
+    }
+
+    // rotation only, for shapes placed in view space
+    pub fn view(&self) -> Mat4 {
+        Mat4::from_quat(self.rotation.inverse())
+    }
+
+    // a screen position onto the plane through the middle of the cloud
+    pub fn unproject(&self, position: Vec2) -> Vec2 {
+        (position - self.pan) / self.zoom
+    }
+
+    // drags the point under `from` to `to`, both in screen units where the
+    // cloud has radius 1 at zoom 1
+    pub fn arcball(&mut self, from: Vec2, to: Vec2) {
+        let a = arcball_point(self.unproject(from));
+        let b = arcball_point(self.unproject(to));
+
+        let drag = Quat::from_rotation_arc(a, b);
+        self.rotation = (self.rotation * drag.inverse()).normalize();
+        self.transition = None;
+    }
+
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.transition = None;
+    }
+
+    pub fn pan_by(&mut self, delta: Vec2) {
+        self.pan += delta;
+        self.transition = None;
+    }
+
+    // animates to a preset, zoom and pan reset
+    pub fn look(&mut self, preset: CameraPreset) {
+        self.animate_to(Pose {
+            rotation: preset.view().inverse(),
+            zoom: 1.0,
+            pan: Vec2::ZERO,
+        });
+    }
+
+    // a preset without the animation
+    pub fn jump(&mut self, preset: CameraPreset) {
+        self.rotation = preset.view().inverse();
+        self.zoom = 1.0;
+        self.pan = Vec2::ZERO;
+        self.transition = None;
+    }
+
+    pub fn reset(&mut self) {
+        self.look(CameraPreset::default());
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.transition.is_some() || self.turntable
+    }
+
+    // advances the transition and the turntable by `seconds`
+    pub fn update(&mut self, seconds: f32) {
+        if let Some(mut transition) = self.transition {
+            transition.time += seconds / TRANSITION_SECONDS;
+
+            let t = transition.time.min(1.0);
+            let t = t * t * (3.0 - 2.0 * t);
+            let (from, to) = (transition.from, transition.to);
+
+            self.rotation = from.rotation.slerp(to.rotation, t).normalize();
+            self.zoom = from.zoom + (to.zoom - from.zoom) * t;
+            self.pan = from.pan.lerp(to.pan, t);
+
+            self.transition = (transition.time < 1.0).then_some(transition);
+        } else if self.turntable {
+            self.turn((TURNTABLE_DEGREES_PER_SECOND * seconds).to_radians());
+        }
+    }
+
+    // around the lightness axis of the cloud
+    pub fn turn(&mut self, angle: f32) {
+        self.rotation = (Quat::from_rotation_y(-angle) * self.rotation).normalize();
+    }
+
+    fn animate_to(&mut self, to: Pose) {
+        let from = Pose {
+            rotation: self.rotation,
+            zoom: self.zoom,
+            pan: self.pan,
+        };
+
+        // the short way round
+        let to = Pose {
+            rotation: if from.rotation.dot(to.rotation) < 0.0 {
+                -to.rotation
+            } else {
+                to.rotation
+            },
+            ..to
+        };
+
+        self.transition = Some(Transition {
+            from,
+            to,
+            time: 0.0,
+        });
+    }
+}
+
+// the hemisphere facing the eye, and its rim for points outside
+fn arcball_point(position: Vec2) -> Vec3 {
+    let d = position.length_squared();
+    if d < 1.0 {
+        position.extend(-(1.0 - d).sqrt())
+    } else {
+        (position / d.sqrt()).extend(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        a.abs_diff_eq(b, 1e-5)
+    }
+
+    #[test]
+    fn presets_look_along_their_axis() {
+        let mut camera = Camera::default();
+        assert!(close(camera.view().transform_vector3(Vec3::Y), Vec3::Y));
+
+        camera.jump(CameraPreset::Top);
+        assert!(close(camera.view().transform_vector3(Vec3::Y), Vec3::NEG_Z));
+
+        camera.jump(CameraPreset::Diagonal);
+        let grey = Vec3::ONE.normalize();
+        assert!(close(camera.view().transform_vector3(grey), Vec3::NEG_Z));
+    }
+
+    #[test]
+    fn arcball_keeps_the_point_under_the_cursor() {
+        let mut camera = Camera::default();
+        camera.jump(CameraPreset::Diagonal);
+
+        let (from, to) = (Vec2::new(0.1, -0.2), Vec2::new(0.5, 0.3));
+        let point = camera.rotation * arcball_point(from);
+        camera.arcball(from, to);
+
+        let seen = camera.view().transform_vector3(point);
+        assert!(close(seen, arcball_point(to)));
+    }
+
+    #[test]
+    fn turning_keeps_the_lightness_axis() {
+        let mut camera = Camera::default();
+        camera.turn(1.0);
+        assert!(close(camera.view().transform_vector3(Vec3::Y), Vec3::Y));
+        assert!(!close(camera.view().transform_vector3(Vec3::X), Vec3::X));
+    }
+
+    #[test]
+    fn look_ends_on_the_preset() {
+        let mut camera = Camera::default();
+        camera.zoom_by(100.0);
+        assert_eq!(camera.zoom, MAX_ZOOM);
+        camera.pan_by(Vec2::ONE);
+
+        camera.look(CameraPreset::Top);
+        assert!(camera.is_animating());
+        camera.update(TRANSITION_SECONDS * 0.5);
+        assert!(camera.is_animating());
+        camera.update(TRANSITION_SECONDS);
+        assert!(!camera.is_animating());
+
+        let mut expected = Camera::default();
+        expected.jump(CameraPreset::Top);
+        assert!(camera.rotation.abs_diff_eq(expected.rotation, 1e-5));
+        assert_eq!((camera.zoom, camera.pan), (1.0, Vec2::ZERO));
+    }
+}
@@ -0,0 +1,10 @@
+pub mod chromaticity;
+mod conversion;
+pub mod difference;
+pub mod gamut;
+pub mod measure;
+pub mod naming;
+pub mod palette;
+pub mod ycbcr;
+
+pub use self::conversion::*;
@@ -0,0 +1,276 @@
+use super::gamut::Gamut;
+use super::*;
+use glam::{Vec2, Vec3, Vec4};
+
+// bins per axis of the density plot, x fastest
+pub const CHROMATICITY_BINS: usize = 128;
+
+// CIE 1931 2 degree observer, x and y of the monochromatic lights of 380 ~ 700 nm
+// in 5 nm steps. the locus barely moves beyond 700 nm
+pub const SPECTRAL_LOCUS: [[f32; 2]; 65] = [
+    [0.1741, 0.0050],
+    [0.1740, 0.0050],
+    [0.1738, 0.0049],
+    [0.1736, 0.0049],
+    [0.1733, 0.0048],
+    [0.1730, 0.0048],
+    [0.1726, 0.0048],
+    [0.1721, 0.0048],
+    [0.1714, 0.0051],
+    [0.1703, 0.0058],
+    [0.1689, 0.0069],
+    [0.1669, 0.0086],
+    [0.1644, 0.0109],
+    [0.1611, 0.0138],
+    [0.1566, 0.0177],
+    [0.1510, 0.0227],
+    [0.1440, 0.0297],
+    [0.1355, 0.0399],
+    [0.1241, 0.0578],
+    [0.1096, 0.0868],
+    [0.0913, 0.1327],
+    [0.0687, 0.2007],
+    [0.0454, 0.2950],
+    [0.0235, 0.4127],
+    [0.0082, 0.5384],
+    [0.0039, 0.6548],
+    [0.0139, 0.7502],
+    [0.0389, 0.8120],
+    [0.0743, 0.8338],
+    [0.1142, 0.8262],
+    [0.1547, 0.8059],
+    [0.1929, 0.7816],
+    [0.2296, 0.7543],
+    [0.2658, 0.7243],
+    [0.3016, 0.6923],
+    [0.3373, 0.6589],
+    [0.3731, 0.6245],
+    [0.4087, 0.5896],
+    [0.4441, 0.5547],
+    [0.4788, 0.5202],
+    [0.5125, 0.4866],
+    [0.5448, 0.4544],
+    [0.5752, 0.4242],
+    [0.6029, 0.3965],
+    [0.6270, 0.3725],
+    [0.6482, 0.3514],
+    [0.6658, 0.3340],
+    [0.6801, 0.3197],
+    [0.6915, 0.3083],
+    [0.7006, 0.2993],
+    [0.7079, 0.2920],
+    [0.7140, 0.2859],
+    [0.7190, 0.2809],
+    [0.7230, 0.2770],
+    [0.7260, 0.2740],
+    [0.7283, 0.2717],
+    [0.7300, 0.2700],
+    [0.7311, 0.2689],
+    [0.7320, 0.2680],
+    [0.7327, 0.2673],
+    [0.7334, 0.2666],
+    [0.7340, 0.2660],
+    [0.7344, 0.2656],
+    [0.7346, 0.2654],
+    [0.7347, 0.2653],
+];
+
+pub const D65: Vec2 = Vec2::new(0.3127, 0.3290);
+
+const LOCUS_COLOR: Vec4 = Vec4::new(0.9, 0.9, 0.9, 0.9);
+const AXIS_COLOR: Vec4 = Vec4::new(0.8, 0.8, 0.8, 0.6);
+const WHITE_POINT_COLOR: Vec4 = Vec4::new(1.0, 1.0, 1.0, 0.9);
+const GAMUT_COLORS: [(Gamut, Vec4); 3] = [
+    (Gamut::Srgb, Vec4::new(1.0, 1.0, 1.0, 0.8)),
+    (Gamut::DisplayP3, Vec4::new(1.0, 0.8, 0.3, 0.8)),
+    (Gamut::Rec2020, Vec4::new(0.3, 0.8, 1.0, 0.8)),
+];
+
+// in the coordinates of the diagram
+const TICK_STEP: f32 = 0.1;
+const TICK_LENGTH: f32 = 0.01;
+const WHITE_POINT_SIZE: f32 = 0.01;
+
+// the shaders switch on the discriminant
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum Diagram {
+    // CIE 1931 xy
+    #[default]
+    Xy,
+    // CIE 1976 u'v', closer to perceptually uniform
+    Uv,
+}
+
+impl Diagram {
+    pub fn project(&self, xy: Vec2) -> Vec2 {
+        match *self {
+            Self::Xy => xy,
+            Self::Uv => xy_to_uv(xy),
+        }
+    }
+
+    pub fn unproject(&self, position: Vec2) -> Vec2 {
+        match *self {
+            Self::Xy => position,
+            Self::Uv => uv_to_xy(position),
+        }
+    }
+
+    // both axes are plotted over 0 ~ range, which holds the whole locus
+    pub fn range(&self) -> f32 {
+        match *self {
+            Self::Xy => 0.9,
+            Self::Uv => 0.65,
+        }
+    }
+
+    // the diagram position of a color, None for black
+    pub fn position(&self, rgb: Vec3) -> Option<Vec2> {
+        rgb_to_xy(rgb).map(|xy| self.project(xy))
+    }
+
+    // matches ChromaticityBin in chromaticity.hlsl
+    pub fn bin(&self, rgb: Vec3) -> Option<usize> {
+        let t = self.position(rgb)? / self.range();
+        if !(0.0..1.0).contains(&t.x) || !(0.0..1.0).contains(&t.y) {
+            return None;
+        }
+
+        let index = |t: f32| ((CHROMATICITY_BINS as f32 * t) as usize).min(CHROMATICITY_BINS - 1);
+        Some(index(t.y) * CHROMATICITY_BINS + index(t.x))
+    }
+
+    // the spectral locus closed by the line of purples, the gamut triangles,
+    // the white point and the axes as a line list in diagram coordinates
+    pub fn guides(&self) -> Vec<(Vec2, Vec4)> {
+        let mut lines = vec![];
+        let mut push = |from: Vec2, to: Vec2, color: Vec4| {
+            lines.push((from, color));
+            lines.push((to, color));
+        };
+
+        let locus: Vec<Vec2> = SPECTRAL_LOCUS
+            .iter()
+            .map(|&xy| self.project(Vec2::from(xy)))
+            .collect();
+        for i in 0..locus.len() {
+            push(locus[i], locus[(i + 1) % locus.len()], LOCUS_COLOR);
+        }
+
+        for (gamut, color) in GAMUT_COLORS {
+            let primaries = gamut.primaries().map(|xy| self.project(xy));
+            for i in 0..3 {
+                push(primaries[i], primaries[(i + 1) % 3], color);
+            }
+        }
+
+        let white = self.project(D65);
+        push(
+            white - Vec2::X * WHITE_POINT_SIZE,
+            white + Vec2::X * WHITE_POINT_SIZE,
+            WHITE_POINT_COLOR,
+        );
+        push(
+            white - Vec2::Y * WHITE_POINT_SIZE,
+            white + Vec2::Y * WHITE_POINT_SIZE,
+            WHITE_POINT_COLOR,
+        );
+
+        let range = self.range();
+        push(Vec2::ZERO, Vec2::X * range, AXIS_COLOR);
+        push(Vec2::ZERO, Vec2::Y * range, AXIS_COLOR);
+
+        let num_ticks = (range / TICK_STEP) as u32;
+        for i in 1..=num_ticks {
+            let t = i as f32 * TICK_STEP;
+            push(Vec2::new(t, 0.0), Vec2::new(t, -TICK_LENGTH), AXIS_COLOR);
+            push(Vec2::new(0.0, t), Vec2::new(-TICK_LENGTH, t), AXIS_COLOR);
+        }
+
+        lines
+    }
+}
+
+// the chromaticity of an sRGB color, None for black
+pub fn rgb_to_xy(rgb: Vec3) -> Option<Vec2> {
+    let xyz = rgb_to_xyz(rgb);
+    let sum = xyz.x + xyz.y + xyz.z;
+    (sum > 0.0).then(|| Vec2::new(xyz.x, xyz.y) / sum)
+}
+
+pub fn xy_to_uv(xy: Vec2) -> Vec2 {
+    let d = -2.0 * xy.x + 12.0 * xy.y + 3.0;
+    Vec2::new(4.0 * xy.x, 9.0 * xy.y) / d
+}
+
+pub fn uv_to_xy(uv: Vec2) -> Vec2 {
+    let d = 6.0 * uv.x - 16.0 * uv.y + 12.0;
+    Vec2::new(9.0 * uv.x, 4.0 * uv.y) / d
+}
+
+// the brightest sRGB color of a chromaticity, its hue where it is out of gamut.
+// matches ChromaticityToRgb in chromaticity.hlsl after Diagram::unproject
+pub fn xy_to_rgb(xy: Vec2) -> Vec3 {
+    let y = xy.y.max(1e-4);
+    let xyz = Vec3::new(xy.x / y, 1.0, (1.0 - xy.x - xy.y) / y);
+
+    let linear = Vec3::new(
+        Vec3::new(3.2406, -1.5372, -0.4986).dot(xyz),
+        Vec3::new(-0.9689, 1.8758, 0.0415).dot(xyz),
+        Vec3::new(0.0557, -0.2040, 1.0570).dot(xyz),
+    )
+    .max(Vec3::ZERO);
+
+    let linear = linear / linear.max_element().max(1e-4);
+    linear.to_array().map(linear_to_srgb).into()
+}
+
+// opacity of a bin of the density plot, matches DensityAlpha in chromaticity.hlsl
+pub fn density_alpha(count: u32, num_pixels: u32) -> f32 {
+    if count == 0 {
+        return 0.0;
+    }
+    let density = count as f32 / num_pixels as f32 * (CHROMATICITY_BINS * CHROMATICITY_BINS) as f32;
+    0.3 + 0.7 * ((1.0 + density).log2() / 8.0).clamp(0.0, 1.0)
+}
+
+// the same counts the GPU accumulates, for exports
+pub fn histogram(pixels: impl IntoIterator<Item = [u8; 3]>, diagram: Diagram) -> Vec<u32> {
+    let mut counts = vec![0; CHROMATICITY_BINS * CHROMATICITY_BINS];
+    for rgb in pixels {
+        if let Some(bin) = diagram.bin(from_rgb8(rgb)) {
+            counts[bin] += 1;
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uv_round_trips() {
+        let uv = xy_to_uv(D65);
+        assert!(uv.abs_diff_eq(Vec2::new(0.1978, 0.4683), 1e-4));
+
+        for xy in SPECTRAL_LOCUS.iter().map(|&xy| Vec2::from(xy)).chain([D65]) {
+            assert!(uv_to_xy(xy_to_uv(xy)).abs_diff_eq(xy, 1e-5), "{xy}");
+            for diagram in [Diagram::Xy, Diagram::Uv] {
+                let position = diagram.project(xy);
+                assert!(diagram.unproject(position).abs_diff_eq(xy, 1e-5));
+            }
+        }
+    }
+
+    #[test]
+    fn white_sits_on_the_white_point() {
+        assert!(rgb_to_xy(Vec3::ONE).unwrap().abs_diff_eq(D65, 1e-3));
+        assert_eq!(rgb_to_xy(Vec3::ZERO), None);
+
+        let counts = histogram([[255; 3], [255; 3], [0; 3]], Diagram::Uv);
+        let bin = Diagram::Uv.bin(Vec3::ONE).unwrap();
+        assert_eq!(counts[bin], 2);
+        assert_eq!(counts.iter().sum::<u32>(), 2);
+    }
+}
@@ -0,0 +1,150 @@
+use glam::Vec3;
+
+// D65 reference white
+const WHITE: Vec3 = Vec3::new(0.95047, 1.0, 1.08883);
+
+pub fn from_rgb8(rgb: [u8; 3]) -> Vec3 {
+    Vec3::new(rgb[0] as f32, rgb[1] as f32, rgb[2] as f32) / 255.0
+}
+
+// clamped and rounded
+pub fn to_rgb8(rgb: Vec3) -> [u8; 3] {
+    let rgb = (rgb.clamp(Vec3::ZERO, Vec3::ONE) * 255.0).round();
+    [rgb.x as u8, rgb.y as u8, rgb.z as u8]
+}
+
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub fn rgb_to_xyz(rgb: Vec3) -> Vec3 {
+    let r = srgb_to_linear(rgb.x);
+    let g = srgb_to_linear(rgb.y);
+    let b = srgb_to_linear(rgb.z);
+
+    Vec3::new(
+        0.4124 * r + 0.3576 * g + 0.1805 * b,
+        0.2126 * r + 0.7152 * g + 0.0722 * b,
+        0.0193 * r + 0.1192 * g + 0.9505 * b,
+    )
+}
+
+pub fn xyz_to_lab(xyz: Vec3) -> Vec3 {
+    fn f(t: f32) -> f32 {
+        const DELTA: f32 = 6.0 / 29.0;
+
+        if t > DELTA * DELTA * DELTA {
+            t.cbrt()
+        } else {
+            t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+        }
+    }
+
+    let x = f(xyz.x / WHITE.x);
+    let y = f(xyz.y / WHITE.y);
+    let z = f(xyz.z / WHITE.z);
+
+    Vec3::new(116.0 * y - 16.0, 500.0 * (x - y), 200.0 * (y - z))
+}
+
+// CIE 1976 L*a*b*, D65
+pub fn rgb_to_lab(rgb: Vec3) -> Vec3 {
+    xyz_to_lab(rgb_to_xyz(rgb))
+}
+
+// Björn Ottosson's Oklab, L in 0 ~ 1
+pub fn rgb_to_oklab(rgb: Vec3) -> Vec3 {
+    let r = srgb_to_linear(rgb.x);
+    let g = srgb_to_linear(rgb.y);
+    let b = srgb_to_linear(rgb.z);
+
+    let l = (0.41222147 * r + 0.53633254 * g + 0.051445993 * b).cbrt();
+    let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+    let s = (0.08830246 * r + 0.28171884 * g + 0.6299787 * b).cbrt();
+
+    Vec3::new(
+        0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+        1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+        0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+    )
+}
+
+pub fn to_hex(rgb: [u8; 3]) -> String {
+    format!("#{:02X}{:02X}{:02X}", rgb[0], rgb[1], rgb[2])
+}
+
+// "#RRGGBB", "RRGGBB" or "#RGB"
+pub fn parse_hex(text: &str) -> Option<[u8; 3]> {
+    let text = text.trim();
+    let hex = text.strip_prefix('#').unwrap_or(text);
+
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    match hex.len() {
+        6 => {
+            let value = u32::from_str_radix(hex, 16).ok()?;
+            Some([(value >> 16) as u8, (value >> 8) as u8, value as u8])
+        }
+        3 => {
+            let value = u32::from_str_radix(hex, 16).ok()?;
+            let expand = |v: u32| (17 * (v & 0xf)) as u8;
+            Some([expand(value >> 8), expand(value >> 4), expand(value)])
+        }
+        _ => None,
+    }
+}
+
+// matches HslToRgb in common.hlsl
+pub fn hsl_to_rgb(hue: f32, saturation: f32, luminance: f32) -> Vec3 {
+    let h = 360.0 * (hue + 1.0).fract();
+    let ma = luminance + 0.5 * saturation;
+    let mi = luminance - 0.5 * saturation;
+    let mm = ma - mi;
+
+    if h < 60.0 {
+        Vec3::new(ma, mi + mm * h / 60.0, mi)
+    } else if h < 120.0 {
+        Vec3::new(mi + mm * (120.0 - h) / 60.0, ma, mi)
+    } else if h < 180.0 {
+        Vec3::new(mi, ma, mi + mm * (h - 120.0) / 60.0)
+    } else if h < 240.0 {
+        Vec3::new(mi, mi + mm * (240.0 - h) / 60.0, ma)
+    } else if h < 300.0 {
+        Vec3::new(mi + mm * (h - 240.0) / 60.0, mi, ma)
+    } else {
+        Vec3::new(ma, mi, mi + mm * (360.0 - h) / 60.0)
+    }
+}
+
+// matches HsvToRgb in common.hlsl
+pub fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> Vec3 {
+    let h = (hue + 1.0).fract();
+    let i = (359.9999 * h / 60.0) as u32;
+    let f = 6.0 * h - i as f32;
+    let s = saturation;
+    let v = value;
+
+    match i {
+        0 => Vec3::new(v, v * (1.0 - s * (1.0 - f)), v * (1.0 - s)),
+        1 => Vec3::new(v * (1.0 - s * f), v, v * (1.0 - s)),
+        2 => Vec3::new(v * (1.0 - s), v, v * (1.0 - s * (1.0 - f))),
+        3 => Vec3::new(v * (1.0 - s), v * (1.0 - s * f), v),
+        4 => Vec3::new(v * (1.0 - s * (1.0 - f)), v * (1.0 - s), v),
+        5 => Vec3::new(v, v * (1.0 - s), v * (1.0 - s * f)),
+        _ => Vec3::splat(v),
+    }
+}
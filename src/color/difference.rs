@@ -0,0 +1,254 @@
+// Color difference formulas on CIE L*a*b*, after Sharma, Wu and Dalal,
+// "The CIEDE2000 Color-Difference Formula: Implementation Notes" (2005).
+
+use glam::Vec3;
+
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum DeltaE {
+    Cie76,
+    Cie94,
+    #[default]
+    Ciede2000,
+}
+
+impl DeltaE {
+    pub const ALL: [Self; 3] = [Self::Cie76, Self::Cie94, Self::Ciede2000];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Self::Cie76 => "dE76",
+            Self::Cie94 => "dE94",
+            Self::Ciede2000 => "dE00",
+        }
+    }
+
+    // `reference` matters for CIE94, which is not symmetric
+    pub fn between(&self, reference: Vec3, sample: Vec3) -> f32 {
+        match *self {
+            Self::Cie76 => delta_e76(reference, sample),
+            Self::Cie94 => delta_e94(reference, sample),
+            Self::Ciede2000 => delta_e2000(reference, sample),
+        }
+    }
+}
+
+pub fn delta_e76(lab1: Vec3, lab2: Vec3) -> f32 {
+    lab1.distance(lab2)
+}
+
+// graphic arts weights, kL = 1, K1 = 0.045, K2 = 0.015
+pub fn delta_e94(lab1: Vec3, lab2: Vec3) -> f32 {
+    let c1 = lab1.y.hypot(lab1.z);
+    let c2 = lab2.y.hypot(lab2.z);
+
+    let dl = lab1.x - lab2.x;
+    let dc = c1 - c2;
+    let da = lab1.y - lab2.y;
+    let db = lab1.z - lab2.z;
+    let dh2 = (da * da + db * db - dc * dc).max(0.0);
+
+    let sc = 1.0 + 0.045 * c1;
+    let sh = 1.0 + 0.015 * c1;
+
+    (dl * dl + (dc / sc).powi(2) + dh2 / (sh * sh)).sqrt()
+}
+
+pub fn delta_e2000(lab1: Vec3, lab2: Vec3) -> f32 {
+    const POW25_7: f32 = 6103515625.0; // 25^7
+
+    let (l1, a1, b1) = (lab1.x, lab1.y, lab1.z);
+    let (l2, a2, b2) = (lab2.x, lab2.y, lab2.z);
+
+    let c_mean = 0.5 * (a1.hypot(b1) + a2.hypot(b2));
+    let c_mean7 = c_mean.powi(7);
+    let g = 0.5 * (1.0 - (c_mean7 / (c_mean7 + POW25_7)).sqrt());
+
+    let a1 = (1.0 + g) * a1;
+    let a2 = (1.0 + g) * a2;
+    let c1 = a1.hypot(b1);
+    let c2 = a2.hypot(b2);
+    let h1 = hue_angle(a1, b1);
+    let h2 = hue_angle(a2, b2);
+
+    let dl = l2 - l1;
+    let dc = c2 - c1;
+    let dh = if c1 * c2 == 0.0 {
+        0.0
+    } else if (h2 - h1).abs() <= 180.0 {
+        h2 - h1
+    } else if h2 - h1 > 180.0 {
+        h2 - h1 - 360.0
+    } else {
+        h2 - h1 + 360.0
+    };
+    let dh = 2.0 * (c1 * c2).sqrt() * (0.5 * dh).to_radians().sin();
+
+    let l_mean = 0.5 * (l1 + l2);
+    let c_mean = 0.5 * (c1 + c2);
+    let h_mean = if c1 * c2 == 0.0 {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180.0 {
+        0.5 * (h1 + h2)
+    } else if h1 + h2 < 360.0 {
+        0.5 * (h1 + h2 + 360.0)
+    } else {
+        0.5 * (h1 + h2 - 360.0)
+    };
+
+    let cos = |degrees: f32| degrees.to_radians().cos();
+    let t =
+        1.0 - 0.17 * cos(h_mean - 30.0) + 0.24 * cos(2.0 * h_mean) + 0.32 * cos(3.0 * h_mean + 6.0)
+            - 0.20 * cos(4.0 * h_mean - 63.0);
+
+    let d_theta = 30.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp();
+    let c_mean7 = c_mean.powi(7);
+    let rc = 2.0 * (c_mean7 / (c_mean7 + POW25_7)).sqrt();
+    let rt = -(2.0 * d_theta).to_radians().sin() * rc;
+
+    let l50 = (l_mean - 50.0).powi(2);
+    let sl = 1.0 + 0.015 * l50 / (20.0 + l50).sqrt();
+    let sc = 1.0 + 0.045 * c_mean;
+    let sh = 1.0 + 0.015 * c_mean * t;
+
+    let l = dl / sl;
+    let c = dc / sc;
+    let h = dh / sh;
+
+    (l * l + c * c + h * h + rt * c * h).max(0.0).sqrt()
+}
+
+fn hue_angle(a: f32, b: f32) -> f32 {
+    if a == 0.0 && b == 0.0 {
+        0.0
+    } else {
+        b.atan2(a).to_degrees().rem_euclid(360.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the test data of Sharma, Wu and Dalal, Table 1
+    const SHARMA: [([f32; 3], [f32; 3], f32); 34] = [
+        ([50.0, 2.6772, -79.7751], [50.0, 0.0, -82.7485], 2.0425),
+        ([50.0, 3.1571, -77.2803], [50.0, 0.0, -82.7485], 2.8615),
+        ([50.0, 2.8361, -74.0200], [50.0, 0.0, -82.7485], 3.4412),
+        ([50.0, -1.3802, -84.2814], [50.0, 0.0, -82.7485], 1.0000),
+        ([50.0, -1.1848, -84.8006], [50.0, 0.0, -82.7485], 1.0000),
+        ([50.0, -0.9009, -85.5211], [50.0, 0.0, -82.7485], 1.0000),
+        ([50.0, 0.0, 0.0], [50.0, -1.0, 2.0], 2.3669),
+        ([50.0, -1.0, 2.0], [50.0, 0.0, 0.0], 2.3669),
+        ([50.0, 2.4900, -0.0010], [50.0, -2.4900, 0.0009], 7.1792),
+        ([50.0, 2.4900, -0.0010], [50.0, -2.4900, 0.0010], 7.1792),
+        ([50.0, 2.4900, -0.0010], [50.0, -2.4900, 0.0011], 7.2195),
+        ([50.0, 2.4900, -0.0010], [50.0, -2.4900, 0.0012], 7.2195),
+        ([50.0, -0.0010, 2.4900], [50.0, 0.0009, -2.4900], 4.8045),
+        ([50.0, -0.0010, 2.4900], [50.0, 0.0010, -2.4900], 4.8045),
+        ([50.0, -0.0010, 2.4900], [50.0, 0.0011, -2.4900], 4.7461),
+        ([50.0, 2.5, 0.0], [50.0, 0.0, -2.5], 4.3065),
+        ([50.0, 2.5, 0.0], [73.0, 25.0, -18.0], 27.1492),
+        ([50.0, 2.5, 0.0], [61.0, -5.0, 29.0], 22.8977),
+        ([50.0, 2.5, 0.0], [56.0, -27.0, -3.0], 31.9030),
+        ([50.0, 2.5, 0.0], [58.0, 24.0, 15.0], 19.4535),
+        ([50.0, 2.5, 0.0], [50.0, 3.1736, 0.5854], 1.0000),
+        ([50.0, 2.5, 0.0], [50.0, 3.2972, 0.0], 1.0000),
+        ([50.0, 2.5, 0.0], [50.0, 1.8634, 0.5757], 1.0000),
+        ([50.0, 2.5, 0.0], [50.0, 3.2592, 0.3350], 1.0000),
+        (
+            [60.2574, -34.0099, 36.2677],
+            [60.4626, -34.1751, 39.4387],
+            1.2644,
+        ),
+        (
+            [63.0109, -31.0961, -5.8663],
+            [62.8187, -29.7946, -4.0864],
+            1.2630,
+        ),
+        (
+            [61.2901, 3.7196, -5.3901],
+            [61.4292, 2.2480, -4.9620],
+            1.8731,
+        ),
+        (
+            [35.0831, -44.1164, 3.7933],
+            [35.0232, -40.0716, 1.5901],
+            1.8645,
+        ),
+        (
+            [22.7233, 20.0904, -46.6940],
+            [23.0331, 14.9730, -42.5619],
+            2.0373,
+        ),
+        (
+            [36.4612, 47.8580, 18.3852],
+            [36.2715, 50.5065, 21.2231],
+            1.4146,
+        ),
+        (
+            [90.8027, -2.0831, 1.4410],
+            [91.1528, -1.6435, 0.0447],
+            1.4441,
+        ),
+        (
+            [90.9257, -0.5406, -0.9208],
+            [88.6381, -0.8985, -0.7239],
+            1.5381,
+        ),
+        (
+            [6.7747, -0.2908, -2.4247],
+            [5.8714, -0.0985, -2.2286],
+            0.6377,
+        ),
+        (
+            [2.0776, 0.0795, -1.1350],
+            [0.9033, -0.0636, -0.5514],
+            0.9082,
+        ),
+    ];
+
+    #[test]
+    fn ciede2000_matches_sharma() {
+        for (i, &(lab1, lab2, expected)) in SHARMA.iter().enumerate() {
+            let (lab1, lab2) = (Vec3::from(lab1), Vec3::from(lab2));
+            for delta_e in [delta_e2000(lab1, lab2), delta_e2000(lab2, lab1)] {
+                assert!(
+                    (delta_e - expected).abs() < 1e-4,
+                    "pair {}: {} != {}",
+                    i + 1,
+                    delta_e,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cie76_and_cie94_spot_values() {
+        let cases = [
+            (
+                [50.0, 2.6772, -79.7751],
+                [50.0, 0.0, -82.7485],
+                4.0011,
+                1.3950,
+            ),
+            ([50.0, 2.5, 0.0], [73.0, 25.0, -18.0], 36.8680, 34.6892),
+            (
+                [60.2574, -34.0099, 36.2677],
+                [60.4626, -34.1751, 39.4387],
+                3.1819,
+                1.3910,
+            ),
+        ];
+
+        for (lab1, lab2, expected76, expected94) in cases {
+            let (lab1, lab2) = (Vec3::from(lab1), Vec3::from(lab2));
+            assert!((delta_e76(lab1, lab2) - expected76).abs() < 1e-3);
+            assert!((delta_e94(lab1, lab2) - expected94).abs() < 1e-3);
+        }
+
+        // CIE94 weighs the chroma of the reference
+        let (lab1, lab2) = (Vec3::new(50.0, 2.5, 0.0), Vec3::new(73.0, 25.0, -18.0));
+        assert!((DeltaE::Cie94.between(lab2, lab1) - 26.1398).abs() < 1e-3);
+    }
+}
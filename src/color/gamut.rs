@@ -0,0 +1,168 @@
+use super::measure::hue;
+use super::*;
+use crate::state::ColorSpace;
+use glam::{Vec2, Vec3};
+use std::f32::consts::PI;
+
+// points along each line of a hull
+const SEGMENTS: u32 = 32;
+
+// linear RGB of each gamut into linear sRGB, rows
+const DISPLAY_P3_TO_SRGB: [Vec3; 3] = [
+    Vec3::new(1.2249401, -0.2249404, 0.0),
+    Vec3::new(-0.0420569, 1.0420571, 0.0),
+    Vec3::new(-0.0196376, -0.0786361, 1.0982735),
+];
+
+const REC2020_TO_SRGB: [Vec3; 3] = [
+    Vec3::new(1.660491, -0.5876411, -0.0728499),
+    Vec3::new(-0.1245505, 1.1328999, -0.0083494),
+    Vec3::new(-0.0181508, -0.1005789, 1.1187297),
+];
+
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum Gamut {
+    #[default]
+    Srgb,
+    DisplayP3,
+    Rec2020,
+}
+
+impl Gamut {
+    // CIE 1931 xy of the red, green and blue primaries
+    pub fn primaries(&self) -> [Vec2; 3] {
+        match *self {
+            Self::Srgb => [
+                Vec2::new(0.64, 0.33),
+                Vec2::new(0.30, 0.60),
+                Vec2::new(0.15, 0.06),
+            ],
+            Self::DisplayP3 => [
+                Vec2::new(0.680, 0.320),
+                Vec2::new(0.265, 0.690),
+                Vec2::new(0.150, 0.060),
+            ],
+            Self::Rec2020 => [
+                Vec2::new(0.708, 0.292),
+                Vec2::new(0.170, 0.797),
+                Vec2::new(0.131, 0.046),
+            ],
+        }
+    }
+
+    // encoded RGB of the gamut into sRGB, out of 0 ~ 1 for colors outside sRGB.
+    // all gamuts are encoded with the sRGB curve, which moves the points along
+    // the hull but not the hull itself
+    pub fn in_srgb(&self, rgb: Vec3) -> Vec3 {
+        let rows = match *self {
+            Self::Srgb => return rgb,
+            Self::DisplayP3 => &DISPLAY_P3_TO_SRGB,
+            Self::Rec2020 => &REC2020_TO_SRGB,
+        };
+
+        let linear = rgb.to_array().map(srgb_to_linear).into();
+        let srgb = Vec3::new(
+            rows[0].dot(linear),
+            rows[1].dot(linear),
+            rows[2].dot(linear),
+        );
+
+        srgb.to_array()
+            .map(|c| c.signum() * linear_to_srgb(c.abs()))
+            .into()
+    }
+
+    // the surface of the RGB cube of the gamut as a line list of positions in the
+    // color cloud and their sRGB colors: the cube edges and the middle of each face
+    pub fn hull(&self, color_space: ColorSpace) -> Vec<(Vec3, Vec3)> {
+        let mut lines = vec![];
+
+        for axis in 0..3 {
+            let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+
+            for (a, b) in [
+                (0.0, 0.0),
+                (0.0, 1.0),
+                (1.0, 0.0),
+                (1.0, 1.0),
+                (0.5, 0.0),
+                (0.5, 1.0),
+                (0.0, 0.5),
+                (1.0, 0.5),
+            ] {
+                let mut from = Vec3::ZERO;
+                from[u] = a;
+                from[v] = b;
+
+                let mut to = from;
+                to[axis] = 1.0;
+
+                self.push_line(&mut lines, color_space, from, to);
+            }
+        }
+
+        lines
+    }
+
+    fn push_line(
+        &self,
+        lines: &mut Vec<(Vec3, Vec3)>,
+        color_space: ColorSpace,
+        from: Vec3,
+        to: Vec3,
+    ) {
+        let vertex = |i: u32| {
+            let srgb = self.in_srgb(from.lerp(to, i as f32 / SEGMENTS as f32));
+            (
+                position(color_space, srgb),
+                srgb.clamp(Vec3::ZERO, Vec3::ONE),
+            )
+        };
+
+        let mut prev = vertex(0);
+        for i in 1..=SEGMENTS {
+            let next = vertex(i);
+            // wide gamuts leave the domain of the HSL mapping
+            if prev.0.is_finite() && next.0.is_finite() {
+                lines.push(prev);
+                lines.push(next);
+            }
+            prev = next;
+        }
+    }
+}
+
+// matches ToPosition in color_cloud.hlsl
+pub fn position(color_space: ColorSpace, rgb: Vec3) -> Vec3 {
+    let ma = rgb.max_element();
+    let mi = rgb.min_element();
+    let (sin, cos) = (2.0 * PI * hue(rgb)).sin_cos();
+
+    match color_space {
+        ColorSpace::Rgb => 2.0 * rgb - 1.0,
+        ColorSpace::Hsv => {
+            let s = ma - mi;
+            Vec3::new(cos * s, 2.0 * ma - 1.0, -sin * s)
+        }
+        ColorSpace::Hsl => {
+            let s = ma - mi;
+            let l = ma + mi - 1.0;
+
+            let r = (1.0 - l * l).sqrt();
+            let s_max = 1.00001 - l.abs();
+            Vec3::new(cos * r * (s / s_max), l, -sin * r * (s / s_max))
+        }
+        ColorSpace::Yuv => {
+            const S: f32 = 2.0 * -0.5;
+            const C: f32 = 2.0 * 0.114572;
+
+            let y = rgb.dot(Vec3::new(0.2126, 0.7152, 0.0722));
+            let u = rgb.dot(Vec3::new(-0.114572, -0.385428, 0.5));
+            let v = rgb.dot(Vec3::new(0.5, -0.451453, -0.045847));
+
+            // 2 * (yuv + (0, 0.5, 0.5)) - 1 as in the shader
+            let (y, u, v) = (2.0 * y - 1.0, -2.0 * u, 2.0 * v);
+            Vec3::new(C * u - S * v, y, S * u + C * v)
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use super::*;
+use glam::Vec3;
+
+// largest C* of the sRGB gamut, reached by blue
+const MAX_LAB_CHROMA: f32 = 134.0;
+
+// the shaders switch on the discriminant
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum SaturationMeasure {
+    #[default]
+    Chroma,
+    HsvS,
+    HslS,
+    LabC,
+}
+
+impl SaturationMeasure {
+    pub const ALL: [Self; 4] = [Self::Chroma, Self::HsvS, Self::HslS, Self::LabC];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Self::Chroma => "chroma",
+            Self::HsvS => "hsv-s",
+            Self::HslS => "hsl-s",
+            Self::LabC => "lab-c",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|measure| measure.name() == name)
+    }
+
+    // matches ToSaturation in common.hlsl
+    pub fn of(&self, rgb: Vec3) -> f32 {
+        let ma = rgb.max_element();
+        let mi = rgb.min_element();
+        let l = (ma + mi) / 2.0;
+
+        match *self {
+            Self::Chroma => ma - mi,
+            Self::HsvS if ma > 0.0 => (ma - mi) / ma,
+            Self::HslS if l > 0.0 && l < 1.0 => {
+                ((ma - mi) / (1.0 - (2.0 * l - 1.0).abs())).clamp(0.0, 1.0)
+            }
+            Self::LabC => {
+                let lab = rgb_to_lab(rgb);
+                (lab.y.hypot(lab.z) / MAX_LAB_CHROMA).clamp(0.0, 1.0)
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum BrightnessMeasure {
+    #[default]
+    Luma,
+    Luminance,
+    HsvV,
+    HslL,
+    LabL,
+}
+
+impl BrightnessMeasure {
+    pub const ALL: [Self; 5] = [
+        Self::Luma,
+        Self::Luminance,
+        Self::HsvV,
+        Self::HslL,
+        Self::LabL,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Self::Luma => "luma",
+            Self::Luminance => "luminance",
+            Self::HsvV => "hsv-v",
+            Self::HslL => "hsl-l",
+            Self::LabL => "lab-l",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|measure| measure.name() == name)
+    }
+
+    // matches ToBrightness in common.hlsl
+    pub fn of(&self, rgb: Vec3) -> f32 {
+        match *self {
+            Self::Luma => rgb.dot(Vec3::new(0.299, 0.587, 0.114)),
+            Self::Luminance => rgb_to_xyz(rgb).y,
+            Self::HsvV => rgb.max_element(),
+            Self::HslL => (rgb.max_element() + rgb.min_element()) / 2.0,
+            Self::LabL => (rgb_to_lab(rgb).x / 100.0).clamp(0.0, 1.0),
+        }
+    }
+}
+
+// hue in 0 ~ 1 as ToHsv in common.hlsl, red at 0
+pub fn hue(rgb: Vec3) -> f32 {
+    let ma = rgb.max_element();
+    let mi = rgb.min_element();
+
+    if ma == mi {
+        0.0
+    } else if mi == rgb.z {
+        ((rgb.y - rgb.x) / (ma - mi) + 1.0) / 6.0
+    } else if mi == rgb.x {
+        ((rgb.z - rgb.y) / (ma - mi) + 3.0) / 6.0
+    } else {
+        ((rgb.x - rgb.z) / (ma - mi) + 5.0) / 6.0
+    }
+}
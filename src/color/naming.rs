@@ -0,0 +1,362 @@
+mod css;
+mod iscc_nbs;
+
+use super::*;
+use glam::Vec3;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+pub const MAX_NAMES: usize = 1024;
+
+// the name lookup table is indexed by the upper bits of each 8 bit channel
+pub const LUT_BITS: u32 = 6;
+pub const LUT_SIZE: usize = 1 << (3 * LUT_BITS);
+
+// ISCC-NBS level 1 categories
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Family {
+    Pink,
+    Red,
+    Orange,
+    Brown,
+    Yellow,
+    Olive,
+    YellowGreen,
+    Green,
+    Blue,
+    Purple,
+    White,
+    Gray,
+    Black,
+}
+
+impl Family {
+    pub const ALL: [Self; 13] = [
+        Self::Pink,
+        Self::Red,
+        Self::Orange,
+        Self::Brown,
+        Self::Yellow,
+        Self::Olive,
+        Self::YellowGreen,
+        Self::Green,
+        Self::Blue,
+        Self::Purple,
+        Self::White,
+        Self::Gray,
+        Self::Black,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Self::Pink => "pink",
+            Self::Red => "red",
+            Self::Orange => "orange",
+            Self::Brown => "brown",
+            Self::Yellow => "yellow",
+            Self::Olive => "olive",
+            Self::YellowGreen => "yellow green",
+            Self::Green => "green",
+            Self::Blue => "blue",
+            Self::Purple => "purple",
+            Self::White => "white",
+            Self::Gray => "gray",
+            Self::Black => "black",
+        }
+    }
+
+    fn of_iscc_nbs(name: &str) -> Self {
+        if name.ends_with("yellow green") {
+            return Self::YellowGreen;
+        }
+
+        if name.ends_with("olive") || name.ends_with("olive green") {
+            return Self::Olive;
+        }
+
+        match name.rsplit(' ').next().unwrap_or(name) {
+            "pink" => Self::Pink,
+            "red" => Self::Red,
+            "orange" => Self::Orange,
+            "brown" => Self::Brown,
+            "yellow" => Self::Yellow,
+            "green" => Self::Green,
+            "blue" => Self::Blue,
+            "violet" | "purple" => Self::Purple,
+            "white" => Self::White,
+            "black" => Self::Black,
+            _ => Self::Gray,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum DictionaryKind {
+    #[default]
+    Css,
+    IsccNbs,
+    Custom,
+}
+
+#[derive(Clone, Debug)]
+pub struct ColorName {
+    pub name: String,
+    pub rgb: [u8; 3],
+    pub family: Family,
+    oklab: Vec3,
+}
+
+#[derive(Clone, Debug)]
+pub struct Dictionary {
+    pub kind: DictionaryKind,
+    pub names: Vec<ColorName>,
+}
+
+// coverage in 0 ~ 1, most covering first
+#[derive(Clone, Default, Debug)]
+pub struct Naming {
+    pub families: Vec<(Family, f32)>,
+    pub names: Vec<(String, f32)>,
+    pub probe: Option<String>,
+}
+
+impl Dictionary {
+    pub fn css() -> Self {
+        Self::from_table(DictionaryKind::Css, &css::NAMES)
+    }
+
+    pub fn iscc_nbs() -> Self {
+        Self::from_table(DictionaryKind::IsccNbs, &iscc_nbs::NAMES)
+    }
+
+    // GIMP, ASE and JSON palettes by extension, otherwise a color list
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        let entries = match extension.as_str() {
+            "gpl" => palette::parse_gpl(&std::fs::read_to_string(path)?),
+            "ase" => palette::parse_ase(&std::fs::read(path)?)?,
+            "json" => palette::parse_json(&std::fs::read_to_string(path)?),
+            _ => parse_entries(&std::fs::read_to_string(path)?),
+        };
+
+        Self::custom(entries)
+    }
+
+    // up to MAX_NAMES entries, the shaders hold no more
+    pub fn custom(entries: Vec<(String, [u8; 3])>) -> std::io::Result<Self> {
+        if entries.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData, "no colors found."));
+        }
+        if entries.len() > MAX_NAMES {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "{} colors, at most {MAX_NAMES} are supported.",
+                    entries.len()
+                ),
+            ));
+        }
+        Ok(Self::new(DictionaryKind::Custom, entries))
+    }
+
+    pub fn nearest(&self, rgb: [u8; 3]) -> Option<&ColorName> {
+        self.names
+            .get(self.nearest_index(rgb_to_oklab(from_rgb8(rgb))))
+    }
+
+    // index of the nearest name for every cell of the 2^LUT_BITS cube, red fastest
+    pub fn lut(&self) -> Vec<u32> {
+        let cells = 1 << LUT_BITS;
+        let step = (256 / cells) as f32;
+        let center = |i: usize| (step * i as f32 + 0.5 * (step - 1.0)) / 255.0;
+
+        let mut lut = Vec::with_capacity(LUT_SIZE);
+        for b in 0..cells {
+            for g in 0..cells {
+                for r in 0..cells {
+                    let oklab = rgb_to_oklab(Vec3::new(center(r), center(g), center(b)));
+                    lut.push(self.nearest_index(oklab) as u32);
+                }
+            }
+        }
+        lut
+    }
+
+    // `counts` holds the number of pixels that fell on each name
+    pub fn breakdown(&self, counts: &[u32]) -> Naming {
+        let counts = &counts[..counts.len().min(self.names.len())];
+        let total = counts.iter().map(|&count| count as u64).sum::<u64>();
+
+        if total == 0 {
+            return Naming::default();
+        }
+
+        let coverage = |count: u64| (count as f64 / total as f64) as f32;
+
+        let mut names = counts
+            .iter()
+            .zip(&self.names)
+            .filter(|(&count, _)| count != 0)
+            .map(|(&count, name)| (name.name.clone(), coverage(count as u64)))
+            .collect::<Vec<_>>();
+        names.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut families = Family::ALL
+            .iter()
+            .map(|&family| {
+                let count = counts
+                    .iter()
+                    .zip(&self.names)
+                    .filter(|(_, name)| name.family == family)
+                    .map(|(&count, _)| count as u64)
+                    .sum::<u64>();
+                (family, coverage(count))
+            })
+            .filter(|&(_, coverage)| coverage > 0.0)
+            .collect::<Vec<_>>();
+        families.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        Naming {
+            families,
+            names,
+            probe: None,
+        }
+    }
+
+    fn from_table(kind: DictionaryKind, table: &[(&str, u32)]) -> Self {
+        Self::new(
+            kind,
+            table
+                .iter()
+                .map(|&(name, rgb)| {
+                    (
+                        name.to_owned(),
+                        [(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8],
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    fn new(kind: DictionaryKind, entries: Vec<(String, [u8; 3])>) -> Self {
+        // colors from other dictionaries take the family of the nearest ISCC-NBS centroid
+        let iscc_nbs = (kind != DictionaryKind::IsccNbs).then(Self::iscc_nbs);
+
+        let names = entries
+            .into_iter()
+            .map(|(name, rgb)| {
+                let oklab = rgb_to_oklab(from_rgb8(rgb));
+                let family = match &iscc_nbs {
+                    Some(iscc_nbs) => iscc_nbs.names[iscc_nbs.nearest_index(oklab)].family,
+                    None => Family::of_iscc_nbs(&name),
+                };
+
+                ColorName {
+                    name,
+                    rgb,
+                    family,
+                    oklab,
+                }
+            })
+            .collect();
+
+        Self { kind, names }
+    }
+
+    fn nearest_index(&self, oklab: Vec3) -> usize {
+        self.names
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                a.oklab
+                    .distance_squared(oklab)
+                    .total_cmp(&b.oklab.distance_squared(oklab))
+            })
+            .map(|(i, _)| i)
+            .unwrap_or_default()
+    }
+}
+
+// one color per line, "name, #RRGGBB", "#RRGGBB name" or tab separated
+fn parse_entries(text: &str) -> Vec<(String, [u8; 3])> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with(';') && !line.starts_with("//"))
+        .filter_map(parse_entry)
+        .collect()
+}
+
+fn parse_entry(line: &str) -> Option<(String, [u8; 3])> {
+    let fields = if line.contains([',', '\t']) {
+        line.split([',', '\t']).map(str::trim).collect::<Vec<_>>()
+    } else {
+        line.split_whitespace().collect::<Vec<_>>()
+    };
+
+    // names such as "bad" are valid hex too, so prefer the field with '#'
+    let index = fields
+        .iter()
+        .position(|field| field.starts_with('#') && parse_hex(field).is_some())
+        .or_else(|| fields.iter().position(|field| parse_hex(field).is_some()))?;
+    let rgb = parse_hex(fields[index])?;
+
+    let name = fields
+        .iter()
+        .enumerate()
+        .filter(|&(i, field)| i != index && !field.is_empty())
+        .map(|(_, field)| *field)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some((if name.is_empty() { to_hex(rgb) } else { name }, rgb))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_color_list() {
+        let text = "\
+; a comment
+// another
+sky blue, #87CEEB
+#FF0000 red
+bad\t#00FF00
+#123
+
+";
+        assert_eq!(
+            parse_entries(text),
+            vec![
+                ("sky blue".to_string(), [0x87, 0xce, 0xeb]),
+                ("red".to_string(), [0xff, 0x00, 0x00]),
+                ("bad".to_string(), [0x00, 0xff, 0x00]),
+                ("#112233".to_string(), [0x11, 0x22, 0x33]),
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_dictionary_is_capped() {
+        let entries = |len: usize| {
+            (0..len)
+                .map(|i| (i.to_string(), [i as u8, 0, 0]))
+                .collect::<Vec<_>>()
+        };
+
+        let dictionary = Dictionary::custom(entries(MAX_NAMES)).unwrap();
+        assert_eq!(dictionary.kind, DictionaryKind::Custom);
+        assert_eq!(dictionary.names.len(), MAX_NAMES);
+        assert_eq!(dictionary.nearest([255, 0, 0]).unwrap().rgb, [255, 0, 0]);
+
+        let error = Dictionary::custom(entries(MAX_NAMES + 1)).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(Dictionary::custom(vec![]).is_err());
+    }
+}
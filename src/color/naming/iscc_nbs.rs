@@ -0,0 +1,270 @@
+// ISCC-NBS centroid colors, in the order of the 267 level 3 categories.
+pub const NAMES: [(&str, u32); 267] = [
+    ("vivid pink", 0xFFB5BA),
+    ("strong pink", 0xEA9399),
+    ("deep pink", 0xE4717A),
+    ("light pink", 0xF9CCCA),
+    ("moderate pink", 0xDEA5A4),
+    ("dark pink", 0xC08081),
+    ("pale pink", 0xEAD8D7),
+    ("grayish pink", 0xC4AEAD),
+    ("pinkish white", 0xEAE3E1),
+    ("pinkish gray", 0xC1B6B3),
+    ("vivid red", 0xBE0032),
+    ("strong red", 0xBC3F4A),
+    ("deep red", 0x841B2D),
+    ("very deep red", 0x5C0923),
+    ("moderate red", 0xAB4E52),
+    ("dark red", 0x722F37),
+    ("very dark red", 0x3F1728),
+    ("light grayish red", 0xAD8884),
+    ("grayish red", 0x905D5D),
+    ("dark grayish red", 0x543D3F),
+    ("blackish red", 0x2E1D21),
+    ("reddish gray", 0x8F817F),
+    ("dark reddish gray", 0x5C504F),
+    ("reddish black", 0x232122),
+    ("vivid yellowish pink", 0xFFB7A5),
+    ("strong yellowish pink", 0xF99379),
+    ("deep yellowish pink", 0xE66761),
+    ("light yellowish pink", 0xF4C2C2),
+    ("moderate yellowish pink", 0xD9A6A9),
+    ("dark yellowish pink", 0xC48379),
+    ("pale yellowish pink", 0xECD5C5),
+    ("grayish yellowish pink", 0xC7ADA3),
+    ("brownish pink", 0xC2AC99),
+    ("vivid reddish orange", 0xE25822),
+    ("strong reddish orange", 0xD9603B),
+    ("deep reddish orange", 0xAA381E),
+    ("moderate reddish orange", 0xCB6D51),
+    ("dark reddish orange", 0x9E4732),
+    ("grayish reddish orange", 0xB4745E),
+    ("strong reddish brown", 0x882D17),
+    ("deep reddish brown", 0x56070C),
+    ("light reddish brown", 0xA87C6D),
+    ("moderate reddish brown", 0x79443B),
+    ("dark reddish brown", 0x3E1D1E),
+    ("light grayish reddish brown", 0x977F73),
+    ("grayish reddish brown", 0x674C47),
+    ("dark grayish reddish brown", 0x43302E),
+    ("vivid orange", 0xF38400),
+    ("brilliant orange", 0xFD943F),
+    ("strong orange", 0xED872D),
+    ("deep orange", 0xBE6516),
+    ("light orange", 0xFAB57F),
+    ("moderate orange", 0xD99058),
+    ("brownish orange", 0xAE6938),
+    ("strong brown", 0x80461B),
+    ("deep brown", 0x593319),
+    ("light brown", 0xA67B5B),
+    ("moderate brown", 0x6F4E37),
+    ("dark brown", 0x422518),
+    ("light grayish brown", 0x958070),
+    ("grayish brown", 0x635147),
+    ("dark grayish brown", 0x3E322C),
+    ("light brownish gray", 0x8E8279),
+    ("brownish gray", 0x5B504F),
+    ("brownish black", 0x28201C),
+    ("vivid orange yellow", 0xF6A600),
+    ("brilliant orange yellow", 0xFFC14F),
+    ("strong orange yellow", 0xEAA221),
+    ("deep orange yellow", 0xC98500),
+    ("light orange yellow", 0xFBC97F),
+    ("moderate orange yellow", 0xE3A857),
+    ("dark orange yellow", 0xBE8A3D),
+    ("pale orange yellow", 0xFAD6A5),
+    ("strong yellowish brown", 0x996515),
+    ("deep yellowish brown", 0x654522),
+    ("light yellowish brown", 0xC19A6B),
+    ("moderate yellowish brown", 0x826644),
+    ("dark yellowish brown", 0x4B3621),
+    ("light grayish yellowish brown", 0xAE9B82),
+    ("grayish yellowish brown", 0x7E6D5A),
+    ("dark grayish yellowish brown", 0x483C32),
+    ("vivid yellow", 0xF3C300),
+    ("brilliant yellow", 0xFADA5E),
+    ("strong yellow", 0xD4AF37),
+    ("deep yellow", 0xAF8D13),
+    ("light yellow", 0xF8DE7E),
+    ("moderate yellow", 0xC9AE5D),
+    ("dark yellow", 0xAB9144),
+    ("pale yellow", 0xF3E5AB),
+    ("grayish yellow", 0xC2B280),
+    ("dark grayish yellow", 0xA18F60),
+    ("yellowish white", 0xF0EAD6),
+    ("yellowish gray", 0xBFB8A5),
+    ("light olive brown", 0x967117),
+    ("moderate olive brown", 0x6C541E),
+    ("dark olive brown", 0x3B3121),
+    ("vivid greenish yellow", 0xDCD300),
+    ("brilliant greenish yellow", 0xE9E450),
+    ("strong greenish yellow", 0xBEB72E),
+    ("deep greenish yellow", 0x9B9400),
+    ("light greenish yellow", 0xEAE679),
+    ("moderate greenish yellow", 0xB9B459),
+    ("dark greenish yellow", 0x98943E),
+    ("pale greenish yellow", 0xEBE8A4),
+    ("grayish greenish yellow", 0xB9B57D),
+    ("light olive", 0x867E36),
+    ("moderate olive", 0x665D1E),
+    ("dark olive", 0x403D21),
+    ("light grayish olive", 0x8C8767),
+    ("grayish olive", 0x515744),
+    ("dark grayish olive", 0x363527),
+    ("light olive gray", 0x8A8776),
+    ("olive gray", 0x57554C),
+    ("olive black", 0x25241D),
+    ("vivid yellow green", 0x8DB600),
+    ("brilliant yellow green", 0xBDDA57),
+    ("strong yellow green", 0x7E9F2E),
+    ("deep yellow green", 0x467129),
+    ("light yellow green", 0xC9DC89),
+    ("moderate yellow green", 0x8A9A5B),
+    ("pale yellow green", 0xDADFB7),
+    ("grayish yellow green", 0x8F9779),
+    ("strong olive green", 0x404F00),
+    ("deep olive green", 0x232F00),
+    ("moderate olive green", 0x4A5D23),
+    ("dark olive green", 0x2B3D26),
+    ("grayish olive green", 0x515744),
+    ("dark grayish olive green", 0x31362B),
+    ("vivid yellowish green", 0x27A64C),
+    ("brilliant yellowish green", 0x83D37D),
+    ("strong yellowish green", 0x44944A),
+    ("deep yellowish green", 0x00622D),
+    ("very deep yellowish green", 0x003118),
+    ("very light yellowish green", 0xB6E5AF),
+    ("light yellowish green", 0x93C592),
+    ("moderate yellowish green", 0x679267),
+    ("dark yellowish green", 0x355E3B),
+    ("very dark yellowish green", 0x173620),
+    ("vivid green", 0x008856),
+    ("brilliant green", 0x3EB489),
+    ("strong green", 0x007959),
+    ("deep green", 0x00543D),
+    ("very light green", 0x8ED1B2),
+    ("light green", 0x6AAB8E),
+    ("moderate green", 0x3B7861),
+    ("dark green", 0x1B4D3E),
+    ("very dark green", 0x1C352D),
+    ("very pale green", 0xC7E6D7),
+    ("pale green", 0x8DA399),
+    ("grayish green", 0x5E716A),
+    ("dark grayish green", 0x3A4B47),
+    ("blackish green", 0x1A2421),
+    ("greenish white", 0xDFEDE8),
+    ("light greenish gray", 0xB2BEB5),
+    ("greenish gray", 0x7D8984),
+    ("dark greenish gray", 0x4E5755),
+    ("greenish black", 0x1E2321),
+    ("vivid bluish green", 0x008882),
+    ("brilliant bluish green", 0x00A693),
+    ("strong bluish green", 0x007A74),
+    ("deep bluish green", 0x00443F),
+    ("very light bluish green", 0x96DED1),
+    ("light bluish green", 0x66ADA4),
+    ("moderate bluish green", 0x317873),
+    ("dark bluish green", 0x004B49),
+    ("very dark bluish green", 0x002A29),
+    ("vivid greenish blue", 0x0085A1),
+    ("brilliant greenish blue", 0x239EBA),
+    ("strong greenish blue", 0x007791),
+    ("deep greenish blue", 0x2E8495),
+    ("very light greenish blue", 0x9CD1DC),
+    ("light greenish blue", 0x66AABC),
+    ("moderate greenish blue", 0x367588),
+    ("dark greenish blue", 0x004958),
+    ("very dark greenish blue", 0x002E3B),
+    ("vivid blue", 0x00A1C2),
+    ("brilliant blue", 0x4997D0),
+    ("strong blue", 0x0067A5),
+    ("deep blue", 0x00416A),
+    ("very light blue", 0xA1CAF1),
+    ("light blue", 0x70A3CC),
+    ("moderate blue", 0x436B95),
+    ("dark blue", 0x00304E),
+    ("very pale blue", 0xBCD4E6),
+    ("pale blue", 0x91A3B0),
+    ("grayish blue", 0x536878),
+    ("dark grayish blue", 0x36454F),
+    ("blackish blue", 0x202830),
+    ("bluish white", 0xE9E9ED),
+    ("light bluish gray", 0xB4BCC0),
+    ("bluish gray", 0x81878B),
+    ("dark bluish gray", 0x51585E),
+    ("bluish black", 0x202428),
+    ("vivid purplish blue", 0x30267A),
+    ("brilliant purplish blue", 0x6C79B8),
+    ("strong purplish blue", 0x545AA7),
+    ("deep purplish blue", 0x272458),
+    ("very light purplish blue", 0xB3BCE2),
+    ("light purplish blue", 0x8791BF),
+    ("moderate purplish blue", 0x4E5180),
+    ("dark purplish blue", 0x252440),
+    ("very pale purplish blue", 0xC0C8E1),
+    ("pale purplish blue", 0x8C92AC),
+    ("grayish purplish blue", 0x4C516D),
+    ("vivid violet", 0x9065CA),
+    ("brilliant violet", 0x7E73B8),
+    ("strong violet", 0x604E97),
+    ("deep violet", 0x32174D),
+    ("very light violet", 0xDCD0FF),
+    ("light violet", 0x8C82B6),
+    ("moderate violet", 0x604E81),
+    ("dark violet", 0x2F2140),
+    ("very pale violet", 0xC4C3DD),
+    ("pale violet", 0x9690AB),
+    ("grayish violet", 0x554C69),
+    ("vivid purple", 0x9A4EAE),
+    ("brilliant purple", 0xD399E6),
+    ("strong purple", 0x875692),
+    ("deep purple", 0x602F6B),
+    ("very deep purple", 0x401A4C),
+    ("very light purple", 0xD5BADB),
+    ("light purple", 0xB695C0),
+    ("moderate purple", 0x86608E),
+    ("dark purple", 0x563C5C),
+    ("very dark purple", 0x301934),
+    ("very pale purple", 0xD6CADD),
+    ("pale purple", 0xAA98A9),
+    ("grayish purple", 0x796878),
+    ("dark grayish purple", 0x50404D),
+    ("blackish purple", 0x291E29),
+    ("purplish white", 0xE8E3E5),
+    ("light purplish gray", 0xBFB9BD),
+    ("purplish gray", 0x8B8589),
+    ("dark purplish gray", 0x5D555B),
+    ("purplish black", 0x242124),
+    ("vivid reddish purple", 0x870074),
+    ("strong reddish purple", 0x9E4F88),
+    ("deep reddish purple", 0x702963),
+    ("very deep reddish purple", 0x54194E),
+    ("light reddish purple", 0xB784A7),
+    ("moderate reddish purple", 0x915C83),
+    ("dark reddish purple", 0x5D3954),
+    ("very dark reddish purple", 0x341731),
+    ("pale reddish purple", 0xAA8A9E),
+    ("grayish reddish purple", 0x836479),
+    ("brilliant purplish pink", 0xFFC8D6),
+    ("strong purplish pink", 0xE68FAC),
+    ("deep purplish pink", 0xDE6FA1),
+    ("light purplish pink", 0xEFBBCC),
+    ("moderate purplish pink", 0xD597AE),
+    ("dark purplish pink", 0xC17E91),
+    ("pale purplish pink", 0xE8CCD7),
+    ("grayish purplish pink", 0xC3A6B1),
+    ("vivid purplish red", 0xCE4676),
+    ("strong purplish red", 0xB3446C),
+    ("deep purplish red", 0x78184A),
+    ("very deep purplish red", 0x54133B),
+    ("moderate purplish red", 0xA8516E),
+    ("dark purplish red", 0x673147),
+    ("very dark purplish red", 0x38152C),
+    ("light grayish purplish red", 0xAF868E),
+    ("grayish purplish red", 0x915F6D),
+    ("white", 0xF2F3F4),
+    ("light gray", 0xB9B8B5),
+    ("medium gray", 0x848482),
+    ("dark gray", 0x555555),
+    ("black", 0x222222),
+];
@@ -0,0 +1,437 @@
+use super::difference::DeltaE;
+use super::naming::{Dictionary, LUT_BITS};
+use super::*;
+use glam::{IVec3, Vec3};
+use std::io::{Error, ErrorKind};
+
+// palettes up to this size are searched whole, larger ones at the lookup table
+// entries of 5x5x5 cells around the color, matches ClosestPaletteColor in
+// common.hlsl
+pub const FULL_SEARCH_COLORS: usize = 128;
+
+// cells between the probes, about 2.5 probes per palette spacing so that the
+// neighbors of the color in the palette are visited
+pub fn probe_stride(num_colors: usize) -> i32 {
+    let cells = (1 << LUT_BITS) as f32;
+    (cells / (2.5 * (num_colors as f32).cbrt()))
+        .round()
+        .max(1.0) as i32
+}
+
+// index of the palette color closest to `rgb` under `formula` and the difference,
+// `lut` is `palette.lut()`, which only holds the Oklab nearest color at the cell centers
+pub fn closest(palette: &Dictionary, lut: &[u32], rgb: Vec3, formula: DeltaE) -> (usize, f32) {
+    let lab = rgb_to_lab(rgb);
+    let delta_e = |i: usize| formula.between(rgb_to_lab(from_rgb8(palette.names[i].rgb)), lab);
+
+    let mut best = (0, f32::INFINITY);
+    let mut visit = |i: usize| {
+        let d = delta_e(i);
+        if d < best.1 {
+            best = (i, d);
+        }
+    };
+
+    if palette.names.len() <= FULL_SEARCH_COLORS {
+        (0..palette.names.len()).for_each(visit);
+        return best;
+    }
+
+    let cells = 1 << LUT_BITS;
+    let stride = probe_stride(palette.names.len());
+    let cell = ((255.0 * rgb).as_uvec3() >> (8 - LUT_BITS)).as_ivec3();
+    for z in -2..=2 {
+        for y in -2..=2 {
+            for x in -2..=2 {
+                let neighbor = (cell + stride * IVec3::new(x, y, z))
+                    .clamp(IVec3::ZERO, IVec3::splat(cells - 1));
+                let index = neighbor.z << (2 * LUT_BITS) | neighbor.y << LUT_BITS | neighbor.x;
+                visit(lut[index as usize] as usize);
+            }
+        }
+    }
+    best
+}
+
+// "R G B name" lines after the "GIMP Palette" header
+pub fn parse_gpl(text: &str) -> Vec<(String, [u8; 3])> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mut channel = || fields.next()?.parse::<u8>().ok();
+            let rgb = [channel()?, channel()?, channel()?];
+
+            let name = fields.collect::<Vec<_>>().join(" ");
+            Some((if name.is_empty() { to_hex(rgb) } else { name }, rgb))
+        })
+        .collect()
+}
+
+// Adobe Swatch Exchange, RGB, gray and CMYK swatches; Lab swatches are skipped
+pub fn parse_ase(data: &[u8]) -> std::io::Result<Vec<(String, [u8; 3])>> {
+    let invalid = || Error::new(ErrorKind::InvalidData, "not an ASE file.");
+
+    let mut reader = Reader(data);
+    if reader.take(4).ok_or_else(invalid)? != b"ASEF" {
+        return Err(invalid());
+    }
+    reader.take(4).ok_or_else(invalid)?;
+    let num_blocks = reader.u32().ok_or_else(invalid)?;
+
+    let mut entries = Vec::new();
+    for _ in 0..num_blocks {
+        let kind = reader.u16().ok_or_else(invalid)?;
+        let len = reader.u32().ok_or_else(invalid)? as usize;
+        let mut block = Reader(reader.take(len).ok_or_else(invalid)?);
+
+        // group starts and ends carry no color
+        if kind != 0x0001 {
+            continue;
+        }
+
+        let name_len = block.u16().ok_or_else(invalid)? as usize;
+        let name = (0..name_len)
+            .filter_map(|_| block.u16())
+            .take_while(|&c| c != 0)
+            .collect::<Vec<_>>();
+        let name = String::from_utf16_lossy(&name);
+
+        let model = block.take(4).ok_or_else(invalid)?;
+        let mut value = || block.f32().map(|v| v.clamp(0.0, 1.0));
+        let rgb = match model {
+            b"RGB " => [value(), value(), value()],
+            b"Gray" => {
+                let v = value();
+                [v, v, v]
+            }
+            b"CMYK" => {
+                let (c, m, y, k) = (value(), value(), value(), value());
+                let channel = |v: Option<f32>| Some((1.0 - v?) * (1.0 - k?));
+                [channel(c), channel(m), channel(y)]
+            }
+            _ => continue,
+        };
+        let [Some(r), Some(g), Some(b)] = rgb else {
+            return Err(invalid());
+        };
+
+        let rgb = [r, g, b].map(|v| (255.0 * v).round() as u8);
+        entries.push((if name.is_empty() { to_hex(rgb) } else { name }, rgb));
+    }
+
+    Ok(entries)
+}
+
+// "#RRGGBB" strings anywhere in the document, named by a "name" key of the same object
+pub fn parse_json(text: &str) -> Vec<(String, [u8; 3])> {
+    let mut entries = Vec::new();
+    let mut name = None;
+    let mut color = None;
+    let mut key = None;
+
+    let mut flush = |name: &mut Option<String>, color: &mut Option<[u8; 3]>| {
+        if let Some(rgb) = color.take() {
+            entries.push((name.take().unwrap_or_else(|| to_hex(rgb)), rgb));
+        }
+        *name = None;
+    };
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let mut string = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => string.extend(chars.next()),
+                        _ => string.push(c),
+                    }
+                }
+
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if chars.next_if_eq(&':').is_some() {
+                    key = Some(string);
+                    continue;
+                }
+
+                if key.as_deref() == Some("name") {
+                    name = Some(string);
+                } else if let Some(rgb) = string
+                    .starts_with('#')
+                    .then(|| parse_hex(&string))
+                    .flatten()
+                {
+                    // a second color in one object, as in a plain list
+                    if color.is_some() {
+                        flush(&mut None, &mut color);
+                    }
+                    color = Some(rgb);
+                }
+                key = None;
+            }
+            '}' => flush(&mut name, &mut color),
+            _ => {}
+        }
+    }
+    flush(&mut name, &mut color);
+
+    entries
+}
+
+// share of the pixels of each palette color within tolerance, and of the rest
+#[derive(Clone, Default, Debug)]
+pub struct Coverage {
+    pub colors: Vec<(String, [u8; 3], f32)>,
+    pub off_palette: f32,
+}
+
+impl Coverage {
+    // `counts` holds the pixels per palette color, in palette order
+    pub fn new(palette: &Dictionary, counts: &[u32], off_palette: u32) -> Self {
+        let total = counts
+            .iter()
+            .take(palette.names.len())
+            .chain([&off_palette])
+            .map(|&count| count as u64)
+            .sum::<u64>();
+
+        if total == 0 {
+            return Self::default();
+        }
+
+        let share = |count: u32| (count as f64 / total as f64) as f32;
+
+        let mut colors = palette
+            .names
+            .iter()
+            .zip(counts)
+            .map(|(name, &count)| (name.name.clone(), name.rgb, share(count)))
+            .collect::<Vec<_>>();
+        colors.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+        Self {
+            colors,
+            off_palette: share(off_palette),
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        Some(f32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ase_block(kind: u16, body: &[u8]) -> Vec<u8> {
+        let mut block = kind.to_be_bytes().to_vec();
+        block.extend((body.len() as u32).to_be_bytes());
+        block.extend(body);
+        block
+    }
+
+    fn ase_name(name: &str) -> Vec<u8> {
+        let utf16: Vec<u16> = name.encode_utf16().chain([0]).collect();
+        let mut data = (utf16.len() as u16).to_be_bytes().to_vec();
+        data.extend(utf16.iter().flat_map(|c| c.to_be_bytes()));
+        data
+    }
+
+    fn ase_color(name: &str, model: &[u8; 4], values: &[f32]) -> Vec<u8> {
+        let mut body = ase_name(name);
+        body.extend(model);
+        body.extend(values.iter().flat_map(|v| v.to_be_bytes()));
+        // global, spot or normal
+        body.extend(2u16.to_be_bytes());
+        ase_block(0x0001, &body)
+    }
+
+    #[test]
+    fn parse_gimp_palette() {
+        let text = "\
+GIMP Palette
+Name: test
+Columns: 2
+# a comment
+255   0   0 Red
+  0 128 255
+ 16  32  64 Deep  navy
+";
+        assert_eq!(
+            parse_gpl(text),
+            vec![
+                ("Red".to_string(), [255, 0, 0]),
+                ("#0080FF".to_string(), [0, 128, 255]),
+                ("Deep navy".to_string(), [16, 32, 64]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_ase_with_group() {
+        let blocks = [
+            ase_block(0xc001, &ase_name("group")),
+            ase_color("red", b"RGB ", &[1.0, 0.0, 0.0]),
+            ase_color("", b"Gray", &[0.5]),
+            ase_color("cyan", b"CMYK", &[1.0, 0.0, 0.0, 0.0]),
+            ase_color("lab", b"LAB ", &[50.0, 0.0, 0.0]),
+            ase_block(0xc002, &[]),
+        ];
+
+        let mut data = b"ASEF".to_vec();
+        data.extend([0, 1, 0, 0]);
+        data.extend((blocks.len() as u32).to_be_bytes());
+        data.extend(blocks.concat());
+
+        assert_eq!(
+            parse_ase(&data).unwrap(),
+            vec![
+                ("red".to_string(), [255, 0, 0]),
+                ("#808080".to_string(), [128, 128, 128]),
+                ("cyan".to_string(), [0, 255, 255]),
+            ]
+        );
+
+        assert!(parse_ase(b"GIF89a").is_err());
+        assert!(parse_ase(&data[..data.len() - 8]).is_err());
+    }
+
+    #[test]
+    fn parse_json_palette() {
+        let text = r##"{
+            "name": "palette",
+            "colors": [
+                {"name": "brick", "hex": "#A0522D"},
+                {"value": "#FFFFFF", "name": "white"},
+                "#000000"
+            ]
+        }"##;
+
+        assert_eq!(
+            parse_json(text),
+            vec![
+                ("brick".to_string(), [0xa0, 0x52, 0x2d]),
+                ("white".to_string(), [0xff, 0xff, 0xff]),
+                ("#000000".to_string(), [0x00, 0x00, 0x00]),
+            ]
+        );
+    }
+
+    #[test]
+    fn coverage_shares() {
+        let palette = Dictionary::custom(vec![
+            ("red".to_string(), [255, 0, 0]),
+            ("green".to_string(), [0, 255, 0]),
+        ])
+        .unwrap();
+
+        let coverage = Coverage::new(&palette, &[1, 6], 3);
+        assert_eq!(
+            coverage.colors,
+            vec![
+                ("green".to_string(), [0, 255, 0], 0.6),
+                ("red".to_string(), [255, 0, 0], 0.1),
+            ]
+        );
+        assert_eq!(coverage.off_palette, 0.3);
+
+        let empty = Coverage::new(&palette, &[0, 0], 0);
+        assert!(empty.colors.is_empty());
+    }
+
+    #[test]
+    fn closest_under_the_formula() {
+        let palette = Dictionary::custom(vec![
+            ("black".to_string(), [0, 0, 0]),
+            ("grey".to_string(), [128, 128, 128]),
+            ("white".to_string(), [255, 255, 255]),
+        ])
+        .unwrap();
+        let lut = palette.lut();
+
+        let (index, delta_e) = closest(&palette, &lut, from_rgb8([120, 120, 120]), DeltaE::Cie76);
+        assert_eq!(index, 1);
+        assert!(delta_e < 5.0);
+
+        let (index, delta_e) = closest(
+            &palette,
+            &lut,
+            from_rgb8([255, 255, 255]),
+            DeltaE::Ciede2000,
+        );
+        assert_eq!(index, 2);
+        assert!(delta_e < 1e-3);
+    }
+
+    // the lookup table path is exact for all but a few percent of the colors,
+    // the rest lands within MAX_MISS of the closest one, which random palettes
+    // of every size stay under with some margin
+    #[test]
+    fn closest_at_the_lut_near_the_full_search() {
+        const MAX_MISS: f32 = 8.0;
+
+        // fixed pseudo random palettes past FULL_SEARCH_COLORS
+        let mut seed = 0x2545_f491_u32;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as u8
+        };
+
+        let formula = DeltaE::Ciede2000;
+        for len in [FULL_SEARCH_COLORS + 1, 300] {
+            let entries = (0..len)
+                .map(|i| (i.to_string(), [next(), next(), next()]))
+                .collect();
+            let palette = Dictionary::custom(entries).unwrap();
+            let lut = palette.lut();
+
+            let (mut misses, mut total) = (0, 0);
+            for b in (0..256).step_by(17) {
+                for g in (0..256).step_by(17) {
+                    for r in (0..256).step_by(17) {
+                        let rgb = from_rgb8([r as u8, g as u8, b as u8]);
+                        let lab = rgb_to_lab(rgb);
+                        let best = palette
+                            .names
+                            .iter()
+                            .map(|name| formula.between(rgb_to_lab(from_rgb8(name.rgb)), lab))
+                            .fold(f32::INFINITY, f32::min);
+
+                        let (_, delta_e) = closest(&palette, &lut, rgb, formula);
+                        assert!(delta_e - best < MAX_MISS, "{len} colors at {rgb}");
+                        misses += (delta_e > best) as u32;
+                        total += 1;
+                    }
+                }
+            }
+            assert!(misses * 50 < total, "{misses} of {total} with {len} colors");
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use glam::Vec3;
+
+// Y'CbCr to R'G'B' of video frames, without touching the transfer function
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum YcbcrMatrix {
+    Bt601,
+    #[default]
+    Bt709,
+    Bt2020,
+}
+
+impl YcbcrMatrix {
+    // luma weights of red and blue
+    fn weights(&self) -> (f32, f32) {
+        match *self {
+            Self::Bt601 => (0.299, 0.114),
+            Self::Bt709 => (0.2126, 0.0722),
+            Self::Bt2020 => (0.2627, 0.0593),
+        }
+    }
+
+    // y in 0 ~ 1, cb and cr in -0.5 ~ 0.5, unclamped
+    pub fn to_rgb(self, y: f32, cb: f32, cr: f32) -> Vec3 {
+        let (kr, kb) = self.weights();
+        let kg = 1.0 - kr - kb;
+
+        let r = y + 2.0 * (1.0 - kr) * cr;
+        let b = y + 2.0 * (1.0 - kb) * cb;
+        let g = (y - kr * r - kb * b) / kg;
+        Vec3::new(r, g, b)
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum YcbcrRange {
+    // 16 ~ 235 and 16 ~ 240 at 8 bits
+    Limited,
+    Full,
+}
+
+impl YcbcrRange {
+    // samples of `bits` to y in 0 ~ 1 and cb, cr in -0.5 ~ 0.5
+    pub fn normalize(&self, bits: u32, y: f32, cb: f32, cr: f32) -> (f32, f32, f32) {
+        let half = (1 << (bits - 1)) as f32;
+
+        match *self {
+            Self::Limited => {
+                let scale = (1 << (bits - 8)) as f32;
+                (
+                    (y - 16.0 * scale) / (219.0 * scale),
+                    (cb - half) / (224.0 * scale),
+                    (cr - half) / (224.0 * scale),
+                )
+            }
+            Self::Full => {
+                let max = ((1 << bits) - 1) as f32;
+                (y / max, (cb - half) / max, (cr - half) / max)
+            }
+        }
+    }
+}
@@ -6,6 +6,7 @@ pub mod context;
 pub mod descriptor;
 pub mod device;
 pub mod resource;
+pub mod state_tracker;
 pub mod swapchain;
 pub mod timer;
 
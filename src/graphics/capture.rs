@@ -121,4 +121,10 @@ impl Capture {
 
         Ok(Self { resource })
     }
+    // a capture sized texture standing in for the screen
+    pub fn from_texture(texture: &Resource) -> Self {
+        Self {
+            resource: texture.share(),
+        }
+    }
 }
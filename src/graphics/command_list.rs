@@ -1,3 +1,7 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Weak;
+
 use windows::core::*;
 use windows::Win32::Graphics::Dxgi::Common::DXGI_FORMAT;
 use windows::Win32::{
@@ -9,6 +13,8 @@ use windows::Win32::{
 use super::context::Resource;
 use super::descriptor::{Descriptor, DescriptorHeap};
 use super::device::Device;
+use super::resource::TrackerKey;
+use super::state_tracker::{Barrier, StateTracker};
 
 pub struct CommandList {
     command_queue: ID3D12CommandQueue,
@@ -18,6 +24,11 @@ pub struct CommandList {
     fence: ID3D12Fence,
     fence_value: u64,
     fence_event: HANDLE,
+
+    // the states the recorded commands leave the resources in
+    state_tracker: RefCell<StateTracker<u64>>,
+    // the tracked resources, forgotten on reset once they are dropped
+    tracked: RefCell<HashMap<u64, Weak<TrackerKey>>>,
 }
 
 impl CommandList {
@@ -53,6 +64,8 @@ impl CommandList {
                 fence,
                 fence_value,
                 fence_event,
+                state_tracker: RefCell::new(StateTracker::new()),
+                tracked: RefCell::new(HashMap::new()),
             })
         }
     }
@@ -62,6 +75,17 @@ impl CommandList {
             self.command_allocator.Reset()?;
             self.command_list.Reset(&self.command_allocator, None)?;
         }
+
+        // ids are never reused, so a dropped resource only takes up room until here
+        let mut state_tracker = self.state_tracker.borrow_mut();
+        self.tracked.borrow_mut().retain(|&id, key| {
+            let alive = key.strong_count() > 0;
+            if !alive {
+                state_tracker.forget(id);
+            }
+            alive
+        });
+
         Ok(())
     }
 
@@ -167,6 +191,66 @@ impl CommandList {
         }
     }
 
+    // the resources in the states the next command uses them in, with only
+    // the barriers the tracked states call for
+    pub fn access(&self, uses: &[(&Resource, D3D12_RESOURCE_STATES)]) {
+        let mut tracked = self.tracked.borrow_mut();
+        for (resource, _) in uses {
+            tracked
+                .entry(resource.id())
+                .or_insert_with(|| resource.tracker_key());
+        }
+        drop(tracked);
+
+        let mut state_tracker = self.state_tracker.borrow_mut();
+
+        let planned = state_tracker.access(
+            &uses
+                .iter()
+                .map(|&(resource, state)| (resource.id(), resource.initial_state(), state.into()))
+                .collect::<Vec<_>>(),
+        );
+        drop(state_tracker);
+
+        let resource = |id: u64| {
+            uses.iter()
+                .find(|(resource, _)| resource.id() == id)
+                .map(|(resource, _)| &resource.resource)
+                .unwrap()
+        };
+
+        let barriers = planned
+            .into_iter()
+            .map(|barrier| match barrier {
+                Barrier::Transition {
+                    resource: id,
+                    before,
+                    after,
+                } => ResourceBarrier::transition(resource(id), before.into(), after.into()),
+                Barrier::Uav { resource: id } => ResourceBarrier::uav(resource(id)),
+            })
+            .collect::<Vec<_>>();
+
+        if !barriers.is_empty() {
+            self.resource_barrier(&barriers);
+        }
+    }
+
+    #[allow(unused)]
+    pub fn copy_buffer_region(
+        &self,
+        dest: &ID3D12Resource,
+        dest_offset: u64,
+        src: &ID3D12Resource,
+        src_offset: u64,
+        size: u64,
+    ) {
+        unsafe {
+            self.command_list
+                .CopyBufferRegion(dest, dest_offset, src, src_offset, size);
+        }
+    }
+
     pub fn clear_unordered_access_view_uint(
         &self,
         resource: &Resource,
@@ -212,6 +296,18 @@ impl CommandList {
         }
     }
 
+    pub fn set_render_targets(&self, rtvs: &[Descriptor], dsv: &Descriptor) {
+        let handles: Vec<_> = rtvs.iter().map(|rtv| rtv.cpu).collect();
+        unsafe {
+            self.command_list.OMSetRenderTargets(
+                handles.len() as _,
+                Some(handles.as_ptr()),
+                false,
+                Some(&dsv.cpu),
+            );
+        }
+    }
+
     pub fn set_viewport(&self, x: f32, y: f32, width: f32, height: f32) {
         unsafe {
             self.command_list.RSSetViewports(&[D3D12_VIEWPORT {
@@ -369,6 +465,18 @@ impl ResourceBarrier {
             },
         }
     }
+
+    pub fn uav(resource: &ID3D12Resource) -> D3D12_RESOURCE_BARRIER {
+        D3D12_RESOURCE_BARRIER {
+            Type: D3D12_RESOURCE_BARRIER_TYPE_UAV,
+            Flags: D3D12_RESOURCE_BARRIER_FLAG_NONE,
+            Anonymous: D3D12_RESOURCE_BARRIER_0 {
+                UAV: std::mem::ManuallyDrop::new(D3D12_RESOURCE_UAV_BARRIER {
+                    pResource: unsafe { std::mem::transmute_copy(resource) },
+                }),
+            },
+        }
+    }
 }
 
 pub fn div_round_up(n: u32, d: u32) -> u32 {
@@ -70,11 +70,7 @@ impl Context {
 
             command_list.reset()?;
 
-            command_list.resource_barrier(&[ResourceBarrier::transition(
-                &resources.buffer(),
-                D3D12_RESOURCE_STATE_PRESENT,
-                D3D12_RESOURCE_STATE_RENDER_TARGET,
-            )]);
+            command_list.access(&[(resources.buffer(), D3D12_RESOURCE_STATE_RENDER_TARGET)]);
 
             let rtv = resources.rtv();
             let dsv = resources.dsv();
@@ -94,11 +90,7 @@ impl Context {
         if let Some(resources) = &self.swapchain.resources {
             let command_list = &mut self.command_list;
 
-            command_list.resource_barrier(&[ResourceBarrier::transition(
-                &resources.buffer(),
-                D3D12_RESOURCE_STATE_RENDER_TARGET,
-                D3D12_RESOURCE_STATE_PRESENT,
-            )]);
+            command_list.access(&[(resources.buffer(), D3D12_RESOURCE_STATE_PRESENT)]);
 
             self.timer.borrow_mut().resolve(command_list);
 
@@ -4,9 +4,9 @@ use windows::Win32::Graphics::Dxgi::Common::*;
 
 use super::context::Resource;
 use super::device::Device;
-use super::swapchain::BUFFER_COUNT;
+use super::swapchain::{BUFFER_COUNT, TRANSPARENCY_FORMATS};
 
-const MAX_DESCRIPTORS: u32 = 64;
+const MAX_DESCRIPTORS: u32 = 128;
 
 #[derive(Clone, Copy, Default, Debug)]
 pub struct Descriptor {
@@ -43,7 +43,7 @@ impl DescriptorHeap {
 
         let rtv_heap = device.create_descriptor_heap(
             D3D12_DESCRIPTOR_HEAP_TYPE_RTV,
-            BUFFER_COUNT,
+            BUFFER_COUNT + TRANSPARENCY_FORMATS.len() as u32,
             D3D12_DESCRIPTOR_HEAP_FLAG_NONE,
         )?;
 
@@ -66,7 +66,7 @@ impl DescriptorHeap {
             srv_size,
             rtv_size,
             dsv_size,
-            num_shader_visibles: 1, // 0 for capture srv
+            num_shader_visibles: 3, // 0 for capture srv, 1 and 2 for transparency
             num_non_shader_visibles: 0,
         })
     }
@@ -206,7 +206,7 @@ impl Device {
         depth_stencil: D3D12_DEPTH_STENCIL_DESC,
         input_elements: &[D3D12_INPUT_ELEMENT_DESC],
         primitive_topology: Option<D3D12_PRIMITIVE_TOPOLOGY_TYPE>,
-        rtv_format: Option<DXGI_FORMAT>,
+        rtv_formats: Option<&[DXGI_FORMAT]>,
         dsv_format: Option<DXGI_FORMAT>,
         sample_desc: Option<DXGI_SAMPLE_DESC>,
     ) -> Result<ID3D12PipelineState> {
@@ -226,8 +226,8 @@ impl Device {
                     },
                     PrimitiveTopologyType: primitive_topology
                         .unwrap_or(D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE),
-                    NumRenderTargets: 1,
-                    RTVFormats: RtvFormats::single(rtv_format),
+                    NumRenderTargets: RtvFormats::count(rtv_formats),
+                    RTVFormats: RtvFormats::array(rtv_formats),
                     DSVFormat: dsv_format.unwrap_or(DXGI_FORMAT_D32_FLOAT),
                     SampleDesc: sample_desc.unwrap_or(SampleDesc::default()),
                     ..Default::default()
@@ -262,7 +262,7 @@ impl Device {
         rasterizer: D3D12_RASTERIZER_DESC,
         depth_stencil: D3D12_DEPTH_STENCIL_DESC,
         primitive_topology: D3D12_PRIMITIVE_TOPOLOGY_TYPE,
-        rtv_format: Option<DXGI_FORMAT>,
+        rtv_formats: Option<&[DXGI_FORMAT]>,
         dsv_format: Option<DXGI_FORMAT>,
     ) -> Result<ID3D12PipelineState> {
         unsafe {
@@ -280,8 +280,8 @@ impl Device {
                 depth_stencil: PipelineSubject::new(depth_stencil),
                 primitive_topology: PipelineSubject::new(primitive_topology),
                 rtv_formats: PipelineSubject::new(D3D12_RT_FORMAT_ARRAY {
-                    RTFormats: RtvFormats::single(rtv_format),
-                    NumRenderTargets: 1,
+                    RTFormats: RtvFormats::array(rtv_formats),
+                    NumRenderTargets: RtvFormats::count(rtv_formats),
                 }),
                 dsv_format: PipelineSubject::new(dsv_format.unwrap_or(DXGI_FORMAT_D32_FLOAT)),
                 sample_desc: PipelineSubject::new(SampleDesc::default()),
@@ -375,6 +375,75 @@ impl BlendState {
             ],
         }
     }
+
+    pub fn premultiplied() -> D3D12_BLEND_DESC {
+        D3D12_BLEND_DESC {
+            AlphaToCoverageEnable: FALSE,
+            IndependentBlendEnable: FALSE,
+            RenderTarget: [
+                D3D12_RENDER_TARGET_BLEND_DESC {
+                    BlendEnable: TRUE,
+                    LogicOpEnable: FALSE,
+                    SrcBlend: D3D12_BLEND_ONE,
+                    DestBlend: D3D12_BLEND_INV_SRC_ALPHA,
+                    BlendOp: D3D12_BLEND_OP_ADD,
+                    SrcBlendAlpha: D3D12_BLEND_ONE,
+                    DestBlendAlpha: D3D12_BLEND_INV_SRC_ALPHA,
+                    BlendOpAlpha: D3D12_BLEND_OP_ADD,
+                    LogicOp: D3D12_LOGIC_OP_NOOP,
+                    RenderTargetWriteMask: D3D12_COLOR_WRITE_ENABLE_ALL.0 as _,
+                },
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+            ],
+        }
+    }
+
+    // weighted blended order independent transparency, the weighted colors are
+    // summed in the first target and the revealage multiplied in the second
+    pub fn weighted_blended() -> D3D12_BLEND_DESC {
+        D3D12_BLEND_DESC {
+            AlphaToCoverageEnable: FALSE,
+            IndependentBlendEnable: TRUE,
+            RenderTarget: [
+                D3D12_RENDER_TARGET_BLEND_DESC {
+                    BlendEnable: TRUE,
+                    LogicOpEnable: FALSE,
+                    SrcBlend: D3D12_BLEND_ONE,
+                    DestBlend: D3D12_BLEND_ONE,
+                    BlendOp: D3D12_BLEND_OP_ADD,
+                    SrcBlendAlpha: D3D12_BLEND_ONE,
+                    DestBlendAlpha: D3D12_BLEND_ONE,
+                    BlendOpAlpha: D3D12_BLEND_OP_ADD,
+                    LogicOp: D3D12_LOGIC_OP_NOOP,
+                    RenderTargetWriteMask: D3D12_COLOR_WRITE_ENABLE_ALL.0 as _,
+                },
+                D3D12_RENDER_TARGET_BLEND_DESC {
+                    BlendEnable: TRUE,
+                    LogicOpEnable: FALSE,
+                    SrcBlend: D3D12_BLEND_ZERO,
+                    DestBlend: D3D12_BLEND_INV_SRC_COLOR,
+                    BlendOp: D3D12_BLEND_OP_ADD,
+                    SrcBlendAlpha: D3D12_BLEND_ZERO,
+                    DestBlendAlpha: D3D12_BLEND_INV_SRC_ALPHA,
+                    BlendOpAlpha: D3D12_BLEND_OP_ADD,
+                    LogicOp: D3D12_LOGIC_OP_NOOP,
+                    RenderTargetWriteMask: D3D12_COLOR_WRITE_ENABLE_ALL.0 as _,
+                },
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+            ],
+        }
+    }
 }
 
 pub struct RasterizerState();
@@ -420,6 +489,16 @@ impl DepthStencilState {
             ..Default::default()
         }
     }
+
+    pub fn depth_read_only() -> D3D12_DEPTH_STENCIL_DESC {
+        D3D12_DEPTH_STENCIL_DESC {
+            DepthEnable: TRUE,
+            DepthWriteMask: D3D12_DEPTH_WRITE_MASK_ZERO,
+            DepthFunc: D3D12_COMPARISON_FUNC_LESS,
+            StencilEnable: FALSE,
+            ..Default::default()
+        }
+    }
 }
 
 pub struct InputElement();
@@ -455,17 +534,18 @@ impl InputElement {
 pub struct RtvFormats();
 
 impl RtvFormats {
-    fn single(format: Option<DXGI_FORMAT>) -> [DXGI_FORMAT; 8] {
-        [
-            format.unwrap_or(DXGI_FORMAT_R8G8B8A8_UNORM),
-            DXGI_FORMAT_UNKNOWN,
-            DXGI_FORMAT_UNKNOWN,
-            DXGI_FORMAT_UNKNOWN,
-            DXGI_FORMAT_UNKNOWN,
-            DXGI_FORMAT_UNKNOWN,
-            DXGI_FORMAT_UNKNOWN,
-            DXGI_FORMAT_UNKNOWN,
-        ]
+    // a single R8G8B8A8_UNORM target by default
+    fn array(formats: Option<&[DXGI_FORMAT]>) -> [DXGI_FORMAT; 8] {
+        let mut array = [DXGI_FORMAT_UNKNOWN; 8];
+        match formats {
+            Some(formats) => array[..formats.len()].copy_from_slice(formats),
+            None => array[0] = DXGI_FORMAT_R8G8B8A8_UNORM,
+        }
+        array
+    }
+
+    fn count(formats: Option<&[DXGI_FORMAT]>) -> u32 {
+        formats.map_or(1, |formats| formats.len() as _)
     }
 }
 
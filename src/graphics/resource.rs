@@ -1,6 +1,8 @@
 use std::mem::size_of;
 use std::ops::Deref;
 use std::os::raw::c_void;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
 
 use windows::core::*;
 use windows::Win32::Foundation::HANDLE;
@@ -9,9 +11,26 @@ use windows::Win32::Graphics::Dxgi::Common::*;
 
 use super::descriptor::*;
 use super::device::*;
+use super::state_tracker::ResourceState;
+
+// the key of a resource in the state tracker, never reused
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+// shared by the wrappers of one resource, the command list holds it weakly to
+// forget the resource once the last of them is dropped
+pub struct TrackerKey(u64);
+
+impl TrackerKey {
+    fn new() -> Arc<Self> {
+        Arc::new(Self(NEXT_ID.fetch_add(1, Ordering::Relaxed)))
+    }
+}
 
 pub struct Resource {
     pub resource: ID3D12Resource,
+    key: Arc<TrackerKey>,
+    // the state it was created in, where the state tracker starts
+    initial_state: ResourceState,
     srv: Descriptor,
     uav: Descriptor,
     misc0: Descriptor,
@@ -29,6 +48,8 @@ impl Resource {
     ) -> Result<Self> {
         Ok(Self {
             resource: device.create_resource(&heap_props, heap_flags, &desc, state, clear)?,
+            key: TrackerKey::new(),
+            initial_state: state.into(),
             srv: Descriptor::default(),
             uav: Descriptor::default(),
             misc0: Descriptor::default(),
@@ -60,7 +81,7 @@ impl Resource {
             HeapProps::upload(),
             D3D12_HEAP_FLAG_NONE,
             ResourceDesc::buffer(size, D3D12_RESOURCE_FLAG_NONE),
-            D3D12_RESOURCE_STATE_COPY_DEST,
+            D3D12_RESOURCE_STATE_GENERIC_READ,
             None,
         )
     }
@@ -122,6 +143,8 @@ impl Resource {
     pub fn wrap(resource: ID3D12Resource) -> Self {
         Self {
             resource,
+            key: TrackerKey::new(),
+            initial_state: ResourceState::COMMON,
             srv: Descriptor::default(),
             uav: Descriptor::default(),
             misc0: Descriptor::default(),
@@ -129,6 +152,31 @@ impl Resource {
         }
     }
 
+    // another wrapper of the same resource, tracked as one with this
+    pub fn share(&self) -> Self {
+        Self {
+            resource: self.resource.clone(),
+            key: Arc::clone(&self.key),
+            initial_state: self.initial_state,
+            srv: self.srv,
+            uav: self.uav,
+            misc0: self.misc0,
+            misc1: self.misc1,
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.key.0
+    }
+
+    pub fn tracker_key(&self) -> Weak<TrackerKey> {
+        Arc::downgrade(&self.key)
+    }
+
+    pub fn initial_state(&self) -> ResourceState {
+        self.initial_state
+    }
+
     pub fn srv(&self) -> &Descriptor {
         &self.srv
     }
@@ -204,6 +252,18 @@ impl Resource {
     }
 }
 
+impl From<D3D12_RESOURCE_STATES> for ResourceState {
+    fn from(value: D3D12_RESOURCE_STATES) -> Self {
+        Self(value.0)
+    }
+}
+
+impl From<ResourceState> for D3D12_RESOURCE_STATES {
+    fn from(value: ResourceState) -> Self {
+        Self(value.0)
+    }
+}
+
 impl Deref for Resource {
     type Target = ID3D12Resource;
 
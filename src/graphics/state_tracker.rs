@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::BitOr;
+
+// the bits of D3D12_RESOURCE_STATES, spelled out so that the planning runs
+// without D3D12
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct ResourceState(pub i32);
+
+impl ResourceState {
+    pub const COMMON: Self = Self(0);
+    pub const VERTEX_AND_CONSTANT_BUFFER: Self = Self(0x1);
+    pub const INDEX_BUFFER: Self = Self(0x2);
+    pub const RENDER_TARGET: Self = Self(0x4);
+    pub const UNORDERED_ACCESS: Self = Self(0x8);
+    pub const DEPTH_WRITE: Self = Self(0x10);
+    pub const DEPTH_READ: Self = Self(0x20);
+    pub const NON_PIXEL_SHADER_RESOURCE: Self = Self(0x40);
+    pub const PIXEL_SHADER_RESOURCE: Self = Self(0x80);
+    pub const STREAM_OUT: Self = Self(0x100);
+    pub const INDIRECT_ARGUMENT: Self = Self(0x200);
+    pub const COPY_DEST: Self = Self(0x400);
+    pub const COPY_SOURCE: Self = Self(0x800);
+
+    pub const SHADER_RESOURCE: Self = Self(0x40 | 0x80);
+    pub const GENERIC_READ: Self = Self(0x1 | 0x2 | 0x40 | 0x80 | 0x200 | 0x800);
+
+    const WRITES: i32 = 0x4 | 0x8 | 0x10 | 0x100 | 0x400;
+
+    // one or more read states, which a resource may be in at once
+    pub fn is_read(self) -> bool {
+        self.0 != 0 && self.0 & Self::WRITES == 0
+    }
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for ResourceState {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Barrier<K> {
+    Transition {
+        resource: K,
+        before: ResourceState,
+        after: ResourceState,
+    },
+    // between two commands writing the same unordered access view
+    Uav {
+        resource: K,
+    },
+}
+
+// the state each resource is left in by the commands recorded so far, and the
+// barriers the next command needs
+#[derive(Default)]
+pub struct StateTracker<K> {
+    states: HashMap<K, ResourceState>,
+}
+
+impl<K: Copy + Eq + Hash> StateTracker<K> {
+    pub fn new() -> Self {
+        Self {
+            states: HashMap::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn state(&self, resource: K) -> Option<ResourceState> {
+        self.states.get(&resource).copied()
+    }
+
+    // a resource the tracker has not seen is in `initial`, the state it was created in
+    pub fn access(&mut self, uses: &[(K, ResourceState, ResourceState)]) -> Vec<Barrier<K>> {
+        let mut barriers = vec![];
+
+        for &(resource, initial, state) in uses {
+            let current = *self.states.entry(resource).or_insert(initial);
+
+            if current == state {
+                if state == ResourceState::UNORDERED_ACCESS {
+                    barriers.push(Barrier::Uav { resource });
+                }
+                continue;
+            }
+
+            // reads stay in the states they already are in, or join them
+            let after = if state.is_read() && current.is_read() {
+                if current.contains(state) {
+                    continue;
+                }
+                current | state
+            } else {
+                state
+            };
+
+            barriers.push(Barrier::Transition {
+                resource,
+                before: current,
+                after,
+            });
+            self.states.insert(resource, after);
+        }
+
+        barriers
+    }
+
+    // for a resource dropped or moved outside the tracked commands
+    pub fn forget(&mut self, resource: K) {
+        self.states.remove(&resource);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_state_needs_no_barrier() {
+        let mut tracker = StateTracker::new();
+
+        let first = tracker.access(&[(0, ResourceState::COMMON, ResourceState::COPY_DEST)]);
+        assert_eq!(first.len(), 1);
+
+        let second = tracker.access(&[(0, ResourceState::COMMON, ResourceState::COPY_DEST)]);
+        assert!(second.is_empty());
+        assert_eq!(tracker.state(0), Some(ResourceState::COPY_DEST));
+    }
+
+    #[test]
+    fn state_change_is_one_transition() {
+        let mut tracker = StateTracker::new();
+        tracker.access(&[(
+            1,
+            ResourceState::UNORDERED_ACCESS,
+            ResourceState::UNORDERED_ACCESS,
+        )]);
+
+        let barriers = tracker.access(&[(
+            1,
+            ResourceState::UNORDERED_ACCESS,
+            ResourceState::COPY_SOURCE,
+        )]);
+        assert_eq!(
+            barriers,
+            vec![Barrier::Transition {
+                resource: 1,
+                before: ResourceState::UNORDERED_ACCESS,
+                after: ResourceState::COPY_SOURCE,
+            }]
+        );
+    }
+
+    #[test]
+    fn uses_in_one_call_are_batched() {
+        let mut tracker = StateTracker::new();
+
+        let barriers = tracker.access(&[
+            (1, ResourceState::COMMON, ResourceState::UNORDERED_ACCESS),
+            (2, ResourceState::COPY_DEST, ResourceState::SHADER_RESOURCE),
+            (
+                3,
+                ResourceState::SHADER_RESOURCE,
+                ResourceState::SHADER_RESOURCE,
+            ),
+        ]);
+        assert_eq!(
+            barriers,
+            vec![
+                Barrier::Transition {
+                    resource: 1,
+                    before: ResourceState::COMMON,
+                    after: ResourceState::UNORDERED_ACCESS,
+                },
+                Barrier::Transition {
+                    resource: 2,
+                    before: ResourceState::COPY_DEST,
+                    after: ResourceState::SHADER_RESOURCE,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_join_and_writes_need_uav_barriers() {
+        let mut tracker = StateTracker::new();
+
+        let barriers = tracker.access(&[(
+            4,
+            ResourceState::PIXEL_SHADER_RESOURCE,
+            ResourceState::INDIRECT_ARGUMENT,
+        )]);
+        assert_eq!(
+            barriers,
+            vec![Barrier::Transition {
+                resource: 4,
+                before: ResourceState::PIXEL_SHADER_RESOURCE,
+                after: ResourceState::PIXEL_SHADER_RESOURCE | ResourceState::INDIRECT_ARGUMENT,
+            }]
+        );
+
+        tracker.access(&[(5, ResourceState::COMMON, ResourceState::UNORDERED_ACCESS)]);
+        let barriers =
+            tracker.access(&[(5, ResourceState::COMMON, ResourceState::UNORDERED_ACCESS)]);
+        assert_eq!(barriers, vec![Barrier::Uav { resource: 5 }]);
+    }
+
+    #[test]
+    fn forgotten_resource_starts_over() {
+        let mut tracker = StateTracker::new();
+        tracker.access(&[(6, ResourceState::COMMON, ResourceState::RENDER_TARGET)]);
+
+        tracker.forget(6);
+        assert_eq!(tracker.state(6), None);
+
+        let barriers = tracker.access(&[(6, ResourceState::COMMON, ResourceState::COMMON)]);
+        assert!(barriers.is_empty());
+    }
+}
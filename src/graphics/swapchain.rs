@@ -19,6 +19,13 @@ use crate::state::Window;
 
 pub const BUFFER_COUNT: u32 = 2;
 
+// the weighted colors and the revealage of the order independent transparency
+pub const TRANSPARENCY_FORMATS: [DXGI_FORMAT; 2] =
+    [DXGI_FORMAT_R32G32B32A32_FLOAT, DXGI_FORMAT_R16_FLOAT];
+// the rtvs follow the swapchain buffers, the srvs follow the capture srv
+pub const TRANSPARENCY_RTV_INDEX: u32 = BUFFER_COUNT;
+pub const TRANSPARENCY_SRV_INDEX: u32 = 1;
+
 pub struct SwapChain {
     pub swapchain: IDXGISwapChain4,
     pub resources: Option<BufferDependentResource>,
@@ -111,6 +118,9 @@ pub struct BufferDependentResource {
     buffers: Vec<Resource>,
 
     depth: Resource,
+
+    accum: Resource,
+    revealage: Resource,
 }
 
 impl BufferDependentResource {
@@ -140,10 +150,31 @@ impl BufferDependentResource {
             )?;
             descriptor_heap.create_dsv(&mut depth, DXGI_FORMAT_D32_FLOAT, D3D12_DSV_FLAG_NONE);
 
+            let mut targets = vec![];
+            for (i, format) in TRANSPARENCY_FORMATS.into_iter().enumerate() {
+                let mut target = Resource::new_texture2d(
+                    device,
+                    desc.BufferDesc.Width,
+                    desc.BufferDesc.Height,
+                    format,
+                    SampleDesc::default(),
+                    D3D12_RESOURCE_FLAG_ALLOW_RENDER_TARGET,
+                    D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE,
+                )?;
+                descriptor_heap.create_rtv(&mut target, TRANSPARENCY_RTV_INDEX + i as u32);
+                descriptor_heap.create_srv_at(TRANSPARENCY_SRV_INDEX + i as u32, &mut target, None);
+
+                targets.push(target);
+            }
+            let revealage = targets.pop().unwrap();
+            let accum = targets.pop().unwrap();
+
             Ok(Self {
                 swapchain,
                 buffers,
                 depth,
+                accum,
+                revealage,
             })
         }
     }
@@ -160,4 +191,8 @@ impl BufferDependentResource {
     pub fn dsv(&self) -> &Descriptor {
         self.depth.dsv()
     }
+
+    pub fn transparency(&self) -> [&Resource; 2] {
+        [&self.accum, &self.revealage]
+    }
 }
@@ -15,16 +15,120 @@ pub const VIEW_RGB_B: ControlId = ControlId(VIEW_RGB.0 + 3);
 pub const VIEW_HUE: ControlId = ControlId(VIEW_RGB_B.0 + 1);
 pub const VIEW_SATURATION: ControlId = ControlId(VIEW_RGB_B.0 + 2);
 pub const VIEW_BRIGHTNESS: ControlId = ControlId(VIEW_RGB_B.0 + 3);
-pub const HISTOGRAM_DISABLE: ControlId = ControlId(VIEW_BRIGHTNESS.0 + 1);
+pub const VIEW_HARMONY: ControlId = ControlId(VIEW_RGB_B.0 + 4);
+pub const VIEW_DELTA_E: ControlId = ControlId(VIEW_RGB_B.0 + 5);
+pub const VIEW_NOTAN: ControlId = ControlId(VIEW_RGB_B.0 + 6);
+pub const VIEW_POSTERIZE: ControlId = ControlId(VIEW_RGB_B.0 + 7);
+pub const VIEW_SQUINT: ControlId = ControlId(VIEW_RGB_B.0 + 8);
+pub const VIEW_LOCAL_CONTRAST: ControlId = ControlId(VIEW_RGB_B.0 + 9);
+pub const VIEW_PALETTE: ControlId = ControlId(VIEW_RGB_B.0 + 10);
+pub const VIEW_PALETTE_COMPLIANCE: ControlId = ControlId(VIEW_RGB_B.0 + 11);
+pub const VIEW_SELECTION: ControlId = ControlId(VIEW_RGB_B.0 + 12);
+pub const HISTOGRAM_DISABLE: ControlId = ControlId(VIEW_SELECTION.0 + 1);
 pub const HISTOGRAM_RGB: ControlId = ControlId(HISTOGRAM_DISABLE.0 + 1);
 pub const HISTOGRAM_HUE: ControlId = ControlId(HISTOGRAM_DISABLE.0 + 2);
 pub const HISTOGRAM_SATURAION: ControlId = ControlId(HISTOGRAM_DISABLE.0 + 3);
 pub const HISTOGRAM_BRIGHTNESS: ControlId = ControlId(HISTOGRAM_DISABLE.0 + 4);
-pub const COLOR_CLOUD_DISABLE: ControlId = ControlId(HISTOGRAM_BRIGHTNESS.0 + 1);
+pub const HISTOGRAM_HUE_WHEEL: ControlId = ControlId(HISTOGRAM_DISABLE.0 + 5);
+pub const HISTOGRAM_HUE_DISC: ControlId = ControlId(HISTOGRAM_DISABLE.0 + 6);
+pub const HISTOGRAM_JOINT: ControlId = ControlId(HISTOGRAM_DISABLE.0 + 7);
+pub const HISTOGRAM_LINEAR: ControlId = ControlId(HISTOGRAM_JOINT.0 + JointAxes::ALL.len() as u32);
+pub const HISTOGRAM_LOG: ControlId = ControlId(HISTOGRAM_LINEAR.0 + 1);
+pub const COLOR_CLOUD_DISABLE: ControlId = ControlId(HISTOGRAM_LOG.0 + 1);
 pub const COLOR_CLOUD_RGB: ControlId = ControlId(COLOR_CLOUD_DISABLE.0 + 1);
 pub const COLOR_CLOUD_HSV: ControlId = ControlId(COLOR_CLOUD_DISABLE.0 + 2);
 pub const COLOR_CLOUD_HSL: ControlId = ControlId(COLOR_CLOUD_DISABLE.0 + 3);
 pub const COLOR_CLOUD_YUV: ControlId = ControlId(COLOR_CLOUD_DISABLE.0 + 4);
+pub const HARMONY_DISABLE: ControlId = ControlId(COLOR_CLOUD_YUV.0 + 1);
+pub const HARMONY_AUTO: ControlId = ControlId(HARMONY_DISABLE.0 + 1);
+pub const HARMONY_TEMPLATE: ControlId = ControlId(HARMONY_DISABLE.0 + 2);
+pub const HARMONY_SCORE: ControlId =
+    ControlId(HARMONY_TEMPLATE.0 + HarmonyTemplate::ALL.len() as u32);
+pub const NAMING_DISABLE: ControlId = ControlId(HARMONY_SCORE.0 + 1);
+pub const NAMING_CSS: ControlId = ControlId(NAMING_DISABLE.0 + 1);
+pub const NAMING_ISCC_NBS: ControlId = ControlId(NAMING_DISABLE.0 + 2);
+pub const NAMING_CUSTOM: ControlId = ControlId(NAMING_DISABLE.0 + 3);
+pub const NAMING_FAMILY: ControlId = ControlId(NAMING_DISABLE.0 + 4);
+pub const NUM_NAMING_FAMILIES: u32 = 8;
+pub const PROBE_COLOR: ControlId = ControlId(NAMING_FAMILY.0 + NUM_NAMING_FAMILIES);
+pub const PROBE_NAME: ControlId = ControlId(PROBE_COLOR.0 + 1);
+pub const PROBE_MEAN: ControlId = ControlId(PROBE_COLOR.0 + 2);
+pub const TARGET_HEX: ControlId = ControlId(PROBE_MEAN.0 + 1);
+pub const TARGET_DELTA_E: ControlId = ControlId(TARGET_HEX.0 + 1);
+pub const TARGET_PROBE: ControlId = ControlId(TARGET_DELTA_E.0 + DeltaE::ALL.len() as u32);
+pub const TARGET_MEAN: ControlId = ControlId(TARGET_PROBE.0 + 1);
+pub const SATURATION_MEASURE: ControlId = ControlId(TARGET_MEAN.0 + 1);
+pub const BRIGHTNESS_MEASURE: ControlId =
+    ControlId(SATURATION_MEASURE.0 + SaturationMeasure::ALL.len() as u32);
+pub const NOTAN_THRESHOLDS: ControlId =
+    ControlId(BRIGHTNESS_MEASURE.0 + BrightnessMeasure::ALL.len() as u32);
+pub const POSTERIZE_LEVELS: ControlId = ControlId(NOTAN_THRESHOLDS.0 + 1);
+pub const POSTERIZE_CHANNEL: ControlId = ControlId(NOTAN_THRESHOLDS.0 + 2);
+pub const POSTERIZE_LIGHTNESS: ControlId = ControlId(NOTAN_THRESHOLDS.0 + 3);
+pub const SQUINT_RADIUS: ControlId = ControlId(POSTERIZE_LIGHTNESS.0 + 1);
+pub const SQUINT_GAUSSIAN: ControlId = ControlId(SQUINT_RADIUS.0 + 1);
+pub const SQUINT_BOX: ControlId = ControlId(SQUINT_RADIUS.0 + 2);
+pub const SQUINT_ANALYSIS_OFF: ControlId = ControlId(SQUINT_RADIUS.0 + 3);
+pub const SQUINT_ANALYSIS_ON: ControlId = ControlId(SQUINT_RADIUS.0 + 4);
+pub const PALETTE_DISABLE: ControlId = ControlId(SQUINT_ANALYSIS_ON.0 + 1);
+pub const PALETTE_LOAD: ControlId = ControlId(PALETTE_DISABLE.0 + 1);
+pub const PALETTE_TOLERANCE: ControlId = ControlId(PALETTE_DISABLE.0 + 2);
+pub const PALETTE_OFF: ControlId = ControlId(PALETTE_DISABLE.0 + 3);
+pub const PALETTE_COVERAGE: ControlId = ControlId(PALETTE_DISABLE.0 + 4);
+pub const NUM_PALETTE_COVERAGES: u32 = 8;
+pub const POINT_SIZE: ControlId = ControlId(PALETTE_COVERAGE.0 + NUM_PALETTE_COVERAGES);
+pub const POINT_MIN_SIZE: ControlId = ControlId(POINT_SIZE.0 + PointSize::ALL.len() as u32);
+pub const POINT_MAX_SIZE: ControlId = ControlId(POINT_MIN_SIZE.0 + 1);
+pub const POINT_SCALE: ControlId = ControlId(POINT_MIN_SIZE.0 + 2);
+pub const POINT_SOLID: ControlId = ControlId(POINT_MIN_SIZE.0 + 3);
+pub const POINT_WEIGHTED: ControlId = ControlId(POINT_MIN_SIZE.0 + 4);
+pub const CLOUD_BITS: ControlId = ControlId(POINT_WEIGHTED.0 + 1);
+pub const SELECTION_DIM: ControlId = ControlId(CLOUD_BITS.0 + 1);
+pub const SELECTION_GREY: ControlId = ControlId(SELECTION_DIM.0 + 1);
+pub const SELECTION_INFO: ControlId = ControlId(SELECTION_DIM.0 + 2);
+pub const CAMERA_PRESET: ControlId = ControlId(SELECTION_INFO.0 + 1);
+pub const CAMERA_ORTHOGRAPHIC: ControlId =
+    ControlId(CAMERA_PRESET.0 + CameraPreset::ALL.len() as u32);
+pub const CAMERA_PERSPECTIVE: ControlId = ControlId(CAMERA_ORTHOGRAPHIC.0 + 1);
+pub const CAMERA_TURNTABLE_OFF: ControlId = ControlId(CAMERA_ORTHOGRAPHIC.0 + 2);
+pub const CAMERA_TURNTABLE_ON: ControlId = ControlId(CAMERA_ORTHOGRAPHIC.0 + 3);
+pub const CLOUD_GUIDES_OFF: ControlId = ControlId(CAMERA_TURNTABLE_ON.0 + 1);
+pub const CLOUD_GUIDES_ON: ControlId = ControlId(CLOUD_GUIDES_OFF.0 + 1);
+pub const CLOUD_GAMUT_NONE: ControlId = ControlId(CLOUD_GUIDES_OFF.0 + 2);
+pub const CLOUD_GAMUT_P3: ControlId = ControlId(CLOUD_GUIDES_OFF.0 + 3);
+pub const CLOUD_GAMUT_REC2020: ControlId = ControlId(CLOUD_GUIDES_OFF.0 + 4);
+pub const CLIP: ControlId = ControlId(CLOUD_GAMUT_REC2020.0 + 1);
+pub const CLIP_LIGHTNESS: ControlId = ControlId(CLIP.0 + CloudClip::ALL.len() as u32);
+pub const CLIP_HUE: ControlId = ControlId(CLIP_LIGHTNESS.0 + 1);
+pub const CLIP_DEPTH: ControlId = ControlId(CLIP_LIGHTNESS.0 + 2);
+pub const CLIP_SLICE: ControlId = ControlId(CLIP_LIGHTNESS.0 + 3);
+pub const VIEW_EXPORT: ControlId = ControlId(CLIP_SLICE.0 + 1);
+pub const CHROMATICITY_DISABLE: ControlId = ControlId(VIEW_EXPORT.0 + 1);
+pub const CHROMATICITY_XY: ControlId = ControlId(CHROMATICITY_DISABLE.0 + 1);
+pub const CHROMATICITY_UV: ControlId = ControlId(CHROMATICITY_DISABLE.0 + 2);
+pub const STATS_RECORD_OFF: ControlId = ControlId(CHROMATICITY_UV.0 + 1);
+pub const STATS_RECORD: ControlId = ControlId(STATS_RECORD_OFF.0 + 1);
+pub const STATS_CHART_OFF: ControlId = ControlId(STATS_RECORD_OFF.0 + 2);
+pub const STATS_CHART_ON: ControlId = ControlId(STATS_RECORD_OFF.0 + 3);
+pub const STATS_WINDOW: ControlId = ControlId(STATS_RECORD_OFF.0 + 4);
+pub const SOURCE_SCREEN: ControlId = ControlId(STATS_WINDOW.0 + 1);
+pub const SOURCE_VIDEO: ControlId = ControlId(SOURCE_SCREEN.0 + 1);
+pub const VIDEO_PREVIOUS: ControlId = ControlId(SOURCE_SCREEN.0 + 2);
+pub const VIDEO_NEXT: ControlId = ControlId(SOURCE_SCREEN.0 + 3);
+pub const VIDEO_FRAME: ControlId = ControlId(SOURCE_SCREEN.0 + 4);
+pub const VIDEO_LEN: ControlId = ControlId(SOURCE_SCREEN.0 + 5);
+pub const MATRIX_BT601: ControlId = ControlId(SOURCE_SCREEN.0 + 6);
+pub const MATRIX_BT709: ControlId = ControlId(SOURCE_SCREEN.0 + 7);
+pub const MATRIX_BT2020: ControlId = ControlId(SOURCE_SCREEN.0 + 8);
+pub const BARCODE_AVERAGE: ControlId = ControlId(MATRIX_BT2020.0 + 1);
+pub const BARCODE_DOMINANT: ControlId = ControlId(BARCODE_AVERAGE.0 + 1);
+pub const BARCODE_EXPORT: ControlId = ControlId(BARCODE_AVERAGE.0 + 2);
+pub const VIEW_COMPARE: ControlId = ControlId(BARCODE_EXPORT.0 + 1);
+pub const COMPARE_OFF: ControlId = ControlId(VIEW_COMPARE.0 + 1);
+pub const COMPARE_SNAPSHOT: ControlId = ControlId(COMPARE_OFF.0 + 1);
+pub const COMPARE_LOAD: ControlId = ControlId(COMPARE_OFF.0 + 2);
+pub const COMPARE_DELTA_E: ControlId = ControlId(COMPARE_OFF.0 + 3);
+pub const COMPARE_EMD: ControlId = ControlId(COMPARE_OFF.0 + 4);
 
 impl ControlId {
     pub fn from_wp(wp: WPARAM) -> Self {
@@ -63,6 +167,126 @@ impl ControlId {
     //     }
     // }
 
+    pub fn from_harmony_template(template: HarmonyTemplate) -> Self {
+        let index = HarmonyTemplate::ALL
+            .iter()
+            .position(|&t| t == template)
+            .unwrap_or_default();
+        Self(HARMONY_TEMPLATE.0 + index as u32)
+    }
+
+    pub fn harmony_template(&self) -> Option<HarmonyTemplate> {
+        self.0
+            .checked_sub(HARMONY_TEMPLATE.0)
+            .and_then(|index| HarmonyTemplate::ALL.get(index as usize).copied())
+    }
+
+    pub fn from_joint_axes(axes: JointAxes) -> Self {
+        let index = JointAxes::ALL
+            .iter()
+            .position(|&a| a == axes)
+            .unwrap_or_default();
+        Self(HISTOGRAM_JOINT.0 + index as u32)
+    }
+
+    pub fn joint_axes(&self) -> Option<JointAxes> {
+        self.0
+            .checked_sub(HISTOGRAM_JOINT.0)
+            .and_then(|index| JointAxes::ALL.get(index as usize).copied())
+    }
+
+    pub fn from_delta_e(delta_e: DeltaE) -> Self {
+        let index = DeltaE::ALL
+            .iter()
+            .position(|&d| d == delta_e)
+            .unwrap_or_default();
+        Self(TARGET_DELTA_E.0 + index as u32)
+    }
+
+    pub fn delta_e(&self) -> Option<DeltaE> {
+        self.0
+            .checked_sub(TARGET_DELTA_E.0)
+            .and_then(|index| DeltaE::ALL.get(index as usize).copied())
+    }
+
+    pub fn from_saturation_measure(measure: SaturationMeasure) -> Self {
+        let index = SaturationMeasure::ALL
+            .iter()
+            .position(|&m| m == measure)
+            .unwrap_or_default();
+        Self(SATURATION_MEASURE.0 + index as u32)
+    }
+
+    pub fn saturation_measure(&self) -> Option<SaturationMeasure> {
+        self.0
+            .checked_sub(SATURATION_MEASURE.0)
+            .and_then(|index| SaturationMeasure::ALL.get(index as usize).copied())
+    }
+
+    pub fn from_brightness_measure(measure: BrightnessMeasure) -> Self {
+        let index = BrightnessMeasure::ALL
+            .iter()
+            .position(|&m| m == measure)
+            .unwrap_or_default();
+        Self(BRIGHTNESS_MEASURE.0 + index as u32)
+    }
+
+    pub fn brightness_measure(&self) -> Option<BrightnessMeasure> {
+        self.0
+            .checked_sub(BRIGHTNESS_MEASURE.0)
+            .and_then(|index| BrightnessMeasure::ALL.get(index as usize).copied())
+    }
+
+    pub fn from_point_size(size: PointSize) -> Self {
+        let index = PointSize::ALL
+            .iter()
+            .position(|&s| s == size)
+            .unwrap_or_default();
+        Self(POINT_SIZE.0 + index as u32)
+    }
+
+    pub fn point_size(&self) -> Option<PointSize> {
+        self.0
+            .checked_sub(POINT_SIZE.0)
+            .and_then(|index| PointSize::ALL.get(index as usize).copied())
+    }
+
+    pub fn from_camera_preset(preset: CameraPreset) -> Self {
+        let index = CameraPreset::ALL
+            .iter()
+            .position(|&p| p == preset)
+            .unwrap_or_default();
+        Self(CAMERA_PRESET.0 + index as u32)
+    }
+
+    pub fn camera_preset(&self) -> Option<CameraPreset> {
+        self.0
+            .checked_sub(CAMERA_PRESET.0)
+            .and_then(|index| CameraPreset::ALL.get(index as usize).copied())
+    }
+
+    pub fn from_clip(clip: CloudClip) -> Self {
+        let index = CloudClip::ALL
+            .iter()
+            .position(|&c| c == clip)
+            .unwrap_or_default();
+        Self(CLIP.0 + index as u32)
+    }
+
+    pub fn clip(&self) -> Option<CloudClip> {
+        self.0
+            .checked_sub(CLIP.0)
+            .and_then(|index| CloudClip::ALL.get(index as usize).copied())
+    }
+
+    pub fn naming_family(index: u32) -> Self {
+        Self(NAMING_FAMILY.0 + index)
+    }
+
+    pub fn palette_coverage(index: u32) -> Self {
+        Self(PALETTE_COVERAGE.0 + index)
+    }
+
     pub fn color_space(&self) -> ColorSpace {
         match *self {
             VIEW_RGB | COLOR_CLOUD_RGB => ColorSpace::Rgb,
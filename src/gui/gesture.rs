@@ -2,22 +2,23 @@ use super::macros::*;
 use windows::Win32::Foundation::*;
 
 pub struct Gesture {
+    // MK_* flags held alone while dragging
+    button: usize,
     prev: Option<(i32, i32)>,
     delta: Option<(i32, i32)>,
 }
 
 impl Gesture {
-    pub fn new() -> Self {
+    pub fn new(button: usize) -> Self {
         Self {
+            button,
             prev: None,
             delta: None,
         }
     }
 
     pub fn on_move(&mut self, wp: WPARAM, lp: LPARAM) -> Option<(i32, i32)> {
-        if wp.0 == 1 {
-            // left button
-
+        if wp.0 == self.button {
             let x = get_x_lp(lp);
             let y = get_y_lp(lp);
 
@@ -3,9 +3,13 @@ use super::scrollbar::Scrollbar;
 use super::theme::Theme;
 use super::widget::*;
 use super::Window;
+use crate::color::*;
 use crate::state::*;
+use crate::video;
+use glam::Vec3;
 use std::mem::*;
 use std::rc::Rc;
+use std::sync::Arc;
 use windows::core::*;
 use windows::s;
 use windows::Win32::Foundation::*;
@@ -107,9 +111,658 @@ impl Panel {
         Ok(())
     }
 
+    pub fn update_readout(&self) {
+        let state = self.state.read();
+
+        if let Some(window) = self.tree.get(&HARMONY_SCORE) {
+            window.set_text_if_changed(&match state.harmony {
+                Some(harmony) if state.harmony_mode.is_enable() => format!(
+                    "{} {:.0}\u{b0} {:.0}%",
+                    harmony.template.name(),
+                    harmony.rotation,
+                    100.0 * harmony.score
+                ),
+                None if state.harmony_mode.is_enable() => "no fit".to_string(),
+                _ => String::new(),
+            });
+        }
+
+        let naming = state.naming.as_deref();
+
+        for i in 0..NUM_NAMING_FAMILIES {
+            if let Some(window) = self.tree.get(&ControlId::naming_family(i)) {
+                window.set_text_if_changed(
+                    &naming
+                        .and_then(|naming| naming.families.get(i as usize))
+                        .map(|(family, coverage)| {
+                            format!("{} {:.1}%", family.name(), 100.0 * coverage)
+                        })
+                        .unwrap_or_default(),
+                );
+            }
+        }
+
+        if let Some(window) = self.tree.get(&PROBE_COLOR) {
+            window.set_text_if_changed(
+                &state
+                    .probe
+                    .and_then(|probe| probe.color)
+                    .map(to_hex)
+                    .unwrap_or_default(),
+            );
+        }
+
+        if let Some(window) = self.tree.get(&PROBE_NAME) {
+            window.set_text_if_changed(
+                &naming
+                    .and_then(|naming| naming.probe.clone())
+                    .unwrap_or_default(),
+            );
+        }
+
+        if let Some(window) = self.tree.get(&NOTAN_THRESHOLDS) {
+            if !window.has_focus()
+                && parse_thresholds(&window.text_string()).as_ref() != Some(&state.notan_thresholds)
+            {
+                window.set_text_if_changed(&format_thresholds(&state.notan_thresholds));
+            }
+        }
+
+        if let Some(window) = self.tree.get(&POSTERIZE_LEVELS) {
+            if !window.has_focus()
+                && parse_levels(&window.text_string()) != Some(state.posterize_levels)
+            {
+                window.set_text_if_changed(&state.posterize_levels.to_string());
+            }
+        }
+
+        if let Some(window) = self.tree.get(&SQUINT_RADIUS) {
+            if !window.has_focus()
+                && parse_radius(&window.text_string()) != Some(state.squint_radius)
+            {
+                window.set_text_if_changed(&state.squint_radius.to_string());
+            }
+        }
+
+        if let Some(window) = self.tree.get(&SELECTION_INFO) {
+            window.set_text_if_changed(&match state.selection {
+                Some(Selection::Bins { lo, hi, .. }) => format!("bins {} - {}", lo, hi),
+                Some(Selection::Box { .. }) => "cloud box".to_string(),
+                Some(Selection::Sphere { radius, .. }) => format!("cloud sphere {:.2}", radius),
+                None => String::new(),
+            });
+        }
+
+        if let Some(window) = self.tree.get(&STATS_WINDOW) {
+            if !window.has_focus()
+                && parse_seconds(&window.text_string()) != Some(state.stats_window)
+            {
+                window.set_text_if_changed(&state.stats_window.to_string());
+            }
+        }
+
+        if let Some(window) = self.tree.get(&CLOUD_BITS) {
+            if !window.has_focus() && parse_bits(&window.text_string()) != Some(state.cloud_bits) {
+                window.set_text_if_changed(&state.cloud_bits.to_string());
+            }
+        }
+
+        if let Some(window) = self.tree.get(&CLIP_LIGHTNESS) {
+            if !window.has_focus()
+                && parse_range(&window.text_string(), 100.0) != Some(state.clip_lightness)
+            {
+                window.set_text_if_changed(&format_range(state.clip_lightness, 100.0));
+            }
+        }
+
+        if let Some(window) = self.tree.get(&CLIP_HUE) {
+            if !window.has_focus()
+                && parse_range(&window.text_string(), 360.0) != Some(state.clip_hue)
+            {
+                window.set_text_if_changed(&format_range(state.clip_hue, 360.0));
+            }
+        }
+
+        if let Some(window) = self.tree.get(&CLIP_DEPTH) {
+            if !window.has_focus() && parse_depth(&window.text_string()) != Some(state.clip_depth) {
+                window.set_text_if_changed(&state.clip_depth.to_string());
+            }
+        }
+
+        if let Some(window) = self.tree.get(&CLIP_SLICE) {
+            if !window.has_focus()
+                && parse_point_size(&window.text_string()) != Some(state.clip_slice)
+            {
+                window.set_text_if_changed(&format_point_size(state.clip_slice));
+            }
+        }
+
+        if let Some(window) = self.tree.get(&POINT_MIN_SIZE) {
+            if !window.has_focus()
+                && parse_point_size(&window.text_string()) != Some(state.point_min_size)
+            {
+                window.set_text_if_changed(&format_point_size(state.point_min_size));
+            }
+        }
+
+        if let Some(window) = self.tree.get(&POINT_MAX_SIZE) {
+            if !window.has_focus()
+                && parse_point_size(&window.text_string()) != Some(state.point_max_size)
+            {
+                window.set_text_if_changed(&format_point_size(state.point_max_size));
+            }
+        }
+
+        if let Some(window) = self.tree.get(&POINT_SCALE) {
+            if !window.has_focus()
+                && parse_point_scale(&window.text_string()) != Some(state.point_scale)
+            {
+                window.set_text_if_changed(&state.point_scale.to_string());
+            }
+        }
+
+        if let Some(window) = self.tree.get(&PALETTE_TOLERANCE) {
+            if !window.has_focus()
+                && parse_tolerance(&window.text_string()) != Some(state.palette_tolerance)
+            {
+                window.set_text_if_changed(&state.palette_tolerance.to_string());
+            }
+        }
+
+        let coverage = state.coverage.as_deref();
+
+        for i in 0..NUM_PALETTE_COVERAGES {
+            if let Some(window) = self.tree.get(&ControlId::palette_coverage(i)) {
+                window.set_text_if_changed(
+                    &coverage
+                        .and_then(|coverage| coverage.colors.get(i as usize))
+                        .map(|(name, rgb, share)| {
+                            format!("{} {} {:.1}%", to_hex(*rgb), name, 100.0 * share)
+                        })
+                        .unwrap_or_default(),
+                );
+            }
+        }
+
+        if let Some(window) = self.tree.get(&PALETTE_OFF) {
+            window.set_text_if_changed(
+                &coverage
+                    .map(|coverage| format!("off palette {:.1}%", 100.0 * coverage.off_palette))
+                    .unwrap_or_default(),
+            );
+        }
+
+        if let Some(window) = self.tree.get(&VIDEO_FRAME) {
+            if !window.has_focus()
+                && parse_frame(&window.text_string(), state.video_len) != Some(state.video_frame)
+            {
+                window.set_text_if_changed(&state.video_frame.to_string());
+            }
+        }
+
+        if let Some(window) = self.tree.get(&VIDEO_LEN) {
+            window.set_text_if_changed(&if state.video_len > 0 {
+                format!("of {}", state.video_len)
+            } else {
+                String::new()
+            });
+        }
+
+        if let Some(window) = self.tree.get(&COMPARE_DELTA_E) {
+            window.set_text_if_changed(
+                &state
+                    .comparison
+                    .map(|comparison| {
+                        format!(
+                            "dE 2000 {:.2} max {:.2}",
+                            comparison.mean_delta_e, comparison.max_delta_e
+                        )
+                    })
+                    .unwrap_or_default(),
+            );
+        }
+
+        if let Some(window) = self.tree.get(&COMPARE_EMD) {
+            window.set_text_if_changed(
+                &state
+                    .comparison
+                    .map(|comparison| format!("EMD {:.4}", comparison.mean_emd()))
+                    .unwrap_or_default(),
+            );
+        }
+
+        if let Some(window) = self.tree.get(&TARGET_HEX) {
+            if !window.has_focus() && parse_hex(&window.text_string()) != state.target {
+                window.set_text_if_changed(&state.target.map(to_hex).unwrap_or_default());
+            }
+        }
+
+        let target = state.target.map(|target| rgb_to_lab(from_rgb8(target)));
+        let delta_e = |rgb: [u8; 3]| {
+            target.map(|target| state.delta_e.between(target, rgb_to_lab(from_rgb8(rgb))))
+        };
+
+        if let Some(window) = self.tree.get(&TARGET_PROBE) {
+            window.set_text_if_changed(
+                &state
+                    .probe
+                    .and_then(|probe| probe.color)
+                    .and_then(delta_e)
+                    .map(|delta_e| format!("{} {:.2}", state.delta_e.name(), delta_e))
+                    .unwrap_or_default(),
+            );
+        }
+
+        if let Some(window) = self.tree.get(&TARGET_MEAN) {
+            window.set_text_if_changed(
+                &state
+                    .probe
+                    .and_then(|probe| delta_e(probe.mean))
+                    .map(|delta_e| format!("mean {} {:.2}", state.delta_e.name(), delta_e))
+                    .unwrap_or_default(),
+            );
+        }
+
+        if let Some(window) = self.tree.get(&PROBE_MEAN) {
+            window.set_text_if_changed(
+                &state
+                    .probe
+                    .map(|probe| format!("mean {}", to_hex(probe.mean)))
+                    .unwrap_or_default(),
+            );
+        }
+    }
+
     fn build(&mut self) -> Result<()> {
         let state = self.state.read();
 
+        let harmony_title = Text::new((0, 0), s!("Harmony"));
+        let harmony_disable = Radio::new(
+            (0, 5),
+            state.harmony_mode == HarmonyMode::Disable,
+            true,
+            s!("Disable"),
+            HARMONY_DISABLE,
+            None,
+        );
+        let harmony_auto = Radio::new(
+            (0, 7),
+            state.harmony_mode == HarmonyMode::Auto,
+            false,
+            s!("Auto"),
+            HARMONY_AUTO,
+            None,
+        );
+        let harmony_templates = HarmonyTemplate::ALL.map(|template| {
+            Radio::new(
+                (0, 7),
+                state.harmony_mode == HarmonyMode::Fixed(template),
+                false,
+                harmony_template_text(template),
+                ControlId::from_harmony_template(template),
+                None,
+            )
+        });
+        let harmony_score = Label::new((0, 7), 100, HARMONY_SCORE);
+
+        let mut harmony_widgets: Vec<&dyn Widget<ControlId>> =
+            vec![&harmony_title, &harmony_disable, &harmony_auto];
+        harmony_widgets.extend(
+            harmony_templates
+                .iter()
+                .map(|radio| radio as &dyn Widget<ControlId>),
+        );
+        harmony_widgets.push(&harmony_score);
+
+        let naming_title = Text::new((0, 0), s!("Names"));
+        let naming_disable = Radio::new(
+            (0, 5),
+            state.naming_mode == NamingMode::Disable,
+            true,
+            s!("Disable"),
+            NAMING_DISABLE,
+            None,
+        );
+        let naming_css = Radio::new(
+            (0, 7),
+            state.naming_mode == NamingMode::Enable(DictionaryKind::Css),
+            false,
+            s!("CSS"),
+            NAMING_CSS,
+            None,
+        );
+        let naming_iscc_nbs = Radio::new(
+            (0, 7),
+            state.naming_mode == NamingMode::Enable(DictionaryKind::IsccNbs),
+            false,
+            s!("ISCC-NBS"),
+            NAMING_ISCC_NBS,
+            None,
+        );
+        let naming_custom = Radio::new(
+            (0, 7),
+            state.naming_mode == NamingMode::Enable(DictionaryKind::Custom),
+            false,
+            s!("Custom..."),
+            NAMING_CUSTOM,
+            None,
+        );
+        let naming_families = (0..NUM_NAMING_FAMILIES)
+            .map(|i| {
+                Label::new(
+                    (0, if i == 0 { 7 } else { 0 }),
+                    100,
+                    ControlId::naming_family(i),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut naming_widgets: Vec<&dyn Widget<ControlId>> = vec![
+            &naming_title,
+            &naming_disable,
+            &naming_css,
+            &naming_iscc_nbs,
+            &naming_custom,
+        ];
+        naming_widgets.extend(
+            naming_families
+                .iter()
+                .map(|label| label as &dyn Widget<ControlId>),
+        );
+
+        let joint_title = Text::new((0, 0), s!("Joint histogram"));
+        let joint_axes = JointAxes::ALL.map(|axes| {
+            Radio::new(
+                (0, 7),
+                state.histogram_mode == HistogramMode::Joint(axes),
+                false,
+                joint_axes_text(axes),
+                ControlId::from_joint_axes(axes),
+                None,
+            )
+        });
+        let joint_linear = Radio::new(
+            (0, 11),
+            !state.histogram_log,
+            true,
+            s!("Linear"),
+            HISTOGRAM_LINEAR,
+            None,
+        );
+        let joint_log = Radio::new(
+            (0, 7),
+            state.histogram_log,
+            false,
+            s!("Log"),
+            HISTOGRAM_LOG,
+            None,
+        );
+
+        let mut joint_widgets: Vec<&dyn Widget<ControlId>> = vec![&joint_title];
+        joint_widgets.extend(
+            joint_axes
+                .iter()
+                .map(|radio| radio as &dyn Widget<ControlId>),
+        );
+        joint_widgets.extend([
+            &joint_linear as &dyn Widget<ControlId>,
+            &joint_log as &dyn Widget<ControlId>,
+        ]);
+
+        let saturation_measures = SaturationMeasure::ALL.map(|measure| {
+            Radio::new(
+                (0, 7),
+                state.saturation_measure == measure,
+                measure == SaturationMeasure::ALL[0],
+                saturation_measure_text(measure),
+                ControlId::from_saturation_measure(measure),
+                None,
+            )
+        });
+        let brightness_measures = BrightnessMeasure::ALL.map(|measure| {
+            Radio::new(
+                (0, 7),
+                state.brightness_measure == measure,
+                measure == BrightnessMeasure::ALL[0],
+                brightness_measure_text(measure),
+                ControlId::from_brightness_measure(measure),
+                None,
+            )
+        });
+
+        let saturation_title = Text::new((0, 0), s!("Saturation"));
+        let brightness_title = Text::new((0, 11), s!("Brightness"));
+
+        let mut measure_widgets: Vec<&dyn Widget<ControlId>> = vec![&saturation_title];
+        measure_widgets.extend(
+            saturation_measures
+                .iter()
+                .map(|radio| radio as &dyn Widget<ControlId>),
+        );
+        measure_widgets.push(&brightness_title);
+        measure_widgets.extend(
+            brightness_measures
+                .iter()
+                .map(|radio| radio as &dyn Widget<ControlId>),
+        );
+
+        let point_title = Text::new((0, 0), s!("Point size"));
+        let point_sizes = PointSize::ALL.map(|size| {
+            Radio::new(
+                (0, 7),
+                state.point_size == size,
+                size == PointSize::ALL[0],
+                point_size_text(size),
+                ControlId::from_point_size(size),
+                None,
+            )
+        });
+        let point_min_title = Text::new((0, 11), s!("Min size (%)"));
+        let point_min_size = Edit::new((0, 5), 80, POINT_MIN_SIZE);
+        let point_max_title = Text::new((0, 11), s!("Max size (%)"));
+        let point_max_size = Edit::new((0, 5), 80, POINT_MAX_SIZE);
+        let point_scale_title = Text::new((0, 11), s!("Scale"));
+        let point_scale = Edit::new((0, 5), 80, POINT_SCALE);
+        let point_opacity_title = Text::new((0, 11), s!("Opacity"));
+        let point_solid = Radio::new(
+            (0, 5),
+            !state.point_opacity,
+            true,
+            s!("Solid"),
+            POINT_SOLID,
+            None,
+        );
+        let point_weighted = Radio::new(
+            (0, 7),
+            state.point_opacity,
+            false,
+            s!("By count"),
+            POINT_WEIGHTED,
+            None,
+        );
+
+        let camera_title = Text::new((0, 0), s!("Camera"));
+        let camera_presets = CameraPreset::ALL.map(|preset| {
+            Radio::new(
+                (0, 7),
+                preset == CameraPreset::default(),
+                preset == CameraPreset::ALL[0],
+                camera_preset_text(preset),
+                ControlId::from_camera_preset(preset),
+                None,
+            )
+        });
+        let projection_title = Text::new((0, 11), s!("Projection"));
+        let camera_orthographic = Radio::new(
+            (0, 5),
+            !state.camera.perspective,
+            true,
+            s!("Orthographic"),
+            CAMERA_ORTHOGRAPHIC,
+            None,
+        );
+        let camera_perspective = Radio::new(
+            (0, 7),
+            state.camera.perspective,
+            false,
+            s!("Perspective"),
+            CAMERA_PERSPECTIVE,
+            None,
+        );
+        let turntable_title = Text::new((0, 11), s!("Turntable"));
+        let turntable_off = Radio::new(
+            (0, 5),
+            !state.camera.turntable,
+            true,
+            s!("Off"),
+            CAMERA_TURNTABLE_OFF,
+            None,
+        );
+        let turntable_on = Radio::new(
+            (0, 7),
+            state.camera.turntable,
+            false,
+            s!("On"),
+            CAMERA_TURNTABLE_ON,
+            None,
+        );
+
+        let mut camera_widgets: Vec<&dyn Widget<ControlId>> = vec![&camera_title];
+        camera_widgets.extend(
+            camera_presets
+                .iter()
+                .map(|radio| radio as &dyn Widget<ControlId>),
+        );
+        camera_widgets.extend([
+            &projection_title as &dyn Widget<ControlId>,
+            &camera_orthographic,
+            &camera_perspective,
+            &turntable_title,
+            &turntable_off,
+            &turntable_on,
+        ]);
+
+        let clip_title = Text::new((0, 0), s!("Clip"));
+        let clips = CloudClip::ALL.map(|clip| {
+            Radio::new(
+                (0, 7),
+                state.clip == clip,
+                clip == CloudClip::ALL[0],
+                clip_text(clip),
+                ControlId::from_clip(clip),
+                None,
+            )
+        });
+        let clip_lightness_title = Text::new((0, 11), s!("Lightness (%)"));
+        let clip_lightness = Edit::new((0, 5), 80, CLIP_LIGHTNESS);
+        let clip_hue_title = Text::new((0, 11), s!("Hue (deg)"));
+        let clip_hue = Edit::new((0, 5), 80, CLIP_HUE);
+        let clip_depth_title = Text::new((0, 11), s!("Plane depth"));
+        let clip_depth = Edit::new((0, 5), 80, CLIP_DEPTH);
+        let clip_slice_title = Text::new((0, 11), s!("Slice (%)"));
+        let clip_slice = Edit::new((0, 5), 80, CLIP_SLICE);
+
+        let mut clip_widgets: Vec<&dyn Widget<ControlId>> = vec![&clip_title];
+        clip_widgets.extend(clips.iter().map(|radio| radio as &dyn Widget<ControlId>));
+        clip_widgets.extend([
+            &clip_lightness_title as &dyn Widget<ControlId>,
+            &clip_lightness,
+            &clip_hue_title,
+            &clip_hue,
+            &clip_depth_title,
+            &clip_depth,
+            &clip_slice_title,
+            &clip_slice,
+        ]);
+
+        let mut point_widgets: Vec<&dyn Widget<ControlId>> = vec![&point_title];
+        point_widgets.extend(
+            point_sizes
+                .iter()
+                .map(|radio| radio as &dyn Widget<ControlId>),
+        );
+        point_widgets.extend([
+            &point_min_title as &dyn Widget<ControlId>,
+            &point_min_size,
+            &point_max_title,
+            &point_max_size,
+            &point_scale_title,
+            &point_scale,
+            &point_opacity_title,
+            &point_solid,
+            &point_weighted,
+        ]);
+
+        let target_title = Text::new((0, 0), s!("Target"));
+        let target_hex = Edit::new((0, 5), 80, TARGET_HEX);
+        let target_delta_es = DeltaE::ALL.map(|delta_e| {
+            Radio::new(
+                (0, 7),
+                state.delta_e == delta_e,
+                delta_e == DeltaE::ALL[0],
+                delta_e_text(delta_e),
+                ControlId::from_delta_e(delta_e),
+                None,
+            )
+        });
+        let target_probe = Label::new((0, 7), 100, TARGET_PROBE);
+        let target_mean = Label::new((0, 0), 100, TARGET_MEAN);
+
+        let mut target_widgets: Vec<&dyn Widget<ControlId>> = vec![&target_title, &target_hex];
+        target_widgets.extend(
+            target_delta_es
+                .iter()
+                .map(|radio| radio as &dyn Widget<ControlId>),
+        );
+        target_widgets.extend([
+            &target_probe as &dyn Widget<ControlId>,
+            &target_mean as &dyn Widget<ControlId>,
+        ]);
+
+        let palette_title = Text::new((0, 0), s!("Palette"));
+        let palette_disable = Radio::new(
+            (0, 5),
+            state.palette.is_none(),
+            true,
+            s!("Disable"),
+            PALETTE_DISABLE,
+            None,
+        );
+        let palette_load = Radio::new(
+            (0, 7),
+            state.palette.is_some(),
+            false,
+            s!("Load..."),
+            PALETTE_LOAD,
+            None,
+        );
+        let palette_tolerance_title = Text::new((0, 11), s!("Tolerance (dE)"));
+        let palette_tolerance = Edit::new((0, 5), 80, PALETTE_TOLERANCE);
+        let palette_coverages = (0..NUM_PALETTE_COVERAGES)
+            .map(|i| {
+                Label::new(
+                    (0, if i == 0 { 7 } else { 0 }),
+                    100,
+                    ControlId::palette_coverage(i),
+                )
+            })
+            .collect::<Vec<_>>();
+        let palette_off = Label::new((0, 0), 100, PALETTE_OFF);
+
+        let mut palette_widgets: Vec<&dyn Widget<ControlId>> = vec![
+            &palette_title,
+            &palette_disable,
+            &palette_load,
+            &palette_tolerance_title,
+            &palette_tolerance,
+        ];
+        palette_widgets.extend(
+            palette_coverages
+                .iter()
+                .map(|label| label as &dyn Widget<ControlId>),
+        );
+        palette_widgets.push(&palette_off);
+
         self.tree.view(
             self.window,
             Rc::clone(&self.theme),
@@ -118,6 +771,105 @@ impl Panel {
                 Axis::Vertical,
                 11,
                 &[
+                    &Stack::new(
+                        (0, 0),
+                        Axis::Vertical,
+                        0,
+                        &[
+                            &Text::new((0, 0), s!("Source")),
+                            &Radio::new(
+                                (0, 5),
+                                state.video.is_none(),
+                                true,
+                                s!("Screen"),
+                                SOURCE_SCREEN,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.video.is_some(),
+                                false,
+                                s!("Video..."),
+                                SOURCE_VIDEO,
+                                None,
+                            ),
+                            &Text::new((0, 11), s!("Frame")),
+                            &Edit::new((0, 5), 80, VIDEO_FRAME),
+                            &Label::new((0, 5), 100, VIDEO_LEN),
+                            &Button::new((0, 7), s!("Previous"), VIDEO_PREVIOUS),
+                            &Button::new((0, 7), s!("Next"), VIDEO_NEXT),
+                            &Text::new((0, 11), s!("YCbCr matrix")),
+                            &Radio::new(
+                                (0, 5),
+                                state.ycbcr_matrix == YcbcrMatrix::Bt601,
+                                true,
+                                s!("BT.601"),
+                                MATRIX_BT601,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.ycbcr_matrix == YcbcrMatrix::Bt709,
+                                false,
+                                s!("BT.709"),
+                                MATRIX_BT709,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.ycbcr_matrix == YcbcrMatrix::Bt2020,
+                                false,
+                                s!("BT.2020"),
+                                MATRIX_BT2020,
+                                None,
+                            ),
+                            &Text::new((0, 11), s!("Barcode")),
+                            &Radio::new(
+                                (0, 5),
+                                state.barcode_color == BarcodeColor::Average,
+                                true,
+                                s!("Average"),
+                                BARCODE_AVERAGE,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.barcode_color == BarcodeColor::Dominant,
+                                false,
+                                s!("Dominant"),
+                                BARCODE_DOMINANT,
+                                None,
+                            ),
+                            &Button::new((0, 7), s!("Export..."), BARCODE_EXPORT),
+                        ],
+                    ),
+                    &Stack::new(
+                        (0, 0),
+                        Axis::Vertical,
+                        0,
+                        &[
+                            &Text::new((0, 0), s!("Compare")),
+                            &Radio::new(
+                                (0, 5),
+                                state.compare.is_none(),
+                                true,
+                                s!("Off"),
+                                COMPARE_OFF,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                false,
+                                false,
+                                s!("Snapshot"),
+                                COMPARE_SNAPSHOT,
+                                None,
+                            ),
+                            &Radio::new((0, 7), false, false, s!("Load..."), COMPARE_LOAD, None),
+                            &Label::new((0, 7), 100, COMPARE_DELTA_E),
+                            &Label::new((0, 0), 100, COMPARE_EMD),
+                        ],
+                    ),
                     &Stack::new(
                         (0, 0),
                         Axis::Vertical,
@@ -154,74 +906,317 @@ impl Panel {
                             ),
                             &Radio::new(
                                 (0, 7),
-                                state.view_mode == ViewMode::Hue,
+                                state.view_mode == ViewMode::Hue,
+                                false,
+                                s!("Hue"),
+                                VIEW_HUE,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.view_mode == ViewMode::Saturation,
+                                false,
+                                s!("Saturation"),
+                                VIEW_SATURATION,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.view_mode == ViewMode::Brightness,
+                                false,
+                                s!("Brightness"),
+                                VIEW_BRIGHTNESS,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.view_mode == ViewMode::Harmony,
+                                false,
+                                s!("Harmony"),
+                                VIEW_HARMONY,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.view_mode == ViewMode::DeltaE,
+                                false,
+                                s!("Delta E"),
+                                VIEW_DELTA_E,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.view_mode == ViewMode::Notan,
+                                false,
+                                s!("Notan"),
+                                VIEW_NOTAN,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.view_mode == ViewMode::Posterize,
+                                false,
+                                s!("Posterize"),
+                                VIEW_POSTERIZE,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.view_mode == ViewMode::Squint,
+                                false,
+                                s!("Squint"),
+                                VIEW_SQUINT,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.view_mode == ViewMode::LocalContrast,
+                                false,
+                                s!("Contrast"),
+                                VIEW_LOCAL_CONTRAST,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.view_mode == ViewMode::Palette,
+                                false,
+                                s!("Palette"),
+                                VIEW_PALETTE,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.view_mode == ViewMode::PaletteCompliance,
+                                false,
+                                s!("Compliance"),
+                                VIEW_PALETTE_COMPLIANCE,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.view_mode == ViewMode::Selection,
+                                false,
+                                s!("Selection"),
+                                VIEW_SELECTION,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.view_mode == ViewMode::Compare,
+                                false,
+                                s!("Compare"),
+                                VIEW_COMPARE,
+                                None,
+                            ),
+                            &Button::new((0, 7), s!("Export..."), VIEW_EXPORT),
+                        ],
+                    ),
+                    &Stack::new(
+                        (0, 0),
+                        Axis::Vertical,
+                        0,
+                        &[
+                            &Text::new((0, 0), s!("Histogram")),
+                            &Radio::new(
+                                (0, 5),
+                                state.histogram_mode == HistogramMode::Disable,
+                                true,
+                                s!("Disable"),
+                                HISTOGRAM_DISABLE,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.histogram_mode == HistogramMode::Rgb,
+                                false,
+                                s!("RGB"),
+                                HISTOGRAM_RGB,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.histogram_mode == HistogramMode::Hue(HueShape::Linear),
+                                false,
+                                s!("Hue"),
+                                HISTOGRAM_HUE,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.histogram_mode == HistogramMode::Hue(HueShape::Wheel),
+                                false,
+                                s!("Hue wheel"),
+                                HISTOGRAM_HUE_WHEEL,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.histogram_mode == HistogramMode::Hue(HueShape::Disc),
+                                false,
+                                s!("Hue disc"),
+                                HISTOGRAM_HUE_DISC,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.histogram_mode == HistogramMode::Saturation,
+                                false,
+                                s!("Saturation"),
+                                HISTOGRAM_SATURAION,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.histogram_mode == HistogramMode::Brightness,
+                                false,
+                                s!("Brightness"),
+                                HISTOGRAM_BRIGHTNESS,
+                                None,
+                            ),
+                        ],
+                    ),
+                    &Stack::new((0, 0), Axis::Vertical, 0, &joint_widgets),
+                    &Stack::new(
+                        (0, 0),
+                        Axis::Vertical,
+                        0,
+                        &[
+                            &Text::new((0, 0), s!("Chromaticity")),
+                            &Radio::new(
+                                (0, 5),
+                                state.chromaticity_mode == ChromaticityMode::Disable,
+                                true,
+                                s!("Disable"),
+                                CHROMATICITY_DISABLE,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.chromaticity_mode == ChromaticityMode::Enable(Diagram::Xy),
+                                false,
+                                s!("CIE xy"),
+                                CHROMATICITY_XY,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.chromaticity_mode == ChromaticityMode::Enable(Diagram::Uv),
                                 false,
-                                s!("Hue"),
-                                VIEW_HUE,
+                                s!("CIE u'v'"),
+                                CHROMATICITY_UV,
+                                None,
+                            ),
+                        ],
+                    ),
+                    &Stack::new(
+                        (0, 0),
+                        Axis::Vertical,
+                        0,
+                        &[
+                            &Text::new((0, 0), s!("Statistics")),
+                            &Radio::new(
+                                (0, 5),
+                                state.stats_recording.is_none(),
+                                true,
+                                s!("Off"),
+                                STATS_RECORD_OFF,
                                 None,
                             ),
                             &Radio::new(
                                 (0, 7),
-                                state.view_mode == ViewMode::Saturation,
+                                state.stats_recording.is_some(),
                                 false,
-                                s!("Saturation"),
-                                VIEW_SATURATION,
+                                s!("Record..."),
+                                STATS_RECORD,
+                                None,
+                            ),
+                            &Text::new((0, 11), s!("Strip chart")),
+                            &Radio::new(
+                                (0, 5),
+                                !state.stats_chart,
+                                true,
+                                s!("Off"),
+                                STATS_CHART_OFF,
                                 None,
                             ),
                             &Radio::new(
                                 (0, 7),
-                                state.view_mode == ViewMode::Brightness,
+                                state.stats_chart,
                                 false,
-                                s!("Brightness"),
-                                VIEW_BRIGHTNESS,
+                                s!("On"),
+                                STATS_CHART_ON,
                                 None,
                             ),
+                            &Text::new((0, 11), s!("Seconds")),
+                            &Edit::new((0, 5), 80, STATS_WINDOW),
                         ],
                     ),
+                    &Stack::new((0, 0), Axis::Vertical, 0, &measure_widgets),
                     &Stack::new(
                         (0, 0),
                         Axis::Vertical,
                         0,
                         &[
-                            &Text::new((0, 0), s!("Histogram")),
+                            &Text::new((0, 0), s!("Notan thresholds")),
+                            &Edit::new((0, 5), 80, NOTAN_THRESHOLDS),
+                            &Text::new((0, 11), s!("Posterize levels")),
+                            &Edit::new((0, 5), 80, POSTERIZE_LEVELS),
                             &Radio::new(
-                                (0, 5),
-                                state.histogram_mode == HistogramMode::Disable,
+                                (0, 7),
+                                !state.posterize_lightness,
                                 true,
-                                s!("Disable"),
-                                HISTOGRAM_DISABLE,
+                                s!("Per channel"),
+                                POSTERIZE_CHANNEL,
                                 None,
                             ),
                             &Radio::new(
                                 (0, 7),
-                                state.histogram_mode == HistogramMode::Rgb,
+                                state.posterize_lightness,
                                 false,
-                                s!("RGB"),
-                                HISTOGRAM_RGB,
+                                s!("Per lightness"),
+                                POSTERIZE_LIGHTNESS,
                                 None,
                             ),
+                        ],
+                    ),
+                    &Stack::new(
+                        (0, 0),
+                        Axis::Vertical,
+                        0,
+                        &[
+                            &Text::new((0, 0), s!("Squint radius")),
+                            &Edit::new((0, 5), 80, SQUINT_RADIUS),
                             &Radio::new(
                                 (0, 7),
-                                state.histogram_mode == HistogramMode::Hue,
-                                false,
-                                s!("Hue"),
-                                HISTOGRAM_HUE,
+                                state.squint_blur == Blur::Gaussian,
+                                true,
+                                s!("Gaussian"),
+                                SQUINT_GAUSSIAN,
                                 None,
                             ),
                             &Radio::new(
                                 (0, 7),
-                                state.histogram_mode == HistogramMode::Saturation,
+                                state.squint_blur == Blur::Box,
                                 false,
-                                s!("Saturation"),
-                                HISTOGRAM_SATURAION,
+                                s!("Box"),
+                                SQUINT_BOX,
+                                None,
+                            ),
+                            &Text::new((0, 11), s!("Analyze")),
+                            &Radio::new(
+                                (0, 5),
+                                !state.squint_analysis,
+                                true,
+                                s!("Original"),
+                                SQUINT_ANALYSIS_OFF,
                                 None,
                             ),
                             &Radio::new(
                                 (0, 7),
-                                state.histogram_mode == HistogramMode::Brightness,
+                                state.squint_analysis,
                                 false,
-                                s!("Brightness"),
-                                HISTOGRAM_BRIGHTNESS,
+                                s!("Squinted"),
+                                SQUINT_ANALYSIS_ON,
                                 None,
                             ),
                         ],
@@ -272,8 +1267,95 @@ impl Panel {
                                 COLOR_CLOUD_YUV,
                                 None,
                             ),
+                            &Text::new((0, 11), s!("Bits per channel")),
+                            &Edit::new((0, 5), 80, CLOUD_BITS),
+                            &Text::new((0, 11), s!("Guides")),
+                            &Radio::new(
+                                (0, 5),
+                                !state.cloud_guides,
+                                true,
+                                s!("Off"),
+                                CLOUD_GUIDES_OFF,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.cloud_guides,
+                                false,
+                                s!("On"),
+                                CLOUD_GUIDES_ON,
+                                None,
+                            ),
+                            &Text::new((0, 11), s!("Wide gamut")),
+                            &Radio::new(
+                                (0, 5),
+                                state.cloud_gamut.is_none(),
+                                true,
+                                s!("None"),
+                                CLOUD_GAMUT_NONE,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.cloud_gamut == Some(Gamut::DisplayP3),
+                                false,
+                                s!("Display P3"),
+                                CLOUD_GAMUT_P3,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.cloud_gamut == Some(Gamut::Rec2020),
+                                false,
+                                s!("Rec.2020"),
+                                CLOUD_GAMUT_REC2020,
+                                None,
+                            ),
+                        ],
+                    ),
+                    &Stack::new(
+                        (0, 0),
+                        Axis::Vertical,
+                        0,
+                        &[
+                            &Text::new((0, 0), s!("Selection")),
+                            &Radio::new(
+                                (0, 5),
+                                !state.selection_grey,
+                                true,
+                                s!("Dim"),
+                                SELECTION_DIM,
+                                None,
+                            ),
+                            &Radio::new(
+                                (0, 7),
+                                state.selection_grey,
+                                false,
+                                s!("Grey"),
+                                SELECTION_GREY,
+                                None,
+                            ),
+                            &Label::new((0, 7), 100, SELECTION_INFO),
+                        ],
+                    ),
+                    &Stack::new((0, 0), Axis::Vertical, 0, &camera_widgets),
+                    &Stack::new((0, 0), Axis::Vertical, 0, &clip_widgets),
+                    &Stack::new((0, 0), Axis::Vertical, 0, &point_widgets),
+                    &Stack::new((0, 0), Axis::Vertical, 0, &harmony_widgets),
+                    &Stack::new((0, 0), Axis::Vertical, 0, &naming_widgets),
+                    &Stack::new(
+                        (0, 0),
+                        Axis::Vertical,
+                        0,
+                        &[
+                            &Text::new((0, 0), s!("Probe")),
+                            &Label::new((0, 5), 100, PROBE_COLOR),
+                            &Label::new((0, 0), 100, PROBE_NAME),
+                            &Label::new((0, 0), 100, PROBE_MEAN),
                         ],
                     ),
+                    &Stack::new((0, 0), Axis::Vertical, 0, &target_widgets),
+                    &Stack::new((0, 0), Axis::Vertical, 0, &palette_widgets),
                 ],
             ),
         )?;
@@ -303,25 +1385,404 @@ impl Panel {
             VIEW_HUE => self.state.set_view_mode(ViewMode::Hue),
             VIEW_SATURATION => self.state.set_view_mode(ViewMode::Saturation),
             VIEW_BRIGHTNESS => self.state.set_view_mode(ViewMode::Brightness),
+            VIEW_HARMONY => self.state.set_view_mode(ViewMode::Harmony),
+            VIEW_DELTA_E => self.state.set_view_mode(ViewMode::DeltaE),
+            VIEW_NOTAN => self.state.set_view_mode(ViewMode::Notan),
+            VIEW_POSTERIZE => self.state.set_view_mode(ViewMode::Posterize),
+            VIEW_SQUINT => self.state.set_view_mode(ViewMode::Squint),
+            VIEW_LOCAL_CONTRAST => self.state.set_view_mode(ViewMode::LocalContrast),
+            VIEW_PALETTE => self.state.set_view_mode(ViewMode::Palette),
+            VIEW_PALETTE_COMPLIANCE => self.state.set_view_mode(ViewMode::PaletteCompliance),
+            VIEW_SELECTION => self.state.set_view_mode(ViewMode::Selection),
+            VIEW_COMPARE => self.state.set_view_mode(ViewMode::Compare),
             HISTOGRAM_DISABLE => self.state.set_histogram_mode(HistogramMode::Disable),
             HISTOGRAM_RGB => self.state.set_histogram_mode(HistogramMode::Rgb),
-            HISTOGRAM_HUE => self.state.set_histogram_mode(HistogramMode::Hue),
+            HISTOGRAM_HUE => self
+                .state
+                .set_histogram_mode(HistogramMode::Hue(HueShape::Linear)),
+            HISTOGRAM_HUE_WHEEL => self
+                .state
+                .set_histogram_mode(HistogramMode::Hue(HueShape::Wheel)),
+            HISTOGRAM_HUE_DISC => self
+                .state
+                .set_histogram_mode(HistogramMode::Hue(HueShape::Disc)),
             HISTOGRAM_SATURAION => self.state.set_histogram_mode(HistogramMode::Saturation),
             HISTOGRAM_BRIGHTNESS => self.state.set_histogram_mode(HistogramMode::Brightness),
-            COLOR_CLOUD_DISABLE => self.state.set_color_cloud_mode(ColorCloudMode::Disable),
-            COLOR_CLOUD_RGB => self
+            CHROMATICITY_DISABLE => self.state.set_chromaticity_mode(ChromaticityMode::Disable),
+            CHROMATICITY_XY => self
+                .state
+                .set_chromaticity_mode(ChromaticityMode::Enable(Diagram::Xy)),
+            CHROMATICITY_UV => self
                 .state
-                .set_color_cloud_mode(ColorCloudMode::Enable(ColorSpace::Rgb)),
-            COLOR_CLOUD_HSV => self
+                .set_chromaticity_mode(ChromaticityMode::Enable(Diagram::Uv)),
+            SOURCE_SCREEN => self.state.set_video(None),
+            SOURCE_VIDEO => self.open_video(),
+            VIDEO_PREVIOUS => {
+                let frame = self.state.video_frame();
+                self.state.set_video_frame(frame.saturating_sub(1));
+            }
+            VIDEO_NEXT => {
+                let (frame, len) = (self.state.video_frame(), self.state.video_len());
+                self.state
+                    .set_video_frame((frame + 1).min(len.saturating_sub(1)));
+            }
+            VIDEO_FRAME => {
+                let text = self.tree.window(&VIDEO_FRAME).text_string();
+                if let Some(frame) = parse_frame(&text, self.state.video_len()) {
+                    self.state.set_video_frame(frame);
+                }
+            }
+            BARCODE_AVERAGE => self.state.set_barcode_color(BarcodeColor::Average),
+            BARCODE_DOMINANT => self.state.set_barcode_color(BarcodeColor::Dominant),
+            BARCODE_EXPORT => self.export_barcode(),
+            COMPARE_OFF => self.state.set_compare(None),
+            // the frame shown now, taken by the executor
+            COMPARE_SNAPSHOT => self.state.set_compare_snapshot(true),
+            COMPARE_LOAD => self.load_compare(),
+            MATRIX_BT601 => self.state.set_ycbcr_matrix(YcbcrMatrix::Bt601),
+            MATRIX_BT709 => self.state.set_ycbcr_matrix(YcbcrMatrix::Bt709),
+            MATRIX_BT2020 => self.state.set_ycbcr_matrix(YcbcrMatrix::Bt2020),
+            STATS_RECORD_OFF => self.state.set_stats_recording(None),
+            STATS_RECORD => self.record_stats(),
+            STATS_CHART_OFF => self.state.set_stats_chart(false),
+            STATS_CHART_ON => self.state.set_stats_chart(true),
+            STATS_WINDOW => {
+                let text = self.tree.window(&STATS_WINDOW).text_string();
+                if let Some(seconds) = parse_seconds(&text) {
+                    self.state.set_stats_window(seconds);
+                }
+            }
+            HISTOGRAM_LINEAR => self.state.set_histogram_log(false),
+            HISTOGRAM_LOG => self.state.set_histogram_log(true),
+            COLOR_CLOUD_DISABLE => self.state.set_color_cloud_mode(ColorCloudMode::Disable),
+            COLOR_CLOUD_RGB => {
+                self.state
+                    .set_color_cloud_mode(ColorCloudMode::Enable(ColorSpace::Rgb));
+                self.look_along_slice();
+            }
+            COLOR_CLOUD_HSV => {
+                self.state
+                    .set_color_cloud_mode(ColorCloudMode::Enable(ColorSpace::Hsv));
+                self.look_along_slice();
+            }
+            COLOR_CLOUD_HSL => {
+                self.state
+                    .set_color_cloud_mode(ColorCloudMode::Enable(ColorSpace::Hsl));
+                self.look_along_slice();
+            }
+            COLOR_CLOUD_YUV => {
+                self.state
+                    .set_color_cloud_mode(ColorCloudMode::Enable(ColorSpace::Yuv));
+                self.look_along_slice();
+            }
+            CLOUD_BITS => {
+                let text = self.tree.window(&CLOUD_BITS).text_string();
+                if let Some(bits) = parse_bits(&text) {
+                    self.state.set_cloud_bits(bits);
+                }
+            }
+            CLOUD_GUIDES_OFF => self.state.set_cloud_guides(false),
+            CLOUD_GUIDES_ON => self.state.set_cloud_guides(true),
+            CLOUD_GAMUT_NONE => self.state.set_cloud_gamut(None),
+            CLOUD_GAMUT_P3 => self.state.set_cloud_gamut(Some(Gamut::DisplayP3)),
+            CLOUD_GAMUT_REC2020 => self.state.set_cloud_gamut(Some(Gamut::Rec2020)),
+            CLIP_LIGHTNESS => {
+                let text = self.tree.window(&CLIP_LIGHTNESS).text_string();
+                if let Some((from, to)) = parse_range(&text, 100.0) {
+                    self.state.set_clip_lightness((from.min(to), from.max(to)));
+                }
+            }
+            CLIP_HUE => {
+                let text = self.tree.window(&CLIP_HUE).text_string();
+                if let Some(range) = parse_range(&text, 360.0) {
+                    self.state.set_clip_hue(range);
+                }
+            }
+            CLIP_DEPTH => {
+                let text = self.tree.window(&CLIP_DEPTH).text_string();
+                if let Some(depth) = parse_depth(&text) {
+                    self.state.set_clip_depth(depth);
+                }
+            }
+            CLIP_SLICE => {
+                let text = self.tree.window(&CLIP_SLICE).text_string();
+                if let Some(lightness) = parse_point_size(&text) {
+                    self.state.set_clip_slice(lightness);
+                }
+            }
+            POINT_MIN_SIZE => {
+                let text = self.tree.window(&POINT_MIN_SIZE).text_string();
+                if let Some(size) = parse_point_size(&text) {
+                    self.state.set_point_min_size(size);
+                }
+            }
+            POINT_MAX_SIZE => {
+                let text = self.tree.window(&POINT_MAX_SIZE).text_string();
+                if let Some(size) = parse_point_size(&text) {
+                    self.state.set_point_max_size(size);
+                }
+            }
+            POINT_SCALE => {
+                let text = self.tree.window(&POINT_SCALE).text_string();
+                if let Some(scale) = parse_point_scale(&text) {
+                    self.state.set_point_scale(scale);
+                }
+            }
+            SELECTION_DIM => self.state.set_selection_grey(false),
+            SELECTION_GREY => self.state.set_selection_grey(true),
+            CAMERA_ORTHOGRAPHIC => self
                 .state
-                .set_color_cloud_mode(ColorCloudMode::Enable(ColorSpace::Hsv)),
-            COLOR_CLOUD_HSL => self
+                .update_camera(|camera| camera.perspective = false),
+            CAMERA_PERSPECTIVE => self.state.update_camera(|camera| camera.perspective = true),
+            CAMERA_TURNTABLE_OFF => self.state.update_camera(|camera| camera.turntable = false),
+            CAMERA_TURNTABLE_ON => self.state.update_camera(|camera| camera.turntable = true),
+            POINT_SOLID => self.state.set_point_opacity(false),
+            POINT_WEIGHTED => self.state.set_point_opacity(true),
+            HARMONY_DISABLE => self.state.set_harmony_mode(HarmonyMode::Disable),
+            HARMONY_AUTO => self.state.set_harmony_mode(HarmonyMode::Auto),
+            NAMING_DISABLE => self.state.set_naming_mode(NamingMode::Disable),
+            NAMING_CSS => self
                 .state
-                .set_color_cloud_mode(ColorCloudMode::Enable(ColorSpace::Hsl)),
-            COLOR_CLOUD_YUV => self
+                .set_naming_mode(NamingMode::Enable(DictionaryKind::Css)),
+            NAMING_ISCC_NBS => self
                 .state
-                .set_color_cloud_mode(ColorCloudMode::Enable(ColorSpace::Yuv)),
-            _ => {}
+                .set_naming_mode(NamingMode::Enable(DictionaryKind::IsccNbs)),
+            NAMING_CUSTOM => self.load_custom_dictionary(),
+            PALETTE_DISABLE => self.state.set_palette(None),
+            PALETTE_LOAD => self.load_palette(),
+            VIEW_EXPORT => self.export_view(),
+            PALETTE_TOLERANCE => {
+                let text = self.tree.window(&PALETTE_TOLERANCE).text_string();
+                if let Some(tolerance) = parse_tolerance(&text) {
+                    self.state.set_palette_tolerance(tolerance);
+                }
+            }
+            NOTAN_THRESHOLDS => {
+                let text = self.tree.window(&NOTAN_THRESHOLDS).text_string();
+                if let Some(thresholds) = parse_thresholds(&text) {
+                    self.state.set_notan_thresholds(thresholds);
+                }
+            }
+            POSTERIZE_LEVELS => {
+                let text = self.tree.window(&POSTERIZE_LEVELS).text_string();
+                if let Some(levels) = parse_levels(&text) {
+                    self.state.set_posterize_levels(levels);
+                }
+            }
+            POSTERIZE_CHANNEL => self.state.set_posterize_lightness(false),
+            POSTERIZE_LIGHTNESS => self.state.set_posterize_lightness(true),
+            SQUINT_RADIUS => {
+                let text = self.tree.window(&SQUINT_RADIUS).text_string();
+                if let Some(radius) = parse_radius(&text) {
+                    self.state.set_squint_radius(radius);
+                }
+            }
+            SQUINT_GAUSSIAN => self.state.set_squint_blur(Blur::Gaussian),
+            SQUINT_BOX => self.state.set_squint_blur(Blur::Box),
+            SQUINT_ANALYSIS_OFF => self.state.set_squint_analysis(false),
+            SQUINT_ANALYSIS_ON => self.state.set_squint_analysis(true),
+            TARGET_HEX => {
+                let text = self.tree.window(&TARGET_HEX).text_string();
+                if text.trim().is_empty() {
+                    self.state.set_target(None);
+                } else if let Some(target) = parse_hex(&text) {
+                    self.state.set_target(Some(target));
+                }
+            }
+            _ => {
+                if let Some(template) = id.harmony_template() {
+                    self.state.set_harmony_mode(HarmonyMode::Fixed(template));
+                } else if let Some(axes) = id.joint_axes() {
+                    self.state.set_histogram_mode(HistogramMode::Joint(axes));
+                } else if let Some(delta_e) = id.delta_e() {
+                    self.state.set_delta_e(delta_e);
+                } else if let Some(measure) = id.saturation_measure() {
+                    self.state.set_saturation_measure(measure);
+                } else if let Some(measure) = id.brightness_measure() {
+                    self.state.set_brightness_measure(measure);
+                } else if let Some(size) = id.point_size() {
+                    self.state.set_point_size(size);
+                } else if let Some(preset) = id.camera_preset() {
+                    self.state.update_camera(|camera| camera.look(preset));
+                } else if let Some(clip) = id.clip() {
+                    // the plane faces the camera as it is now
+                    if clip == CloudClip::Plane {
+                        self.state
+                            .set_clip_normal(self.state.camera().rotation * Vec3::Z);
+                    }
+                    self.state.set_clip(clip);
+                    self.look_along_slice();
+                }
+            }
+        }
+    }
+
+    // the slice lies on the screen, across the lightness axis of the color space
+    fn look_along_slice(&self) {
+        if self.state.clip() != CloudClip::Slice {
+            return;
+        }
+        if let Some(color_space) = self.state.color_cloud_mode().color_space() {
+            self.state
+                .update_camera(|camera| camera.look(color_space.lightness_view()));
+        }
+    }
+
+    fn load_custom_dictionary(&mut self) {
+        if let Some(path) = open_file_dialog(
+            self.window.hwnd(),
+            s!("Color list (*.txt;*.csv;*.gpl;*.ase;*.json)\0*.txt;*.csv;*.gpl;*.ase;*.json\0All files (*.*)\0*.*\0"),
+        ) {
+            match Dictionary::load(&path) {
+                Ok(dictionary) => self.state.set_custom_dictionary(Some(Arc::new(dictionary))),
+                Err(e) => unsafe {
+                    let msg = e.to_string() + "\0";
+                    MessageBoxA(
+                        self.window.hwnd(),
+                        PCSTR(msg.as_ptr()),
+                        s!("Custom names"),
+                        MB_OK,
+                    );
+                },
+            }
+        }
+
+        if self.state.custom_dictionary().is_some() {
+            self.state
+                .set_naming_mode(NamingMode::Enable(DictionaryKind::Custom));
+        } else {
+            // nothing loaded, go back to the current selection
+            let id = match self.state.naming_mode() {
+                NamingMode::Disable => NAMING_DISABLE,
+                NamingMode::Enable(DictionaryKind::Css) => NAMING_CSS,
+                NamingMode::Enable(DictionaryKind::IsccNbs) => NAMING_ISCC_NBS,
+                NamingMode::Enable(DictionaryKind::Custom) => NAMING_CUSTOM,
+            };
+            self.tree.window(&NAMING_CUSTOM).set_check(false);
+            self.tree.window(&id).set_check(true);
+        }
+    }
+
+    // the executor writes the next frame
+    fn export_view(&mut self) {
+        if let Some(path) = save_file_dialog(
+            self.window.hwnd(),
+            s!("PNG image (*.png)\0*.png\0"),
+            s!("png"),
+        ) {
+            self.state.set_view_export(Some(path));
+        }
+    }
+
+    // opened by the executor, which reports a bad file
+    fn open_video(&mut self) {
+        match open_file_dialog(
+            self.window.hwnd(),
+            s!("Video (*.y4m;*.rgb;*.raw)\0*.y4m;*.rgb;*.raw\0All files (*.*)\0*.*\0"),
+        ) {
+            Some(path) => {
+                self.state.set_video_frame(0);
+                self.state.set_video(Some(path));
+            }
+            None if self.state.video().is_none() => {
+                self.tree.window(&SOURCE_VIDEO).set_check(false);
+                self.tree.window(&SOURCE_SCREEN).set_check(true);
+            }
+            None => (),
+        }
+    }
+
+    fn export_barcode(&mut self) {
+        if self.state.video().is_none() {
+            unsafe {
+                MessageBoxA(
+                    self.window.hwnd(),
+                    s!("Open a video first"),
+                    s!("Barcode"),
+                    MB_OK,
+                )
+            };
+            return;
+        }
+
+        if let Some(path) = save_file_dialog(
+            self.window.hwnd(),
+            s!("PNG image (*.png)\0*.png\0"),
+            s!("png"),
+        ) {
+            self.state.set_barcode_export(Some(path));
+        }
+    }
+
+    // a PPM or the first frame of a video, in the YCbCr matrix of the source
+    fn load_compare(&mut self) {
+        if let Some(path) = open_file_dialog(
+            self.window.hwnd(),
+            s!("Image or video (*.ppm;*.y4m;*.rgb;*.raw)\0*.ppm;*.y4m;*.rgb;*.raw\0All files (*.*)\0*.*\0"),
+        ) {
+            match video::load_frame(&path, None, 0, self.state.ycbcr_matrix()) {
+                Ok(image) => self.state.set_compare(Some(Arc::new(image))),
+                Err(e) => unsafe {
+                    let msg = e.to_string() + "\0";
+                    MessageBoxA(
+                        self.window.hwnd(),
+                        PCSTR(msg.as_ptr()),
+                        s!("Compare"),
+                        MB_OK,
+                    );
+                },
+            }
+        }
+
+        if self.state.compare().is_none() {
+            // nothing loaded, go back to off
+            self.tree.window(&COMPARE_LOAD).set_check(false);
+            self.tree.window(&COMPARE_OFF).set_check(true);
+        }
+    }
+
+    fn record_stats(&mut self) {
+        // a click on the checked radio keeps the running recording
+        if self.state.stats_recording().is_some() {
+            return;
+        }
+
+        match save_file_dialog(
+            self.window.hwnd(),
+            s!("CSV (*.csv)\0*.csv\0JSON (*.json)\0*.json\0"),
+            s!("csv"),
+        ) {
+            Some(path) => self.state.set_stats_recording(Some(path)),
+            None => {
+                self.tree.window(&STATS_RECORD).set_check(false);
+                self.tree.window(&STATS_RECORD_OFF).set_check(true);
+            }
+        }
+    }
+
+    fn load_palette(&mut self) {
+        if let Some(path) = open_file_dialog(
+            self.window.hwnd(),
+            s!("Palette (*.gpl;*.ase;*.json;*.txt)\0*.gpl;*.ase;*.json;*.txt;*.csv\0All files (*.*)\0*.*\0"),
+        ) {
+            match Dictionary::load(&path) {
+                Ok(palette) => self.state.set_palette(Some(Arc::new(palette))),
+                Err(e) => unsafe {
+                    let msg = e.to_string() + "\0";
+                    MessageBoxA(
+                        self.window.hwnd(),
+                        PCSTR(msg.as_ptr()),
+                        s!("Palette"),
+                        MB_OK,
+                    );
+                },
+            }
+        }
+
+        if self.state.palette().is_none() {
+            // nothing loaded, go back to disable
+            self.tree.window(&PALETTE_LOAD).set_check(false);
+            self.tree.window(&PALETTE_DISABLE).set_check(true);
         }
     }
 
@@ -406,6 +1867,198 @@ impl Panel {
     }
 }
 
+fn harmony_template_text(template: HarmonyTemplate) -> PCSTR {
+    match template {
+        HarmonyTemplate::SmallI => s!("i"),
+        HarmonyTemplate::V => s!("V"),
+        HarmonyTemplate::L => s!("L"),
+        HarmonyTemplate::I => s!("I"),
+        HarmonyTemplate::T => s!("T"),
+        HarmonyTemplate::Y => s!("Y"),
+        HarmonyTemplate::X => s!("X"),
+        HarmonyTemplate::Complementary => s!("Complement"),
+        HarmonyTemplate::Analogous => s!("Analogous"),
+        HarmonyTemplate::Triadic => s!("Triadic"),
+        HarmonyTemplate::SplitComplementary => s!("Split"),
+    }
+}
+
+// percentages such as "30 70", in ascending order
+fn parse_thresholds(text: &str) -> Option<Vec<f32>> {
+    let mut thresholds = text
+        .split([',', ' '])
+        .filter(|field| !field.is_empty())
+        .map(|field| field.parse::<f32>().ok().filter(|&v| v > 0.0 && v < 100.0))
+        .collect::<Option<Vec<_>>>()?;
+
+    if thresholds.is_empty() || thresholds.len() > MAX_NOTAN_THRESHOLDS {
+        return None;
+    }
+
+    thresholds.sort_by(f32::total_cmp);
+    Some(thresholds.into_iter().map(|v| v / 100.0).collect())
+}
+
+fn format_thresholds(thresholds: &[f32]) -> String {
+    thresholds
+        .iter()
+        .map(|v| format!("{}", (100.0 * v).round()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn parse_levels(text: &str) -> Option<u32> {
+    text.trim()
+        .parse()
+        .ok()
+        .filter(|levels| (2..=256).contains(levels))
+}
+
+// quantization of the color cloud
+fn parse_bits(text: &str) -> Option<u32> {
+    text.trim()
+        .parse()
+        .ok()
+        .filter(|bits| (4..=8).contains(bits))
+}
+
+// percentage of the sphere radius
+fn parse_point_size(text: &str) -> Option<f32> {
+    text.trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|size| (0.0..=100.0).contains(size))
+        .map(|size| size / 100.0)
+}
+
+fn format_point_size(size: f32) -> String {
+    format!("{}", (1e4 * size).round() / 100.0)
+}
+
+fn parse_point_scale(text: &str) -> Option<f32> {
+    text.trim()
+        .parse()
+        .ok()
+        .filter(|scale| (0.1..=10.0).contains(scale))
+}
+
+// "from-to" out of `max`, as fractions of it
+fn parse_range(text: &str, max: f32) -> Option<(f32, f32)> {
+    let (from, to) = text.split_once('-')?;
+    let parse = |field: &str| {
+        field
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|v| (0.0..=max).contains(v))
+            .map(|v| v / max)
+    };
+    Some((parse(from)?, parse(to)?))
+}
+
+fn format_range((from, to): (f32, f32), max: f32) -> String {
+    format!("{}-{}", (max * from).round(), (max * to).round())
+}
+
+// along the view direction, in units of the sphere radius
+fn parse_depth(text: &str) -> Option<f32> {
+    text.trim()
+        .parse()
+        .ok()
+        .filter(|depth| (-1.5..=1.5).contains(depth))
+}
+
+// delta E within which a color counts as a palette color
+fn parse_tolerance(text: &str) -> Option<f32> {
+    text.trim()
+        .parse()
+        .ok()
+        .filter(|tolerance| (0.0..=100.0).contains(tolerance))
+}
+
+// frame of the open video, counted from 0
+fn parse_frame(text: &str, len: usize) -> Option<usize> {
+    text.trim().parse().ok().filter(|&frame| frame < len)
+}
+
+// length of the strip chart
+fn parse_seconds(text: &str) -> Option<f32> {
+    text.trim()
+        .parse()
+        .ok()
+        .filter(|seconds| (1.0..=60.0).contains(seconds))
+}
+
+// blur radius in pixels
+fn parse_radius(text: &str) -> Option<u32> {
+    text.trim()
+        .parse()
+        .ok()
+        .filter(|radius| (1..=64).contains(radius))
+}
+
+fn joint_axes_text(axes: JointAxes) -> PCSTR {
+    match axes {
+        JointAxes::HueSaturation => s!("Hue x Sat"),
+        JointAxes::HueBrightness => s!("Hue x Bri"),
+        JointAxes::ChromaBrightness => s!("Chroma x Bri"),
+    }
+}
+
+fn point_size_text(size: PointSize) -> PCSTR {
+    match size {
+        PointSize::Linear => s!("Linear"),
+        PointSize::Sqrt => s!("Sqrt"),
+        PointSize::Log => s!("Log"),
+        PointSize::Uniform => s!("Uniform"),
+    }
+}
+
+fn clip_text(clip: CloudClip) -> PCSTR {
+    match clip {
+        CloudClip::Disable => s!("Off"),
+        CloudClip::Lightness => s!("Lightness"),
+        CloudClip::Hue => s!("Hue"),
+        CloudClip::Plane => s!("Plane"),
+        CloudClip::Slice => s!("Slice"),
+    }
+}
+
+fn camera_preset_text(preset: CameraPreset) -> PCSTR {
+    match preset {
+        CameraPreset::Top => s!("Top (hue wheel)"),
+        CameraPreset::Side => s!("Side (lightness)"),
+        CameraPreset::Diagonal => s!("RGB diagonal"),
+    }
+}
+
+fn delta_e_text(delta_e: DeltaE) -> PCSTR {
+    match delta_e {
+        DeltaE::Cie76 => s!("dE 1976"),
+        DeltaE::Cie94 => s!("dE 1994"),
+        DeltaE::Ciede2000 => s!("dE 2000"),
+    }
+}
+
+fn saturation_measure_text(measure: SaturationMeasure) -> PCSTR {
+    match measure {
+        SaturationMeasure::Chroma => s!("Chroma"),
+        SaturationMeasure::HsvS => s!("HSV S"),
+        SaturationMeasure::HslS => s!("HSL S"),
+        SaturationMeasure::LabC => s!("Lab C*"),
+    }
+}
+
+fn brightness_measure_text(measure: BrightnessMeasure) -> PCSTR {
+    match measure {
+        BrightnessMeasure::Luma => s!("Luma"),
+        BrightnessMeasure::Luminance => s!("Luminance"),
+        BrightnessMeasure::HsvV => s!("HSV V"),
+        BrightnessMeasure::HslL => s!("HSL L"),
+        BrightnessMeasure::LabL => s!("Lab L*"),
+    }
+}
+
 extern "system" fn wndproc(hwnd: HWND, msg: u32, wp: WPARAM, lp: LPARAM) -> LRESULT {
     unsafe {
         if msg == WM_CREATE {
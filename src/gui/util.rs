@@ -1,5 +1,8 @@
+use std::path::PathBuf;
+use windows::core::*;
 use windows::Win32::Foundation::*;
 use windows::Win32::System::LibraryLoader::*;
+use windows::Win32::UI::Controls::Dialogs::*;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
 pub fn cursor_pos() -> (i32, i32) {
@@ -13,3 +16,44 @@ pub fn cursor_pos() -> (i32, i32) {
 pub fn module_handle() -> HMODULE {
     unsafe { GetModuleHandleA(None).expect("failed to get current module handle.") }
 }
+
+// `filter` is pairs of description and pattern, each terminated by a null
+pub fn open_file_dialog(owner: HWND, filter: PCSTR) -> Option<PathBuf> {
+    let mut path = [0u8; MAX_PATH as usize];
+
+    let mut ofn = OPENFILENAMEA {
+        lStructSize: std::mem::size_of::<OPENFILENAMEA>() as _,
+        hwndOwner: owner,
+        lpstrFilter: filter,
+        lpstrFile: PSTR(path.as_mut_ptr()),
+        nMaxFile: path.len() as _,
+        Flags: OFN_FILEMUSTEXIST | OFN_PATHMUSTEXIST | OFN_NOCHANGEDIR,
+        ..Default::default()
+    };
+
+    unsafe { GetOpenFileNameA(&mut ofn) }.as_bool().then(|| {
+        let len = path.iter().position(|&c| c == 0).unwrap_or(path.len());
+        PathBuf::from(String::from_utf8_lossy(&path[..len]).into_owned())
+    })
+}
+
+// `extension` is appended when the name has none, without the dot
+pub fn save_file_dialog(owner: HWND, filter: PCSTR, extension: PCSTR) -> Option<PathBuf> {
+    let mut path = [0u8; MAX_PATH as usize];
+
+    let mut ofn = OPENFILENAMEA {
+        lStructSize: std::mem::size_of::<OPENFILENAMEA>() as _,
+        hwndOwner: owner,
+        lpstrFilter: filter,
+        lpstrFile: PSTR(path.as_mut_ptr()),
+        nMaxFile: path.len() as _,
+        lpstrDefExt: extension,
+        Flags: OFN_OVERWRITEPROMPT | OFN_PATHMUSTEXIST | OFN_NOCHANGEDIR,
+        ..Default::default()
+    };
+
+    unsafe { GetSaveFileNameA(&mut ofn) }.as_bool().then(|| {
+        let len = path.iter().position(|&c| c == 0).unwrap_or(path.len());
+        PathBuf::from(String::from_utf8_lossy(&path[..len]).into_owned())
+    })
+}
@@ -3,6 +3,7 @@ use super::panel::Panel;
 use super::theme::*;
 use super::*;
 use crate::state::*;
+use glam::Vec2;
 use std::mem::*;
 use std::rc::Rc;
 use windows::core::*;
@@ -12,9 +13,20 @@ use windows::Win32::UI::HiDpi::*;
 use windows::Win32::UI::Input::KeyboardAndMouse::*;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
+// selection drag, started with ctrl or shift in the cloud or anywhere on a histogram
+#[derive(Clone, Copy, Debug)]
+enum Pick {
+    Bins,
+    Box,
+    Sphere,
+}
+
 pub struct Viewer {
     pub window: Window,
+    // left button rotates, middle button pans
     gesture: Gesture,
+    pan_gesture: Gesture,
+    picking: Option<(Pick, (i32, i32))>,
     panel: Box<Panel>,
     state: RefState,
     theme: Rc<Theme>,
@@ -26,7 +38,9 @@ impl Viewer {
 
         Box::new(Self {
             window: Window(HWND(0)),
-            gesture: Gesture::new(),
+            gesture: Gesture::new(0x01),
+            pan_gesture: Gesture::new(0x10),
+            picking: None,
             panel: Panel::new(Rc::clone(&theme), RefState::clone(&state)),
             state,
             theme,
@@ -81,8 +95,12 @@ impl Viewer {
     }
 
     fn update_transparency_and_panel_visibility(&mut self) {
+        // the histogram takes the mouse while brushing a selection
+        let brushing = self.state.view_mode() == ViewMode::Selection
+            && self.state.histogram_mode().is_brushable();
+
         match self.state.color_cloud_mode() {
-            ColorCloudMode::Disable => {
+            ColorCloudMode::Disable if !brushing => {
                 self.window
                     .set_transparency(on_nc_hit_test(self.window.hwnd()).0 as u32 == HTCLIENT);
             }
@@ -92,6 +110,7 @@ impl Viewer {
         }
 
         self.panel.update_visibility();
+        self.panel.update_readout();
     }
 
     fn on_create(&mut self, hwnd: HWND) -> LRESULT {
@@ -118,14 +137,140 @@ impl Viewer {
     }
 
     fn on_mouse_move(&mut self, wp: WPARAM, lp: LPARAM) -> LRESULT {
-        if let Some((dx, dy)) = self.gesture.on_move(wp, lp) {
-            let width = self.window.client_size().0.min(480) as f32;
-            self.state.move_camera(dx as f32 / width, dy as f32 / width);
+        if let Some((pick, start)) = self.picking {
+            self.update_selection(pick, start, (get_x_lp(lp), get_y_lp(lp)));
+            return LRESULT(0);
+        }
+
+        let (width, height) = self.window.client_size();
+        if width <= 0 || height <= 0 {
+            return LRESULT(0);
+        }
+
+        let (x, y) = (get_x_lp(lp), get_y_lp(lp));
+        let to = cloud_position(width, height, (x, y));
+        let from = |(dx, dy): (i32, i32)| cloud_position(width, height, (x - dx, y - dy));
+
+        if let Some(delta) = self.gesture.on_move(wp, lp) {
+            let from = from(delta);
+            self.state.update_camera(|camera| camera.arcball(from, to));
+        }
+
+        if let Some(delta) = self.pan_gesture.on_move(wp, lp) {
+            let from = from(delta);
+            self.state.update_camera(|camera| camera.pan_by(to - from));
+        }
+
+        LRESULT(0)
+    }
+
+    fn on_mouse_wheel(&mut self, wp: WPARAM) -> LRESULT {
+        if self.state.color_cloud_mode().is_enable() {
+            let notches = wheel_delta(wp) as f32 / 120.0;
+            self.state
+                .update_camera(|camera| camera.zoom_by(1.1f32.powf(notches)));
+        }
+        LRESULT(0)
+    }
+
+    fn on_left_button_down(&mut self, lp: LPARAM) -> LRESULT {
+        if self.state.view_mode() != ViewMode::Selection {
+            return LRESULT(0);
+        }
+
+        let pressed = |key: VIRTUAL_KEY| unsafe { GetKeyState(key.0 as _) } < 0;
+
+        let pick = match self.state.color_cloud_mode() {
+            ColorCloudMode::Enable(_) if pressed(VK_CONTROL) => Some(Pick::Box),
+            ColorCloudMode::Enable(_) if pressed(VK_SHIFT) => Some(Pick::Sphere),
+            ColorCloudMode::Disable if self.state.histogram_mode().is_brushable() => {
+                Some(Pick::Bins)
+            }
+            _ => None,
+        };
+
+        if let Some(pick) = pick {
+            let start = (get_x_lp(lp), get_y_lp(lp));
+            self.picking = Some((pick, start));
+            self.update_selection(pick, start, start);
+            unsafe { SetCapture(self.window.hwnd()) };
         }
 
         LRESULT(0)
     }
 
+    fn on_left_button_up(&mut self) -> LRESULT {
+        if self.picking.take().is_some() {
+            unsafe { ReleaseCapture() };
+        }
+        LRESULT(0)
+    }
+
+    fn on_right_button_up(&mut self) -> LRESULT {
+        if self.state.view_mode() == ViewMode::Selection {
+            self.state.set_selection(None);
+        }
+        LRESULT(0)
+    }
+
+    fn update_selection(&mut self, pick: Pick, start: (i32, i32), end: (i32, i32)) {
+        let (width, height) = self.window.client_size();
+        if width <= 0 || height <= 0 {
+            return;
+        }
+
+        let camera = self.state.camera();
+        let selection = match (pick, self.state.color_cloud_mode().color_space()) {
+            (Pick::Bins, _) => {
+                let bin =
+                    |x: i32| (255.0 * x as f32 / width as f32).round().clamp(0.0, 255.0) as u32;
+                let (a, b) = (bin(start.0), bin(end.0));
+                Selection::Bins {
+                    mode: self.state.histogram_mode(),
+                    lo: a.min(b),
+                    hi: a.max(b),
+                }
+            }
+            (Pick::Box, Some(color_space)) => {
+                let a = cloud_position(width, height, start);
+                let b = cloud_position(width, height, end);
+                Selection::Box {
+                    color_space,
+                    projection: camera.projection(),
+                    min: a.min(b),
+                    max: a.max(b),
+                }
+            }
+            (Pick::Sphere, Some(color_space)) => {
+                // centered on the plane through the middle of the cloud
+                let center = camera.unproject(cloud_position(width, height, start));
+                let end = camera.unproject(cloud_position(width, height, end));
+                Selection::Sphere {
+                    color_space,
+                    rotation: camera.rotation,
+                    center: center.extend(0.0),
+                    radius: center.distance(end),
+                }
+            }
+            _ => return,
+        };
+
+        self.state.set_selection(Some(selection));
+    }
+
+    fn on_reset_camera(&mut self) -> LRESULT {
+        self.state.update_camera(|camera| camera.reset());
+        LRESULT(0)
+    }
+
+    // the color under the cursor becomes the delta E target
+    fn on_pick_target(&mut self) -> LRESULT {
+        if let Some(color) = self.state.probe().and_then(|probe| probe.color) {
+            self.state.set_target(Some(color));
+        }
+        LRESULT(0)
+    }
+
     fn handle_message(&mut self, hwnd: HWND, msg: u32, wp: WPARAM, lp: LPARAM) -> Option<LRESULT> {
         match msg {
             WM_CREATE => Some(self.on_create(hwnd)),
@@ -133,6 +278,12 @@ impl Viewer {
             WM_SIZE => Some(self.on_size()),
             WM_TIMER => Some(self.on_timer()),
             WM_MOUSEMOVE => Some(self.on_mouse_move(wp, lp)),
+            WM_MOUSEWHEEL => Some(self.on_mouse_wheel(wp)),
+            WM_LBUTTONDOWN => Some(self.on_left_button_down(lp)),
+            WM_LBUTTONUP => Some(self.on_left_button_up()),
+            WM_RBUTTONUP => Some(self.on_right_button_up()),
+            WM_KEYDOWN if wp.0 == b'T' as usize => Some(self.on_pick_target()),
+            WM_KEYDOWN if wp.0 == b'R' as usize => Some(self.on_reset_camera()),
             _ => None,
         }
     }
@@ -158,6 +309,22 @@ extern "system" fn wndproc(hwnd: HWND, msg: u32, wp: WPARAM, lp: LPARAM) -> LRES
     }
 }
 
+// view space of the color cloud under a client position, as the cloud passes draw it
+fn cloud_position(width: i32, height: i32, (x, y): (i32, i32)) -> Vec2 {
+    let aspect = width as f32 / height as f32;
+    let scale = if aspect > 1.0 {
+        Vec2::new(1.0 / aspect, 1.0)
+    } else {
+        Vec2::new(1.0, 1.0 * aspect)
+    };
+
+    let ndc = Vec2::new(
+        2.0 * x as f32 / width as f32 - 1.0,
+        1.0 - 2.0 * y as f32 / height as f32,
+    );
+    ndc / (0.95 * scale)
+}
+
 fn on_close(hwnd: HWND) -> LRESULT {
     unsafe {
         DestroyWindow(hwnd);
@@ -91,6 +91,156 @@ impl<K: Key> Widget<K> for Text {
     }
 }
 
+#[derive(Debug)]
+pub struct Label<K: Key> {
+    key: K,
+    width: i32,
+    offset: (i32, i32),
+}
+
+impl<K: Key> Label<K> {
+    pub fn new(offset: (i32, i32), width: i32, key: K) -> Self {
+        Self { key, width, offset }
+    }
+}
+
+impl<K: Key> Widget<K> for Label<K> {
+    fn create(
+        &self,
+        ctx: &CreateContext,
+        x: i32,
+        y: i32,
+        visible: bool,
+        _enable: bool,
+        cache: &mut HashMap<K, Window>,
+    ) -> Result<()> {
+        let (x, y) = (
+            x + ctx.dpi.absolute(self.offset.0),
+            y + ctx.dpi.absolute(self.offset.1),
+        );
+        let (cx, cy) = (ctx.dpi.absolute(self.width), ctx.dpi.absolute(17));
+
+        let window = match cache.entry(self.key) {
+            Entry::Occupied(o) => {
+                let window = *o.get();
+                window.set_position(x, y);
+                window
+            }
+            Entry::Vacant(v) => {
+                let window = *v.insert(Window::create(
+                    s!("STATIC"),
+                    s!(""),
+                    WINDOW_EX_STYLE(0),
+                    WS_VISIBLE | WS_CHILD | WS_CLIPSIBLINGS,
+                    x,
+                    y,
+                    cx,
+                    cy,
+                    ctx.parent.hwnd(),
+                    self.key.into(),
+                    None,
+                )?);
+                window.set_font(ctx.theme.font());
+                window.apply_dark_mode();
+                window
+            }
+        };
+
+        window.set_visibility(visible);
+
+        Ok(())
+    }
+
+    fn update(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn size(&self, ctx: &CreateContext) -> (i32, i32) {
+        (
+            ctx.dpi.absolute(self.width + self.offset.0),
+            ctx.dpi.absolute(17 + self.offset.1),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Edit<K: Key> {
+    key: K,
+    width: i32,
+    offset: (i32, i32),
+}
+
+impl<K: Key> Edit<K> {
+    pub fn new(offset: (i32, i32), width: i32, key: K) -> Self {
+        Self { key, width, offset }
+    }
+}
+
+impl<K: Key> Widget<K> for Edit<K> {
+    fn create(
+        &self,
+        ctx: &CreateContext,
+        x: i32,
+        y: i32,
+        visible: bool,
+        enable: bool,
+        cache: &mut HashMap<K, Window>,
+    ) -> Result<()> {
+        let (x, y) = (
+            x + ctx.dpi.absolute(self.offset.0),
+            y + ctx.dpi.absolute(self.offset.1),
+        );
+        let (cx, cy) = (ctx.dpi.absolute(self.width), ctx.dpi.absolute(20));
+
+        let window = match cache.entry(self.key) {
+            Entry::Occupied(o) => {
+                let window = *o.get();
+                window.set_position(x, y);
+                window
+            }
+            Entry::Vacant(v) => {
+                let window = *v.insert(Window::create(
+                    s!("EDIT"),
+                    s!(""),
+                    WINDOW_EX_STYLE(0),
+                    WS_VISIBLE
+                        | WS_CHILD
+                        | WS_CLIPSIBLINGS
+                        | WS_BORDER
+                        | WS_TABSTOP
+                        | WINDOW_STYLE(ES_AUTOHSCROLL as _),
+                    x,
+                    y,
+                    cx,
+                    cy,
+                    ctx.parent.hwnd(),
+                    self.key.into(),
+                    None,
+                )?);
+                window.set_font(ctx.theme.font());
+                window.apply_dark_mode();
+                window
+            }
+        };
+
+        window.set_visibility(visible);
+        window.set_enabled(enable);
+
+        Ok(())
+    }
+
+    fn update(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn size(&self, ctx: &CreateContext) -> (i32, i32) {
+        (
+            ctx.dpi.absolute(self.width + self.offset.0),
+            ctx.dpi.absolute(20 + self.offset.1),
+        )
+    }
+}
+
 #[derive(Debug)]
 pub struct Check<K: Key> {
     key: K,
@@ -171,6 +321,79 @@ impl<K: Key> Widget<K> for Check<K> {
     }
 }
 
+#[derive(Debug)]
+pub struct Button<K: Key> {
+    key: K,
+    text: PCSTR,
+    offset: (i32, i32),
+}
+
+impl<K: Key> Button<K> {
+    pub fn new(offset: (i32, i32), text: PCSTR, key: K) -> Self {
+        Self { key, text, offset }
+    }
+}
+
+impl<K: Key> Widget<K> for Button<K> {
+    fn create(
+        &self,
+        ctx: &CreateContext,
+        x: i32,
+        y: i32,
+        visible: bool,
+        enable: bool,
+        cache: &mut HashMap<K, Window>,
+    ) -> Result<()> {
+        let (x, y) = (
+            x + ctx.dpi.absolute(self.offset.0),
+            y + ctx.dpi.absolute(self.offset.1),
+        );
+        let (cx, cy) = (ctx.dpi.absolute(80), ctx.dpi.absolute(23));
+
+        let window = match cache.entry(self.key) {
+            Entry::Occupied(o) => {
+                let window = *o.get();
+                window.set_position(x, y);
+                window
+            }
+            Entry::Vacant(v) => {
+                let window = *v.insert(Window::create(
+                    s!("BUTTON"),
+                    PCSTR::from_raw(self.text.as_ptr()),
+                    WINDOW_EX_STYLE(0),
+                    WS_VISIBLE | WS_CHILD | WS_CLIPSIBLINGS | WINDOW_STYLE(BS_PUSHBUTTON as _),
+                    x,
+                    y,
+                    cx,
+                    cy,
+                    ctx.parent.hwnd(),
+                    self.key.into(),
+                    None,
+                )?);
+                window.set_font(ctx.theme.font());
+                window.apply_dark_mode();
+                window
+            }
+        };
+
+        window.set_visibility(visible);
+        window.set_enabled(enable);
+
+        Ok(())
+    }
+
+    fn update(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn size(&self, ctx: &CreateContext) -> (i32, i32) {
+        (
+            ctx.dpi.absolute(80 + self.offset.0),
+            ctx.dpi.absolute(23 + self.offset.1),
+        )
+    }
+}
+
 #[derive(Debug)]
 pub struct Radio<'a, K: Key> {
     key: K,
@@ -427,4 +650,8 @@ impl<K: Key> Tree<K> {
     pub fn window(&self, key: &K) -> Window {
         self.cache[key]
     }
+
+    pub fn get(&self, key: &K) -> Option<Window> {
+        self.cache.get(key).copied()
+    }
 }
@@ -242,6 +242,30 @@ impl Window {
         }
     }
 
+    #[allow(unused)]
+    pub fn set_text_if_changed(&self, text: &str) {
+        let current = self.text();
+        let len = current
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(current.len());
+
+        if &current[..len] != text.as_bytes() {
+            let text = text.to_owned() + "\0";
+            self.set_text(PCSTR(text.as_ptr()));
+        }
+    }
+
+    pub fn text_string(&self) -> String {
+        let text = self.text();
+        let len = text.iter().position(|&c| c == 0).unwrap_or(text.len());
+        String::from_utf8_lossy(&text[..len]).into_owned()
+    }
+
+    pub fn has_focus(&self) -> bool {
+        unsafe { GetFocus() == self.0 }
+    }
+
     #[allow(unused)]
     pub fn scroll(&self, dx: i32, dy: i32, flags: SCROLL_WINDOW_FLAGS) -> i32 {
         unsafe { ScrollWindowEx(self.0, dx, dy, None, None, None, None, flags) }
@@ -0,0 +1,557 @@
+// `iromiru render`, the color cloud, a histogram, a chromaticity diagram or a view
+// mode of an image or video file drawn by the software renderer, optionally against
+// a second image, and `iromiru barcode`, the barcode and the shots of a video,
+// without a window or a GPU
+
+use crate::analysis::barcode::{BarcodeColor, DEFAULT_CUT_THRESHOLD};
+use crate::analysis::compare::Comparison;
+use crate::color::naming::Dictionary;
+use crate::color::parse_hex;
+use crate::color::ycbcr::YcbcrMatrix;
+use crate::render::chromaticity::Chromaticity;
+use crate::render::cloud::Cloud;
+use crate::render::gif::Gif;
+use crate::render::histogram::Histogram;
+use crate::render::joint::Joint;
+use crate::render::{barcode, png, view, Image};
+use crate::state::*;
+use crate::video::{self, Video};
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub const USAGE: &str = "\
+usage: iromiru render <input.ppm|y4m|rgb> <output.png|svg|gif> [options]
+  --cloud rgb|hsv|hsl|yuv                 color cloud, the default
+  --histogram rgb|hue|saturation|brightness|joint-hue-saturation|joint-hue-brightness|
+      joint-chroma-brightness             the joint ones as a heat map
+  --chromaticity xy|uv                    CIE 1931 xy or CIE 1976 u'v' diagram
+  --view-mode MODE                        the image through a view mode, as PNG:
+      rgb[:CHANNELS]|hue|saturation|brightness|harmony|delta-e|notan|posterize|squint|contrast|
+      palette|compliance|compare
+  --target RRGGBB                         reference color of delta-e
+  --palette <colors.gpl|ase|json|txt>     palette of palette and compliance
+  --tolerance DE                          delta E within which compliance passes a color, 5
+                                          by default
+  --saturation chroma|hsv-s|hsl-s|lab-c   saturation of the histogram and the views, chroma
+                                          by default
+  --brightness luma|luminance|hsv-v|hsl-l|lab-l
+                                          brightness of the histogram and the views, luma
+                                          by default
+  --compare <other.ppm|y4m|rgb>           prints the difference from the input, compare is
+                                          its delta E 2000 heat map, the histograms and the
+                                          cloud are drawn for both in two colors
+  --size WxH                              512x512 by default
+  --view top|side|diagonal
+  --perspective
+  --bits 4..8                             color cloud quantization
+  --point-size linear|sqrt|log|uniform
+  --weighted                              opacity by pixel count
+  --frames N                              a turntable of N frames, out_000.png ...
+  --delay CS                              frame delay of the GIF in 1/100 s
+  --frame N|all                           frame of a video, all goes to out_000.png ...
+  --matrix bt601|bt709|bt2020             Y'CbCr matrix of a Y4M video, bt709 by default
+  --raw-size WxH                          frame size of a raw RGB video
+
+usage: iromiru barcode <input.y4m|rgb> <output.png> [options]
+  a column per frame over the palettes of the shots, and the shot list as output.json
+  --color average|dominant                average by default
+  --threshold 0..1                        histogram distance of a cut, 0.4 by default
+  --size WxH                              a pixel per frame and 256 high by default
+  --matrix bt601|bt709|bt2020
+  --raw-size WxH
+
+usage: iromiru --listen [PORT]
+  the viewer with a JSON-RPC automation API on 127.0.0.1:PORT, 7410 by default";
+
+// background of the GIF frames, which have no alpha
+const GIF_BACKGROUND: [u8; 3] = [0x40, 0x40, 0x40];
+
+#[derive(Clone, Copy)]
+enum Output {
+    Png,
+    Svg,
+    Gif,
+}
+
+// everything but the state
+struct Options {
+    kind: Output,
+    width: u32,
+    height: u32,
+    frames: u32,
+    delay: u16,
+    view_mode: Option<ViewMode>,
+}
+
+pub fn run(args: &[String]) -> Result<()> {
+    let [input, output, options @ ..] = args else {
+        return Err(invalid(USAGE));
+    };
+
+    let mut state = RefState::new().read();
+    state.color_cloud_mode = ColorCloudMode::Enable(ColorSpace::Rgb);
+
+    let (mut width, mut height) = (512, 512);
+    let mut frames = 1;
+    let mut delay = 5;
+    let mut view_mode = None;
+    let mut video_frame = Some(0);
+    let mut matrix = YcbcrMatrix::Bt709;
+    let mut raw_size = None;
+    let mut compare = None;
+
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let mut value = || {
+            options
+                .next()
+                .map(String::as_str)
+                .ok_or_else(|| invalid(USAGE))
+        };
+
+        match option.as_str() {
+            "--cloud" => {
+                let text = value()?;
+                let color_space = ColorSpace::from_name(text)
+                    .ok_or_else(|| invalid(&format!("unknown color space {}", text)))?;
+                state.color_cloud_mode = ColorCloudMode::Enable(color_space);
+                state.histogram_mode = HistogramMode::Disable;
+                state.chromaticity_mode = ChromaticityMode::Disable;
+            }
+            "--histogram" => {
+                let text = value()?;
+                state.histogram_mode = HistogramMode::from_name(text)
+                    .filter(|mode| mode.is_enable())
+                    .ok_or_else(|| invalid(&format!("unknown histogram {}", text)))?;
+                state.color_cloud_mode = ColorCloudMode::Disable;
+                state.chromaticity_mode = ChromaticityMode::Disable;
+            }
+            "--chromaticity" => {
+                state.chromaticity_mode = match value()? {
+                    "xy" => ChromaticityMode::Enable(Diagram::Xy),
+                    "uv" => ChromaticityMode::Enable(Diagram::Uv),
+                    other => return Err(invalid(&format!("unknown diagram {}", other))),
+                };
+                state.color_cloud_mode = ColorCloudMode::Disable;
+                state.histogram_mode = HistogramMode::Disable;
+            }
+            "--view-mode" => {
+                let text = value()?;
+                view_mode = Some(
+                    ViewMode::from_name(text)
+                        .ok_or_else(|| invalid(&format!("unknown view mode {}", text)))?,
+                );
+            }
+            "--target" => {
+                let text = value()?;
+                state.target =
+                    Some(parse_hex(text).ok_or_else(|| invalid(&format!("bad color {}", text)))?);
+            }
+            "--palette" => {
+                let path = value()?;
+                let palette = Dictionary::load(Path::new(path))
+                    .map_err(|e| invalid(&format!("{}: {}", path, e)))?;
+                state.palette = Some(Arc::new(palette));
+            }
+            "--tolerance" => {
+                state.palette_tolerance = value()?
+                    .parse()
+                    .ok()
+                    .filter(|tolerance: &f32| *tolerance >= 0.0)
+                    .ok_or_else(|| invalid("tolerance must be a delta E of 0 or more"))?;
+            }
+            "--saturation" => {
+                let text = value()?;
+                state.saturation_measure = SaturationMeasure::from_name(text)
+                    .ok_or_else(|| invalid(&format!("unknown saturation {}", text)))?;
+            }
+            "--brightness" => {
+                let text = value()?;
+                state.brightness_measure = BrightnessMeasure::from_name(text)
+                    .ok_or_else(|| invalid(&format!("unknown brightness {}", text)))?;
+            }
+            "--compare" => compare = Some(value()?),
+            "--size" => {
+                let size = value()?;
+                (width, height) = parse_size(size)
+                    .filter(|&(w, h)| w <= 0xffff && h <= 0xffff)
+                    .ok_or_else(|| invalid(&format!("bad size {}", size)))?;
+            }
+            "--view" => {
+                let preset = match value()? {
+                    "top" => CameraPreset::Top,
+                    "side" => CameraPreset::Side,
+                    "diagonal" => CameraPreset::Diagonal,
+                    other => return Err(invalid(&format!("unknown view {}", other))),
+                };
+                state.camera.jump(preset);
+            }
+            "--perspective" => state.camera.perspective = true,
+            "--bits" => {
+                state.cloud_bits = value()?
+                    .parse()
+                    .ok()
+                    .filter(|bits| (4..=8).contains(bits))
+                    .ok_or_else(|| invalid("bits must be 4 ~ 8"))?;
+            }
+            "--point-size" => {
+                state.point_size = match value()? {
+                    "linear" => PointSize::Linear,
+                    "sqrt" => PointSize::Sqrt,
+                    "log" => PointSize::Log,
+                    "uniform" => PointSize::Uniform,
+                    other => return Err(invalid(&format!("unknown point size {}", other))),
+                };
+            }
+            "--weighted" => state.point_opacity = true,
+            "--frames" => {
+                frames = value()?
+                    .parse()
+                    .ok()
+                    .filter(|&frames| frames > 0)
+                    .ok_or_else(|| invalid("frames must be a positive number"))?;
+            }
+            "--delay" => {
+                delay = value()?
+                    .parse()
+                    .map_err(|_| invalid("delay must be in hundredths of a second"))?;
+            }
+            "--frame" => {
+                video_frame = match value()? {
+                    "all" => None,
+                    frame => Some(
+                        frame
+                            .parse()
+                            .map_err(|_| invalid("frame must be a number or all"))?,
+                    ),
+                };
+            }
+            "--matrix" => {
+                matrix = parse_matrix(value()?)?;
+            }
+            "--raw-size" => {
+                let size = value()?;
+                raw_size =
+                    Some(parse_size(size).ok_or_else(|| invalid(&format!("bad size {}", size)))?);
+            }
+            other => return Err(invalid(&format!("unknown option {}\n{}", other, USAGE))),
+        }
+    }
+
+    let kind = match Path::new(output).extension().and_then(|e| e.to_str()) {
+        Some("png") => Output::Png,
+        Some("svg") => Output::Svg,
+        Some("gif") => Output::Gif,
+        _ => return Err(invalid("the output must be .png, .svg or .gif")),
+    };
+
+    if view_mode.is_some() && !matches!(kind, Output::Png) {
+        return Err(invalid("view modes are written as .png"));
+    }
+
+    // the views that would otherwise draw the image unchanged
+    match view_mode {
+        Some(ViewMode::DeltaE) if state.target.is_none() => {
+            return Err(invalid("the delta-e view mode needs --target"));
+        }
+        Some(ViewMode::Palette | ViewMode::PaletteCompliance) if state.palette.is_none() => {
+            return Err(invalid("the palette view modes need --palette"));
+        }
+        Some(ViewMode::Selection) => {
+            return Err(invalid("the selection view mode is made in the viewer"));
+        }
+        Some(ViewMode::Compare) if compare.is_none() => {
+            return Err(invalid("the compare view mode needs --compare"));
+        }
+        _ => {}
+    }
+
+    if let Some(path) = compare {
+        // the same frame of a video
+        let frame = video_frame.ok_or_else(|| invalid("a comparison is of a single frame"))?;
+        state.compare = Some(Arc::new(video::load_frame(path, raw_size, frame, matrix)?));
+    }
+
+    let options = Options {
+        kind,
+        width,
+        height,
+        frames,
+        delay,
+        view_mode,
+    };
+
+    let is_ppm = Path::new(input)
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("ppm"));
+    if is_ppm {
+        let image = Image::load_ppm(input)?;
+        return render(&mut state, &image, Path::new(output), &options);
+    }
+
+    let mut video = Video::open(input, raw_size)?;
+    match video_frame {
+        Some(frame) => {
+            let image = video.read(frame, matrix)?;
+            render(&mut state, &image, Path::new(output), &options)
+        }
+        None => {
+            if frames > 1 {
+                return Err(invalid("a turntable is of a single frame"));
+            }
+
+            for frame in 0..video.len() {
+                let image = video.read(frame, matrix)?;
+                let path = frame_path(Path::new(output), frame, video.len());
+                render(&mut state.clone(), &image, &path, &options)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn render(state: &mut State, image: &Image, output: &Path, options: &Options) -> Result<()> {
+    let &Options {
+        kind,
+        width,
+        height,
+        frames,
+        delay,
+        view_mode,
+    } = options;
+
+    if image.pixels.is_empty() {
+        return Err(invalid("the image is empty"));
+    }
+
+    let compare = state.compare.clone();
+    if let Some(reference) = &compare {
+        // the top left area both cover, as the compare view mode draws it
+        let width = image.width.min(reference.width);
+        let height = image.height.min(reference.height);
+        if (image.width, image.height) != (reference.width, reference.height) {
+            println!(
+                "the images differ in size, {}x{} and {}x{}, compared over the top left {}x{}",
+                image.width, image.height, reference.width, reference.height, width, height
+            );
+        }
+
+        let comparison =
+            Comparison::new(&image.crop(width, height), &reference.crop(width, height))?;
+        let [r, g, b] = comparison.emd;
+        println!(
+            "delta E 2000 mean {:.2} max {:.2}, EMD R {:.4} G {:.4} B {:.4} mean {:.4}",
+            comparison.mean_delta_e,
+            comparison.max_delta_e,
+            r,
+            g,
+            b,
+            comparison.mean_emd()
+        );
+    }
+
+    if let Some(view_mode) = view_mode {
+        state.view_mode = view_mode;
+        return view::save(state, image, output);
+    }
+
+    if let Some(chromaticity) = Chromaticity::new(state, image, width, height) {
+        return match kind {
+            Output::Png => {
+                let canvas = chromaticity.rasterize();
+                png::save(output, width, height, &canvas.to_rgba8())
+            }
+            Output::Svg => chromaticity.svg().save(output),
+            Output::Gif => {
+                let mut gif = Gif::new(width, height);
+                gif.frame(
+                    &chromaticity.rasterize().to_rgb8_over(GIF_BACKGROUND),
+                    delay,
+                );
+                gif.save(output)
+            }
+        };
+    }
+
+    if let Some(joint) = Joint::new(state, image, width, height) {
+        return match kind {
+            Output::Png => {
+                let canvas = joint.rasterize();
+                png::save(output, width, height, &canvas.to_rgba8())
+            }
+            Output::Svg => joint.svg().save(output),
+            Output::Gif => {
+                let mut gif = Gif::new(width, height);
+                gif.frame(&joint.rasterize().to_rgb8_over(GIF_BACKGROUND), delay);
+                gif.save(output)
+            }
+        };
+    }
+
+    if state.histogram_mode.is_enable() {
+        let histogram = match &compare {
+            Some(reference) => Histogram::compare(state, image, reference, width, height),
+            None => Histogram::new(state, image, width, height),
+        }
+        .ok_or_else(|| invalid("no software rendering of this histogram"))?;
+
+        return match kind {
+            Output::Png => {
+                let canvas = histogram.rasterize();
+                png::save(output, width, height, &canvas.to_rgba8())
+            }
+            Output::Svg => histogram.svg().save(output),
+            Output::Gif => {
+                let mut gif = Gif::new(width, height);
+                gif.frame(&histogram.rasterize().to_rgb8_over(GIF_BACKGROUND), delay);
+                gif.save(output)
+            }
+        };
+    }
+
+    // a full turn around the lightness axis over the frames
+    let step = std::f32::consts::TAU / frames as f32;
+    let mut gif = Gif::new(width, height);
+
+    for frame in 0..frames {
+        let cloud = match &compare {
+            Some(reference) => Cloud::compare(state, image, reference, width, height),
+            None => Cloud::new(state, image, width, height),
+        };
+        let path = frame_path(output, frame as usize, frames as usize);
+
+        match kind {
+            Output::Png => {
+                let canvas = cloud.rasterize();
+                png::save(&path, width, height, &canvas.to_rgba8())?;
+            }
+            Output::Svg => cloud.svg().save(&path)?,
+            Output::Gif => gif.frame(&cloud.rasterize().to_rgb8_over(GIF_BACKGROUND), delay),
+        }
+
+        state.camera.turn(step);
+    }
+
+    if let Output::Gif = kind {
+        gif.save(output)?;
+    }
+
+    Ok(())
+}
+
+pub fn barcode(args: &[String]) -> Result<()> {
+    let [input, output, options @ ..] = args else {
+        return Err(invalid(USAGE));
+    };
+
+    let mut color = BarcodeColor::Average;
+    let mut threshold = DEFAULT_CUT_THRESHOLD;
+    let mut size = None;
+    let mut matrix = YcbcrMatrix::Bt709;
+    let mut raw_size = None;
+
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let mut value = || {
+            options
+                .next()
+                .map(String::as_str)
+                .ok_or_else(|| invalid(USAGE))
+        };
+
+        match option.as_str() {
+            "--color" => {
+                color = match value()? {
+                    "average" => BarcodeColor::Average,
+                    "dominant" => BarcodeColor::Dominant,
+                    other => return Err(invalid(&format!("unknown color {}", other))),
+                };
+            }
+            "--threshold" => {
+                threshold = value()?
+                    .parse()
+                    .ok()
+                    .filter(|threshold| (0.0..=1.0).contains(threshold))
+                    .ok_or_else(|| invalid("threshold must be 0 ~ 1"))?;
+            }
+            "--size" => {
+                let text = value()?;
+                size = Some(
+                    parse_size(text)
+                        .filter(|&(w, h)| w <= 0xffff && h <= 0xffff)
+                        .ok_or_else(|| invalid(&format!("bad size {}", text)))?,
+                );
+            }
+            "--matrix" => {
+                matrix = parse_matrix(value()?)?;
+            }
+            "--raw-size" => {
+                let text = value()?;
+                raw_size =
+                    Some(parse_size(text).ok_or_else(|| invalid(&format!("bad size {}", text)))?);
+            }
+            other => return Err(invalid(&format!("unknown option {}\n{}", other, USAGE))),
+        }
+    }
+
+    if !Path::new(output)
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("png"))
+    {
+        return Err(invalid("the barcode is written as .png"));
+    }
+
+    let mut video = Video::open(input, raw_size)?;
+    let barcode = barcode::export(
+        &mut video,
+        matrix,
+        color,
+        threshold,
+        size,
+        Path::new(output),
+    )?;
+    println!(
+        "{} frames, {} shots",
+        barcode.columns.len(),
+        barcode.shots.len()
+    );
+
+    Ok(())
+}
+
+// out.png is kept for a single frame, a turntable goes to out_000.png ...
+fn frame_path(path: &Path, frame: usize, frames: usize) -> PathBuf {
+    if frames == 1 {
+        return path.to_path_buf();
+    }
+
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}_{:03}.{}", stem, frame, extension))
+}
+
+fn parse_matrix(text: &str) -> Result<YcbcrMatrix> {
+    match text {
+        "bt601" => Ok(YcbcrMatrix::Bt601),
+        "bt709" => Ok(YcbcrMatrix::Bt709),
+        "bt2020" => Ok(YcbcrMatrix::Bt2020),
+        other => Err(invalid(&format!("unknown matrix {}", other))),
+    }
+}
+
+fn parse_size(text: &str) -> Option<(u32, u32)> {
+    text.split_once('x')
+        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+        .filter(|&(w, h)| w > 0 && h > 0)
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, message.to_string())
+}
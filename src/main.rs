@@ -1,23 +1,43 @@
 #![windows_subsystem = "windows"]
 
-use crate::gui::viewer::*;
-use crate::state::*;
-use crate::worker::*;
-use gui::compositor::Compositor;
-use windows::core::Result;
-use windows::core::PCSTR;
-use windows::System::*;
-use windows::Win32::Foundation::*;
-use windows::Win32::System::WinRT::*;
-use windows::Win32::UI::WindowsAndMessaging::*;
+#[cfg(windows)]
+use {
+    crate::{gui::compositor::Compositor, gui::viewer::*, state::*, worker::*},
+    windows::core::{Error, Result, PCSTR},
+    windows::System::*,
+    windows::Win32::{
+        Foundation::*, System::Console::*, System::WinRT::*, UI::WindowsAndMessaging::*,
+    },
+};
 
+// the viewer uses more of these than the headless renderer
+#[cfg_attr(not(windows), allow(dead_code))]
+mod analysis;
+#[cfg_attr(not(windows), allow(dead_code))]
+mod automation;
+#[cfg_attr(not(windows), allow(dead_code))]
+mod camera;
+#[cfg_attr(not(windows), allow(dead_code))]
+mod color;
+#[cfg(windows)]
 mod graphics;
+#[cfg(windows)]
 mod gui;
+mod headless;
+#[cfg(windows)]
 pub mod pass;
+#[cfg(windows)]
+mod recorder;
+mod render;
+#[cfg_attr(not(windows), allow(dead_code))]
 mod state;
+#[cfg_attr(not(windows), allow(dead_code))]
+mod video;
+#[cfg(windows)]
 mod worker;
 
-fn func() -> Result<()> {
+#[cfg(windows)]
+fn func(listen: Option<u16>) -> Result<()> {
     unsafe {
         RoInitialize(RO_INIT_MULTITHREADED)?;
     }
@@ -27,6 +47,15 @@ fn func() -> Result<()> {
     let state = RefState::new();
     // state.set_color_cloud_mode(ColorCloudMode::Enable(ColorSpace::Hsl));
 
+    if let Some(port) = listen {
+        if let Err(e) = automation::listen(RefState::clone(&state), port) {
+            return Err(Error::new(
+                E_FAIL,
+                format!("listen on port {}: {}", port, e).into(),
+            ));
+        }
+    }
+
     let viewer = Viewer::new(RefState::clone(&state));
     viewer.create()?;
 
@@ -49,8 +78,43 @@ fn func() -> Result<()> {
     Ok(())
 }
 
-fn main() -> Result<()> {
-    let ret: Result<()> = func();
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let command = match args.get(1).map(String::as_str) {
+        Some("render") => Some(headless::run as fn(&[String]) -> _),
+        Some("barcode") => Some(headless::barcode as fn(&[String]) -> _),
+        _ => None,
+    };
+
+    if let Some(command) = command {
+        attach_console();
+        if let Err(e) = command(&args[2..]) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    viewer(&args[1..])
+}
+
+#[cfg(windows)]
+fn viewer(args: &[String]) {
+    // the automation API next to the viewer
+    let listen = match args.first().map(String::as_str) {
+        Some("--listen") => match args.get(1).map(|port| port.parse()) {
+            None => Some(automation::DEFAULT_PORT),
+            Some(Ok(port)) => Some(port),
+            Some(Err(_)) => {
+                attach_console();
+                eprintln!("{}", headless::USAGE);
+                std::process::exit(2);
+            }
+        },
+        _ => None,
+    };
+
+    let ret: Result<()> = func(listen);
 
     if let Err(e) = ret {
         unsafe {
@@ -58,10 +122,27 @@ fn main() -> Result<()> {
             MessageBoxA(None, PCSTR(msg.as_ptr()), PCSTR("err\0".as_ptr()), MB_OK);
         }
     }
+}
 
-    Ok(())
+#[cfg(not(windows))]
+fn viewer(_args: &[String]) {
+    eprintln!("{}", headless::USAGE);
+    std::process::exit(2);
 }
 
+// the windows subsystem starts without a console, what the command line prints
+// goes to the one it was run from
+#[cfg(windows)]
+fn attach_console() {
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+#[cfg(windows)]
 struct DispatcherQueueWrapper {
     #[allow(unused)]
     dispatcher_queue_controller: DispatcherQueueController,
@@ -70,6 +151,7 @@ struct DispatcherQueueWrapper {
     dispatcher_queue: DispatcherQueue,
 }
 
+#[cfg(windows)]
 impl DispatcherQueueWrapper {
     fn new() -> Result<Self> {
         let dispatcher_queue_controller = unsafe {
@@ -1,22 +1,60 @@
+mod analysis_pass;
+mod blur_pass;
+mod chromaticity_pass;
 mod color_cloud_count_pass;
+mod color_cloud_guide_pass;
 mod color_cloud_indirect_pass;
 mod color_cloud_mesh_pass;
 mod color_cloud_pass;
+mod color_cloud_transparency_pass;
+mod color_name_pass;
+mod compare_pass;
+mod harmony_pass;
 mod histogram_pass;
+mod palette_pass;
+mod probe_pass;
+mod selection_pass;
+mod snapshot_pass;
+mod stats_pass;
+mod video_pass;
 mod view_pass;
 
+use crate::analysis::barcode::DEFAULT_CUT_THRESHOLD;
+use crate::analysis::compare::Comparison;
 use crate::graphics::capture::*;
 use crate::graphics::context::*;
+use crate::graphics::descriptor::Descriptor;
 use crate::gui::compositor::Compositor;
+use crate::recorder::Recorder;
+use crate::render::{barcode, view, Image};
 use crate::state::*;
+use crate::video::Video;
 use windows::core::*;
 use windows::Win32::Graphics::Direct3D12::*;
 use windows::Win32::Graphics::Dxgi::Common::DXGI_FORMAT_R8G8B8A8_UNORM;
 use windows::Win32::Graphics::Dxgi::*;
 use windows::Win32::System::WinRT::*;
+use windows::Win32::UI::WindowsAndMessaging::{MessageBoxA, MB_OK};
 
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use self::analysis_pass::*;
+use self::blur_pass::BlurPass;
+use self::chromaticity_pass::ChromaticityPass;
 use self::color_cloud_pass::ColorCloudPass;
+use self::color_name_pass::ColorNamePass;
+use self::compare_pass::ComparePass;
+use self::harmony_pass::HarmonyPass;
 use self::histogram_pass::HistogramPass;
+use self::palette_pass::PalettePass;
+use self::probe_pass::ProbePass;
+use self::selection_pass::SelectionPass;
+use self::snapshot_pass::SnapshotPass;
+use self::stats_pass::StatsPass;
+use self::video_pass::VideoPass;
 use self::view_pass::ViewPass;
 
 pub enum RootParam {
@@ -39,9 +77,20 @@ pub struct Executor {
     capturer: Capturer,
 
     root_signature: ID3D12RootSignature,
-    view_pass: ViewPass,
-    color_cloud_pass: ColorCloudPass,
-    histogram_pass: HistogramPass,
+    blur_pass: BlurPass,
+    snapshot_pass: SnapshotPass,
+    video_pass: VideoPass,
+    passes: PassRegistry,
+
+    video: Option<(PathBuf, Video)>,
+    // the decoded frame and what it was decoded with
+    video_image: Option<((usize, YcbcrMatrix), Image)>,
+    recorder: Option<(PathBuf, Recorder)>,
+    // the view area held by the automation API
+    frozen: Option<Image>,
+    // the comparison of a past frame with the compared image, one at a time
+    comparison_job: Option<JoinHandle<std::io::Result<Comparison>>>,
+    last_update: Instant,
 }
 
 impl Executor {
@@ -58,9 +107,44 @@ impl Executor {
                 Context::new(&factory, window, DXGI_FORMAT_R8G8B8A8_UNORM, compositor)?;
 
             let root_signature = Self::create_root_signature(&mut context)?;
-            let view_pass = ViewPass::new(&mut context, &root_signature)?;
-            let color_cloud_pass = ColorCloudPass::new(&mut context, &root_signature)?;
-            let histogram_pass = HistogramPass::new(&mut context, &root_signature)?;
+            let blur_pass = BlurPass::new(
+                &mut context,
+                &root_signature,
+                capturer.width(),
+                capturer.height(),
+            )?;
+            let snapshot_pass = SnapshotPass::new(
+                &mut context,
+                &root_signature,
+                capturer.width(),
+                capturer.height(),
+            )?;
+            let video_pass = VideoPass::new(
+                &mut context,
+                &root_signature,
+                capturer.width(),
+                capturer.height(),
+            )?;
+
+            // in the order they draw, the probe first for the color names to name it
+            let passes = PassRegistry::new(vec![
+                Box::new(SelectionPass::new(&mut context, &root_signature)?),
+                Box::new(ViewPass::new(&mut context, &root_signature)?),
+                Box::new(ComparePass::new(
+                    &mut context,
+                    &root_signature,
+                    capturer.width(),
+                    capturer.height(),
+                )?),
+                Box::new(ProbePass::new(&mut context, &root_signature)?),
+                Box::new(ColorCloudPass::new(&mut context, &root_signature)?),
+                Box::new(ColorNamePass::new(&mut context, &root_signature)?),
+                Box::new(PalettePass::new(&mut context, &root_signature)?),
+                Box::new(HistogramPass::new(&mut context, &root_signature)?),
+                Box::new(ChromaticityPass::new(&mut context, &root_signature)?),
+                Box::new(HarmonyPass::new(&mut context, &root_signature)?),
+                Box::new(StatsPass::new(&mut context, &root_signature)?),
+            ])?;
 
             Ok(Self {
                 state,
@@ -68,9 +152,16 @@ impl Executor {
                 context,
                 capturer,
                 root_signature,
-                view_pass,
-                color_cloud_pass,
-                histogram_pass,
+                blur_pass,
+                snapshot_pass,
+                video_pass,
+                passes,
+                video: None,
+                video_image: None,
+                recorder: None,
+                frozen: None,
+                comparison_job: None,
+                last_update: Instant::now(),
             })
         }
     }
@@ -92,18 +183,68 @@ impl Executor {
     }
 
     fn update(&mut self, mut state: State) -> Result<()> {
-        state.rect = self.window.client_rect();
+        let now = Instant::now();
+        let seconds = now.duration_since(self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        if state.camera.is_animating() {
+            self.state.update_camera(|camera| camera.update(seconds));
+            state.camera = self.state.camera();
+        }
+
+        state.rect = state
+            .capture_rect
+            .unwrap_or_else(|| self.window.client_rect());
         state.rect.left = state.rect.left.max(0);
         state.rect.top = state.rect.top.max(0);
         state.rect.right = state.rect.right.min(self.capturer.width() as _);
         state.rect.bottom = state.rect.bottom.min(self.capturer.height() as _);
 
+        self.update_video(&state);
+
+        if let Some(path) = state.barcode_export.clone() {
+            self.state.set_barcode_export(None);
+            self.export_barcode(&state, path);
+        }
+
+        if !state.frozen {
+            self.frozen = None;
+        }
+
+        // a frozen frame or a video sits at the top left of the window, which may be larger
+        let source_size = match (&self.frozen, &self.video) {
+            (Some(image), _) => Some((image.width, image.height)),
+            (None, Some((_, video))) => Some((video.width(), video.height())),
+            _ => None,
+        };
+        if let Some((source_width, source_height)) = source_size {
+            state.rect.right = state.rect.right.min(state.rect.left + source_width as i32);
+            state.rect.bottom = state.rect.bottom.min(state.rect.top + source_height as i32);
+        }
+
         let (width, height) = rect_size(&state.rect);
         if width <= 0 || height <= 0 {
             return Ok(());
         }
 
-        let capture = match self.capturer.capture(&mut self.context)? {
+        let (x, y) = cursor_pos();
+        state.cursor = (state.rect.left <= x
+            && x < state.rect.right
+            && state.rect.top <= y
+            && y < state.rect.bottom)
+            .then_some((x, y));
+
+        self.update_recorder(&state);
+
+        let capture = if self.frozen.is_some() {
+            self.frozen_frame(&state)?
+        } else if self.video.is_some() {
+            self.video_frame(&state)?
+        } else {
+            self.capturer.capture(&mut self.context)?
+        };
+
+        let capture = match capture {
             Some(capture) => capture,
             _ => {
                 std::thread::sleep(std::time::Duration::from_millis(1));
@@ -114,7 +255,7 @@ impl Executor {
         self.context.begin_draw(
             width as _,
             height as _,
-            if state.color_cloud_mode.is_enable() {
+            if state.color_cloud_mode.is_enable() || state.chromaticity_mode.is_enable() {
                 &[0.0, 0.0, 0.0, 0.25]
             } else {
                 &[0.0, 0.0, 0.0, 0.0]
@@ -129,28 +270,253 @@ impl Executor {
             .command_list
             .set_graphics_root_signature(&self.root_signature);
 
-        self.context
-            .command_list
-            .set_compute_descriptor_table(RootParam::Capture, capture.resource.srv());
+        if self.video.is_some() || self.frozen.is_some() {
+            self.video_pass.process(&mut self.context, &state)?;
+        }
 
-        self.context
-            .command_list
-            .set_graphics_descriptor_table(RootParam::Capture, capture.resource.srv());
+        bind_frame(&self.context, capture.resource.srv());
 
-        self.view_pass
-            .process(&mut self.context, &state, &capture)?;
+        if state.needs_blur() {
+            self.blur_pass
+                .process(&mut self.context, &state, &capture)?;
+        }
+
+        let captured = *capture.resource.srv();
+        let squinted = *self.blur_pass.output().srv();
+        let view_frame = if state.view_mode == ViewMode::Squint {
+            squinted
+        } else {
+            captured
+        };
+        let analysis_frame = if state.squint_analysis {
+            squinted
+        } else {
+            captured
+        };
+
+        self.passes.prepare(&mut self.context, &state)?;
 
-        self.color_cloud_pass
-            .process(&mut self.context, &state, &capture)?;
+        // the frame the view reads, blurred for Squint
+        if state.needs_snapshot() {
+            bind_frame(&self.context, &view_frame);
+            self.snapshot_pass
+                .process(&mut self.context, &state, &capture)?;
+        }
 
-        self.histogram_pass
-            .process(&mut self.context, &state, &capture)?;
+        self.passes
+            .process(&mut self.context, &state, &capture, |input| match input {
+                Input::View => view_frame,
+                Input::Analysis => analysis_frame,
+            })?;
 
         self.context.end_draw()?;
 
+        self.passes.finish(&state, &self.state)?;
+
+        if let Some(path) = state.view_export.clone() {
+            self.state.set_view_export(None);
+            self.export_view(&state, path)?;
+        }
+
+        if state.compare_snapshot {
+            self.state.set_compare_snapshot(false);
+            self.state
+                .set_compare(Some(Arc::new(self.snapshot(&state)?)));
+        }
+
+        self.update_comparison(&state)?;
+
+        if state.frame_request {
+            self.state.set_frame_request(false);
+            self.state.set_frame(Some(Arc::new(self.snapshot(&state)?)));
+        }
+
+        if state.frozen && self.frozen.is_none() {
+            self.frozen = Some(self.snapshot(&state)?);
+        }
+
+        if state.needs_stats() {
+            if let (Some((_, recorder)), Some(stats)) = (&self.recorder, self.state.stats()) {
+                recorder.record(&stats, state.saturation_measure);
+            }
+        }
+
+        Ok(())
+    }
+
+    // the view area of the last frame
+    fn snapshot(&self, state: &State) -> Result<Image> {
+        let (width, height) = rect_size(&state.rect);
+        Ok(Image {
+            width: width as _,
+            height: height as _,
+            pixels: self.snapshot_pass.pixels(width as _, height as _)?,
+        })
+    }
+
+    // encoded and written off the loop
+    fn export_view(&self, state: &State, path: PathBuf) -> Result<()> {
+        let image = self.snapshot(state)?;
+
+        let mut state = state.clone();
+        if state.view_mode == ViewMode::Squint {
+            // the snapshot already is the blurred frame
+            state.view_mode = ViewMode::Brightness;
+        }
+
+        std::thread::spawn(move || {
+            if let Err(e) = view::save(&state, &image, &path) {
+                report("Export view", e);
+            }
+        });
+
         Ok(())
     }
 
+    // the metrics of the latest frame whenever the previous ones are done
+    fn update_comparison(&mut self, state: &State) -> Result<()> {
+        let finished = self
+            .comparison_job
+            .as_ref()
+            .is_some_and(|job| job.is_finished());
+        if finished {
+            if let Some(Ok(Ok(comparison))) = self.comparison_job.take().map(JoinHandle::join) {
+                self.state
+                    .set_comparison(state.compare.is_some().then_some(comparison));
+            }
+        }
+
+        let Some(reference) = state.compare.clone() else {
+            self.state.set_comparison(None);
+            return Ok(());
+        };
+
+        if self.comparison_job.is_none() {
+            let image = self.snapshot(state)?;
+            // the frame and the reference over the top left area they share,
+            // which the heat map of the compare view covers
+            self.comparison_job = Some(std::thread::spawn(move || {
+                let width = image.width.min(reference.width);
+                let height = image.height.min(reference.height);
+                Comparison::new(&image.crop(width, height), &reference.crop(width, height))
+            }));
+        }
+
+        Ok(())
+    }
+
+    // opens and closes the video as the panel asks
+    fn update_video(&mut self, state: &State) {
+        if self.video.as_ref().map(|(path, _)| path) == state.video.as_ref() {
+            return;
+        }
+
+        self.video = None;
+        self.video_image = None;
+        self.state.set_video_len(0);
+
+        if let Some(path) = &state.video {
+            match Video::open(path, None) {
+                Ok(video) => {
+                    self.state.set_video_len(video.len());
+                    self.video = Some((path.clone(), video));
+                }
+                Err(e) => {
+                    self.state.set_video(None);
+                    report("Open video", e);
+                }
+            }
+        }
+    }
+
+    // every frame decoded again off the loop, from a file of its own
+    fn export_barcode(&self, state: &State, path: PathBuf) {
+        let Some((video_path, _)) = &self.video else {
+            return;
+        };
+        let video_path = video_path.clone();
+        let (matrix, color) = (state.ycbcr_matrix, state.barcode_color);
+
+        std::thread::spawn(move || {
+            let result = Video::open(video_path, None).and_then(|mut video| {
+                barcode::export(
+                    &mut video,
+                    matrix,
+                    color,
+                    DEFAULT_CUT_THRESHOLD,
+                    None,
+                    &path,
+                )
+            });
+            if let Err(e) = result {
+                report("Export barcode", e);
+            }
+        });
+    }
+
+    // the selected frame uploaded in place of the screen
+    fn video_frame(&mut self, state: &State) -> Result<Option<Capture>> {
+        let Some((_, video)) = &mut self.video else {
+            return Ok(None);
+        };
+
+        let key = (state.video_frame.min(video.len() - 1), state.ycbcr_matrix);
+        if self.video_image.as_ref().map(|(decoded, _)| *decoded) != Some(key) {
+            match video.read(key.0, key.1) {
+                Ok(image) => self.video_image = Some((key, image)),
+                Err(e) => {
+                    self.state.set_video(None);
+                    report("Open video", e);
+                    return Ok(None);
+                }
+            }
+        }
+
+        if let Some((_, image)) = &self.video_image {
+            self.video_pass.upload(state, image)?;
+        }
+
+        // nothing paces the loop as the screen capture does
+        std::thread::sleep(Duration::from_millis(16));
+
+        Ok(Some(self.video_pass.capture()))
+    }
+
+    // the frozen view area uploaded as a video frame is
+    fn frozen_frame(&mut self, state: &State) -> Result<Option<Capture>> {
+        let Some(image) = &self.frozen else {
+            return Ok(None);
+        };
+        self.video_pass.upload(state, image)?;
+
+        std::thread::sleep(Duration::from_millis(16));
+
+        Ok(Some(self.video_pass.capture()))
+    }
+
+    // starts and stops the recording as the panel asks
+    fn update_recorder(&mut self, state: &State) {
+        if self.recorder.as_ref().map(|(path, _)| path) == state.stats_recording.as_ref() {
+            return;
+        }
+
+        if let Some((_, mut recorder)) = self.recorder.take() {
+            if let Err(e) = recorder.stop() {
+                report("Record statistics", e);
+            }
+        }
+
+        if let Some(path) = &state.stats_recording {
+            match Recorder::new(path) {
+                Ok(recorder) => self.recorder = Some((path.clone(), recorder)),
+                Err(e) => {
+                    self.state.set_stats_recording(None);
+                    report("Record statistics", e);
+                }
+            }
+        }
+    }
+
     fn create_root_signature(context: &mut Context) -> Result<ID3D12RootSignature> {
         let ranges_0 = [D3D12_DESCRIPTOR_RANGE {
             RangeType: D3D12_DESCRIPTOR_RANGE_TYPE_SRV,
@@ -212,7 +578,7 @@ impl Executor {
                 Anonymous: D3D12_ROOT_PARAMETER_0 {
                     Constants: D3D12_ROOT_CONSTANTS {
                         ShaderRegister: 0,
-                        Num32BitValues: 29,
+                        Num32BitValues: 32,
                         ..Default::default()
                     },
                 },
@@ -244,4 +610,23 @@ impl Executor {
     }
 }
 
+fn bind_frame(context: &Context, srv: &Descriptor) {
+    context
+        .command_list
+        .set_compute_descriptor_table(RootParam::Capture, srv);
+
+    context
+        .command_list
+        .set_graphics_descriptor_table(RootParam::Capture, srv);
+}
+
+// shown off the executor loop, which keeps running
+fn report(title: &str, e: std::io::Error) {
+    let msg = e.to_string() + "\0";
+    let title = title.to_string() + "\0";
+    std::thread::spawn(move || unsafe {
+        MessageBoxA(None, PCSTR(msg.as_ptr()), PCSTR(title.as_ptr()), MB_OK);
+    });
+}
+
 unsafe impl Send for Executor {}
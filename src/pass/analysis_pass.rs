@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use windows::core::*;
+use windows::Win32::Foundation::E_INVALIDARG;
+
+use crate::graphics::{capture::Capture, descriptor::Descriptor, *};
+use crate::state::*;
+
+use super::bind_frame;
+
+// a resource one pass leaves for the others to bind
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Slot {
+    // the 256^3 color counts of the color cloud, an SRV
+    ColorCounts,
+    // the palette lookup table and colors, UAVs of the view
+    Palette,
+    // the selection mask, t1 of the view
+    Selection,
+}
+
+// the frame bound as t0 while a pass runs
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Input {
+    // as the view shows it, blurred for Squint
+    View,
+    // as the analysis sees it, blurred on request
+    Analysis,
+}
+
+#[derive(Default)]
+pub struct Slots(HashMap<Slot, Descriptor>);
+
+impl Slots {
+    pub fn insert(&mut self, slot: Slot, descriptor: Descriptor) {
+        self.0.insert(slot, descriptor);
+    }
+
+    // every read slot is published, PassRegistry::new checks it
+    pub fn get(&self, slot: Slot) -> &Descriptor {
+        &self.0[&slot]
+    }
+}
+
+// a scope or analysis run on every frame, self-contained but for the slots
+pub trait AnalysisPass {
+    fn name(&self) -> &'static str;
+
+    fn input(&self) -> Input {
+        Input::Analysis
+    }
+
+    // the slots whose writers have to run first
+    fn reads(&self) -> &'static [Slot] {
+        &[]
+    }
+
+    // the slots filled by `process`
+    fn writes(&self) -> &'static [Slot] {
+        &[]
+    }
+
+    // the descriptors of the written slots, once at registration
+    fn publish(&self, _slots: &mut Slots) {}
+
+    fn is_enabled(&self, state: &State) -> bool;
+
+    // before any pass runs, for what the others read without waiting on `process`
+    fn prepare(&mut self, _context: &mut Context, _state: &State) -> Result<()> {
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        capture: &Capture,
+        slots: &Slots,
+    ) -> Result<()>;
+
+    // after the frame, of enabled and disabled passes alike, the results read
+    // back into the shared state
+    fn finish(&mut self, _state: &State, _shared: &RefState) -> Result<()> {
+        Ok(())
+    }
+}
+
+// the passes in the order they run, which is the order they draw in
+pub struct PassRegistry {
+    passes: Vec<Box<dyn AnalysisPass>>,
+    slots: Slots,
+}
+
+impl PassRegistry {
+    // each pass after the ones before it unless it reads what a later one
+    // writes, fails on a slot read but not published or on passes reading
+    // each other's slots
+    pub fn new(passes: Vec<Box<dyn AnalysisPass>>) -> Result<Self> {
+        let mut slots = Slots::default();
+        for pass in &passes {
+            pass.publish(&mut slots);
+        }
+
+        for pass in &passes {
+            if let Some(slot) = pass.reads().iter().find(|slot| !slots.0.contains_key(slot)) {
+                return Err(invalid(&format!(
+                    "{} reads {:?}, which no pass publishes",
+                    pass.name(),
+                    slot
+                )));
+            }
+        }
+
+        Ok(Self {
+            passes: sort(passes)?,
+            slots,
+        })
+    }
+
+    pub fn prepare(&mut self, context: &mut Context, state: &State) -> Result<()> {
+        for pass in &mut self.passes {
+            if pass.is_enabled(state) {
+                pass.prepare(context, state)?;
+            }
+        }
+        Ok(())
+    }
+
+    // `frame` gives the SRV of each input
+    pub fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        capture: &Capture,
+        frame: impl Fn(Input) -> Descriptor,
+    ) -> Result<()> {
+        let mut bound = None;
+        for pass in &mut self.passes {
+            if !pass.is_enabled(state) {
+                continue;
+            }
+
+            let input = pass.input();
+            if bound != Some(input) {
+                bind_frame(context, &frame(input));
+                bound = Some(input);
+            }
+
+            pass.process(context, state, capture, &self.slots)?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(&mut self, state: &State, shared: &RefState) -> Result<()> {
+        for pass in &mut self.passes {
+            pass.finish(state, shared)?;
+        }
+        Ok(())
+    }
+}
+
+// stable topological order, a pass moves only behind the writers of its reads
+fn sort(mut pending: Vec<Box<dyn AnalysisPass>>) -> Result<Vec<Box<dyn AnalysisPass>>> {
+    let mut passes = vec![];
+    while !pending.is_empty() {
+        let Some(ready) = pending.iter().position(|pass| {
+            pass.reads().iter().all(|slot| {
+                pending
+                    .iter()
+                    .all(|other| std::ptr::eq(other, pass) || !other.writes().contains(slot))
+            })
+        }) else {
+            let names = pending.iter().map(|pass| pass.name()).collect::<Vec<_>>();
+            return Err(invalid(&format!(
+                "passes read each other's slots: {}",
+                names.join(", ")
+            )));
+        };
+        passes.push(pending.remove(ready));
+    }
+    Ok(passes)
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(E_INVALIDARG, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Pass {
+        name: &'static str,
+        reads: &'static [Slot],
+        writes: &'static [Slot],
+    }
+
+    impl AnalysisPass for Pass {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn reads(&self) -> &'static [Slot] {
+            self.reads
+        }
+
+        fn writes(&self) -> &'static [Slot] {
+            self.writes
+        }
+
+        fn publish(&self, slots: &mut Slots) {
+            for &slot in self.writes {
+                slots.insert(slot, Descriptor::default());
+            }
+        }
+
+        fn is_enabled(&self, _state: &State) -> bool {
+            true
+        }
+
+        fn process(
+            &mut self,
+            _context: &mut Context,
+            _state: &State,
+            _capture: &Capture,
+            _slots: &Slots,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn pass(
+        name: &'static str,
+        reads: &'static [Slot],
+        writes: &'static [Slot],
+    ) -> Box<dyn AnalysisPass> {
+        Box::new(Pass {
+            name,
+            reads,
+            writes,
+        })
+    }
+
+    fn names(registry: &PassRegistry) -> Vec<&'static str> {
+        registry.passes.iter().map(|pass| pass.name()).collect()
+    }
+
+    #[test]
+    fn keeps_the_registration_order_of_independent_passes() {
+        let registry = PassRegistry::new(vec![
+            pass("a", &[], &[]),
+            pass("b", &[], &[Slot::Palette]),
+            pass("c", &[], &[]),
+        ])
+        .unwrap();
+        assert_eq!(names(&registry), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn moves_a_reader_behind_the_writer() {
+        let registry = PassRegistry::new(vec![
+            pass("view", &[Slot::Palette, Slot::Selection], &[]),
+            pass("probe", &[], &[]),
+            pass("palette", &[], &[Slot::Palette]),
+            pass("selection", &[Slot::ColorCounts], &[Slot::Selection]),
+            pass("cloud", &[], &[Slot::ColorCounts]),
+        ])
+        .unwrap();
+        assert_eq!(
+            names(&registry),
+            ["probe", "palette", "cloud", "selection", "view"]
+        );
+    }
+
+    #[test]
+    fn rejects_a_slot_nobody_publishes() {
+        let registry = PassRegistry::new(vec![pass("view", &[Slot::Palette], &[])]);
+        assert!(registry.is_err());
+    }
+
+    #[test]
+    fn rejects_passes_reading_each_other() {
+        let registry = PassRegistry::new(vec![
+            pass("a", &[Slot::Palette], &[Slot::Selection]),
+            pass("b", &[Slot::Selection], &[Slot::Palette]),
+        ]);
+        assert!(registry.is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use std::mem::size_of;
+
+use windows::{
+    core::*,
+    w,
+    Win32::{
+        Foundation::RECT,
+        Graphics::{Direct3D12::*, Dxgi::Common::*},
+    },
+};
+
+use crate::{
+    graphics::{capture::Capture, *},
+    state::*,
+};
+
+use super::RootParam;
+
+const SHADER_RESOURCE: D3D12_RESOURCE_STATES = D3D12_RESOURCE_STATES(
+    D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE.0 | D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE.0,
+);
+
+// separable blur of the view area into a capture sized texture
+pub struct BlurPass {
+    horizontal_pso: ID3D12PipelineState,
+    vertical_pso: ID3D12PipelineState,
+
+    intermediate: Resource,
+    output: Resource,
+}
+
+impl BlurPass {
+    pub fn new(
+        context: &mut Context,
+        root_signature: &ID3D12RootSignature,
+        width: u32,
+        height: u32,
+    ) -> Result<Self> {
+        let device = &context.device;
+        let compiler = &context.compiler;
+        let descriptor_heap = &mut context.descriptor_heap;
+
+        let horizontal_pso = device.create_compute_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\blur.hlsl"),
+                w!("BlurHorizontalCs"),
+                w!("cs_6_0"),
+                &[],
+            )?,
+        )?;
+
+        let vertical_pso = device.create_compute_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\blur.hlsl"),
+                w!("BlurVerticalCs"),
+                w!("cs_6_0"),
+                &[],
+            )?,
+        )?;
+
+        let mut textures = [(); 2].map(|_| {
+            Resource::new_texture2d(
+                device,
+                width,
+                height,
+                DXGI_FORMAT_R8G8B8A8_UNORM,
+                SampleDesc::default(),
+                D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+                SHADER_RESOURCE,
+            )
+        });
+
+        for texture in textures.iter_mut().flatten() {
+            descriptor_heap.create_srv_tex2d(texture);
+            descriptor_heap.create_uav(
+                texture,
+                false,
+                &D3D12_UNORDERED_ACCESS_VIEW_DESC {
+                    Format: DXGI_FORMAT_R8G8B8A8_UNORM,
+                    ViewDimension: D3D12_UAV_DIMENSION_TEXTURE2D,
+                    Anonymous: D3D12_UNORDERED_ACCESS_VIEW_DESC_0 {
+                        Texture2D: D3D12_TEX2D_UAV {
+                            MipSlice: 0,
+                            PlaneSlice: 0,
+                        },
+                    },
+                },
+            );
+        }
+
+        let [intermediate, output] = textures;
+
+        Ok(Self {
+            horizontal_pso,
+            vertical_pso,
+            intermediate: intermediate?,
+            output: output?,
+        })
+    }
+
+    pub fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        _capture: &Capture,
+    ) -> Result<()> {
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            radius: u32,
+            gaussian: u32,
+        }
+        const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
+
+        let command_list = &context.command_list;
+
+        let params = Params {
+            rect: state.rect,
+            radius: state.squint_radius,
+            gaussian: (state.squint_blur == Blur::Gaussian) as _,
+        };
+
+        const THREADS: u32 = 8;
+        let (width, height) = rect_size(&state.rect);
+
+        // the horizontal pass reads the capture, the vertical pass the intermediate
+        for (pso, src, dst) in [
+            (&self.horizontal_pso, None, &self.intermediate),
+            (&self.vertical_pso, Some(&self.intermediate), &self.output),
+        ] {
+            command_list.access(&[(dst, D3D12_RESOURCE_STATE_UNORDERED_ACCESS)]);
+
+            command_list.set_pipeline_state(pso);
+
+            command_list.set_compute_constants(
+                RootParam::Constants,
+                NUM_CONSTS,
+                &params as *const _ as _,
+            );
+
+            if let Some(src) = src {
+                command_list.set_compute_descriptor_table(RootParam::Srvs, src.srv());
+            }
+            command_list.set_compute_descriptor_table(RootParam::Uavs, dst.uav());
+
+            command_list.dispatch(
+                div_round_up(width as _, THREADS),
+                div_round_up(height as _, THREADS),
+                1,
+            );
+
+            command_list.access(&[(dst, SHADER_RESOURCE)]);
+        }
+
+        Ok(())
+    }
+
+    // bound in place of the capture when the squinted frame is viewed or analyzed
+    pub fn output(&self) -> &Resource {
+        &self.output
+    }
+}
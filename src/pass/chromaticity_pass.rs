@@ -0,0 +1,295 @@
+use crate::color::chromaticity::CHROMATICITY_BINS;
+use crate::graphics::{capture::Capture, context::*};
+use crate::state::*;
+use std::mem::size_of;
+use windows::core::*;
+use windows::Win32::Foundation::RECT;
+use windows::Win32::Graphics::Direct3D::Dxc::DxcDefine;
+use windows::Win32::Graphics::Direct3D::*;
+use windows::Win32::Graphics::Dxgi::Common::*;
+use windows::{w, Win32::Graphics::Direct3D12::*};
+
+use super::analysis_pass::*;
+use super::RootParam;
+
+const DENSITY_LEN: u32 = (CHROMATICITY_BINS * CHROMATICITY_BINS) as u32;
+
+// the locus, three triangles, the white point and the ticks fit with room to spare
+const MAX_VERTICES: usize = 512;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct Vertex {
+    position: [f32; 2],
+    color: [f32; 4],
+}
+
+pub struct ChromaticityPass {
+    create_pso: ID3D12PipelineState,
+    density_pso: ID3D12PipelineState,
+    guide_pso: ID3D12PipelineState,
+
+    density_buf: Resource,
+    vertex_buf: Resource,
+    // the diagram the guides in `vertex_buf` are drawn for
+    guides: Option<(Diagram, usize)>,
+}
+
+impl ChromaticityPass {
+    pub fn new(context: &mut Context, root_signature: &ID3D12RootSignature) -> Result<Self> {
+        let device = &context.device;
+        let compiler = &context.compiler;
+        let descriptor_heap = &mut context.descriptor_heap;
+
+        let create_pso = device.create_compute_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\chromaticity.hlsl"),
+                w!("CreateCs"),
+                w!("cs_6_5"),
+                &[DxcDefine {
+                    Name: w!("CREATE"),
+                    Value: w!(""),
+                }],
+            )?,
+        )?;
+
+        let defines = [DxcDefine {
+            Name: w!("DRAW"),
+            Value: w!(""),
+        }];
+
+        let ps = compiler.compile(
+            w!("shaders\\chromaticity.hlsl"),
+            w!("DrawPs"),
+            w!("ps_6_0"),
+            &defines,
+        )?;
+
+        let density_pso = device.create_graphics_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\chromaticity.hlsl"),
+                w!("DensityVs"),
+                w!("vs_6_0"),
+                &defines,
+            )?,
+            &ps,
+            BlendState::alpha(),
+            RasterizerState::no_cull(),
+            DepthStencilState::none(),
+            &[],
+            None,
+            None,
+            None,
+            None,
+        )?;
+
+        let guide_pso = device.create_graphics_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\chromaticity.hlsl"),
+                w!("GuideVs"),
+                w!("vs_6_0"),
+                &defines,
+            )?,
+            &ps,
+            BlendState::alpha(),
+            RasterizerState::no_cull(),
+            DepthStencilState::none(),
+            &[
+                InputElement::per_vertex(s!("POSITION"), DXGI_FORMAT_R32G32_FLOAT, 0),
+                InputElement::per_vertex(s!("COLOR"), DXGI_FORMAT_R32G32B32A32_FLOAT, 0),
+            ],
+            Some(D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE),
+            None,
+            None,
+            None,
+        )?;
+
+        let mut density_buf = Resource::new_buffer(
+            device,
+            4 * DENSITY_LEN as u64,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        descriptor_heap.create_srv_buffer(
+            &mut density_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            DENSITY_LEN,
+        );
+        descriptor_heap.create_uav_buffer(
+            &mut density_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            DENSITY_LEN,
+            None,
+            None,
+        );
+        descriptor_heap.create_uav_to_clear(&mut density_buf, DENSITY_LEN, 0);
+
+        let vertex_buf =
+            Resource::new_upload_buffer(device, (size_of::<Vertex>() * MAX_VERTICES) as _)?;
+
+        Ok(Self {
+            create_pso,
+            density_pso,
+            guide_pso,
+            density_buf,
+            vertex_buf,
+            guides: None,
+        })
+    }
+
+    fn create(&mut self, context: &mut Context, state: &State, diagram: Diagram) -> Result<()> {
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            diagram: u32,
+            range: f32,
+        }
+        const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
+
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.density_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS)]);
+
+        command_list.clear_unordered_access_view_uint(&self.density_buf, &[0; 4], &[]);
+
+        command_list.set_pipeline_state(&self.create_pso);
+
+        command_list.set_compute_constants(
+            RootParam::Constants,
+            NUM_CONSTS,
+            &Params {
+                rect: state.rect,
+                diagram: diagram as _,
+                range: diagram.range(),
+            } as *const _ as _,
+        );
+
+        command_list.set_compute_descriptor_table(RootParam::Uavs, self.density_buf.uav());
+
+        const THREADS: u32 = 8;
+        let (width, height) = rect_size(&state.rect);
+
+        command_list.dispatch(
+            div_round_up(width as _, THREADS),
+            div_round_up(height as _, THREADS),
+            1,
+        );
+
+        command_list.access(&[(
+            &self.density_buf,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        Ok(())
+    }
+
+    fn draw(&mut self, context: &mut Context, state: &State, diagram: Diagram) -> Result<()> {
+        #[repr(C)]
+        struct Params {
+            scale: [f32; 2],
+            inv_pixel_count: f32,
+            diagram: u32,
+            range: f32,
+        }
+        const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
+
+        let num_vertices = match self.guides {
+            Some((key, num_vertices)) if key == diagram => num_vertices,
+            _ => {
+                let mut vertices: Vec<Vertex> = diagram
+                    .guides()
+                    .into_iter()
+                    .map(|(position, color)| Vertex {
+                        position: position.to_array(),
+                        color: color.to_array(),
+                    })
+                    .collect();
+                vertices.truncate(MAX_VERTICES);
+                self.vertex_buf.write(vertices.as_ptr(), vertices.len())?;
+
+                self.guides = Some((diagram, vertices.len()));
+                vertices.len()
+            }
+        };
+
+        let command_list = &context.command_list;
+
+        // a square in the middle of the window
+        let (width, height) = rect_size(&state.rect);
+        let aspect = width as f32 / height as f32;
+        let params = Params {
+            scale: if aspect > 1.0 {
+                [1.0 / aspect, 1.0]
+            } else {
+                [1.0, aspect]
+            },
+            inv_pixel_count: 1.0 / ((width * height) as f32),
+            diagram: diagram as _,
+            range: diagram.range(),
+        };
+
+        command_list.set_pipeline_state(&self.density_pso);
+
+        command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+
+        command_list.set_graphics_constants(
+            RootParam::Constants,
+            NUM_CONSTS,
+            &params as *const _ as _,
+        );
+
+        command_list.set_graphics_descriptor_table(RootParam::Srvs, self.density_buf.srv());
+
+        command_list.draw(6 * DENSITY_LEN, 1);
+
+        command_list.set_pipeline_state(&self.guide_pso);
+
+        command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_LINELIST);
+
+        command_list.set_vertex_buffer(
+            0,
+            &[D3D12_VERTEX_BUFFER_VIEW {
+                BufferLocation: unsafe { self.vertex_buf.GetGPUVirtualAddress() },
+                SizeInBytes: (size_of::<Vertex>() * num_vertices) as _,
+                StrideInBytes: size_of::<Vertex>() as _,
+            }],
+        );
+
+        command_list.draw(num_vertices as _, 1);
+
+        Ok(())
+    }
+}
+
+impl AnalysisPass for ChromaticityPass {
+    fn name(&self) -> &'static str {
+        "chromaticity"
+    }
+
+    fn is_enabled(&self, state: &State) -> bool {
+        state.chromaticity_mode.is_enable()
+    }
+
+    fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        _capture: &Capture,
+        _slots: &Slots,
+    ) -> Result<()> {
+        let Some(diagram) = state.chromaticity_mode.diagram() else {
+            return Ok(());
+        };
+
+        self.create(context, state, diagram)?;
+        self.draw(context, state, diagram)?;
+
+        Ok(())
+    }
+}
@@ -10,9 +10,19 @@ use windows::{w, Win32::Graphics::Direct3D12::*};
 
 use super::RootParam;
 
+// bins of the finest quantization below 8 bits per channel
+const MAX_BINS: u32 = 128 * 128 * 128;
+
+// the count and the sums of the offsets in the cell, BinStride in color_cloud.hlsl
+const BIN_WORDS: u32 = 4;
+
+// the 256^3 counts stay beside the bins, the selection, the palette and the
+// color names read them at full precision whatever the cloud quantization
 pub struct ColorCloudCountPass {
     count_pso: ID3D12PipelineState,
+    bin_pso: ID3D12PipelineState,
     count_buf: Resource,
+    bin_buf: Resource,
 }
 
 impl ColorCloudCountPass {
@@ -40,6 +50,19 @@ impl ColorCloudCountPass {
             )?,
         )?;
 
+        let bin_pso = device.create_compute_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\color_cloud.hlsl"),
+                w!("BinCs"),
+                w!("cs_6_0"),
+                &[DxcDefine {
+                    Name: w!("BIN"),
+                    Value: w!(""),
+                }],
+            )?,
+        )?;
+
         let mut count_buf = Resource::new_buffer(
             &device,
             4 * 256 * 256 * 256,
@@ -65,9 +88,37 @@ impl ColorCloudCountPass {
 
         descriptor_heap.create_uav_to_clear(&mut count_buf, 256 * 256 * 256, 0);
 
+        let bin_words = BIN_WORDS * MAX_BINS;
+        let mut bin_buf = Resource::new_buffer(
+            device,
+            4 * bin_words as u64,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        descriptor_heap.create_srv_buffer(
+            &mut bin_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            bin_words,
+        );
+
+        descriptor_heap.create_uav_buffer(
+            &mut bin_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            bin_words,
+            None,
+            None,
+        );
+
+        descriptor_heap.create_uav_to_clear(&mut bin_buf, bin_words, 0);
+
         Ok(Self {
             count_pso,
+            bin_pso,
             count_buf,
+            bin_buf,
         })
     }
 
@@ -77,14 +128,22 @@ impl ColorCloudCountPass {
         state: &State,
         _capture: &Capture,
     ) -> Result<()> {
-        if state.color_cloud_mode.is_enable() {
+        if state.needs_color_count() {
             self.clear(context)?;
             self.count(context, state)?;
             self.transition(context)?;
         }
+
+        if state.needs_color_bins() {
+            self.bin(context, state)?;
+        }
         Ok(())
     }
 
+    pub fn count_buf(&self) -> &Resource {
+        &self.count_buf
+    }
+
     #[allow(unused)]
     pub fn dump(&self) -> Result<()> {
         unsafe { Ok(()) }
@@ -93,11 +152,7 @@ impl ColorCloudCountPass {
     fn clear(&mut self, context: &mut Context) -> Result<()> {
         let command_list = &context.command_list;
 
-        command_list.resource_barrier(&[ResourceBarrier::transition(
-            &self.count_buf,
-            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
-            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-        )]);
+        command_list.access(&[(&self.count_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS)]);
 
         command_list.clear_unordered_access_view_uint(&self.count_buf, &[0, 0, 0, 0], &[]);
 
@@ -134,12 +189,51 @@ impl ColorCloudCountPass {
         Ok(())
     }
 
+    fn bin(&mut self, context: &mut Context, state: &State) -> Result<()> {
+        #[repr(C)]
+        struct Params {
+            bits: u32,
+        }
+
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.bin_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS)]);
+
+        command_list.clear_unordered_access_view_uint(&self.bin_buf, &[0, 0, 0, 0], &[]);
+
+        command_list.set_pipeline_state(&self.bin_pso);
+
+        command_list.set_compute_constants(
+            RootParam::Constants,
+            size_of::<Params>() as u32 / 4,
+            &Params {
+                bits: state.cloud_bits,
+            } as *const _ as _,
+        );
+
+        command_list.set_compute_descriptor_table(RootParam::Srvs, self.count_buf.srv());
+        command_list.set_compute_descriptor_table(RootParam::Uavs, self.bin_buf.uav());
+
+        const THREAD: u32 = 8;
+        command_list.dispatch(256 / THREAD, 256 / THREAD, 256 / THREAD);
+
+        command_list.access(&[(
+            &self.bin_buf,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        // the cloud draws the bins in place of the colors
+        command_list.set_compute_descriptor_table(RootParam::Srvs, self.bin_buf.srv());
+        command_list.set_graphics_descriptor_table(RootParam::Srvs, self.bin_buf.srv());
+
+        Ok(())
+    }
+
     fn transition(&mut self, context: &mut Context) -> Result<()> {
         let command_list = &context.command_list;
 
-        command_list.resource_barrier(&[ResourceBarrier::transition(
+        command_list.access(&[(
             &self.count_buf,
-            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
             D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
         )]);
 
@@ -0,0 +1,404 @@
+use crate::color::gamut::position;
+use crate::graphics::{capture::Capture, context::*, swapchain::TRANSPARENCY_FORMATS};
+use crate::state::*;
+use glam::*;
+use std::mem::size_of;
+use windows::core::*;
+use windows::Win32::Graphics::Direct3D::Dxc::DxcDefine;
+use windows::Win32::Graphics::Direct3D::*;
+use windows::Win32::Graphics::Dxgi::Common::*;
+use windows::{w, Win32::Graphics::Direct3D12::*};
+
+use super::RootParam;
+
+const MAX_VERTICES: usize = 16384;
+
+const HULL_OPACITY: f32 = 0.5;
+const GAMUT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.35];
+const AXIS_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 0.8];
+const LABEL_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+
+// in units of the cloud radius
+const TICK_LENGTH: f32 = 0.06;
+const LABEL_OFFSET: f32 = 0.15;
+const LABEL_SIZE: f32 = 0.05;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct Vertex {
+    position: [f32; 3],
+    color: [f32; 4],
+}
+
+impl Vertex {
+    fn new(position: Vec3, color: [f32; 4]) -> Self {
+        Self {
+            position: position.to_array(),
+            color,
+        }
+    }
+}
+
+// sRGB hull, a wider gamut, the neutral axis and hue ticks
+struct Guides {
+    key: (ColorSpace, Option<Gamut>),
+    lines: Vec<Vertex>,
+    // letters placed next to a point of the cloud
+    labels: Vec<(Vec3, char)>,
+}
+
+pub struct ColorCloudGuidePass {
+    line_pso: ID3D12PipelineState,
+    label_pso: ID3D12PipelineState,
+
+    vertex_buf: Resource,
+    guides: Option<Guides>,
+    // of the last upload, the labels follow the lines
+    num_lines: usize,
+    num_vertices: usize,
+}
+
+impl ColorCloudGuidePass {
+    pub fn new(context: &mut Context, root_signature: &ID3D12RootSignature) -> Result<Self> {
+        let device = &context.device;
+        let compiler = &context.compiler;
+
+        let defines = [DxcDefine {
+            Name: w!("GUIDE"),
+            Value: w!(""),
+        }];
+
+        let vs = compiler.compile(
+            w!("shaders\\color_cloud.hlsl"),
+            w!("GuideVs"),
+            w!("vs_6_0"),
+            &defines,
+        )?;
+
+        let ps = compiler.compile(
+            w!("shaders\\color_cloud.hlsl"),
+            w!("GuidePs"),
+            w!("ps_6_0"),
+            &defines,
+        )?;
+
+        let transparent_ps = compiler.compile(
+            w!("shaders\\color_cloud.hlsl"),
+            w!("GuideTransparentPs"),
+            w!("ps_6_0"),
+            &defines,
+        )?;
+
+        let input_elements = [
+            InputElement::per_vertex(s!("POSITION"), DXGI_FORMAT_R32G32B32_FLOAT, 0),
+            InputElement::per_vertex(s!("COLOR"), DXGI_FORMAT_R32G32B32A32_FLOAT, 0),
+        ];
+
+        // hidden behind the opaque points, blended with the weighted ones
+        let line_pso = device.create_graphics_pipeline(
+            root_signature,
+            &vs,
+            &transparent_ps,
+            BlendState::weighted_blended(),
+            RasterizerState::default(),
+            DepthStencilState::depth_read_only(),
+            &input_elements,
+            Some(D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE),
+            Some(&TRANSPARENCY_FORMATS),
+            None,
+            None,
+        )?;
+
+        // always on top
+        let label_pso = device.create_graphics_pipeline(
+            root_signature,
+            &vs,
+            &ps,
+            BlendState::premultiplied(),
+            RasterizerState::default(),
+            DepthStencilState::none(),
+            &input_elements,
+            Some(D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE),
+            None,
+            None,
+            None,
+        )?;
+
+        let vertex_buf =
+            Resource::new_upload_buffer(device, (size_of::<Vertex>() * MAX_VERTICES) as _)?;
+
+        Ok(Self {
+            line_pso,
+            label_pso,
+            vertex_buf,
+            guides: None,
+            num_lines: 0,
+            num_vertices: 0,
+        })
+    }
+
+    // the lines between ColorCloudTransparencyPass::begin and resolve
+    pub fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        _capture: &Capture,
+    ) -> Result<()> {
+        self.num_lines = 0;
+        self.num_vertices = 0;
+
+        let Some(color_space) = state.color_cloud_mode.color_space() else {
+            return Ok(());
+        };
+
+        if !state.cloud_guides {
+            return Ok(());
+        }
+
+        let key = (color_space, state.cloud_gamut);
+        if self.guides.as_ref().map(|guides| guides.key) != Some(key) {
+            self.guides = Some(Guides::new(color_space, state.cloud_gamut));
+        }
+        let guides = self.guides.as_ref().unwrap();
+
+        // the letters face the screen wherever the camera is
+        let projection = state.camera.projection();
+        let mut vertices = guides.lines.clone();
+        let num_lines = vertices.len();
+
+        for &(anchor, letter) in &guides.labels {
+            let clip = projection * anchor.extend(1.0);
+            let center = clip.xy() / clip.w;
+
+            for &[x0, y0, x1, y1] in glyph(letter) {
+                for (x, y) in [(x0, y0), (x1, y1)] {
+                    let offset = LABEL_SIZE * Vec2::new(x - 0.4, y - 0.5);
+                    vertices.push(Vertex::new((center + offset).extend(-1.0), LABEL_COLOR));
+                }
+            }
+        }
+
+        vertices.truncate(MAX_VERTICES);
+        self.vertex_buf.write(vertices.as_ptr(), vertices.len())?;
+
+        self.num_lines = num_lines.min(vertices.len());
+        self.num_vertices = vertices.len();
+
+        self.draw(
+            context,
+            &self.line_pso,
+            projection,
+            scale(state),
+            0,
+            self.num_lines,
+        );
+
+        Ok(())
+    }
+
+    // after the resolve, over everything
+    pub fn draw_labels(&self, context: &mut Context, state: &State) {
+        self.draw(
+            context,
+            &self.label_pso,
+            Mat4::IDENTITY,
+            scale(state),
+            self.num_lines,
+            self.num_vertices - self.num_lines,
+        );
+    }
+
+    fn draw(
+        &self,
+        context: &mut Context,
+        pso: &ID3D12PipelineState,
+        projection: Mat4,
+        scale: Vec2,
+        first: usize,
+        count: usize,
+    ) {
+        #[repr(C)]
+        struct Params {
+            projection: Mat4,
+            scale: Vec2,
+        }
+
+        if count == 0 {
+            return;
+        }
+
+        let command_list = &context.command_list;
+        command_list.set_pipeline_state(pso);
+
+        command_list.set_graphics_constants(
+            RootParam::Constants,
+            size_of::<Params>() as u32 / 4,
+            &Params { projection, scale } as *const _ as _,
+        );
+
+        command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_LINELIST);
+
+        command_list.set_vertex_buffer(
+            0,
+            &[D3D12_VERTEX_BUFFER_VIEW {
+                BufferLocation: unsafe { self.vertex_buf.GetGPUVirtualAddress() }
+                    + (size_of::<Vertex>() * first) as u64,
+                SizeInBytes: (size_of::<Vertex>() * count) as _,
+                StrideInBytes: size_of::<Vertex>() as _,
+            }],
+        );
+
+        command_list.draw(count as _, 1);
+    }
+}
+
+fn scale(state: &State) -> Vec2 {
+    let (width, height) = rect_size(&state.rect);
+    let aspect = width as f32 / height as f32;
+    if aspect > 1.0 {
+        Vec2::new(1.0 / aspect, 1.0)
+    } else {
+        Vec2::new(1.0, 1.0 * aspect)
+    }
+}
+
+impl Guides {
+    fn new(color_space: ColorSpace, gamut: Option<Gamut>) -> Self {
+        let mut lines: Vec<Vertex> = Gamut::Srgb
+            .hull(color_space)
+            .into_iter()
+            .map(|(position, color)| Vertex::new(position, color.extend(HULL_OPACITY).into()))
+            .collect();
+
+        if let Some(gamut) = gamut {
+            lines.extend(
+                gamut
+                    .hull(color_space)
+                    .into_iter()
+                    .map(|(position, _)| Vertex::new(position, GAMUT_COLOR)),
+            );
+        }
+
+        let black = position(color_space, Vec3::ZERO);
+        let white = position(color_space, Vec3::ONE);
+        lines.push(Vertex::new(black, AXIS_COLOR));
+        lines.push(Vertex::new(white, AXIS_COLOR));
+
+        let mut labels = vec![];
+
+        match color_space {
+            ColorSpace::Rgb => {
+                for (rgb, letter) in [(Vec3::X, 'R'), (Vec3::Y, 'G'), (Vec3::Z, 'B')] {
+                    let corner = position(color_space, rgb);
+                    labels.push((corner + LABEL_OFFSET * (corner - black).normalize(), letter));
+                }
+            }
+            _ => {
+                let neutral = match color_space {
+                    ColorSpace::Hsv => 'V',
+                    ColorSpace::Hsl => 'L',
+                    _ => 'Y',
+                };
+                labels.push((white + LABEL_OFFSET * Vec3::Y, neutral));
+
+                // every 30 degrees, the primaries and secondaries named
+                for i in 0..12 {
+                    let point = position(color_space, hue_rgb(i as f32 / 12.0));
+                    let outward = (point * Vec3::new(1.0, 0.0, 1.0)).normalize();
+
+                    lines.push(Vertex::new(point, AXIS_COLOR));
+                    lines.push(Vertex::new(point + TICK_LENGTH * outward, AXIS_COLOR));
+
+                    if i % 2 == 0 {
+                        let letter = ['R', 'Y', 'G', 'C', 'B', 'M'][i / 2];
+                        labels.push((point + LABEL_OFFSET * outward, letter));
+                    }
+                }
+            }
+        }
+
+        if color_space == ColorSpace::Yuv {
+            // chroma axes through the middle grey, as YuvToPosition places u and v
+            const S: f32 = 2.0 * -0.5;
+            const C: f32 = 2.0 * 0.114572;
+            let u = Vec3::new(-C, 0.0, -S);
+            let v = Vec3::new(-S, 0.0, C);
+
+            for axis in [u, v] {
+                lines.push(Vertex::new(-axis, AXIS_COLOR));
+                lines.push(Vertex::new(axis, AXIS_COLOR));
+            }
+            labels.push(((1.0 + LABEL_OFFSET) * u, 'U'));
+            labels.push(((1.0 + LABEL_OFFSET) * v, 'V'));
+        }
+
+        Self {
+            key: (color_space, gamut),
+            lines,
+            labels,
+        }
+    }
+}
+
+// full saturation and value
+fn hue_rgb(hue: f32) -> Vec3 {
+    let f = |n: f32| {
+        let k = (n + 6.0 * hue) % 6.0;
+        1.0 - k.min(4.0 - k).clamp(0.0, 1.0)
+    };
+    Vec3::new(f(5.0), f(3.0), f(1.0))
+}
+
+// strokes x0, y0, x1, y1 in a 0.8 x 1 box, for the letters of the guides
+fn glyph(letter: char) -> &'static [[f32; 4]] {
+    match letter {
+        'R' => &[
+            [0.0, 0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.8, 1.0],
+            [0.8, 1.0, 0.8, 0.5],
+            [0.8, 0.5, 0.0, 0.5],
+            [0.3, 0.5, 0.8, 0.0],
+        ],
+        'G' => &[
+            [0.8, 1.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 0.8, 0.0],
+            [0.8, 0.0, 0.8, 0.5],
+            [0.8, 0.5, 0.4, 0.5],
+        ],
+        'B' => &[
+            [0.0, 0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.6, 1.0],
+            [0.6, 1.0, 0.8, 0.75],
+            [0.8, 0.75, 0.6, 0.5],
+            [0.6, 0.5, 0.0, 0.5],
+            [0.6, 0.5, 0.8, 0.25],
+            [0.8, 0.25, 0.6, 0.0],
+            [0.6, 0.0, 0.0, 0.0],
+        ],
+        'C' => &[
+            [0.8, 1.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 0.8, 0.0],
+        ],
+        'M' => &[
+            [0.0, 0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.4, 0.5],
+            [0.4, 0.5, 0.8, 1.0],
+            [0.8, 1.0, 0.8, 0.0],
+        ],
+        'Y' => &[
+            [0.0, 1.0, 0.4, 0.5],
+            [0.8, 1.0, 0.4, 0.5],
+            [0.4, 0.5, 0.4, 0.0],
+        ],
+        'V' => &[[0.0, 1.0, 0.4, 0.0], [0.4, 0.0, 0.8, 1.0]],
+        'L' => &[[0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 0.8, 0.0]],
+        'U' => &[
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 0.8, 0.0],
+            [0.8, 0.0, 0.8, 1.0],
+        ],
+        _ => &[],
+    }
+}
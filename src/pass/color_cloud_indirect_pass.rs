@@ -1,4 +1,4 @@
-use crate::graphics::{capture::Capture, context::*};
+use crate::graphics::{capture::Capture, context::*, swapchain::TRANSPARENCY_FORMATS};
 use crate::state::*;
 use glam::*;
 use std::mem::size_of;
@@ -19,6 +19,7 @@ const COMPACT_BLOCK: u32 = 4;
 const COMPACT_THREAD: u32 = 8;
 const GRID: u32 = 256 / (COMPACT_BLOCK * COMPACT_THREAD);
 const GRID3: u32 = GRID * GRID * GRID;
+const GROUP_BINS: u32 = 256 * 256 * 256 / GRID3;
 
 const COMMAND_SIZE: u32 = std::mem::size_of::<IndirectCommand>() as _;
 const COUNT_OFFSET: u32 = 4096 * (((COMMAND_SIZE * GRID3) + 4096 - 1) / 4096);
@@ -27,6 +28,7 @@ pub struct ColorCloudIndirectPass {
     command_signature: ID3D12CommandSignature,
     compact_pso: ID3D12PipelineState,
     draw_pso: ID3D12PipelineState,
+    blend_pso: ID3D12PipelineState,
 
     packed_buf: Resource,
     command_buf: Resource,
@@ -60,42 +62,74 @@ impl ColorCloudIndirectPass {
             )?,
         )?;
 
-        let draw_pso = device.create_graphics_pipeline(
-            &root_signature,
-            &compiler.compile(
-                w!("shaders\\color_cloud.hlsl"),
-                w!("DrawVs"),
-                w!("vs_6_0"),
-                &[
-                    DxcDefine {
-                        Name: w!("DRAW"),
-                        Value: w!(""),
-                    },
-                    DxcDefine {
-                        Name: w!("INDIRECT"),
-                        Value: w!(""),
-                    },
-                ],
-            )?,
-            &compiler.compile(
-                w!("shaders\\color_cloud.hlsl"),
-                w!("DrawPs"),
-                w!("ps_6_0"),
-                &[DxcDefine {
+        let vs = compiler.compile(
+            w!("shaders\\color_cloud.hlsl"),
+            w!("DrawVs"),
+            w!("vs_6_0"),
+            &[
+                DxcDefine {
                     Name: w!("DRAW"),
                     Value: w!(""),
-                }],
-            )?,
+                },
+                DxcDefine {
+                    Name: w!("INDIRECT"),
+                    Value: w!(""),
+                },
+            ],
+        )?;
+
+        let ps = compiler.compile(
+            w!("shaders\\color_cloud.hlsl"),
+            w!("DrawPs"),
+            w!("ps_6_0"),
+            &[DxcDefine {
+                Name: w!("DRAW"),
+                Value: w!(""),
+            }],
+        )?;
+
+        let transparent_ps = compiler.compile(
+            w!("shaders\\color_cloud.hlsl"),
+            w!("DrawTransparentPs"),
+            w!("ps_6_0"),
+            &[DxcDefine {
+                Name: w!("DRAW"),
+                Value: w!(""),
+            }],
+        )?;
+
+        let input_elements = [InputElement::per_instance(
+            s!("COLOR_INDEX"),
+            DXGI_FORMAT_R32_UINT,
+            0,
+        )];
+
+        let draw_pso = device.create_graphics_pipeline(
+            &root_signature,
+            &vs,
+            &ps,
             BlendState::none(),
             RasterizerState::default(),
             DepthStencilState::depth(),
-            &[InputElement::per_instance(
-                s!("COLOR_INDEX"),
-                DXGI_FORMAT_R32_UINT,
-                0,
-            )],
+            &input_elements,
+            None,
+            None,
             None,
             None,
+        )?;
+
+        // count weighted opacity, points do not hide the ones behind them and
+        // are blended order independently
+        let blend_pso = device.create_graphics_pipeline(
+            root_signature,
+            &vs,
+            &transparent_ps,
+            BlendState::weighted_blended(),
+            RasterizerState::default(),
+            DepthStencilState::depth_read_only(),
+            &input_elements,
+            None,
+            Some(&TRANSPARENCY_FORMATS),
             None,
             None,
         )?;
@@ -138,6 +172,7 @@ impl ColorCloudIndirectPass {
             command_signature,
             compact_pso,
             draw_pso,
+            blend_pso,
             packed_buf,
             command_buf,
         })
@@ -151,7 +186,7 @@ impl ColorCloudIndirectPass {
     ) -> Result<()> {
         if state.color_cloud_mode.is_enable() {
             self.clear(context)?;
-            self.pack(context)?;
+            self.pack(context, state)?;
             self.draw(context, state)?;
         }
         Ok(())
@@ -160,24 +195,35 @@ impl ColorCloudIndirectPass {
     fn clear(&mut self, context: &mut Context) -> Result<()> {
         let command_list = &context.command_list;
 
-        command_list.resource_barrier(&[ResourceBarrier::transition(
-            &self.command_buf,
-            D3D12_RESOURCE_STATE_INDIRECT_ARGUMENT,
-            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-        )]);
+        command_list.access(&[(&self.command_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS)]);
 
         command_list.clear_unordered_access_view_uint(&self.command_buf, &[0, 0, 0, 0], &[]);
 
         Ok(())
     }
 
-    fn pack(&self, context: &mut Context) -> Result<()> {
+    fn pack(&self, context: &mut Context, state: &State) -> Result<()> {
+        #[repr(C)]
+        struct Params {
+            bits: u32,
+        }
+
         let command_list = &context.command_list;
         command_list.set_pipeline_state(&self.compact_pso);
 
+        command_list.set_compute_constants(
+            RootParam::Constants,
+            size_of::<Params>() as u32 / 4,
+            &Params {
+                bits: state.cloud_bits,
+            } as *const _ as _,
+        );
+
         command_list.set_compute_descriptor_table(RootParam::Uavs, self.packed_buf.uav());
 
-        command_list.dispatch(GRID, GRID, GRID);
+        // fewer groups with coarser bins
+        let num_bins = 1 << (3 * state.cloud_bits);
+        command_list.dispatch(div_round_up(num_bins, GROUP_BINS), 1, 1);
 
         Ok(())
     }
@@ -186,16 +232,27 @@ impl ColorCloudIndirectPass {
         #[repr(C)]
         struct Params {
             projection: Mat4,
+            clip: Vec4,
             scale: Vec2,
             num_pixels: u32,
             color_space: u32,
+            size_mapping: u32,
+            min_size: f32,
+            max_size: f32,
+            size_scale: f32,
+            weighted_opacity: u32,
+            bits: u32,
+            clip_mode: u32,
         }
 
         let (width, height) = rect_size(&state.rect);
         let aspect = width as f32 / height as f32;
 
+        let (clip_mode, clip) = state.shader_clip();
+
         let params = Params {
-            projection: Mat4::from_quat(state.rotation).inverse(),
+            projection: state.camera.projection(),
+            clip,
             scale: if aspect > 1.0 {
                 Vec2::new(1.0 / aspect, 1.0)
             } else {
@@ -203,16 +260,23 @@ impl ColorCloudIndirectPass {
             },
             num_pixels: (width * height) as _,
             color_space: state.color_cloud_mode.color_space().unwrap() as _,
+            size_mapping: state.point_size as _,
+            min_size: state.point_min_size,
+            max_size: state.point_max_size,
+            size_scale: state.point_scale * state.camera.zoom,
+            weighted_opacity: state.point_opacity as _,
+            bits: state.cloud_bits,
+            clip_mode,
         };
 
         let command_list = &context.command_list;
-        command_list.set_pipeline_state(&self.draw_pso);
+        command_list.set_pipeline_state(if state.point_opacity {
+            &self.blend_pso
+        } else {
+            &self.draw_pso
+        });
 
-        command_list.resource_barrier(&[ResourceBarrier::transition(
-            &self.command_buf,
-            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-            D3D12_RESOURCE_STATE_INDIRECT_ARGUMENT,
-        )]);
+        command_list.access(&[(&self.command_buf, D3D12_RESOURCE_STATE_INDIRECT_ARGUMENT)]);
 
         command_list.set_graphics_constants(
             RootParam::Constants,
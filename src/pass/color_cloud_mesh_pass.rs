@@ -1,4 +1,4 @@
-use crate::graphics::{capture::Capture, context::*};
+use crate::graphics::{capture::Capture, context::*, swapchain::TRANSPARENCY_FORMATS};
 use crate::state::*;
 use glam::*;
 use std::mem::size_of;
@@ -10,6 +10,7 @@ use super::RootParam;
 
 pub struct ColorCloudMeshPass {
     draw_pso: ID3D12PipelineState,
+    blend_pso: ID3D12PipelineState,
 }
 
 impl ColorCloudMeshPass {
@@ -17,53 +18,75 @@ impl ColorCloudMeshPass {
         let device = &context.device;
         let compiler = &context.compiler;
 
+        let r#as = compiler.compile(
+            w!("shaders\\color_cloud.hlsl"),
+            w!("DrawAs"),
+            w!("as_6_5"),
+            &[
+                DxcDefine {
+                    Name: w!("DRAW"),
+                    Value: w!(""),
+                },
+                DxcDefine {
+                    Name: w!("MESH"),
+                    Value: w!(""),
+                },
+            ],
+        )?;
+
+        let ms = compiler.compile(
+            w!("shaders\\color_cloud.hlsl"),
+            w!("DrawMs"),
+            w!("ms_6_5"),
+            &[
+                DxcDefine {
+                    Name: w!("DRAW"),
+                    Value: w!(""),
+                },
+                DxcDefine {
+                    Name: w!("MESH"),
+                    Value: w!(""),
+                },
+            ],
+        )?;
+
+        let ps = compiler.compile(
+            w!("shaders\\color_cloud.hlsl"),
+            w!("DrawPs"),
+            w!("ps_6_0"),
+            &[
+                DxcDefine {
+                    Name: w!("DRAW"),
+                    Value: w!(""),
+                },
+                DxcDefine {
+                    Name: w!("PIXEL"),
+                    Value: w!(""),
+                },
+            ],
+        )?;
+
+        let transparent_ps = compiler.compile(
+            w!("shaders\\color_cloud.hlsl"),
+            w!("DrawTransparentPs"),
+            w!("ps_6_0"),
+            &[
+                DxcDefine {
+                    Name: w!("DRAW"),
+                    Value: w!(""),
+                },
+                DxcDefine {
+                    Name: w!("PIXEL"),
+                    Value: w!(""),
+                },
+            ],
+        )?;
+
         let draw_pso = device.create_mesh_shader_pipeline(
             &root_signature,
-            Some(&compiler.compile(
-                w!("shaders\\color_cloud.hlsl"),
-                w!("DrawAs"),
-                w!("as_6_5"),
-                &[
-                    DxcDefine {
-                        Name: w!("DRAW"),
-                        Value: w!(""),
-                    },
-                    DxcDefine {
-                        Name: w!("MESH"),
-                        Value: w!(""),
-                    },
-                ],
-            )?),
-            &compiler.compile(
-                w!("shaders\\color_cloud.hlsl"),
-                w!("DrawMs"),
-                w!("ms_6_5"),
-                &[
-                    DxcDefine {
-                        Name: w!("DRAW"),
-                        Value: w!(""),
-                    },
-                    DxcDefine {
-                        Name: w!("MESH"),
-                        Value: w!(""),
-                    },
-                ],
-            )?,
-            &compiler.compile(
-                w!("shaders\\color_cloud.hlsl"),
-                w!("DrawPs"),
-                w!("ps_6_0"),
-                &[
-                    DxcDefine {
-                        Name: w!("DRAW"),
-                        Value: w!(""),
-                    },
-                    DxcDefine {
-                        Name: w!("PIXEL"),
-                        Value: w!(""),
-                    },
-                ],
-            )?,
+            Some(&r#as),
+            &ms,
+            &ps,
             BlendState::none(),
             RasterizerState::default(),
             DepthStencilState::depth(),
@@ -72,7 +95,25 @@ impl ColorCloudMeshPass {
             None,
         )?;
 
-        Ok(Self { draw_pso })
+        // count weighted opacity, points do not hide the ones behind them and
+        // are blended order independently
+        let blend_pso = device.create_mesh_shader_pipeline(
+            root_signature,
+            Some(&r#as),
+            &ms,
+            &transparent_ps,
+            BlendState::weighted_blended(),
+            RasterizerState::default(),
+            DepthStencilState::depth_read_only(),
+            D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            Some(&TRANSPARENCY_FORMATS),
+            None,
+        )?;
+
+        Ok(Self {
+            draw_pso,
+            blend_pso,
+        })
     }
 
     pub fn process(
@@ -91,16 +132,27 @@ impl ColorCloudMeshPass {
         #[repr(C)]
         struct Params {
             projection: Mat4,
+            clip: Vec4,
             scale: Vec2,
             num_pixels: u32,
             color_space: u32,
+            size_mapping: u32,
+            min_size: f32,
+            max_size: f32,
+            size_scale: f32,
+            weighted_opacity: u32,
+            bits: u32,
+            clip_mode: u32,
         }
 
         let (width, height) = rect_size(&state.rect);
         let aspect = width as f32 / height as f32;
 
+        let (clip_mode, clip) = state.shader_clip();
+
         let params = Params {
-            projection: Mat4::from_quat(state.rotation).inverse(),
+            projection: state.camera.projection(),
+            clip,
             scale: if aspect > 1.0 {
                 Vec2::new(1.0 / aspect, 1.0)
             } else {
@@ -108,11 +160,22 @@ impl ColorCloudMeshPass {
             },
             num_pixels: (width * height) as _,
             color_space: state.color_cloud_mode.color_space().unwrap() as _,
+            size_mapping: state.point_size as _,
+            min_size: state.point_min_size,
+            max_size: state.point_max_size,
+            size_scale: state.point_scale * state.camera.zoom,
+            weighted_opacity: state.point_opacity as _,
+            bits: state.cloud_bits,
+            clip_mode,
         };
 
         let command_list = &context.command_list;
 
-        command_list.set_pipeline_state(&self.draw_pso);
+        command_list.set_pipeline_state(if state.point_opacity {
+            &self.blend_pso
+        } else {
+            &self.draw_pso
+        });
 
         command_list.set_graphics_constants(
             RootParam::Constants,
@@ -121,7 +184,8 @@ impl ColorCloudMeshPass {
         );
 
         const GRID: u32 = 8;
-        command_list.dispatch_mesh(256 / GRID, 256 / GRID, 256 / GRID);
+        let dim = (1 << state.cloud_bits) / GRID;
+        command_list.dispatch_mesh(dim, dim, dim);
 
         Ok(())
     }
@@ -333,7 +397,7 @@ impl ColorCloudMeshPass {
 //         let aspect = width as f32 / height as f32;
 
 //         let params = Params {
-//             projection: Mat4::from_quat(state.rotation).inverse(),
+//             projection: state.camera.projection(),
 //             scale: if aspect > 1.0 {
 //                 Vec2::new(1.0 / aspect, 1.0)
 //             } else {
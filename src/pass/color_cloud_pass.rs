@@ -3,14 +3,19 @@ use crate::state::*;
 use windows::core::*;
 use windows::Win32::Graphics::Direct3D12::ID3D12RootSignature;
 
+use super::analysis_pass::*;
 use super::color_cloud_count_pass::ColorCloudCountPass;
+use super::color_cloud_guide_pass::ColorCloudGuidePass;
 use super::color_cloud_indirect_pass::ColorCloudIndirectPass;
 use super::color_cloud_mesh_pass::ColorCloudMeshPass;
+use super::color_cloud_transparency_pass::ColorCloudTransparencyPass;
 
 pub struct ColorCloudPass {
     count_pass: ColorCloudCountPass,
     mesh_pass: Option<ColorCloudMeshPass>,
     indirect_pass: Option<ColorCloudIndirectPass>,
+    guide_pass: ColorCloudGuidePass,
+    transparency_pass: ColorCloudTransparencyPass,
 }
 
 impl ColorCloudPass {
@@ -24,29 +29,77 @@ impl ColorCloudPass {
             None => Some(ColorCloudIndirectPass::new(context, root_signature)?),
         };
 
+        let guide_pass = ColorCloudGuidePass::new(context, root_signature)?;
+
+        let transparency_pass = ColorCloudTransparencyPass::new(context, root_signature)?;
+
         Ok(Self {
             count_pass,
             mesh_pass,
             indirect_pass,
+            guide_pass,
+            transparency_pass,
         })
     }
 
-    pub fn process(
+    fn draw_points(
         &mut self,
         context: &mut Context,
         state: &State,
         capture: &Capture,
     ) -> Result<()> {
-        if state.color_cloud_mode.is_enable() {
-            self.count_pass.process(context, state, capture)?;
+        if let Some(mesh_pass) = &mut self.mesh_pass {
+            mesh_pass.process(context, state, capture)?;
+        }
+
+        if let Some(indirect_pass) = &mut self.indirect_pass {
+            indirect_pass.process(context, state, capture)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl AnalysisPass for ColorCloudPass {
+    fn name(&self) -> &'static str {
+        "color cloud"
+    }
+
+    fn writes(&self) -> &'static [Slot] {
+        &[Slot::ColorCounts]
+    }
+
+    fn publish(&self, slots: &mut Slots) {
+        slots.insert(Slot::ColorCounts, *self.count_pass.count_buf().srv());
+    }
 
-            if let Some(mesh_pass) = &mut self.mesh_pass {
-                mesh_pass.process(context, state, capture)?;
+    fn is_enabled(&self, state: &State) -> bool {
+        state.needs_color_count()
+    }
+
+    fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        capture: &Capture,
+        _slots: &Slots,
+    ) -> Result<()> {
+        self.count_pass.process(context, state, capture)?;
+
+        if state.color_cloud_mode.is_enable() {
+            // the opaque points write the depth the guide lines are tested against
+            if !state.point_opacity {
+                self.draw_points(context, state, capture)?;
             }
 
-            if let Some(indirect_pass) = &mut self.indirect_pass {
-                indirect_pass.process(context, state, capture)?;
+            self.transparency_pass.begin(context);
+            if state.point_opacity {
+                self.draw_points(context, state, capture)?;
             }
+            self.guide_pass.process(context, state, capture)?;
+            self.transparency_pass.resolve(context);
+
+            self.guide_pass.draw_labels(context, state);
         }
 
         Ok(())
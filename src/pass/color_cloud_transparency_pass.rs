@@ -0,0 +1,92 @@
+use crate::graphics::context::*;
+use windows::core::*;
+use windows::Win32::Graphics::Direct3D::Dxc::DxcDefine;
+use windows::Win32::Graphics::Direct3D::*;
+use windows::{w, Win32::Graphics::Direct3D12::*};
+
+use super::RootParam;
+
+// weighted blended order independent transparency, what is drawn between begin
+// and resolve is accumulated in the swapchain sized targets and then composited
+// over the back buffer, whatever the order of the draws
+pub struct ColorCloudTransparencyPass {
+    resolve_pso: ID3D12PipelineState,
+}
+
+impl ColorCloudTransparencyPass {
+    pub fn new(context: &mut Context, root_signature: &ID3D12RootSignature) -> Result<Self> {
+        let device = &context.device;
+        let compiler = &context.compiler;
+
+        let defines = [DxcDefine {
+            Name: w!("RESOLVE"),
+            Value: w!(""),
+        }];
+
+        let resolve_pso = device.create_graphics_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\color_cloud.hlsl"),
+                w!("ResolveVs"),
+                w!("vs_6_0"),
+                &defines,
+            )?,
+            &compiler.compile(
+                w!("shaders\\color_cloud.hlsl"),
+                w!("ResolvePs"),
+                w!("ps_6_0"),
+                &defines,
+            )?,
+            BlendState::premultiplied(),
+            RasterizerState::no_cull(),
+            DepthStencilState::none(),
+            &[],
+            None,
+            None,
+            None,
+            None,
+        )?;
+
+        Ok(Self { resolve_pso })
+    }
+
+    pub fn begin(&self, context: &mut Context) {
+        let Some(resources) = &context.swapchain.resources else {
+            return;
+        };
+        let [accum, revealage] = resources.transparency();
+
+        let command_list = &context.command_list;
+        command_list.access(&[
+            (accum, D3D12_RESOURCE_STATE_RENDER_TARGET),
+            (revealage, D3D12_RESOURCE_STATE_RENDER_TARGET),
+        ]);
+
+        command_list.clear_render_target_view(accum.rtv(), &[0.0; 4]);
+        command_list.clear_render_target_view(revealage.rtv(), &[1.0; 4]);
+
+        command_list.set_render_targets(&[*accum.rtv(), *revealage.rtv()], resources.dsv());
+    }
+
+    // back to the back buffer with the transparent draws over it
+    pub fn resolve(&self, context: &mut Context) {
+        let Some(resources) = &context.swapchain.resources else {
+            return;
+        };
+        let [accum, revealage] = resources.transparency();
+
+        let command_list = &context.command_list;
+        command_list.access(&[
+            (accum, D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE),
+            (revealage, D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE),
+        ]);
+
+        command_list.set_render_target(resources.rtv(), resources.dsv());
+
+        command_list.set_pipeline_state(&self.resolve_pso);
+        // the revealage srv follows the accum srv
+        command_list.set_graphics_descriptor_table(RootParam::Srvs, accum.srv());
+        command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+        command_list.draw(3, 1);
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use windows::{
+    core::*,
+    w,
+    Win32::Graphics::{Direct3D12::*, Dxgi::Common::*},
+};
+
+use crate::{
+    color::naming::*,
+    graphics::{capture::Capture, *},
+    state::*,
+};
+
+use super::analysis_pass::*;
+use super::RootParam;
+
+pub struct ColorNamePass {
+    pso: ID3D12PipelineState,
+    name_count_buf: Resource,
+    lut_buf: Resource,
+    upload_buf: Resource,
+    readback_buf: Resource,
+    dictionary: Option<Arc<Dictionary>>,
+    dictionaries: HashMap<DictionaryKind, Arc<Dictionary>>,
+}
+
+impl ColorNamePass {
+    pub fn new(context: &mut Context, root_signature: &ID3D12RootSignature) -> Result<Self> {
+        let device = &context.device;
+        let compiler = &context.compiler;
+        let descriptor_heap = &mut context.descriptor_heap;
+
+        let pso = device.create_compute_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\color_name.hlsl"),
+                w!("CountNamesCs"),
+                w!("cs_6_0"),
+                &[],
+            )?,
+        )?;
+
+        let mut name_count_buf = Resource::new_buffer(
+            device,
+            4 * MAX_NAMES as u64,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        let mut lut_buf = Resource::new_buffer(
+            device,
+            4 * LUT_SIZE as u64,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        let upload_buf = Resource::new_upload_buffer(device, 4 * LUT_SIZE as u64)?;
+        let readback_buf = Resource::new_staging_buffer(device, 4 * MAX_NAMES as u64)?;
+
+        // u0 and u1 must be adjacent in the heap
+        descriptor_heap.create_uav_buffer(
+            &mut name_count_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            MAX_NAMES as _,
+            None,
+            None,
+        );
+
+        descriptor_heap.create_uav_buffer(
+            &mut lut_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            LUT_SIZE as _,
+            None,
+            None,
+        );
+
+        descriptor_heap.create_uav_to_clear(&mut name_count_buf, MAX_NAMES as _, 0);
+
+        Ok(Self {
+            pso,
+            name_count_buf,
+            lut_buf,
+            upload_buf,
+            readback_buf,
+            dictionary: None,
+            dictionaries: HashMap::new(),
+        })
+    }
+
+    // pixel count of each name of the dictionary, of the last processed frame
+    pub fn counts(&self) -> Result<Vec<u32>> {
+        self.readback_buf.read(MAX_NAMES)
+    }
+
+    // the built-in dictionaries are built once, on first use
+    fn dictionary(&mut self, state: &State) -> Option<Arc<Dictionary>> {
+        match state.naming_mode.dictionary()? {
+            DictionaryKind::Custom => state.custom_dictionary.clone(),
+            kind => {
+                let dictionary = self.dictionaries.entry(kind).or_insert_with(|| {
+                    Arc::new(match kind {
+                        DictionaryKind::IsccNbs => Dictionary::iscc_nbs(),
+                        _ => Dictionary::css(),
+                    })
+                });
+                Some(Arc::clone(dictionary))
+            }
+        }
+    }
+
+    fn upload(&mut self, context: &mut Context, dictionary: &Arc<Dictionary>) -> Result<()> {
+        if let Some(uploaded) = &self.dictionary {
+            if Arc::ptr_eq(uploaded, dictionary) {
+                return Ok(());
+            }
+        }
+
+        let lut = dictionary.lut();
+        self.upload_buf.write(lut.as_ptr(), lut.len())?;
+
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.lut_buf, D3D12_RESOURCE_STATE_COPY_DEST)]);
+
+        command_list.copy_buffer_region(&self.lut_buf, 0, &self.upload_buf, 0, 4 * LUT_SIZE as u64);
+
+        command_list.access(&[(&self.lut_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS)]);
+
+        self.dictionary = Some(Arc::clone(dictionary));
+
+        Ok(())
+    }
+
+    fn clear(&mut self, context: &mut Context) -> Result<()> {
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.name_count_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS)]);
+
+        command_list.clear_unordered_access_view_uint(&self.name_count_buf, &[0; 4], &[]);
+
+        Ok(())
+    }
+
+    fn count(&mut self, context: &mut Context, count_buf: &Descriptor) -> Result<()> {
+        let command_list = &context.command_list;
+
+        command_list.set_pipeline_state(&self.pso);
+
+        command_list.set_compute_descriptor_table(RootParam::Srvs, count_buf);
+        command_list.set_compute_descriptor_table(RootParam::Uavs, self.name_count_buf.uav());
+
+        const THREADS: u32 = 8;
+        command_list.dispatch(256 / THREADS, 256 / THREADS, 256 / THREADS);
+
+        Ok(())
+    }
+
+    fn transition(&mut self, context: &mut Context) -> Result<()> {
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.name_count_buf, D3D12_RESOURCE_STATE_COPY_SOURCE)]);
+
+        command_list.copy_buffer_region(
+            &self.readback_buf,
+            0,
+            &self.name_count_buf,
+            0,
+            4 * MAX_NAMES as u64,
+        );
+
+        command_list.access(&[(
+            &self.name_count_buf,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        Ok(())
+    }
+}
+
+impl AnalysisPass for ColorNamePass {
+    fn name(&self) -> &'static str {
+        "color name"
+    }
+
+    fn reads(&self) -> &'static [Slot] {
+        &[Slot::ColorCounts]
+    }
+
+    fn is_enabled(&self, state: &State) -> bool {
+        match state.naming_mode.dictionary() {
+            Some(DictionaryKind::Custom) => state.custom_dictionary.is_some(),
+            kind => kind.is_some(),
+        }
+    }
+
+    fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        _capture: &Capture,
+        slots: &Slots,
+    ) -> Result<()> {
+        let Some(dictionary) = self.dictionary(state) else {
+            return Ok(());
+        };
+
+        self.upload(context, &dictionary)?;
+        self.clear(context)?;
+        self.count(context, slots.get(Slot::ColorCounts))?;
+        self.transition(context)?;
+        Ok(())
+    }
+
+    // after the probe, whose color is named too
+    fn finish(&mut self, state: &State, shared: &RefState) -> Result<()> {
+        let dictionary = match &self.dictionary {
+            Some(dictionary) if self.is_enabled(state) => dictionary,
+            _ => {
+                shared.set_naming(None);
+                return Ok(());
+            }
+        };
+
+        let mut naming = dictionary.breakdown(&self.counts()?);
+        naming.probe = shared
+            .probe()
+            .and_then(|probe| probe.color)
+            .and_then(|color| dictionary.nearest(color))
+            .map(|name| name.name.clone());
+        shared.set_naming(Some(Arc::new(naming)));
+
+        Ok(())
+    }
+}
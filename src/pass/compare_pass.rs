@@ -0,0 +1,177 @@
+use std::mem::size_of;
+use std::sync::Arc;
+
+use windows::{
+    core::*,
+    w,
+    Win32::{
+        Foundation::RECT,
+        Graphics::{Direct3D::*, Direct3D12::*, Dxgi::Common::*},
+    },
+};
+
+use crate::{
+    analysis::compare::MAX_HEAT_DELTA_E,
+    graphics::{capture::Capture, *},
+    render::Image,
+    state::*,
+};
+
+use super::analysis_pass::*;
+use super::RootParam;
+
+// the per pixel CIEDE2000 of the frame and the compared image, in place of the view
+pub struct ComparePass {
+    pso: ID3D12PipelineState,
+
+    upload_buf: Resource,
+    max_width: u32,
+    max_height: u32,
+    // the image in the buffer and the part of it which fits the capture
+    uploaded: Option<(Arc<Image>, (u32, u32))>,
+}
+
+impl ComparePass {
+    pub fn new(
+        context: &mut Context,
+        root_signature: &ID3D12RootSignature,
+        width: u32,
+        height: u32,
+    ) -> Result<Self> {
+        let device = &context.device;
+        let compiler = &context.compiler;
+        let descriptor_heap = &mut context.descriptor_heap;
+
+        let pso = device.create_graphics_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\compare.hlsl"),
+                w!("CompareVs"),
+                w!("vs_6_0"),
+                &[],
+            )?,
+            &compiler.compile(
+                w!("shaders\\compare.hlsl"),
+                w!("ComparePs"),
+                w!("ps_6_0"),
+                &[],
+            )?,
+            BlendState::none(),
+            RasterizerState::no_cull(),
+            DepthStencilState::none(),
+            &[],
+            None,
+            None,
+            None,
+            None,
+        )?;
+
+        let max_pixels = width as usize * height as usize;
+        let mut upload_buf = Resource::new_upload_buffer(device, 4 * max_pixels as u64)?;
+        descriptor_heap.create_srv_buffer(
+            &mut upload_buf,
+            Some(DXGI_FORMAT_R8G8B8A8_UNORM),
+            None,
+            max_pixels as _,
+        );
+
+        Ok(Self {
+            pso,
+            upload_buf,
+            max_width: width,
+            max_height: height,
+            uploaded: None,
+        })
+    }
+
+    // the part within the capture, the view area never exceeds it
+    fn upload(&mut self, image: &Arc<Image>) -> Result<(u32, u32)> {
+        let width = image.width.min(self.max_width);
+        let height = image.height.min(self.max_height);
+
+        let pixels = (0..height as usize)
+            .flat_map(|y| {
+                let row = y * image.width as usize;
+                image.pixels[row..row + width as usize]
+                    .iter()
+                    .map(|&[r, g, b]| [r, g, b, 0xff])
+            })
+            .collect::<Vec<_>>();
+        self.upload_buf.write(pixels.as_ptr(), pixels.len())?;
+
+        self.uploaded = Some((Arc::clone(image), (width, height)));
+        Ok((width, height))
+    }
+
+    fn draw(&self, context: &mut Context, state: &State, size: (u32, u32)) -> Result<()> {
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            size: [u32; 2],
+            max_delta_e: f32,
+        }
+        const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
+
+        let command_list = &context.command_list;
+
+        command_list.set_pipeline_state(&self.pso);
+
+        command_list.set_graphics_constants(
+            RootParam::Constants,
+            NUM_CONSTS,
+            &Params {
+                rect: state.rect,
+                size: [size.0, size.1],
+                max_delta_e: MAX_HEAT_DELTA_E,
+            } as *const _ as _,
+        );
+
+        command_list.set_graphics_descriptor_table(RootParam::Srvs, self.upload_buf.srv());
+
+        command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+
+        command_list.draw(6, 1);
+
+        Ok(())
+    }
+}
+
+impl AnalysisPass for ComparePass {
+    fn name(&self) -> &'static str {
+        "compare"
+    }
+
+    fn input(&self) -> Input {
+        Input::View
+    }
+
+    fn is_enabled(&self, state: &State) -> bool {
+        state.view_mode == ViewMode::Compare && state.compare.is_some()
+    }
+
+    fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        _capture: &Capture,
+        _slots: &Slots,
+    ) -> Result<()> {
+        let Some(image) = &state.compare else {
+            return Ok(());
+        };
+
+        let size = match &self.uploaded {
+            Some((uploaded, size)) if Arc::ptr_eq(uploaded, image) => *size,
+            _ => self.upload(image)?,
+        };
+
+        self.draw(context, state, size)
+    }
+
+    fn finish(&mut self, state: &State, _shared: &RefState) -> Result<()> {
+        if state.compare.is_none() {
+            self.uploaded = None;
+        }
+        Ok(())
+    }
+}
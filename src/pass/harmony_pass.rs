@@ -0,0 +1,124 @@
+use std::mem::size_of;
+
+use windows::{
+    core::*,
+    w,
+    Win32::Graphics::{Direct3D::*, Direct3D12::*},
+};
+
+use crate::{
+    graphics::{capture::Capture, context::*},
+    state::*,
+};
+
+use super::analysis_pass::*;
+use super::RootParam;
+
+pub struct HarmonyPass {
+    pso: ID3D12PipelineState,
+}
+
+impl HarmonyPass {
+    pub fn new(context: &mut Context, root_signature: &ID3D12RootSignature) -> Result<Self> {
+        let device = &context.device;
+        let compiler = &context.compiler;
+
+        let pso = device.create_graphics_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\harmony.hlsl"),
+                w!("WheelVs"),
+                w!("vs_6_0"),
+                &[],
+            )?,
+            &compiler.compile(
+                w!("shaders\\harmony.hlsl"),
+                w!("WheelPs"),
+                w!("ps_6_0"),
+                &[],
+            )?,
+            BlendState::alpha(),
+            RasterizerState::no_cull(),
+            DepthStencilState::none(),
+            &[],
+            None,
+            None,
+            None,
+            None,
+        )?;
+
+        Ok(Self { pso })
+    }
+
+    fn draw(&self, context: &mut Context, state: &State, harmony: &Harmony) -> Result<()> {
+        #[repr(C)]
+        struct Params {
+            area: [f32; 4],
+            centers: [f32; 3],
+            num_sectors: u32,
+            half_widths: [f32; 3],
+        }
+        const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
+
+        let (width, height) = rect_size(&state.rect);
+        let (width, height) = (width as f32, height as f32);
+
+        let size = 0.3 * width.min(height);
+        let margin = 8.0;
+        let right = 1.0 - 2.0 * margin / width;
+        let top = 1.0 - 2.0 * margin / height;
+        let area = [
+            right - 2.0 * size / width,
+            top - 2.0 * size / height,
+            right,
+            top,
+        ];
+
+        let (centers, half_widths, num_sectors) = harmony.shader_sectors();
+
+        let command_list = &context.command_list;
+
+        command_list.set_pipeline_state(&self.pso);
+
+        command_list.set_graphics_constants(
+            RootParam::Constants,
+            NUM_CONSTS,
+            &Params {
+                area,
+                centers,
+                num_sectors,
+                half_widths,
+            } as *const _ as _,
+        );
+
+        command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+
+        command_list.draw(6, 1);
+
+        Ok(())
+    }
+}
+
+impl AnalysisPass for HarmonyPass {
+    fn name(&self) -> &'static str {
+        "harmony"
+    }
+
+    fn is_enabled(&self, state: &State) -> bool {
+        state.harmony_mode.is_enable() && state.harmony.is_some()
+    }
+
+    fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        _capture: &Capture,
+        _slots: &Slots,
+    ) -> Result<()> {
+        if let Some(harmony) = state.harmony {
+            self.draw(context, state, &harmony)?;
+        }
+
+        Ok(())
+    }
+}
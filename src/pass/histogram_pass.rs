@@ -1,4 +1,5 @@
 use std::mem::size_of;
+use std::sync::Arc;
 
 use windows::{
     core::*,
@@ -14,20 +15,51 @@ use windows::{
 };
 
 use crate::{
+    analysis::{compare::COMPARE_COLORS, harmony, joint::JOINT_BINS},
     graphics::{capture::Capture, *},
+    render::{histogram, Image},
     state::*,
 };
 
+use super::analysis_pass::*;
 use super::RootParam;
 
 pub struct HistogramPass {
     create_pso: ID3D12PipelineState,
+    create_joint_pso: ID3D12PipelineState,
     fill_pso: ID3D12PipelineState,
     line_pso: ID3D12PipelineState,
-
-    buffers: [Resource; 3],
+    wheel_fill_pso: ID3D12PipelineState,
+    wheel_line_pso: ID3D12PipelineState,
+    disc_pso: ID3D12PipelineState,
+    joint_pso: ID3D12PipelineState,
+    threshold_pso: ID3D12PipelineState,
+
+    buffers: [Resource; 4],
+    joint_buf: Resource,
+    readback_buf: Resource,
+
+    // the curves of the compared image, binned on the CPU
+    reference_bufs: [Resource; 3],
+    // what they were binned of and how many there are
+    reference: Option<(Arc<Image>, ReferenceKey, usize)>,
+    // the weighted hue histogram and the template the harmony was fit to
+    fitted: Option<(Vec<u32>, Option<HarmonyTemplate>)>,
 }
 
+// the settings the curves of the compared image depend on, the size of the view
+// area scales them to its pixel count
+type ReferenceKey = (
+    HistogramMode,
+    SaturationMeasure,
+    BrightnessMeasure,
+    (i32, i32),
+);
+
+const WEIGHTED_HUE: usize = 3;
+
+const JOINT_LEN: u32 = (JOINT_BINS * JOINT_BINS) as u32;
+
 impl HistogramPass {
     pub fn new(context: &mut Context, root_signature: &ID3D12RootSignature) -> Result<Self> {
         let device = &context.device;
@@ -47,65 +79,29 @@ impl HistogramPass {
             )?,
         )?;
 
-        let fill_pso = device.create_graphics_pipeline(
+        let create_joint_pso = device.create_compute_pipeline(
             root_signature,
             &compiler.compile(
                 w!("shaders\\histogram.hlsl"),
-                w!("FillVs"),
-                w!("vs_6_0"),
-                &[DxcDefine {
-                    Name: w!("DRAW"),
-                    Value: w!(""),
-                }],
-            )?,
-            &compiler.compile(
-                w!("shaders\\histogram.hlsl"),
-                w!("DrawPs"),
-                w!("ps_6_0"),
+                w!("CreateJointCs"),
+                w!("cs_6_5"),
                 &[DxcDefine {
-                    Name: w!("DRAW"),
+                    Name: w!("CREATE_JOINT"),
                     Value: w!(""),
                 }],
             )?,
-            BlendState::alpha(),
-            RasterizerState::no_cull(),
-            DepthStencilState::none(),
-            &[],
-            None,
-            None,
-            None,
-            None,
         )?;
 
-        let line_pso = device.create_graphics_pipeline(
-            root_signature,
-            &compiler.compile(
-                w!("shaders\\histogram.hlsl"),
-                w!("LineVs"),
-                w!("vs_6_0"),
-                &[DxcDefine {
-                    Name: w!("DRAW"),
-                    Value: w!(""),
-                }],
-            )?,
-            &compiler.compile(
-                w!("shaders\\histogram.hlsl"),
-                w!("DrawPs"),
-                w!("ps_6_0"),
-                &[DxcDefine {
-                    Name: w!("DRAW"),
-                    Value: w!(""),
-                }],
-            )?,
-            BlendState::alpha(),
-            RasterizerState::no_cull(),
-            DepthStencilState::none(),
-            &[],
-            None,
-            None,
-            None,
-            None,
-        )?;
+        let fill_pso = create_draw_pipeline(device, compiler, root_signature, w!("FillVs"))?;
+        let line_pso = create_draw_pipeline(device, compiler, root_signature, w!("LineVs"))?;
+        let wheel_fill_pso =
+            create_draw_pipeline(device, compiler, root_signature, w!("WheelFillVs"))?;
+        let wheel_line_pso =
+            create_draw_pipeline(device, compiler, root_signature, w!("WheelLineVs"))?;
+        let disc_pso = create_draw_pipeline(device, compiler, root_signature, w!("DiscVs"))?;
+        let joint_pso = create_draw_pipeline(device, compiler, root_signature, w!("JointVs"))?;
+        let threshold_pso =
+            create_draw_pipeline(device, compiler, root_signature, w!("ThresholdVs"))?;
 
         let mut buffers = [
             Resource::new_buffer(
@@ -126,8 +122,32 @@ impl HistogramPass {
                 D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
                 D3D12_RESOURCE_STATE_COMMON,
             )?,
+            Resource::new_buffer(
+                device,
+                4 * 256,
+                D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+                D3D12_RESOURCE_STATE_COMMON,
+            )?,
         ];
 
+        let mut joint_buf = Resource::new_buffer(
+            device,
+            4 * JOINT_LEN as u64,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        let readback_buf = Resource::new_staging_buffer(device, 4 * 256)?;
+
+        let mut reference_bufs = [
+            Resource::new_upload_buffer(device, 4 * 256)?,
+            Resource::new_upload_buffer(device, 4 * 256)?,
+            Resource::new_upload_buffer(device, 4 * 256)?,
+        ];
+        for buffer in &mut reference_bufs {
+            descriptor_heap.create_srv_buffer(buffer, Some(DXGI_FORMAT_R32_UINT), None, 256);
+        }
+
         for buffer in &mut buffers {
             descriptor_heap.create_srv_buffer(buffer, Some(DXGI_FORMAT_R32_UINT), None, 256);
         }
@@ -147,48 +167,89 @@ impl HistogramPass {
             descriptor_heap.create_uav_to_clear(buffer, 256, 0);
         }
 
+        descriptor_heap.create_srv_buffer(
+            &mut joint_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            JOINT_LEN,
+        );
+        descriptor_heap.create_uav_buffer(
+            &mut joint_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            JOINT_LEN,
+            None,
+            None,
+        );
+        descriptor_heap.create_uav_to_clear(&mut joint_buf, JOINT_LEN, 0);
+
         Ok(Self {
             create_pso,
+            create_joint_pso,
             fill_pso,
             line_pso,
+            wheel_fill_pso,
+            wheel_line_pso,
+            disc_pso,
+            joint_pso,
+            threshold_pso,
             buffers,
+            joint_buf,
+            readback_buf,
+            reference_bufs,
+            reference: None,
+            fitted: None,
         })
     }
 
-    pub fn process(
-        &mut self,
-        context: &mut Context,
-        state: &State,
-        _capture: &Capture,
-    ) -> Result<()> {
-        if state.histogram_mode != HistogramMode::Disable {
-            self.clear(context)?;
-            self.create(context, state)?;
-            self.draw(context, state)?;
+    // saturation weighted hue histogram of the last processed frame
+    pub fn weighted_hue_histogram(&self) -> Result<Vec<u32>> {
+        self.readback_buf.read(256)
+    }
+
+    // the number of curves of the compared image, none when there is no image
+    // or the histogram is not a linear strip
+    fn upload_reference(&mut self, state: &State) -> Result<usize> {
+        let Some(image) = &state.compare else {
+            self.reference = None;
+            return Ok(0);
+        };
+
+        let key = (
+            state.histogram_mode,
+            state.saturation_measure,
+            state.brightness_measure,
+            rect_size(&state.rect),
+        );
+        if let Some((uploaded, uploaded_key, len)) = &self.reference {
+            if Arc::ptr_eq(uploaded, image) && *uploaded_key == key {
+                return Ok(*len);
+            }
         }
-        Ok(())
+
+        let curves = histogram::bins(state, image).unwrap_or_default();
+        let (width, height) = key.3;
+        let scale = (width * height) as f32 / image.pixels.len() as f32;
+
+        for (buffer, bins) in self.reference_bufs.iter().zip(&curves) {
+            let bins = bins.map(|count| (scale * count as f32).round() as u32);
+            buffer.write(bins.as_ptr(), bins.len())?;
+        }
+
+        self.reference = Some((Arc::clone(image), key, curves.len()));
+        Ok(curves.len())
     }
 
     fn clear(&mut self, context: &mut Context) -> Result<()> {
         let command_list = &context.command_list;
 
-        command_list.resource_barrier(&[
-            ResourceBarrier::transition(
-                &self.buffers[0],
-                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
-                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-            ),
-            ResourceBarrier::transition(
-                &self.buffers[1],
-                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
-                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-            ),
-            ResourceBarrier::transition(
-                &self.buffers[2],
-                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
-                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-            ),
-        ]);
+        command_list.access(
+            &self
+                .buffers
+                .iter()
+                .map(|buffer| (buffer, D3D12_RESOURCE_STATE_UNORDERED_ACCESS))
+                .collect::<Vec<_>>(),
+        );
 
         for buffer in &self.buffers {
             command_list.clear_unordered_access_view_uint(buffer, &[0; 4], &[]);
@@ -202,6 +263,9 @@ impl HistogramPass {
         struct Params {
             rect: RECT,
             mode: u32,
+            weighted: u32,
+            saturation_measure: u32,
+            brightness_measure: u32,
         }
         const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
 
@@ -214,7 +278,10 @@ impl HistogramPass {
             NUM_CONSTS,
             &Params {
                 rect: state.rect,
-                mode: state.histogram_mode as _,
+                mode: get_mode(state),
+                weighted: state.needs_harmony() as _,
+                saturation_measure: state.saturation_measure as _,
+                brightness_measure: state.brightness_measure as _,
             } as *const _ as _,
         );
 
@@ -232,45 +299,183 @@ impl HistogramPass {
         Ok(())
     }
 
-    fn draw(&mut self, context: &mut Context, state: &State) -> Result<()> {
+    fn create_joint(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        axes: JointAxes,
+    ) -> Result<()> {
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            axes: u32,
+            saturation_measure: u32,
+            brightness_measure: u32,
+        }
+        const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
+
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.joint_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS)]);
+
+        command_list.clear_unordered_access_view_uint(&self.joint_buf, &[0; 4], &[]);
+
+        command_list.set_pipeline_state(&self.create_joint_pso);
+
+        command_list.set_compute_constants(
+            RootParam::Constants,
+            NUM_CONSTS,
+            &Params {
+                rect: state.rect,
+                axes: axes as _,
+                saturation_measure: state.saturation_measure as _,
+                brightness_measure: state.brightness_measure as _,
+            } as *const _ as _,
+        );
+
+        command_list.set_compute_descriptor_table(RootParam::Uavs, self.joint_buf.uav());
+
+        const THREADS: u32 = 8;
+        let (width, height) = rect_size(&state.rect);
+
+        command_list.dispatch(
+            div_round_up(width as _, THREADS),
+            div_round_up(height as _, THREADS),
+            1,
+        );
+
+        command_list.access(&[(
+            &self.joint_buf,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        Ok(())
+    }
+
+    fn transition(&mut self, context: &mut Context) -> Result<()> {
+        let command_list = &context.command_list;
+
+        command_list.access(
+            &self
+                .buffers
+                .iter()
+                .enumerate()
+                .map(|(i, buffer)| {
+                    (
+                        buffer,
+                        if i == WEIGHTED_HUE {
+                            D3D12_RESOURCE_STATE_COPY_SOURCE
+                        } else {
+                            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE
+                        },
+                    )
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        command_list.copy_buffer_region(
+            &self.readback_buf,
+            0,
+            &self.buffers[WEIGHTED_HUE],
+            0,
+            4 * 256,
+        );
+
+        command_list.access(&[(
+            &self.buffers[WEIGHTED_HUE],
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        Ok(())
+    }
+
+    fn draw(&mut self, context: &mut Context, state: &State, num_references: usize) -> Result<()> {
         #[repr(C)]
         struct Params {
             color: [f32; 4],
             scale: [f32; 2],
             inv_pixel_count: f32,
             mode: u32,
+            log_scale: u32,
+            _padding: [u32; 3],
+            thresholds: [f32; MAX_NOTAN_THRESHOLDS],
         }
         const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
 
-        let command_list = &context.command_list;
+        // the lines of the frame and the compared image in two colors
+        let [compare_line, reference_line] =
+            COMPARE_COLORS.map(|color| color.extend(0.9).to_array());
+        let comparing = num_references > 0;
 
-        command_list.resource_barrier(&[
-            ResourceBarrier::transition(
-                &self.buffers[0],
-                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
-            ),
-            ResourceBarrier::transition(
-                &self.buffers[1],
-                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
-            ),
-            ResourceBarrier::transition(
-                &self.buffers[2],
-                D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
-                D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
-            ),
-        ]);
+        let command_list = &context.command_list;
 
         let (width, height) = rect_size(&state.rect);
         let mut params = Params {
             color: [0.0; 4],
             scale: [(height as f32) / (width as f32), 1.0],
             inv_pixel_count: 4.0 / ((width * height) as f32),
-            mode: state.histogram_mode as _,
+            mode: get_mode(state),
+            log_scale: state.histogram_log as _,
+            _padding: [0; 3],
+            thresholds: state.shader_notan_thresholds(),
         };
 
-        if state.histogram_mode == HistogramMode::Rgb {
+        if joint_axes(state).is_some() {
+            const JOINT_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.9];
+
+            command_list.set_pipeline_state(match state.histogram_mode {
+                HistogramMode::Joint(_) => &self.joint_pso,
+                _ => &self.disc_pso,
+            });
+
+            command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+
+            params.color = JOINT_COLOR;
+            command_list.set_graphics_constants(
+                RootParam::Constants,
+                NUM_CONSTS,
+                &params as *const _ as _,
+            );
+
+            command_list.set_graphics_descriptor_table(RootParam::Srvs, self.joint_buf.srv());
+
+            command_list.draw(6 * JOINT_LEN, 1);
+        } else if state.histogram_mode == HistogramMode::Hue(HueShape::Wheel) {
+            const FILL_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+            const LINE_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.9];
+
+            let buffer = &self.buffers[0];
+
+            command_list.set_pipeline_state(&self.wheel_fill_pso);
+
+            command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
+
+            params.color = FILL_COLOR;
+            command_list.set_graphics_constants(
+                RootParam::Constants,
+                NUM_CONSTS,
+                &params as *const _ as _,
+            );
+
+            command_list.set_graphics_descriptor_table(RootParam::Srvs, buffer.srv());
+
+            command_list.draw(2 * 257, 1);
+
+            command_list.set_pipeline_state(&self.wheel_line_pso);
+
+            command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_LINESTRIP);
+
+            params.color = LINE_COLOR;
+            command_list.set_graphics_constants(
+                RootParam::Constants,
+                NUM_CONSTS,
+                &params as *const _ as _,
+            );
+
+            command_list.set_graphics_descriptor_table(RootParam::Srvs, buffer.srv());
+
+            command_list.draw(257, 1);
+        } else if state.histogram_mode == HistogramMode::Rgb {
             const FILL_COLORS: [[f32; 4]; 3] = [
                 [0.5, 0.0, 0.0, 0.6],
                 [0.0, 0.5, 0.0, 0.6],
@@ -283,7 +488,7 @@ impl HistogramPass {
                 [0.0, 0.0, 0.8, 0.8],
             ];
 
-            for (i, buffer) in self.buffers.iter().enumerate() {
+            for (i, buffer) in self.buffers[..3].iter().enumerate() {
                 command_list.set_pipeline_state(&self.fill_pso);
 
                 command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
@@ -303,7 +508,11 @@ impl HistogramPass {
 
                 command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_LINESTRIP);
 
-                params.color = LINE_COLORS[i];
+                params.color = if comparing {
+                    compare_line
+                } else {
+                    LINE_COLORS[i]
+                };
                 command_list.set_graphics_constants(
                     RootParam::Constants,
                     NUM_CONSTS,
@@ -339,7 +548,13 @@ impl HistogramPass {
 
             command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_LINESTRIP);
 
-            params.color = LINE_COLOR;
+            if comparing {
+                // in one color rather than by hue
+                params.color = compare_line;
+                params.mode = 0;
+            } else {
+                params.color = LINE_COLOR;
+            }
             command_list.set_graphics_constants(
                 RootParam::Constants,
                 NUM_CONSTS,
@@ -351,6 +566,149 @@ impl HistogramPass {
             command_list.draw(256, 1);
         }
 
+        if comparing {
+            command_list.set_pipeline_state(&self.line_pso);
+
+            command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_LINESTRIP);
+
+            params.color = reference_line;
+            params.mode = 0;
+            command_list.set_graphics_constants(
+                RootParam::Constants,
+                NUM_CONSTS,
+                &params as *const _ as _,
+            );
+
+            for buffer in &self.reference_bufs[..num_references] {
+                command_list.set_graphics_descriptor_table(RootParam::Srvs, buffer.srv());
+
+                command_list.draw(256, 1);
+            }
+        }
+
+        if state.view_mode == ViewMode::Notan && state.histogram_mode == HistogramMode::Brightness {
+            const THRESHOLD_COLOR: [f32; 4] = [0.9, 0.3, 0.3, 0.9];
+
+            command_list.set_pipeline_state(&self.threshold_pso);
+
+            command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_LINELIST);
+
+            params.color = THRESHOLD_COLOR;
+            command_list.set_graphics_constants(
+                RootParam::Constants,
+                NUM_CONSTS,
+                &params as *const _ as _,
+            );
+
+            command_list.draw(2 * MAX_NOTAN_THRESHOLDS as u32, 1);
+        }
+
+        Ok(())
+    }
+}
+
+fn get_mode(state: &State) -> u32 {
+    match state.histogram_mode {
+        HistogramMode::Disable => 0,
+        HistogramMode::Rgb => 1,
+        HistogramMode::Hue(_) => 2,
+        HistogramMode::Saturation => 3,
+        HistogramMode::Brightness => 4,
+        HistogramMode::Joint(_) => 5,
+    }
+}
+
+// the disc is the hue x saturation joint histogram wrapped around the wheel
+fn joint_axes(state: &State) -> Option<JointAxes> {
+    match state.histogram_mode {
+        HistogramMode::Hue(HueShape::Disc) => Some(JointAxes::HueSaturation),
+        HistogramMode::Joint(axes) => Some(axes),
+        _ => None,
+    }
+}
+
+fn create_draw_pipeline(
+    device: &Device,
+    compiler: &Compiler,
+    root_signature: &ID3D12RootSignature,
+    vs: PCWSTR,
+) -> Result<ID3D12PipelineState> {
+    device.create_graphics_pipeline(
+        root_signature,
+        &compiler.compile(
+            w!("shaders\\histogram.hlsl"),
+            vs,
+            w!("vs_6_0"),
+            &[DxcDefine {
+                Name: w!("DRAW"),
+                Value: w!(""),
+            }],
+        )?,
+        &compiler.compile(
+            w!("shaders\\histogram.hlsl"),
+            w!("DrawPs"),
+            w!("ps_6_0"),
+            &[DxcDefine {
+                Name: w!("DRAW"),
+                Value: w!(""),
+            }],
+        )?,
+        BlendState::alpha(),
+        RasterizerState::no_cull(),
+        DepthStencilState::none(),
+        &[],
+        None,
+        None,
+        None,
+        None,
+    )
+}
+
+impl AnalysisPass for HistogramPass {
+    fn name(&self) -> &'static str {
+        "histogram"
+    }
+
+    // the harmony is fit to the saturation weighted hue histogram
+    fn is_enabled(&self, state: &State) -> bool {
+        state.histogram_mode.is_enable() || state.needs_harmony()
+    }
+
+    fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        _capture: &Capture,
+        _slots: &Slots,
+    ) -> Result<()> {
+        self.clear(context)?;
+        self.create(context, state)?;
+        self.transition(context)?;
+
+        if let Some(axes) = joint_axes(state) {
+            self.create_joint(context, state, axes)?;
+        }
+
+        if state.histogram_mode.is_enable() {
+            let num_references = self.upload_reference(state)?;
+            self.draw(context, state, num_references)?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self, state: &State, shared: &RefState) -> Result<()> {
+        // the fit tries every rotation of every template, so only when the hues change
+        if state.needs_harmony() {
+            let key = (
+                self.weighted_hue_histogram()?,
+                state.harmony_mode.template(),
+            );
+            if self.fitted.as_ref() != Some(&key) {
+                shared.set_harmony(harmony::fit(&key.0, key.1));
+                self.fitted = Some(key);
+            }
+        }
         Ok(())
     }
 }
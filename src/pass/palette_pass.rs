@@ -0,0 +1,285 @@
+use std::mem::size_of;
+use std::sync::Arc;
+
+use windows::{
+    core::*,
+    w,
+    Win32::Graphics::{Direct3D12::*, Dxgi::Common::*},
+};
+
+use crate::{
+    color::{naming::*, palette::Coverage},
+    graphics::{capture::Capture, *},
+    state::*,
+};
+
+use super::analysis_pass::*;
+use super::RootParam;
+
+// the last coverage entry counts the off palette pixels
+const COVERAGE_LEN: usize = MAX_NAMES + 1;
+
+pub struct PalettePass {
+    pso: ID3D12PipelineState,
+    coverage_buf: Resource,
+    lut_buf: Resource,
+    colors_buf: Resource,
+    upload_buf: Resource,
+    readback_buf: Resource,
+    palette: Option<Arc<Dictionary>>,
+}
+
+impl PalettePass {
+    pub fn new(context: &mut Context, root_signature: &ID3D12RootSignature) -> Result<Self> {
+        let device = &context.device;
+        let compiler = &context.compiler;
+        let descriptor_heap = &mut context.descriptor_heap;
+
+        let pso = device.create_compute_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\palette.hlsl"),
+                w!("CountPaletteCs"),
+                w!("cs_6_0"),
+                &[],
+            )?,
+        )?;
+
+        let mut coverage_buf = Resource::new_buffer(
+            device,
+            4 * COVERAGE_LEN as u64,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        let mut lut_buf = Resource::new_buffer(
+            device,
+            4 * LUT_SIZE as u64,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        let mut colors_buf = Resource::new_buffer(
+            device,
+            4 * MAX_NAMES as u64,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        let upload_buf = Resource::new_upload_buffer(device, 4 * (LUT_SIZE + MAX_NAMES) as u64)?;
+        let readback_buf = Resource::new_staging_buffer(device, 4 * COVERAGE_LEN as u64)?;
+
+        // u0, u1 and u2 must be adjacent in the heap
+        descriptor_heap.create_uav_buffer(
+            &mut coverage_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            COVERAGE_LEN as _,
+            None,
+            None,
+        );
+
+        descriptor_heap.create_uav_buffer(
+            &mut lut_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            LUT_SIZE as _,
+            None,
+            None,
+        );
+
+        descriptor_heap.create_uav_buffer(
+            &mut colors_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            MAX_NAMES as _,
+            None,
+            None,
+        );
+
+        descriptor_heap.create_uav_to_clear(&mut coverage_buf, COVERAGE_LEN as _, 0);
+
+        Ok(Self {
+            pso,
+            coverage_buf,
+            lut_buf,
+            colors_buf,
+            upload_buf,
+            readback_buf,
+            palette: None,
+        })
+    }
+
+    pub fn upload(&mut self, context: &mut Context, palette: &Arc<Dictionary>) -> Result<()> {
+        if let Some(uploaded) = &self.palette {
+            if Arc::ptr_eq(uploaded, palette) {
+                return Ok(());
+            }
+        }
+
+        let mut data = palette.lut();
+        data.extend(palette.names.iter().map(|name| {
+            let [r, g, b] = name.rgb.map(u32::from);
+            (r << 16) | (g << 8) | b
+        }));
+        self.upload_buf.write(data.as_ptr(), data.len())?;
+
+        let command_list = &context.command_list;
+
+        command_list.access(&[
+            (&self.lut_buf, D3D12_RESOURCE_STATE_COPY_DEST),
+            (&self.colors_buf, D3D12_RESOURCE_STATE_COPY_DEST),
+        ]);
+
+        command_list.copy_buffer_region(&self.lut_buf, 0, &self.upload_buf, 0, 4 * LUT_SIZE as u64);
+
+        command_list.copy_buffer_region(
+            &self.colors_buf,
+            0,
+            &self.upload_buf,
+            4 * LUT_SIZE as u64,
+            4 * palette.names.len() as u64,
+        );
+
+        command_list.access(&[
+            (&self.lut_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS),
+            (&self.colors_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS),
+        ]);
+
+        self.palette = Some(Arc::clone(palette));
+
+        Ok(())
+    }
+
+    // pixels per palette color and off palette pixels, of the last processed frame
+    pub fn counts(&self) -> Result<(Vec<u32>, u32)> {
+        let mut counts = self.readback_buf.read::<u32>(COVERAGE_LEN)?;
+        let off_palette = counts.pop().unwrap_or_default();
+        Ok((counts, off_palette))
+    }
+
+    fn clear(&mut self, context: &mut Context) -> Result<()> {
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.coverage_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS)]);
+
+        command_list.clear_unordered_access_view_uint(&self.coverage_buf, &[0; 4], &[]);
+
+        Ok(())
+    }
+
+    fn count(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        count_buf: &Descriptor,
+    ) -> Result<()> {
+        #[repr(C)]
+        struct Params {
+            tolerance: f32,
+            delta_e_formula: u32,
+            off_palette: u32,
+            num_colors: u32,
+        }
+        const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
+
+        let command_list = &context.command_list;
+
+        command_list.set_pipeline_state(&self.pso);
+
+        command_list.set_compute_constants(
+            RootParam::Constants,
+            NUM_CONSTS,
+            &Params {
+                tolerance: state.palette_tolerance,
+                delta_e_formula: state.delta_e as _,
+                off_palette: MAX_NAMES as _,
+                num_colors: state
+                    .palette
+                    .as_ref()
+                    .map(|palette| palette.names.len() as _)
+                    .unwrap_or_default(),
+            } as *const _ as _,
+        );
+
+        command_list.set_compute_descriptor_table(RootParam::Srvs, count_buf);
+        command_list.set_compute_descriptor_table(RootParam::Uavs, self.coverage_buf.uav());
+
+        const THREADS: u32 = 8;
+        command_list.dispatch(256 / THREADS, 256 / THREADS, 256 / THREADS);
+
+        Ok(())
+    }
+
+    fn transition(&mut self, context: &mut Context) -> Result<()> {
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.coverage_buf, D3D12_RESOURCE_STATE_COPY_SOURCE)]);
+
+        command_list.copy_buffer_region(
+            &self.readback_buf,
+            0,
+            &self.coverage_buf,
+            0,
+            4 * COVERAGE_LEN as u64,
+        );
+
+        command_list.access(&[(
+            &self.coverage_buf,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        Ok(())
+    }
+}
+
+impl AnalysisPass for PalettePass {
+    fn name(&self) -> &'static str {
+        "palette"
+    }
+
+    fn reads(&self) -> &'static [Slot] {
+        &[Slot::ColorCounts]
+    }
+
+    // the lookup table and colors, u1 and u2 of the view shader
+    fn publish(&self, slots: &mut Slots) {
+        slots.insert(Slot::Palette, *self.coverage_buf.uav());
+    }
+
+    fn is_enabled(&self, state: &State) -> bool {
+        state.palette.is_some()
+    }
+
+    // the view reads the palette before the colors are counted
+    fn prepare(&mut self, context: &mut Context, state: &State) -> Result<()> {
+        match &state.palette {
+            Some(palette) => self.upload(context, palette),
+            None => Ok(()),
+        }
+    }
+
+    fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        _capture: &Capture,
+        slots: &Slots,
+    ) -> Result<()> {
+        self.clear(context)?;
+        self.count(context, state, slots.get(Slot::ColorCounts))?;
+        self.transition(context)?;
+        Ok(())
+    }
+
+    fn finish(&mut self, state: &State, shared: &RefState) -> Result<()> {
+        match &state.palette {
+            Some(palette) => {
+                let (counts, off_palette) = self.counts()?;
+                shared.set_coverage(Some(Arc::new(Coverage::new(palette, &counts, off_palette))));
+            }
+            None => shared.set_coverage(None),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use std::mem::size_of;
+
+use windows::{
+    core::*,
+    w,
+    Win32::{
+        Foundation::RECT,
+        Graphics::{Direct3D12::*, Dxgi::Common::*},
+    },
+};
+
+use crate::{
+    analysis::probe::*,
+    graphics::{capture::Capture, *},
+    state::*,
+};
+
+use super::analysis_pass::*;
+use super::RootParam;
+
+pub struct ProbePass {
+    pso: ID3D12PipelineState,
+    probe_buf: Resource,
+    readback_buf: Resource,
+}
+
+impl ProbePass {
+    pub fn new(context: &mut Context, root_signature: &ID3D12RootSignature) -> Result<Self> {
+        let device = &context.device;
+        let compiler = &context.compiler;
+        let descriptor_heap = &mut context.descriptor_heap;
+
+        let pso = device.create_compute_pipeline(
+            root_signature,
+            &compiler.compile(w!("shaders\\probe.hlsl"), w!("ProbeCs"), w!("cs_6_0"), &[])?,
+        )?;
+
+        let mut probe_buf = Resource::new_buffer(
+            device,
+            4 * PROBE_LEN as u64,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        let readback_buf = Resource::new_staging_buffer(device, 4 * PROBE_LEN as u64)?;
+
+        descriptor_heap.create_uav_buffer(
+            &mut probe_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            PROBE_LEN as _,
+            None,
+            None,
+        );
+
+        descriptor_heap.create_uav_to_clear(&mut probe_buf, PROBE_LEN as _, 0);
+
+        Ok(Self {
+            pso,
+            probe_buf,
+            readback_buf,
+        })
+    }
+
+    // probe of the last processed frame
+    pub fn result(&self) -> Result<Probe> {
+        Ok(Probe::from_buffer(&self.readback_buf.read(PROBE_LEN)?))
+    }
+
+    fn clear(&mut self, context: &mut Context) -> Result<()> {
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.probe_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS)]);
+
+        command_list.clear_unordered_access_view_uint(&self.probe_buf, &[0; 4], &[]);
+
+        Ok(())
+    }
+
+    fn probe(&mut self, context: &mut Context, state: &State) -> Result<()> {
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            cursor: [u32; 2],
+            has_cursor: u32,
+        }
+        const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
+
+        let command_list = &context.command_list;
+
+        command_list.set_pipeline_state(&self.pso);
+
+        let cursor = state.cursor.unwrap_or_default();
+        command_list.set_compute_constants(
+            RootParam::Constants,
+            NUM_CONSTS,
+            &Params {
+                rect: state.rect,
+                cursor: [cursor.0 as _, cursor.1 as _],
+                has_cursor: state.cursor.is_some() as _,
+            } as *const _ as _,
+        );
+
+        command_list.set_compute_descriptor_table(RootParam::Uavs, self.probe_buf.uav());
+
+        const THREADS: u32 = 8;
+        let (width, height) = rect_size(&state.rect);
+
+        command_list.dispatch(
+            div_round_up(width as _, THREADS),
+            div_round_up(height as _, THREADS),
+            1,
+        );
+
+        Ok(())
+    }
+
+    fn transition(&mut self, context: &mut Context) -> Result<()> {
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.probe_buf, D3D12_RESOURCE_STATE_COPY_SOURCE)]);
+
+        command_list.copy_buffer_region(
+            &self.readback_buf,
+            0,
+            &self.probe_buf,
+            0,
+            4 * PROBE_LEN as u64,
+        );
+
+        command_list.access(&[(
+            &self.probe_buf,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        Ok(())
+    }
+}
+
+impl AnalysisPass for ProbePass {
+    fn name(&self) -> &'static str {
+        "probe"
+    }
+
+    fn is_enabled(&self, _state: &State) -> bool {
+        true
+    }
+
+    fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        _capture: &Capture,
+        _slots: &Slots,
+    ) -> Result<()> {
+        self.clear(context)?;
+        self.probe(context, state)?;
+        self.transition(context)?;
+        Ok(())
+    }
+
+    fn finish(&mut self, _state: &State, shared: &RefState) -> Result<()> {
+        shared.set_probe(Some(self.result()?));
+        Ok(())
+    }
+}
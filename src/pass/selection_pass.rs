@@ -0,0 +1,189 @@
+use std::mem::size_of;
+
+use glam::*;
+use windows::{
+    core::*,
+    w,
+    Win32::Graphics::{Direct3D::Dxc::DxcDefine, Direct3D12::*, Dxgi::Common::*},
+};
+
+use crate::{
+    graphics::{capture::Capture, *},
+    state::*,
+};
+
+use super::analysis_pass::*;
+use super::RootParam;
+
+const SHADER_RESOURCE: D3D12_RESOURCE_STATES = D3D12_RESOURCE_STATES(
+    D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE.0 | D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE.0,
+);
+
+// one bit per 24 bit color
+const MASK_LEN: u32 = 256 * 256 * 256 / 32;
+
+pub struct SelectionPass {
+    pso: ID3D12PipelineState,
+    mask_buf: Resource,
+}
+
+impl SelectionPass {
+    pub fn new(context: &mut Context, root_signature: &ID3D12RootSignature) -> Result<Self> {
+        let device = &context.device;
+        let compiler = &context.compiler;
+        let descriptor_heap = &mut context.descriptor_heap;
+
+        let pso = device.create_compute_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\color_cloud.hlsl"),
+                w!("SelectCs"),
+                w!("cs_6_0"),
+                &[DxcDefine {
+                    Name: w!("SELECT"),
+                    Value: w!(""),
+                }],
+            )?,
+        )?;
+
+        let mut mask_buf = Resource::new_buffer(
+            device,
+            4 * MASK_LEN as u64,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        descriptor_heap.create_srv_buffer(
+            &mut mask_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            MASK_LEN,
+        );
+
+        descriptor_heap.create_uav_buffer(
+            &mut mask_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            MASK_LEN,
+            None,
+            None,
+        );
+
+        Ok(Self { pso, mask_buf })
+    }
+
+    fn select(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        selection: &Selection,
+    ) -> Result<()> {
+        #[repr(C)]
+        #[derive(Default)]
+        struct Params {
+            projection: Mat4,
+            shape: Vec4,
+            kind: u32,
+            color_space: u32,
+            channel: u32,
+            saturation_measure: u32,
+            brightness_measure: u32,
+        }
+
+        let mut params = Params {
+            saturation_measure: state.saturation_measure as _,
+            brightness_measure: state.brightness_measure as _,
+            ..Default::default()
+        };
+
+        match *selection {
+            Selection::Bins { mode, lo, hi } => {
+                params.kind = 1;
+                params.channel = match mode {
+                    HistogramMode::Hue(_) => 2,
+                    HistogramMode::Saturation => 3,
+                    _ => 4,
+                };
+                params.shape = Vec4::new(lo as f32, hi as f32, 0.0, 0.0);
+            }
+            Selection::Box {
+                color_space,
+                projection,
+                min,
+                max,
+            } => {
+                params.kind = 2;
+                params.color_space = color_space as _;
+                params.projection = projection;
+                params.shape = Vec4::new(min.x, min.y, max.x, max.y);
+            }
+            Selection::Sphere {
+                color_space,
+                rotation,
+                center,
+                radius,
+            } => {
+                params.kind = 3;
+                params.color_space = color_space as _;
+                params.projection = Mat4::from_quat(rotation).inverse();
+                params.shape = center.extend(radius);
+            }
+        }
+
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.mask_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS)]);
+
+        command_list.set_pipeline_state(&self.pso);
+
+        command_list.set_compute_constants(
+            RootParam::Constants,
+            size_of::<Params>() as u32 / 4,
+            &params as *const _ as _,
+        );
+
+        command_list.set_compute_descriptor_table(RootParam::Uavs, self.mask_buf.uav());
+
+        const THREAD: u32 = 8;
+        command_list.dispatch(256 / 32 / THREAD, 256 / THREAD, 256 / THREAD);
+
+        command_list.access(&[(&self.mask_buf, SHADER_RESOURCE)]);
+
+        Ok(())
+    }
+}
+
+impl AnalysisPass for SelectionPass {
+    fn name(&self) -> &'static str {
+        "selection"
+    }
+
+    fn input(&self) -> Input {
+        Input::View
+    }
+
+    fn writes(&self) -> &'static [Slot] {
+        &[Slot::Selection]
+    }
+
+    fn publish(&self, slots: &mut Slots) {
+        slots.insert(Slot::Selection, *self.mask_buf.srv());
+    }
+
+    fn is_enabled(&self, state: &State) -> bool {
+        state.view_mode == ViewMode::Selection && state.selection.is_some()
+    }
+
+    fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        _capture: &Capture,
+        _slots: &Slots,
+    ) -> Result<()> {
+        if let Some(selection) = &state.selection {
+            self.select(context, state, selection)?;
+        }
+        Ok(())
+    }
+}
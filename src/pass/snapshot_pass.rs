@@ -0,0 +1,140 @@
+use std::mem::size_of;
+
+use windows::{
+    core::*,
+    w,
+    Win32::{
+        Foundation::RECT,
+        Graphics::{Direct3D12::*, Dxgi::Common::*},
+    },
+};
+
+use crate::{
+    graphics::{capture::Capture, *},
+    state::*,
+};
+
+use super::RootParam;
+
+// copies the pixels of the view area back to the CPU, for exports
+pub struct SnapshotPass {
+    pso: ID3D12PipelineState,
+    snapshot_buf: Resource,
+    readback_buf: Resource,
+    len: u32,
+}
+
+impl SnapshotPass {
+    pub fn new(
+        context: &mut Context,
+        root_signature: &ID3D12RootSignature,
+        width: u32,
+        height: u32,
+    ) -> Result<Self> {
+        let device = &context.device;
+        let compiler = &context.compiler;
+        let descriptor_heap = &mut context.descriptor_heap;
+
+        let pso = device.create_compute_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\snapshot.hlsl"),
+                w!("SnapshotCs"),
+                w!("cs_6_0"),
+                &[],
+            )?,
+        )?;
+
+        let len = width * height;
+
+        let mut snapshot_buf = Resource::new_buffer(
+            device,
+            4 * len as u64,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        let readback_buf = Resource::new_staging_buffer(device, 4 * len as u64)?;
+
+        descriptor_heap.create_uav_buffer(
+            &mut snapshot_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            len,
+            None,
+            None,
+        );
+
+        Ok(Self {
+            pso,
+            snapshot_buf,
+            readback_buf,
+            len,
+        })
+    }
+
+    // of the frame bound at the time
+    pub fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        _capture: &Capture,
+    ) -> Result<()> {
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+        }
+        const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
+
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.snapshot_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS)]);
+
+        command_list.set_pipeline_state(&self.pso);
+
+        command_list.set_compute_constants(
+            RootParam::Constants,
+            NUM_CONSTS,
+            &Params { rect: state.rect } as *const _ as _,
+        );
+
+        command_list.set_compute_descriptor_table(RootParam::Uavs, self.snapshot_buf.uav());
+
+        const THREADS: u32 = 8;
+        let (width, height) = rect_size(&state.rect);
+
+        command_list.dispatch(
+            div_round_up(width as _, THREADS),
+            div_round_up(height as _, THREADS),
+            1,
+        );
+
+        command_list.access(&[(&self.snapshot_buf, D3D12_RESOURCE_STATE_COPY_SOURCE)]);
+
+        command_list.copy_buffer_region(
+            &self.readback_buf,
+            0,
+            &self.snapshot_buf,
+            0,
+            4 * self.len as u64,
+        );
+
+        command_list.access(&[(
+            &self.snapshot_buf,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        Ok(())
+    }
+
+    // pixels of the last processed frame, `width` by `height` of its rect
+    pub fn pixels(&self, width: u32, height: u32) -> Result<Vec<[u8; 3]>> {
+        let len = (width * height).min(self.len) as usize;
+        Ok(self
+            .readback_buf
+            .read::<u32>(len)?
+            .into_iter()
+            .map(|rgb| [(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8])
+            .collect())
+    }
+}
@@ -0,0 +1,451 @@
+use std::collections::VecDeque;
+use std::mem::size_of;
+use std::time::{Duration, Instant};
+
+use windows::{
+    core::*,
+    w,
+    Win32::{
+        Foundation::RECT,
+        Graphics::{
+            Direct3D::{Dxc::DxcDefine, *},
+            Direct3D12::*,
+            Dxgi::Common::*,
+        },
+    },
+};
+
+use crate::{
+    analysis::stats::*,
+    color::hsv_to_rgb,
+    graphics::{capture::Capture, *},
+    state::*,
+};
+
+use super::analysis_pass::*;
+use super::RootParam;
+
+// samples drawn at most, longer histories are thinned out
+const MAX_SAMPLES: usize = 4096;
+// 5 series and the hue band, 2 vertices each
+const VERTICES_PER_SAMPLE: usize = 12;
+const MAX_VERTICES: usize = 6 + VERTICES_PER_SAMPLE * MAX_SAMPLES;
+
+// clip space band at the bottom of the window
+const CHART_TOP: f32 = -0.6;
+const CHART_BOTTOM: f32 = -1.0;
+const HUE_BAND: f32 = 0.03;
+
+const BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const LUMA_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 0.9];
+const SATURATION_COLOR: [f32; 4] = [0.9, 0.4, 0.9, 0.9];
+const SHADOW_CLIP_COLOR: [f32; 4] = [0.3, 0.5, 1.0, 0.9];
+const HIGHLIGHT_CLIP_COLOR: [f32; 4] = [1.0, 0.4, 0.3, 0.9];
+const UNIQUE_COLORS_COLOR: [f32; 4] = [0.4, 0.9, 0.4, 0.9];
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct Vertex {
+    position: [f32; 2],
+    color: [f32; 4],
+}
+
+impl Vertex {
+    fn new(x: f32, y: f32, color: [f32; 4]) -> Self {
+        Self {
+            position: [x, y],
+            color,
+        }
+    }
+}
+
+pub struct StatsPass {
+    stats_pso: ID3D12PipelineState,
+    count_pso: ID3D12PipelineState,
+    background_pso: ID3D12PipelineState,
+    line_pso: ID3D12PipelineState,
+
+    stats_buf: Resource,
+    color_bits: Resource,
+    readback_buf: Resource,
+    vertex_buf: Resource,
+
+    // the statistics of the frames within the strip chart
+    history: VecDeque<(Instant, Stats)>,
+}
+
+impl StatsPass {
+    pub fn new(context: &mut Context, root_signature: &ID3D12RootSignature) -> Result<Self> {
+        let device = &context.device;
+        let compiler = &context.compiler;
+        let descriptor_heap = &mut context.descriptor_heap;
+
+        let stats_pso = device.create_compute_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\stats.hlsl"),
+                w!("StatsCs"),
+                w!("cs_6_0"),
+                &[DxcDefine {
+                    Name: w!("STATS"),
+                    Value: w!(""),
+                }],
+            )?,
+        )?;
+
+        let count_pso = device.create_compute_pipeline(
+            root_signature,
+            &compiler.compile(
+                w!("shaders\\stats.hlsl"),
+                w!("CountCs"),
+                w!("cs_6_0"),
+                &[DxcDefine {
+                    Name: w!("COUNT"),
+                    Value: w!(""),
+                }],
+            )?,
+        )?;
+
+        let defines = [DxcDefine {
+            Name: w!("CHART"),
+            Value: w!(""),
+        }];
+        let vs = compiler.compile(
+            w!("shaders\\stats.hlsl"),
+            w!("ChartVs"),
+            w!("vs_6_0"),
+            &defines,
+        )?;
+        let ps = compiler.compile(
+            w!("shaders\\stats.hlsl"),
+            w!("ChartPs"),
+            w!("ps_6_0"),
+            &defines,
+        )?;
+        let input_elements = [
+            InputElement::per_vertex(s!("POSITION"), DXGI_FORMAT_R32G32_FLOAT, 0),
+            InputElement::per_vertex(s!("COLOR"), DXGI_FORMAT_R32G32B32A32_FLOAT, 0),
+        ];
+
+        let background_pso = device.create_graphics_pipeline(
+            root_signature,
+            &vs,
+            &ps,
+            BlendState::alpha(),
+            RasterizerState::no_cull(),
+            DepthStencilState::none(),
+            &input_elements,
+            None,
+            None,
+            None,
+            None,
+        )?;
+
+        let line_pso = device.create_graphics_pipeline(
+            root_signature,
+            &vs,
+            &ps,
+            BlendState::alpha(),
+            RasterizerState::no_cull(),
+            DepthStencilState::none(),
+            &input_elements,
+            Some(D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE),
+            None,
+            None,
+            None,
+        )?;
+
+        let mut stats_buf = Resource::new_buffer(
+            device,
+            4 * STATS_LEN as u64,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        let mut color_bits = Resource::new_buffer(
+            device,
+            4 * COLOR_BITS_LEN as u64,
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            D3D12_RESOURCE_STATE_COMMON,
+        )?;
+
+        let readback_buf = Resource::new_staging_buffer(device, 4 * STATS_LEN as u64)?;
+
+        let vertex_buf =
+            Resource::new_upload_buffer(device, (size_of::<Vertex>() * MAX_VERTICES) as _)?;
+
+        descriptor_heap.create_srv_buffer(
+            &mut color_bits,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            COLOR_BITS_LEN as _,
+        );
+
+        // u0 and u1 of StatsCs, one table
+        descriptor_heap.create_uav_buffer(
+            &mut stats_buf,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            STATS_LEN as _,
+            None,
+            None,
+        );
+        descriptor_heap.create_uav_buffer(
+            &mut color_bits,
+            Some(DXGI_FORMAT_R32_UINT),
+            None,
+            COLOR_BITS_LEN as _,
+            None,
+            None,
+        );
+
+        descriptor_heap.create_uav_to_clear(&mut stats_buf, STATS_LEN as _, 0);
+        descriptor_heap.create_uav_to_clear(&mut color_bits, COLOR_BITS_LEN as _, 0);
+
+        Ok(Self {
+            stats_pso,
+            count_pso,
+            background_pso,
+            line_pso,
+            stats_buf,
+            color_bits,
+            readback_buf,
+            vertex_buf,
+            history: VecDeque::new(),
+        })
+    }
+
+    // statistics of the last processed frame
+    pub fn result(&self) -> Result<Stats> {
+        Ok(Stats::from_buffer(&self.readback_buf.read(STATS_LEN)?))
+    }
+
+    fn clear(&mut self, context: &mut Context) -> Result<()> {
+        let command_list = &context.command_list;
+
+        command_list.access(&[
+            (&self.stats_buf, D3D12_RESOURCE_STATE_UNORDERED_ACCESS),
+            (&self.color_bits, D3D12_RESOURCE_STATE_UNORDERED_ACCESS),
+        ]);
+
+        command_list.clear_unordered_access_view_uint(&self.stats_buf, &[0; 4], &[]);
+        command_list.clear_unordered_access_view_uint(&self.color_bits, &[0; 4], &[]);
+
+        Ok(())
+    }
+
+    fn stats(&mut self, context: &mut Context, state: &State) -> Result<()> {
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+            saturation_measure: u32,
+        }
+        const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
+
+        let command_list = &context.command_list;
+
+        command_list.set_pipeline_state(&self.stats_pso);
+
+        command_list.set_compute_constants(
+            RootParam::Constants,
+            NUM_CONSTS,
+            &Params {
+                rect: state.rect,
+                saturation_measure: state.saturation_measure as _,
+            } as *const _ as _,
+        );
+
+        command_list.set_compute_descriptor_table(RootParam::Uavs, self.stats_buf.uav());
+
+        const THREADS: u32 = 8;
+        let (width, height) = rect_size(&state.rect);
+
+        command_list.dispatch(
+            div_round_up(width as _, THREADS),
+            div_round_up(height as _, THREADS),
+            1,
+        );
+
+        Ok(())
+    }
+
+    // the set bits are the colors of the frame
+    fn count(&mut self, context: &mut Context) -> Result<()> {
+        let command_list = &context.command_list;
+
+        command_list.access(&[(
+            &self.color_bits,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        command_list.set_pipeline_state(&self.count_pso);
+
+        command_list.set_compute_descriptor_table(RootParam::Srvs, self.color_bits.srv());
+        command_list.set_compute_descriptor_table(RootParam::Uavs, self.stats_buf.uav());
+
+        const THREADS: u32 = 64;
+        command_list.dispatch(COLOR_BITS_LEN as u32 / THREADS, 1, 1);
+
+        Ok(())
+    }
+
+    fn transition(&mut self, context: &mut Context) -> Result<()> {
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.stats_buf, D3D12_RESOURCE_STATE_COPY_SOURCE)]);
+
+        command_list.copy_buffer_region(
+            &self.readback_buf,
+            0,
+            &self.stats_buf,
+            0,
+            4 * STATS_LEN as u64,
+        );
+
+        command_list.access(&[(
+            &self.stats_buf,
+            D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+        )]);
+
+        Ok(())
+    }
+
+    // the last `state.stats_window` seconds of the history across the bottom of
+    // the window, the newest on the right
+    fn draw_chart(&mut self, context: &mut Context, state: &State, now: Instant) -> Result<()> {
+        let mut vertices = vec![];
+
+        for (x, y) in [(-1.0, CHART_BOTTOM), (1.0, CHART_BOTTOM), (-1.0, CHART_TOP)] {
+            vertices.push(Vertex::new(x, y, BACKGROUND_COLOR));
+        }
+        for (x, y) in [(1.0, CHART_BOTTOM), (1.0, CHART_TOP), (-1.0, CHART_TOP)] {
+            vertices.push(Vertex::new(x, y, BACKGROUND_COLOR));
+        }
+
+        let x_of = |time: Instant| {
+            let age = now.saturating_duration_since(time).as_secs_f32();
+            1.0 - 2.0 * age / state.stats_window
+        };
+        let y_of = |value: f32| {
+            let top = CHART_TOP - HUE_BAND;
+            CHART_BOTTOM + (top - CHART_BOTTOM) * value.clamp(0.0, 1.0)
+        };
+        let series = |stats: &Stats| {
+            [
+                (stats.mean_luma, LUMA_COLOR),
+                (stats.mean_saturation, SATURATION_COLOR),
+                (stats.shadow_clip, SHADOW_CLIP_COLOR),
+                (stats.highlight_clip, HIGHLIGHT_CLIP_COLOR),
+                // 2^24 colors at the top
+                (
+                    (stats.unique_colors as f32 + 1.0).log2() / 24.0,
+                    UNIQUE_COLORS_COLOR,
+                ),
+            ]
+        };
+
+        let samples: Vec<_> = self
+            .history
+            .iter()
+            .filter(|(time, _)| x_of(*time) >= -1.0)
+            .collect();
+        let step = samples.len().div_ceil(MAX_SAMPLES);
+        let samples: Vec<_> = samples.into_iter().step_by(step.max(1)).collect();
+
+        for pair in samples.windows(2) {
+            let (t0, s0) = pair[0];
+            let (t1, s1) = pair[1];
+            let (x0, x1) = (x_of(*t0), x_of(*t1));
+
+            for ((v0, color), (v1, _)) in series(s0).into_iter().zip(series(s1)) {
+                vertices.push(Vertex::new(x0, y_of(v0), color));
+                vertices.push(Vertex::new(x1, y_of(v1), color));
+            }
+
+            if let Some(hue) = s1.dominant_hue {
+                let color = hsv_to_rgb(hue / 360.0, 0.8, 0.9).extend(1.0).into();
+                vertices.push(Vertex::new(x1, CHART_TOP, color));
+                vertices.push(Vertex::new(x1, CHART_TOP - HUE_BAND, color));
+            }
+        }
+
+        vertices.truncate(MAX_VERTICES);
+        self.vertex_buf.write(vertices.as_ptr(), vertices.len())?;
+
+        let command_list = &context.command_list;
+        let view = |first: usize, count: usize| D3D12_VERTEX_BUFFER_VIEW {
+            BufferLocation: unsafe { self.vertex_buf.GetGPUVirtualAddress() }
+                + (size_of::<Vertex>() * first) as u64,
+            SizeInBytes: (size_of::<Vertex>() * count) as _,
+            StrideInBytes: size_of::<Vertex>() as _,
+        };
+
+        command_list.set_pipeline_state(&self.background_pso);
+        command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+        command_list.set_vertex_buffer(0, &[view(0, 6)]);
+        command_list.draw(6, 1);
+
+        if vertices.len() > 6 {
+            command_list.set_pipeline_state(&self.line_pso);
+            command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_LINELIST);
+            command_list.set_vertex_buffer(0, &[view(6, vertices.len() - 6)]);
+            command_list.draw((vertices.len() - 6) as _, 1);
+        }
+
+        Ok(())
+    }
+}
+
+impl AnalysisPass for StatsPass {
+    fn name(&self) -> &'static str {
+        "stats"
+    }
+
+    fn is_enabled(&self, state: &State) -> bool {
+        state.needs_stats()
+    }
+
+    fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        _capture: &Capture,
+        _slots: &Slots,
+    ) -> Result<()> {
+        self.clear(context)?;
+        self.stats(context, state)?;
+        self.count(context)?;
+        self.transition(context)?;
+
+        if state.stats_chart {
+            self.draw_chart(context, state, Instant::now())?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self, state: &State, shared: &RefState) -> Result<()> {
+        if !state.needs_stats() {
+            self.history.clear();
+            return Ok(());
+        }
+
+        let stats = self.result()?;
+        shared.set_stats(Some(stats));
+        if state.stats_request {
+            shared.set_stats_request(false);
+        }
+
+        let now = Instant::now();
+        let window = Duration::from_secs_f32(state.stats_window);
+        self.history.push_back((now, stats));
+        while let Some(&(time, _)) = self.history.front() {
+            if now.duration_since(time) <= window {
+                break;
+            }
+            self.history.pop_front();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use std::mem::size_of;
+
+use windows::{
+    core::*,
+    w,
+    Win32::{
+        Foundation::RECT,
+        Graphics::{Direct3D12::*, Dxgi::Common::*},
+    },
+};
+
+use crate::{
+    graphics::{capture::Capture, *},
+    render::Image,
+    state::*,
+};
+
+use super::RootParam;
+
+const SHADER_RESOURCE: D3D12_RESOURCE_STATES = D3D12_RESOURCE_STATES(
+    D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE.0 | D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE.0,
+);
+
+// a video frame placed at the top left of the view area of a capture sized
+// texture, which the passes read in place of the screen
+pub struct VideoPass {
+    pso: ID3D12PipelineState,
+
+    texture: Resource,
+    upload_buf: Resource,
+    max_pixels: usize,
+}
+
+impl VideoPass {
+    pub fn new(
+        context: &mut Context,
+        root_signature: &ID3D12RootSignature,
+        width: u32,
+        height: u32,
+    ) -> Result<Self> {
+        let device = &context.device;
+        let compiler = &context.compiler;
+        let descriptor_heap = &mut context.descriptor_heap;
+
+        let pso = device.create_compute_pipeline(
+            root_signature,
+            &compiler.compile(w!("shaders\\video.hlsl"), w!("VideoCs"), w!("cs_6_0"), &[])?,
+        )?;
+
+        let mut texture = Resource::new_texture2d(
+            device,
+            width,
+            height,
+            DXGI_FORMAT_R8G8B8A8_UNORM,
+            SampleDesc::default(),
+            D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            SHADER_RESOURCE,
+        )?;
+
+        descriptor_heap.create_srv_tex2d(&mut texture);
+        descriptor_heap.create_uav(
+            &mut texture,
+            false,
+            &D3D12_UNORDERED_ACCESS_VIEW_DESC {
+                Format: DXGI_FORMAT_R8G8B8A8_UNORM,
+                ViewDimension: D3D12_UAV_DIMENSION_TEXTURE2D,
+                Anonymous: D3D12_UNORDERED_ACCESS_VIEW_DESC_0 {
+                    Texture2D: D3D12_TEX2D_UAV {
+                        MipSlice: 0,
+                        PlaneSlice: 0,
+                    },
+                },
+            },
+        );
+
+        // the view area never exceeds the capture
+        let max_pixels = width as usize * height as usize;
+        let mut upload_buf = Resource::new_upload_buffer(device, 4 * max_pixels as u64)?;
+        descriptor_heap.create_srv_buffer(
+            &mut upload_buf,
+            Some(DXGI_FORMAT_R8G8B8A8_UNORM),
+            None,
+            max_pixels as _,
+        );
+
+        Ok(Self {
+            pso,
+            texture,
+            upload_buf,
+            max_pixels,
+        })
+    }
+
+    // the part of `image` within the view area, which the caller has clipped to the image
+    pub fn upload(&mut self, state: &State, image: &Image) -> Result<()> {
+        let (width, height) = rect_size(&state.rect);
+        let (width, height) = (width as usize, height as usize);
+
+        let pixels = (0..height)
+            .flat_map(|y| {
+                let row = y * image.width as usize;
+                image.pixels[row..row + width]
+                    .iter()
+                    .map(|&[r, g, b]| [r, g, b, 0xff])
+            })
+            .take(self.max_pixels)
+            .collect::<Vec<_>>();
+
+        self.upload_buf.write(pixels.as_ptr(), pixels.len())
+    }
+
+    pub fn process(&mut self, context: &mut Context, state: &State) -> Result<()> {
+        #[repr(C)]
+        struct Params {
+            rect: RECT,
+        }
+        const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
+
+        let command_list = &context.command_list;
+
+        command_list.access(&[(&self.texture, D3D12_RESOURCE_STATE_UNORDERED_ACCESS)]);
+
+        command_list.set_pipeline_state(&self.pso);
+
+        command_list.set_compute_constants(
+            RootParam::Constants,
+            NUM_CONSTS,
+            &Params { rect: state.rect } as *const _ as _,
+        );
+
+        command_list.set_compute_descriptor_table(RootParam::Srvs, self.upload_buf.srv());
+        command_list.set_compute_descriptor_table(RootParam::Uavs, self.texture.uav());
+
+        const THREADS: u32 = 8;
+        let (width, height) = rect_size(&state.rect);
+        command_list.dispatch(
+            div_round_up(width as _, THREADS),
+            div_round_up(height as _, THREADS),
+            1,
+        );
+
+        command_list.access(&[(&self.texture, SHADER_RESOURCE)]);
+
+        Ok(())
+    }
+
+    pub fn capture(&self) -> Capture {
+        Capture::from_texture(&self.texture)
+    }
+}
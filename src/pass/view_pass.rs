@@ -10,10 +10,12 @@ use windows::{
 };
 
 use crate::{
-    graphics::{capture::Capture, context::*},
-    state::{State, ViewMode},
+    color::*,
+    graphics::{capture::Capture, context::*, descriptor::Descriptor},
+    state::{State, ViewMode, MAX_DELTA_E, MAX_NOTAN_THRESHOLDS},
 };
 
+use super::analysis_pass::*;
 use super::RootParam;
 
 pub struct ViewPass {
@@ -42,30 +44,40 @@ impl ViewPass {
         Ok(Self { pso })
     }
 
-    pub fn process(
-        &mut self,
+    pub fn view(
+        &self,
         context: &mut Context,
         state: &State,
-        _capture: &Capture,
+        palette_uavs: &Descriptor,
+        selection_srvs: &Descriptor,
     ) -> Result<()> {
-        if state.view_mode.is_enable() {
-            self.view(context, state)?;
-        }
-
-        Ok(())
-    }
-
-    pub fn view(&self, context: &mut Context, state: &State) -> Result<()> {
         #[repr(C)]
         struct Params {
             rect: RECT,
             mask: [f32; 4],
             mode: u32,
+            harmony_centers: [f32; 3],
+            harmony_half_widths: [f32; 3],
+            num_harmony_sectors: u32,
+            target_lab: [f32; 3],
+            delta_e_formula: u32,
+            max_delta_e: f32,
+            saturation_measure: u32,
+            brightness_measure: u32,
+            posterize_levels: u32,
+            notan_thresholds: [f32; MAX_NOTAN_THRESHOLDS],
+            posterize_lightness: u32,
+            num_palette_colors: u32,
         }
         const NUM_CONSTS: u32 = size_of::<Params>() as u32 / 4;
 
         let command_list = &context.command_list;
 
+        let (harmony_centers, harmony_half_widths, num_harmony_sectors) = state
+            .harmony
+            .map(|harmony| harmony.shader_sectors())
+            .unwrap_or_default();
+
         command_list.set_pipeline_state(&self.pso);
 
         command_list.set_graphics_constants(
@@ -75,9 +87,35 @@ impl ViewPass {
                 rect: state.rect,
                 mask: get_mask(state),
                 mode: get_mode(state),
+                harmony_centers,
+                harmony_half_widths,
+                num_harmony_sectors,
+                target_lab: state
+                    .target
+                    .map(|target| rgb_to_lab(from_rgb8(target)).to_array())
+                    .unwrap_or_default(),
+                delta_e_formula: state.delta_e as _,
+                max_delta_e: if state.view_mode == ViewMode::PaletteCompliance {
+                    state.palette_tolerance
+                } else {
+                    MAX_DELTA_E
+                },
+                saturation_measure: state.saturation_measure as _,
+                brightness_measure: state.brightness_measure as _,
+                posterize_levels: state.posterize_levels,
+                notan_thresholds: state.shader_notan_thresholds(),
+                posterize_lightness: state.posterize_lightness as _,
+                num_palette_colors: state
+                    .palette
+                    .as_ref()
+                    .map(|palette| palette.names.len() as _)
+                    .unwrap_or_default(),
             } as *const _ as _,
         );
 
+        command_list.set_graphics_descriptor_table(RootParam::Srvs, selection_srvs);
+        command_list.set_graphics_descriptor_table(RootParam::Uavs, palette_uavs);
+
         command_list.set_primivive_topology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
 
         command_list.draw(6, 1);
@@ -86,6 +124,43 @@ impl ViewPass {
     }
 }
 
+impl AnalysisPass for ViewPass {
+    fn name(&self) -> &'static str {
+        "view"
+    }
+
+    fn input(&self) -> Input {
+        Input::View
+    }
+
+    fn reads(&self) -> &'static [Slot] {
+        &[Slot::Selection, Slot::Palette]
+    }
+
+    fn is_enabled(&self, state: &State) -> bool {
+        // the compare pass draws over the whole view
+        let compared = state.view_mode == ViewMode::Compare && state.compare.is_some();
+
+        // a video or a frozen frame has no screen behind the window to show through
+        (state.view_mode.is_enable() || state.video.is_some() || state.frozen) && !compared
+    }
+
+    fn process(
+        &mut self,
+        context: &mut Context,
+        state: &State,
+        _capture: &Capture,
+        slots: &Slots,
+    ) -> Result<()> {
+        self.view(
+            context,
+            state,
+            slots.get(Slot::Palette),
+            slots.get(Slot::Selection),
+        )
+    }
+}
+
 fn get_mode(state: &State) -> u32 {
     match state.view_mode {
         ViewMode::Original => 0,
@@ -93,6 +168,27 @@ fn get_mode(state: &State) -> u32 {
         ViewMode::Hue => 2,
         ViewMode::Saturation => 3,
         ViewMode::Brightness => 4,
+        ViewMode::Harmony => 5,
+        ViewMode::DeltaE if state.target.is_some() => 6,
+        ViewMode::DeltaE => 0,
+        ViewMode::Notan => 7,
+        ViewMode::Posterize => 8,
+        // brightness of the blurred frame bound in place of the capture
+        ViewMode::Squint => 4,
+        ViewMode::LocalContrast => 9,
+        ViewMode::Palette if state.palette.is_some() => 10,
+        ViewMode::PaletteCompliance if state.palette.is_some() => 11,
+        ViewMode::Palette | ViewMode::PaletteCompliance => 0,
+        ViewMode::Selection if state.selection.is_some() => {
+            if state.selection_grey {
+                13
+            } else {
+                12
+            }
+        }
+        ViewMode::Selection => 0,
+        // drawn by the compare pass
+        ViewMode::Compare => 0,
     }
 }
 
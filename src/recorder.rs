@@ -0,0 +1,150 @@
+use crate::analysis::stats::Stats;
+use crate::color::measure::SaturationMeasure;
+use std::fs::File;
+use std::io::{BufWriter, Error, Result, Write};
+use std::path::Path;
+use std::sync::mpsc::{channel, Sender};
+use std::thread::JoinHandle;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+// a row of the recording
+struct Sample {
+    // seconds since the Unix epoch and since the recording started
+    timestamp: f64,
+    elapsed: f64,
+    stats: Stats,
+    // the measure of mean_saturation
+    saturation_measure: SaturationMeasure,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Format {
+    Csv,
+    Json,
+}
+
+// statistics of each frame written to a CSV or JSON file off the executor loop
+pub struct Recorder {
+    start: Instant,
+    sender: Option<Sender<Sample>>,
+    handler: Option<JoinHandle<Result<()>>>,
+}
+
+impl Recorder {
+    // .json writes an array of objects, anything else CSV
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let format = match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("json") => Format::Json,
+            _ => Format::Csv,
+        };
+
+        // opened here so that a bad path fails at once
+        let mut writer = BufWriter::new(File::create(path)?);
+        let (sender, receiver) = channel::<Sample>();
+
+        let handler = std::thread::spawn(move || {
+            match format {
+                Format::Csv => writeln!(
+                    writer,
+                    "timestamp,elapsed,mean_luma,mean_saturation,saturation_measure,dominant_hue,shadow_clip,highlight_clip,unique_colors"
+                )?,
+                Format::Json => write!(writer, "[")?,
+            }
+
+            for (i, sample) in receiver.iter().enumerate() {
+                match format {
+                    Format::Csv => write_csv(&mut writer, &sample)?,
+                    Format::Json => {
+                        write!(writer, "{}\n  ", if i == 0 { "" } else { "," })?;
+                        write_json(&mut writer, &sample)?;
+                    }
+                }
+            }
+
+            if format == Format::Json {
+                writeln!(writer, "\n]")?;
+            }
+            writer.flush()
+        });
+
+        Ok(Self {
+            start: Instant::now(),
+            sender: Some(sender),
+            handler: Some(handler),
+        })
+    }
+
+    pub fn record(&self, stats: &Stats, saturation_measure: SaturationMeasure) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or_default();
+
+        if let Some(sender) = &self.sender {
+            // a dead writer reports its error on stop
+            _ = sender.send(Sample {
+                timestamp,
+                elapsed: self.start.elapsed().as_secs_f64(),
+                stats: *stats,
+                saturation_measure,
+            });
+        }
+    }
+
+    // closes the file after the rows sent so far
+    pub fn stop(&mut self) -> Result<()> {
+        self.sender = None;
+        match self.handler.take() {
+            Some(handler) => handler
+                .join()
+                .unwrap_or_else(|_| Err(Error::other("the recording writer panicked"))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        _ = self.stop();
+    }
+}
+
+fn write_csv(writer: &mut impl Write, sample: &Sample) -> Result<()> {
+    let stats = &sample.stats;
+    writeln!(
+        writer,
+        "{:.3},{:.3},{:.4},{:.4},{},{},{:.6},{:.6},{}",
+        sample.timestamp,
+        sample.elapsed,
+        stats.mean_luma,
+        stats.mean_saturation,
+        sample.saturation_measure.name(),
+        stats
+            .dominant_hue
+            .map(|hue| hue.to_string())
+            .unwrap_or_default(),
+        stats.shadow_clip,
+        stats.highlight_clip,
+        stats.unique_colors
+    )
+}
+
+fn write_json(writer: &mut impl Write, sample: &Sample) -> Result<()> {
+    let stats = &sample.stats;
+    write!(
+        writer,
+        r#"{{"timestamp": {:.3}, "elapsed": {:.3}, "mean_luma": {:.4}, "mean_saturation": {:.4}, "saturation_measure": "{}", "dominant_hue": {}, "shadow_clip": {:.6}, "highlight_clip": {:.6}, "unique_colors": {}}}"#,
+        sample.timestamp,
+        sample.elapsed,
+        stats.mean_luma,
+        stats.mean_saturation,
+        sample.saturation_measure.name(),
+        stats
+            .dominant_hue
+            .map(|hue| hue.to_string())
+            .unwrap_or_else(|| "null".to_string()),
+        stats.shadow_clip,
+        stats.highlight_clip,
+        stats.unique_colors
+    )
+}
@@ -0,0 +1,212 @@
+// CPU renderer of the color cloud and the histograms, following the shaders
+// so the images match the window. Nothing here touches Direct3D or Win32.
+
+pub mod barcode;
+pub mod canvas;
+pub mod chromaticity;
+pub mod cloud;
+pub mod gif;
+pub mod histogram;
+pub mod joint;
+pub mod png;
+pub mod svg;
+pub mod view;
+
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+#[derive(Clone, Debug)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    // binary PPM (P6) with 8 bits per channel
+    pub fn load_ppm<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = std::fs::read(path)?;
+        let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+
+        // magic, width, height and max value, separated by whitespace and comments
+        let mut fields = vec![];
+        let mut pos = 0;
+        while fields.len() < 4 {
+            match data.get(pos) {
+                Some(b'#') => {
+                    while data.get(pos).is_some_and(|&c| c != b'\n') {
+                        pos += 1;
+                    }
+                }
+                Some(c) if c.is_ascii_whitespace() => pos += 1,
+                Some(_) => {
+                    let start = pos;
+                    while data.get(pos).is_some_and(|c| !c.is_ascii_whitespace()) {
+                        pos += 1;
+                    }
+                    fields.push(String::from_utf8_lossy(&data[start..pos]).to_string());
+                }
+                None => return Err(invalid("truncated PPM header")),
+            }
+        }
+        // a single whitespace ends the header
+        pos += 1;
+
+        if fields[0] != "P6" {
+            return Err(invalid("not a binary PPM"));
+        }
+
+        let parse = |field: &str| field.parse::<u32>().map_err(|_| invalid("bad PPM header"));
+        let (width, height, max) = (parse(&fields[1])?, parse(&fields[2])?, parse(&fields[3])?);
+        if max != 255 {
+            return Err(invalid("only 8 bit PPM is supported"));
+        }
+
+        let size = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|len| len.checked_mul(3))
+            .ok_or_else(|| invalid("PPM too large"))?;
+        let body = data
+            .get(pos..)
+            .and_then(|body| body.get(..size))
+            .ok_or_else(|| invalid("truncated PPM data"))?;
+
+        Ok(Self {
+            width,
+            height,
+            pixels: body.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+        })
+    }
+
+    // the top left `width` x `height`, at most the whole image
+    pub fn crop(&self, width: u32, height: u32) -> Self {
+        let (width, height) = (width.min(self.width), height.min(self.height));
+        let pixels = (0..height as usize)
+            .flat_map(|y| {
+                let row = y * self.width as usize;
+                self.pixels[row..row + width as usize].iter().copied()
+            })
+            .collect();
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    // pixels of each color bin of `bits` per channel, in bin order as
+    // FlattenBinId in color_cloud.hlsl
+    pub fn color_bins(&self, bits: u32) -> BTreeMap<u32, ColorBin> {
+        let mut bins = BTreeMap::new();
+        for &rgb in &self.pixels {
+            bins.entry(color_bin(rgb, bits))
+                .or_insert_with(ColorBin::default)
+                .add(rgb);
+        }
+        bins
+    }
+}
+
+// the colors merged into a bin, the count and the count weighted sums of the channels
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ColorBin {
+    pub count: u32,
+    sum: [u64; 3],
+}
+
+impl ColorBin {
+    pub fn add(&mut self, rgb: [u8; 3]) {
+        self.count += 1;
+        for (sum, c) in self.sum.iter_mut().zip(rgb) {
+            *sum += c as u64;
+        }
+    }
+
+    pub fn merge(&mut self, other: &ColorBin) {
+        self.count += other.count;
+        for (sum, other) in self.sum.iter_mut().zip(other.sum) {
+            *sum += other;
+        }
+    }
+
+    // mean of the merged colors, BinCentroid in color_cloud.hlsl
+    pub fn centroid(&self) -> [f32; 3] {
+        self.sum
+            .map(|sum| sum as f32 / (255.0 * self.count.max(1) as f32))
+    }
+}
+
+// the color bin of `bits` per channel, FlattenBinId in color_cloud.hlsl
+pub fn color_bin(rgb: [u8; 3], bits: u32) -> u32 {
+    let [r, g, b] = rgb.map(|c| (c >> (8 - bits)) as u32);
+    b << (2 * bits) | g << bits | r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(name: &str, data: &[u8]) -> Result<Image> {
+        let path = std::env::temp_dir().join(format!("iromiru-{}-{name}.ppm", std::process::id()));
+        std::fs::write(&path, data)?;
+        let image = Image::load_ppm(&path);
+        std::fs::remove_file(&path)?;
+        image
+    }
+
+    #[test]
+    fn loads_a_ppm() {
+        let image = load(
+            "small",
+            b"P6\n# comment\n2 1\n255\n\x01\x02\x03\x04\x05\x06",
+        )
+        .unwrap();
+        assert_eq!((image.width, image.height), (2, 1));
+        assert_eq!(image.pixels, [[1, 2, 3], [4, 5, 6]]);
+    }
+
+    #[test]
+    fn rejects_a_size_past_the_data() {
+        let error = load("truncated", b"P6 2 2 255\n\x01\x02\x03").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+        let error = load("huge", b"P6 4294967295 4294967295 255\n").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn crops_the_top_left() {
+        let image = Image {
+            width: 3,
+            height: 2,
+            pixels: (0..6).map(|i| [i; 3]).collect(),
+        };
+
+        let cropped = image.crop(2, 5);
+        assert_eq!((cropped.width, cropped.height), (2, 2));
+        assert_eq!(cropped.pixels, [[0; 3], [1; 3], [3; 3], [4; 3]]);
+    }
+
+    #[test]
+    fn places_the_bins_at_the_centroid() {
+        let image = Image {
+            width: 4,
+            height: 1,
+            pixels: vec![[0, 0, 0], [0, 0, 0], [0, 0, 60], [255, 255, 255]],
+        };
+
+        let bins = image.color_bins(2);
+        assert_eq!(bins.len(), 2);
+
+        // three pixels in the darkest cell, off its center
+        let dark = bins[&0];
+        assert_eq!(dark.count, 3);
+        assert_eq!(dark.centroid(), [0.0, 0.0, 20.0 / 255.0]);
+
+        let white = bins[&color_bin([255, 255, 255], 2)];
+        assert_eq!(white.count, 1);
+        assert_eq!(white.centroid(), [1.0, 1.0, 1.0]);
+    }
+}
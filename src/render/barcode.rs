@@ -0,0 +1,178 @@
+use super::png;
+use crate::analysis::barcode::*;
+use crate::automation::Json;
+use crate::color::to_hex;
+use crate::color::ycbcr::YcbcrMatrix;
+use crate::video::Video;
+use std::io::Result;
+use std::path::Path;
+
+// the palettes of the shots take the bottom of the strip
+const PALETTE_BAND: f32 = 0.25;
+const SHOT_SEPARATOR: [u8; 4] = [0, 0, 0, 0xff];
+
+// every frame of `video`, the strip to `output` and the shot list next to it as .json
+pub fn export(
+    video: &mut Video,
+    matrix: YcbcrMatrix,
+    color: BarcodeColor,
+    threshold: f32,
+    size: Option<(u32, u32)>,
+    output: &Path,
+) -> Result<Barcode> {
+    let mut builder = BarcodeBuilder::new(color, threshold);
+    for frame in 0..video.len() {
+        builder.push(&video.read(frame, matrix)?);
+    }
+    let barcode = builder.finish();
+
+    let (width, height) = size.unwrap_or((barcode.columns.len() as u32, 256));
+    png::save(output, width, height, &rasterize(&barcode, width, height))?;
+    std::fs::write(
+        output.with_extension("json"),
+        format!("{}\n", json(&barcode, video.frame_rate())),
+    )?;
+
+    Ok(barcode)
+}
+
+// a column per frame, stretched or squeezed to `width`, over the shot palettes,
+// transparent without frames
+pub fn rasterize(barcode: &Barcode, width: u32, height: u32) -> Vec<[u8; 4]> {
+    let mut pixels = vec![[0; 4]; width as usize * height as usize];
+    let len = barcode.columns.len();
+    if len == 0 {
+        return pixels;
+    }
+
+    let band = ((height as f32 * PALETTE_BAND) as u32).min(height);
+    let top = height - band;
+
+    let frame_of = |x: u32| (x as usize * len / width as usize).min(len - 1);
+    let shot_of = |frame: usize| {
+        barcode
+            .shots
+            .iter()
+            .find(|shot| (shot.start..shot.end).contains(&frame))
+    };
+
+    for x in 0..width {
+        let frame = frame_of(x);
+        let [r, g, b] = barcode.columns[frame];
+        for y in 0..top {
+            pixels[(y * width + x) as usize] = [r, g, b, 0xff];
+        }
+
+        let Some(shot) = shot_of(frame) else {
+            continue;
+        };
+
+        // the first column of a shot after the first
+        let separator = shot.start > 0 && (x == 0 || frame_of(x - 1) < shot.start);
+
+        // colors stacked by their share, the largest on top
+        let mut y = top;
+        for (i, &([r, g, b], share)) in shot.palette.iter().enumerate() {
+            let end = if i + 1 == shot.palette.len() {
+                height
+            } else {
+                (y + (share * band as f32).round() as u32).min(height)
+            };
+            for y in y..end {
+                pixels[(y * width + x) as usize] = if separator {
+                    SHOT_SEPARATOR
+                } else {
+                    [r, g, b, 0xff]
+                };
+            }
+            y = end;
+        }
+    }
+
+    pixels
+}
+
+// frames and seconds of each shot with its palette
+pub fn json(barcode: &Barcode, frame_rate: Option<f32>) -> Json {
+    // to the millisecond and the share to 4 digits
+    let round = |value: f32, scale: f64| Json::Number((value as f64 * scale).round() / scale);
+    let time = |frame: usize| frame_rate.map_or(Json::Null, |rate| round(frame as f32 / rate, 1e3));
+
+    let shots = barcode
+        .shots
+        .iter()
+        .map(|shot| {
+            let palette = shot
+                .palette
+                .iter()
+                .map(|&(rgb, share)| {
+                    Json::object([("color", to_hex(rgb).into()), ("share", round(share, 1e4))])
+                })
+                .collect();
+
+            Json::object([
+                ("start", Json::Number(shot.start as f64)),
+                ("end", Json::Number(shot.end as f64)),
+                ("start_time", time(shot.start)),
+                ("end_time", time(shot.end)),
+                ("palette", Json::Array(palette)),
+            ])
+        })
+        .collect();
+
+    Json::object([
+        ("frames", Json::Number(barcode.columns.len() as f64)),
+        (
+            "frame_rate",
+            frame_rate.map_or(Json::Null, |rate| round(rate, 1e3)),
+        ),
+        ("shots", Json::Array(shots)),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rasterizes_no_frames() {
+        let barcode = Barcode {
+            columns: vec![],
+            shots: vec![],
+        };
+        assert_eq!(rasterize(&barcode, 4, 2), vec![[0; 4]; 8]);
+    }
+
+    #[test]
+    fn lists_the_shots() {
+        let barcode = Barcode {
+            columns: vec![[0, 0, 0]; 3],
+            shots: vec![
+                Shot {
+                    start: 0,
+                    end: 2,
+                    palette: vec![([255, 0, 0], 0.75), ([0, 0, 255], 0.25)],
+                },
+                Shot {
+                    start: 2,
+                    end: 3,
+                    palette: vec![([0, 0, 0], 1.0)],
+                },
+            ],
+        };
+
+        let text = json(&barcode, Some(24.0)).to_string();
+        let parsed = Json::parse(&text).unwrap();
+        assert_eq!(parsed.get("frames"), Some(&Json::Number(3.0)));
+
+        let shots = parsed.get("shots").and_then(Json::as_array).unwrap();
+        assert_eq!(shots.len(), 2);
+        assert_eq!(shots[1].get("start_time"), Some(&Json::Number(0.083)));
+        let color = shots[0].get("palette").and_then(Json::as_array).unwrap()[0].get("color");
+        assert_eq!(color.and_then(Json::as_str), Some("#FF0000"));
+
+        let text = json(&barcode, None).to_string();
+        let parsed = Json::parse(&text).unwrap();
+        assert_eq!(parsed.get("frame_rate"), Some(&Json::Null));
+    }
+}
@@ -0,0 +1,129 @@
+use glam::{Vec3, Vec4};
+
+// a render target with a depth buffer, colors are premultiplied as in the swapchain
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Vec4>,
+    depth: Vec<f32>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, clear: [f32; 4]) -> Self {
+        let len = (width * height) as usize;
+        Self {
+            width,
+            height,
+            pixels: vec![Vec4::from(clear); len],
+            depth: vec![1.0; len],
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        (0 <= x && x < self.width as i32 && 0 <= y && y < self.height as i32)
+            .then(|| (y as u32 * self.width + x as u32) as usize)
+    }
+
+    // DepthStencilState::depth and depth_read_only, the LESS test
+    pub fn depth_test(&mut self, x: i32, y: i32, depth: f32, write: bool) -> bool {
+        match self.index(x, y) {
+            Some(i) if depth < self.depth[i] => {
+                if write {
+                    self.depth[i] = depth;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // BlendState::none
+    pub fn write(&mut self, x: i32, y: i32, color: Vec4) {
+        if let Some(i) = self.index(x, y) {
+            self.pixels[i] = color;
+        }
+    }
+
+    // BlendState::premultiplied
+    pub fn blend_premultiplied(&mut self, x: i32, y: i32, color: Vec4) {
+        if let Some(i) = self.index(x, y) {
+            self.pixels[i] = color + (1.0 - color.w) * self.pixels[i];
+        }
+    }
+
+    // BlendState::alpha, the source alpha replaces the destination alpha
+    pub fn blend_alpha(&mut self, x: i32, y: i32, color: Vec4) {
+        if let Some(i) = self.index(x, y) {
+            let dst = self.pixels[i];
+            let rgb = color.truncate() * color.w + dst.truncate() * (1.0 - color.w);
+            self.pixels[i] = rgb.extend(color.w);
+        }
+    }
+
+    // straight alpha for the image formats
+    pub fn to_rgba8(&self) -> Vec<[u8; 4]> {
+        self.pixels
+            .iter()
+            .map(|&p| {
+                let rgb = if p.w > 0.0 {
+                    p.truncate() / p.w
+                } else {
+                    p.truncate()
+                };
+                let c = rgb.extend(p.w).clamp(Vec4::ZERO, Vec4::ONE) * 255.0;
+                [c.x, c.y, c.z, c.w].map(|v| v.round() as u8)
+            })
+            .collect()
+    }
+
+    // composited over an opaque background, for formats without alpha
+    pub fn to_rgb8_over(&self, background: [u8; 3]) -> Vec<[u8; 3]> {
+        let background = Vec3::from(background.map(|c| c as f32 / 255.0));
+        self.pixels
+            .iter()
+            .map(|&p| {
+                let c =
+                    (p.truncate() + (1.0 - p.w) * background).clamp(Vec3::ZERO, Vec3::ONE) * 255.0;
+                [c.x, c.y, c.z].map(|v| v.round() as u8)
+            })
+            .collect()
+    }
+}
+
+// the accum and revealage targets of ColorCloudTransparencyPass
+pub struct Transparency {
+    accum: Vec<Vec4>,
+    revealage: Vec<f32>,
+}
+
+impl Transparency {
+    pub fn new(canvas: &Canvas) -> Self {
+        let len = canvas.pixels.len();
+        Self {
+            accum: vec![Vec4::ZERO; len],
+            revealage: vec![1.0; len],
+        }
+    }
+
+    // Accumulate in color_cloud.hlsl through BlendState::weighted_blended
+    pub fn accumulate(&mut self, canvas: &Canvas, x: i32, y: i32, color: Vec4, depth: f32) {
+        if let Some(i) = canvas.index(x, y) {
+            let d = 1.0 - depth;
+            let weight = color.w * (3e3 * d * d * d).clamp(1e-2, 3e3);
+            self.accum[i] += weight * color;
+            self.revealage[i] *= 1.0 - color.w;
+        }
+    }
+
+    // ResolvePs through BlendState::premultiplied
+    pub fn resolve(&self, canvas: &mut Canvas) {
+        let width = canvas.width as usize;
+        for (i, (&accum, &revealage)) in self.accum.iter().zip(&self.revealage).enumerate() {
+            if revealage < 1.0 {
+                let color = accum.truncate() / accum.w.clamp(1e-5, 5e4);
+                let (x, y) = ((i % width) as i32, (i / width) as i32);
+                canvas.blend_premultiplied(x, y, color.extend(1.0) * (1.0 - revealage));
+            }
+        }
+    }
+}
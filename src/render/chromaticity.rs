@@ -0,0 +1,111 @@
+use super::canvas::Canvas;
+use super::svg::Svg;
+use super::Image;
+use crate::color::chromaticity::*;
+use crate::state::*;
+use glam::*;
+
+// as the executor clears behind the plot
+const CLEAR_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.25];
+
+pub struct Chromaticity {
+    pub width: u32,
+    pub height: u32,
+    diagram: Diagram,
+    counts: Vec<u32>,
+    num_pixels: u32,
+}
+
+impl Chromaticity {
+    pub fn new(state: &State, image: &Image, width: u32, height: u32) -> Option<Self> {
+        let diagram = state.chromaticity_mode.diagram()?;
+
+        Some(Self {
+            width,
+            height,
+            diagram,
+            counts: histogram(image.pixels.iter().copied(), diagram),
+            num_pixels: image.pixels.len() as u32,
+        })
+    }
+
+    // matches PlotPosition in chromaticity.hlsl, in pixels from the top left
+    fn to_pixel(&self, position: Vec2) -> Vec2 {
+        let aspect = self.width as f32 / self.height as f32;
+        let scale = if aspect > 1.0 {
+            Vec2::new(1.0 / aspect, 1.0)
+        } else {
+            Vec2::new(1.0, aspect)
+        };
+
+        let ndc = 0.9 * scale * (2.0 * position / self.diagram.range() - 1.0);
+        Vec2::new(
+            0.5 * (ndc.x + 1.0) * self.width as f32,
+            0.5 * (1.0 - ndc.y) * self.height as f32,
+        )
+    }
+
+    // the corners of the square of a bin in pixels and its color
+    fn bins(&self) -> impl Iterator<Item = (Vec2, Vec2, Vec4)> + '_ {
+        let step = self.diagram.range() / CHROMATICITY_BINS as f32;
+
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(move |(index, &count)| {
+                let bin = Vec2::new(
+                    (index % CHROMATICITY_BINS) as f32,
+                    (index / CHROMATICITY_BINS) as f32,
+                );
+                let center = self.diagram.unproject(step * (bin + 0.5));
+                let color = xy_to_rgb(center).extend(density_alpha(count, self.num_pixels));
+
+                let min = self.to_pixel(step * bin);
+                let max = self.to_pixel(step * (bin + 1.0));
+                (min, max, color)
+            })
+    }
+
+    pub fn rasterize(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height, CLEAR_COLOR);
+
+        // y grows downwards on the screen, upwards in the diagram
+        for (min, max, color) in self.bins() {
+            for y in max.y.round() as i32..min.y.round() as i32 {
+                for x in min.x.round() as i32..max.x.round() as i32 {
+                    canvas.blend_alpha(x, y, color);
+                }
+            }
+        }
+
+        let guides = self.diagram.guides();
+        for line in guides.chunks_exact(2) {
+            let (a, b) = (self.to_pixel(line[0].0), self.to_pixel(line[1].0));
+            let steps = (b - a).abs().max_element().ceil().max(1.0) as i32;
+            for s in 0..=steps {
+                let p = a.lerp(b, s as f32 / steps as f32);
+                canvas.blend_alpha(p.x as i32, p.y as i32, line[0].1);
+            }
+        }
+
+        canvas
+    }
+
+    pub fn svg(&self) -> Svg {
+        let mut svg = Svg::new(self.width, self.height);
+        svg.background(CLEAR_COLOR);
+
+        for (min, max, color) in self.bins() {
+            let square = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+            svg.polygon(&square, color);
+        }
+
+        for line in self.diagram.guides().chunks_exact(2) {
+            let points = [self.to_pixel(line[0].0), self.to_pixel(line[1].0)];
+            svg.polyline(&points, line[0].1);
+        }
+
+        svg
+    }
+}
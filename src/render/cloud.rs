@@ -0,0 +1,247 @@
+use super::canvas::{Canvas, Transparency};
+use super::svg::Svg;
+use super::{ColorBin, Image};
+use crate::analysis::compare::divergence_color;
+use crate::color::gamut::position;
+use crate::color::measure::hue;
+use crate::state::*;
+use glam::*;
+
+// as in color_cloud.hlsl
+const MAX_RATE: f32 = 0.04;
+const MIN_OPACITY: f32 = 0.15;
+
+const CLEAR_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.25];
+
+// a point of the cloud on the screen, the triangle sprite of DrawMs and DrawVs
+// cut to its inscribed disc by DrawPs
+#[derive(Clone, Copy, Debug)]
+pub struct Point {
+    // pixels from the top left
+    pub center: Vec2,
+    pub radius: f32,
+    pub depth: f32,
+    pub color: Vec3,
+    pub opacity: f32,
+}
+
+pub struct Cloud {
+    pub width: u32,
+    pub height: u32,
+    // in the order of the bins, which is the drawing order of the opaque ones
+    pub points: Vec<Point>,
+    pub weighted: bool,
+}
+
+impl Cloud {
+    pub fn new(state: &State, image: &Image, width: u32, height: u32) -> Self {
+        let bins = image.color_bins(state.cloud_bits).into_values().map(|bin| {
+            let centroid = Vec3::from(bin.centroid());
+            (bin.count as f32, centroid, centroid)
+        });
+
+        Self::build(state, width, height, image.pixels.len() as f32, bins)
+    }
+
+    // the bins of both images sized by the larger share and colored by which has more
+    pub fn compare(state: &State, a: &Image, b: &Image, width: u32, height: u32) -> Self {
+        let bits = state.cloud_bits;
+        let (na, nb) = (a.pixels.len() as f32, b.pixels.len() as f32);
+
+        // the point sits at the centroid of the colors of both images
+        let mut shares = std::collections::BTreeMap::new();
+        for (index, bin) in a.color_bins(bits) {
+            let (merged, share_a, _) =
+                shares
+                    .entry(index)
+                    .or_insert((ColorBin::default(), 0.0, 0.0));
+            merged.merge(&bin);
+            *share_a = bin.count as f32 / na;
+        }
+        for (index, bin) in b.color_bins(bits) {
+            let (merged, _, share_b) =
+                shares
+                    .entry(index)
+                    .or_insert((ColorBin::default(), 0.0, 0.0));
+            merged.merge(&bin);
+            *share_b = bin.count as f32 / nb;
+        }
+
+        let bins = shares
+            .into_values()
+            .map(|(merged, share_a, share_b): (ColorBin, f32, f32)| {
+                (
+                    share_a.max(share_b) * na,
+                    Vec3::from(merged.centroid()),
+                    divergence_color(share_a, share_b),
+                )
+            });
+
+        Self::build(state, width, height, na, bins)
+    }
+
+    // bins of the count, the centroid and the color of their point
+    fn build(
+        state: &State,
+        width: u32,
+        height: u32,
+        num_pixels: f32,
+        bins: impl Iterator<Item = (f32, Vec3, Vec3)>,
+    ) -> Self {
+        let color_space = state.color_cloud_mode.color_space().unwrap_or_default();
+
+        let aspect = width as f32 / height as f32;
+        let scale = if aspect > 1.0 {
+            Vec2::new(1.0 / aspect, 1.0)
+        } else {
+            Vec2::new(1.0, 1.0 * aspect)
+        };
+
+        let projection = state.camera.projection();
+        let size_scale = state.point_scale * state.camera.zoom;
+        let (clip_mode, clip) = state.shader_clip();
+
+        let log_weight = |count: f32| {
+            ((1.0 + count).log2() / (1.0 + MAX_RATE * num_pixels).log2()).clamp(0.0, 1.0)
+        };
+
+        let calc_size = |count: f32| {
+            let rate = (count / num_pixels).min(MAX_RATE);
+            let weight = match state.point_size {
+                PointSize::Linear => rate / MAX_RATE,
+                PointSize::Sqrt => (rate / MAX_RATE).sqrt(),
+                PointSize::Log => log_weight(count),
+                PointSize::Uniform => 0.0,
+            };
+            size_scale * (state.point_max_size * weight).max(state.point_min_size)
+        };
+
+        let calc_opacity = |count: f32| {
+            if state.point_opacity {
+                MIN_OPACITY + (1.0 - MIN_OPACITY) * log_weight(count)
+            } else {
+                1.0
+            }
+        };
+
+        let is_clipped = |rgb: Vec3, position: Vec3| match clip_mode {
+            1 => {
+                let l = lightness(color_space, rgb);
+                l < clip.x || clip.y < l
+            }
+            2 => {
+                let saturation = rgb.max_element() - rgb.min_element();
+                saturation > 0.0 && (hue(rgb) - clip.x).rem_euclid(1.0) > clip.y
+            }
+            3 => position.dot(clip.xyz()) > clip.w,
+            _ => false,
+        };
+
+        let mut points = vec![];
+
+        for (count, color, point_color) in bins {
+            let center = position(color_space, color);
+
+            if is_clipped(color, center) {
+                continue;
+            }
+
+            let clip_position = projection * center.extend(1.0);
+            // behind the eye
+            if clip_position.w <= 0.0 {
+                continue;
+            }
+
+            let depth = 0.25 * clip_position.z + 0.5 - 0.0001 * color.y;
+            if !(0.0..=1.0).contains(&depth) {
+                continue;
+            }
+
+            let ndc = 0.95 * scale * clip_position.xy() / clip_position.w;
+            let radius = 0.95 * scale.x * calc_size(count) / clip_position.w;
+
+            points.push(Point {
+                center: Vec2::new(
+                    0.5 * (ndc.x + 1.0) * width as f32,
+                    0.5 * (1.0 - ndc.y) * height as f32,
+                ),
+                radius: 0.5 * radius * width as f32,
+                depth,
+                color: point_color,
+                opacity: calc_opacity(count),
+            });
+        }
+
+        Self {
+            width,
+            height,
+            points,
+            weighted: state.point_opacity,
+        }
+    }
+
+    // the opaque points write depth, the weighted ones are blended order independently
+    pub fn rasterize(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height, CLEAR_COLOR);
+        let mut transparency = Transparency::new(&canvas);
+
+        for point in &self.points {
+            let color = (point.opacity * point.color).extend(point.opacity);
+
+            let x0 = (point.center.x - point.radius).floor() as i32;
+            let x1 = (point.center.x + point.radius).ceil() as i32;
+            let y0 = (point.center.y - point.radius).floor() as i32;
+            let y1 = (point.center.y + point.radius).ceil() as i32;
+
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    let d = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) - point.center;
+                    if d.length_squared() > point.radius * point.radius {
+                        continue;
+                    }
+
+                    if self.weighted {
+                        if canvas.depth_test(x, y, point.depth, false) {
+                            transparency.accumulate(&canvas, x, y, color, point.depth);
+                        }
+                    } else if canvas.depth_test(x, y, point.depth, true) {
+                        canvas.write(x, y, color);
+                    }
+                }
+            }
+        }
+
+        if self.weighted {
+            transparency.resolve(&mut canvas);
+        }
+
+        canvas
+    }
+
+    // circles painted back to front, svg has no order independent blending
+    pub fn svg(&self) -> Svg {
+        let mut svg = Svg::new(self.width, self.height);
+        svg.background(CLEAR_COLOR);
+
+        // a stable sort keeps the first drawn in front on equal depths
+        let mut points: Vec<&Point> = self.points.iter().collect();
+        points.reverse();
+        points.sort_by(|a, b| b.depth.total_cmp(&a.depth));
+
+        for point in points {
+            svg.circle(point.center, point.radius, point.color, point.opacity);
+        }
+
+        svg
+    }
+}
+
+// matches Lightness in color_cloud.hlsl
+fn lightness(color_space: ColorSpace, rgb: Vec3) -> f32 {
+    match color_space {
+        ColorSpace::Rgb => (rgb.x + rgb.y + rgb.z) / 3.0,
+        ColorSpace::Hsv => rgb.max_element(),
+        ColorSpace::Hsl => (rgb.max_element() + rgb.min_element()) / 2.0,
+        ColorSpace::Yuv => rgb.dot(Vec3::new(0.2126, 0.7152, 0.0722)),
+    }
+}
@@ -0,0 +1,159 @@
+// animated GIF on a fixed 6 x 6 x 6 color cube, looping forever
+
+use std::collections::HashMap;
+use std::io::Result;
+use std::path::Path;
+
+const LEVELS: u32 = 6;
+const MIN_CODE_SIZE: u8 = 8;
+const MAX_CODE: u16 = 4096;
+
+pub struct Gif {
+    width: u16,
+    height: u16,
+    data: Vec<u8>,
+}
+
+impl Gif {
+    pub fn new(width: u32, height: u32) -> Self {
+        let (width, height) = (width as u16, height as u16);
+
+        let mut data = b"GIF89a".to_vec();
+        data.extend(width.to_le_bytes());
+        data.extend(height.to_le_bytes());
+        // a global color table of 256 entries
+        data.extend([0xf7, 0, 0]);
+        for i in 0..256 {
+            let rgb = if i < LEVELS * LEVELS * LEVELS {
+                [i / (LEVELS * LEVELS), i / LEVELS % LEVELS, i % LEVELS].map(|c| (c * 51) as u8)
+            } else {
+                [0; 3]
+            };
+            data.extend(rgb);
+        }
+
+        data.extend([0x21, 0xff, 0x0b]);
+        data.extend(b"NETSCAPE2.0");
+        data.extend([0x03, 0x01, 0x00, 0x00, 0x00]);
+
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    // `delay` in hundredths of a second
+    pub fn frame(&mut self, pixels: &[[u8; 3]], delay: u16) {
+        self.data.extend([0x21, 0xf9, 0x04, 0x04]);
+        self.data.extend(delay.to_le_bytes());
+        self.data.extend([0x00, 0x00]);
+
+        self.data.push(0x2c);
+        self.data.extend([0u8; 4]);
+        self.data.extend(self.width.to_le_bytes());
+        self.data.extend(self.height.to_le_bytes());
+        self.data.push(0);
+
+        let indices: Vec<u8> = pixels.iter().map(|&rgb| quantize(rgb)).collect();
+
+        self.data.push(MIN_CODE_SIZE);
+        for block in lzw(&indices).chunks(255) {
+            self.data.push(block.len() as u8);
+            self.data.extend(block);
+        }
+        self.data.push(0);
+    }
+
+    pub fn save<P: AsRef<Path>>(mut self, path: P) -> Result<()> {
+        self.data.push(0x3b);
+        std::fs::write(path, self.data)
+    }
+}
+
+fn quantize(rgb: [u8; 3]) -> u8 {
+    let [r, g, b] = rgb.map(|c| (c as u32 * (LEVELS - 1) + 127) / 255);
+    (r * LEVELS * LEVELS + g * LEVELS + b) as u8
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    // least significant bit first
+    fn write(&mut self, code: u16, width: u32) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+fn lzw(indices: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << MIN_CODE_SIZE;
+    let end = clear + 1;
+
+    let mut writer = BitWriter {
+        bytes: vec![],
+        buffer: 0,
+        bits: 0,
+    };
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut width = MIN_CODE_SIZE as u32 + 1;
+
+    writer.write(clear, width);
+
+    let mut prefix: Option<u16> = None;
+    for &index in indices {
+        let Some(code) = prefix else {
+            prefix = Some(index as u16);
+            continue;
+        };
+
+        if let Some(&longer) = table.get(&(code, index)) {
+            prefix = Some(longer);
+            continue;
+        }
+
+        writer.write(code, width);
+        if next < MAX_CODE {
+            table.insert((code, index), next);
+            next += 1;
+            // the decoder widens one code later, when it adds the same entry
+            if next > 1 << width && width < 12 {
+                width += 1;
+            }
+        } else {
+            writer.write(clear, width);
+            table.clear();
+            next = end + 1;
+            width = MIN_CODE_SIZE as u32 + 1;
+        }
+        prefix = Some(index as u16);
+    }
+
+    if let Some(code) = prefix {
+        writer.write(code, width);
+        // the decoder adds an entry for the last code too
+        if next >= 1 << width && width < 12 {
+            width += 1;
+        }
+    }
+    writer.write(end, width);
+
+    writer.finish()
+}
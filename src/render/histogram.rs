@@ -0,0 +1,229 @@
+use super::canvas::Canvas;
+use super::svg::Svg;
+use super::Image;
+use crate::analysis::compare::COMPARE_COLORS;
+use crate::color::hsl_to_rgb;
+use crate::color::measure::hue;
+use crate::state::*;
+use glam::*;
+
+// one curve of the strip, colors as histogram_pass.rs sets them
+struct Channel {
+    bins: [u32; 256],
+    fill: [f32; 4],
+    line: [f32; 4],
+}
+
+pub struct Histogram {
+    pub width: u32,
+    pub height: u32,
+    channels: Vec<Channel>,
+    // the hue strip colors each bin
+    hue: bool,
+    // the lines are in COMPARE_COLORS rather than by bin
+    compare: bool,
+    inv_pixel_count: f32,
+}
+
+impl Histogram {
+    // only the linear strips, the joint histograms are render::joint and the
+    // wheel and the disc have no software path
+    pub fn new(state: &State, image: &Image, width: u32, height: u32) -> Option<Self> {
+        const GREY_FILL: [f32; 4] = [0.8, 0.8, 0.8, 0.6];
+        const GREY_LINE: [f32; 4] = [0.8, 0.8, 0.8, 0.9];
+
+        let mut channels = bins(state, image)?
+            .into_iter()
+            .map(|bins| Channel {
+                bins,
+                fill: GREY_FILL,
+                line: GREY_LINE,
+            })
+            .collect::<Vec<_>>();
+
+        if state.histogram_mode == HistogramMode::Rgb {
+            for (i, channel) in channels.iter_mut().enumerate() {
+                channel.fill = [0.0, 0.0, 0.0, 0.6];
+                channel.fill[i] = 0.5;
+                channel.line = [0.0, 0.0, 0.0, 0.8];
+                channel.line[i] = 0.8;
+            }
+        }
+
+        Some(Self {
+            width,
+            height,
+            channels,
+            hue: state.histogram_mode == HistogramMode::Hue(HueShape::Linear),
+            compare: false,
+            inv_pixel_count: 4.0 / image.pixels.len() as f32,
+        })
+    }
+
+    // the strips of `a` with the lines of `b` over them, `b` scaled to the pixel count of `a`
+    pub fn compare(state: &State, a: &Image, b: &Image, width: u32, height: u32) -> Option<Self> {
+        let mut histogram = Self::new(state, a, width, height)?;
+        let [color_a, color_b] = COMPARE_COLORS.map(|color| color.extend(0.9).to_array());
+
+        for channel in &mut histogram.channels {
+            channel.line = color_a;
+        }
+
+        let scale = a.pixels.len() as f32 / b.pixels.len() as f32;
+        for bins in self::bins(state, b)? {
+            histogram.channels.push(Channel {
+                bins: bins.map(|count| (scale * count as f32).round() as u32),
+                fill: [0.0; 4],
+                line: color_b,
+            });
+        }
+        histogram.compare = true;
+
+        Some(histogram)
+    }
+
+    // clip space x, y of the top of a bin
+    fn vertex(&self, index: usize, count: u32) -> Vec2 {
+        Vec2::new(
+            2.0 * (index as f32 / 255.0) - 1.0,
+            self.inv_pixel_count * count as f32 - 1.0,
+        )
+    }
+
+    // the vertex color of FillVs and LineVs
+    fn color(&self, channel: &Channel, index: usize, fill: bool) -> Vec4 {
+        if self.hue && (fill || !self.compare) {
+            let l = if fill { 0.8 } else { 0.6 };
+            let alpha = if fill {
+                channel.fill[3]
+            } else {
+                channel.line[3]
+            };
+            hsl_to_rgb(index as f32 / 256.0, 0.8, l).extend(alpha)
+        } else {
+            Vec4::from(if fill { channel.fill } else { channel.line })
+        }
+    }
+
+    fn to_pixel(&self, position: Vec2) -> Vec2 {
+        Vec2::new(
+            0.5 * (position.x + 1.0) * self.width as f32,
+            0.5 * (1.0 - position.y) * self.height as f32,
+        )
+    }
+
+    // filled with BlendState::alpha onto a transparent target, then the line on top
+    pub fn rasterize(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height, [0.0; 4]);
+
+        for channel in &self.channels {
+            // the triangle strip between the bottom and the tops of the bins
+            for x in 0..self.width as i32 {
+                if channel.fill[3] == 0.0 {
+                    break;
+                }
+
+                let ndc = 2.0 * (x as f32 + 0.5) / self.width as f32 - 1.0;
+                let t = 255.0 * (ndc + 1.0) / 2.0;
+                let (i, f) = ((t as usize).min(254), t.fract());
+
+                let a = self.vertex(i, channel.bins[i]);
+                let b = self.vertex(i + 1, channel.bins[i + 1]);
+                let top = self.to_pixel(Vec2::new(ndc, a.y + (b.y - a.y) * f)).y;
+                let color = self
+                    .color(channel, i, true)
+                    .lerp(self.color(channel, i + 1, true), f);
+
+                for y in 0..self.height as i32 {
+                    if y as f32 + 0.5 > top {
+                        canvas.blend_alpha(x, y, color);
+                    }
+                }
+            }
+
+            // the line strip, a pixel wide
+            for i in 0..255 {
+                let a = self.to_pixel(self.vertex(i, channel.bins[i]));
+                let b = self.to_pixel(self.vertex(i + 1, channel.bins[i + 1]));
+                let (ca, cb) = (
+                    self.color(channel, i, false),
+                    self.color(channel, i + 1, false),
+                );
+
+                let steps = (b - a).abs().max_element().ceil().max(1.0) as i32;
+                // the end pixel belongs to the next segment
+                for s in 0..steps {
+                    let f = s as f32 / steps as f32;
+                    let p = a.lerp(b, f);
+                    canvas.blend_alpha(p.x as i32, p.y as i32, ca.lerp(cb, f));
+                }
+            }
+        }
+
+        canvas
+    }
+
+    pub fn svg(&self) -> Svg {
+        let mut svg = Svg::new(self.width, self.height);
+
+        for channel in &self.channels {
+            let tops: Vec<Vec2> = (0..256)
+                .map(|i| self.to_pixel(self.vertex(i, channel.bins[i])))
+                .collect();
+            let bottom = self.height as f32;
+
+            if channel.fill[3] == 0.0 {
+                svg.polyline(&tops, Vec4::from(channel.line));
+            } else if self.hue {
+                // a quad and a segment per bin, each in the color of its bin
+                for i in 0..255 {
+                    let (a, b) = (tops[i], tops[i + 1]);
+                    let quad = [Vec2::new(a.x, bottom), a, b, Vec2::new(b.x, bottom)];
+                    svg.polygon(&quad, self.color(channel, i, true));
+                    svg.polyline(&[a, b], self.color(channel, i, false));
+                }
+            } else {
+                let mut area = vec![Vec2::new(tops[0].x, bottom)];
+                area.extend(&tops);
+                area.push(Vec2::new(tops[255].x, bottom));
+
+                svg.polygon(&area, Vec4::from(channel.fill));
+                svg.polyline(&tops, Vec4::from(channel.line));
+            }
+        }
+
+        svg
+    }
+}
+
+// the counts of each curve of the linear strips, R, G and B or a single one,
+// binned as CreateCs does
+pub fn bins(state: &State, image: &Image) -> Option<Vec<[u32; 256]>> {
+    let colors = image
+        .pixels
+        .iter()
+        .map(|&rgb| Vec3::from(rgb.map(|c| c as f32 / 255.0)));
+
+    let bin = |value: f32| ((255.0 * value) as usize).min(255);
+    let channel = |value: &dyn Fn(Vec3) -> f32| {
+        let mut bins = [0; 256];
+        for color in colors.clone() {
+            bins[bin(value(color))] += 1;
+        }
+        bins
+    };
+
+    Some(match state.histogram_mode {
+        HistogramMode::Rgb => vec![channel(&|c| c.x), channel(&|c| c.y), channel(&|c| c.z)],
+        HistogramMode::Hue(HueShape::Linear) => vec![channel(&hue)],
+        HistogramMode::Saturation => {
+            let measure = state.saturation_measure;
+            vec![channel(&|c| measure.of(c))]
+        }
+        HistogramMode::Brightness => {
+            let measure = state.brightness_measure;
+            vec![channel(&|c| measure.of(c))]
+        }
+        _ => return None,
+    })
+}
@@ -0,0 +1,100 @@
+use super::canvas::Canvas;
+use super::svg::Svg;
+use super::Image;
+use crate::analysis::joint::*;
+use crate::color::hsv_to_rgb;
+use crate::state::*;
+use glam::*;
+
+// the alpha of JOINT_COLOR in histogram_pass.rs
+const JOINT_ALPHA: f32 = 0.9;
+
+// the heat map of JointVs, two axes of the joint histogram
+pub struct Joint {
+    pub width: u32,
+    pub height: u32,
+    counts: Vec<u32>,
+    inv_pixel_count: f32,
+    log_scale: bool,
+}
+
+impl Joint {
+    pub fn new(state: &State, image: &Image, width: u32, height: u32) -> Option<Self> {
+        let HistogramMode::Joint(axes) = state.histogram_mode else {
+            return None;
+        };
+
+        Some(Self {
+            width,
+            height,
+            counts: histogram(
+                image.pixels.iter().copied(),
+                axes,
+                state.saturation_measure,
+                state.brightness_measure,
+            ),
+            inv_pixel_count: 4.0 / image.pixels.len() as f32,
+            log_scale: state.histogram_log,
+        })
+    }
+
+    // matches JointDensity in histogram.hlsl
+    fn density(&self, count: u32) -> f32 {
+        let density = self.inv_pixel_count * count as f32 * (JOINT_BINS * JOINT_BINS) as f32 / 4.0;
+        if self.log_scale {
+            ((1.0 + density).log2() / 4.0).clamp(0.0, 1.0)
+        } else {
+            (density / 16.0).clamp(0.0, 1.0)
+        }
+    }
+
+    // the corners of the square of a bin in pixels and its color, as JointVs
+    // squeezes x to a square in the middle
+    fn bins(&self) -> impl Iterator<Item = (Vec2, Vec2, Vec4)> + '_ {
+        let scale = self.height as f32 / self.width as f32;
+        let to_pixel = move |corner: Vec2| {
+            let position = 2.0 * corner / JOINT_BINS as f32 - 1.0;
+            Vec2::new(
+                0.5 * (scale * position.x + 1.0) * self.width as f32,
+                0.5 * (1.0 - position.y) * self.height as f32,
+            )
+        };
+
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(move |(index, &count)| {
+                let bin = Vec2::new((index % JOINT_BINS) as f32, (index / JOINT_BINS) as f32);
+                let t = self.density(count);
+                let color = hsv_to_rgb((1.0 - t) * 2.0 / 3.0, 0.9, 0.9).extend(JOINT_ALPHA);
+                (to_pixel(bin), to_pixel(bin + 1.0), color)
+            })
+    }
+
+    pub fn rasterize(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height, [0.0; 4]);
+
+        // y grows downwards on the screen, upwards in the histogram
+        for (min, max, color) in self.bins() {
+            for y in max.y.round() as i32..min.y.round() as i32 {
+                for x in min.x.round() as i32..max.x.round() as i32 {
+                    canvas.blend_alpha(x, y, color);
+                }
+            }
+        }
+
+        canvas
+    }
+
+    pub fn svg(&self) -> Svg {
+        let mut svg = Svg::new(self.width, self.height);
+
+        for (min, max, color) in self.bins() {
+            let square = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+            svg.polygon(&square, color);
+        }
+
+        svg
+    }
+}
@@ -0,0 +1,79 @@
+// PNG with stored deflate blocks, large but without a compression library
+
+use std::io::Result;
+use std::path::Path;
+
+pub fn save<P: AsRef<Path>>(path: P, width: u32, height: u32, pixels: &[[u8; 4]]) -> Result<()> {
+    // 8 bit RGBA, non interlaced
+    let mut header = vec![];
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    header.extend([8, 6, 0, 0, 0]);
+
+    // each row starts with filter type none
+    let mut raw = Vec::with_capacity(pixels.len() * 4 + height as usize);
+    for row in pixels.chunks(width as usize) {
+        raw.push(0);
+        raw.extend(row.iter().flatten());
+    }
+
+    let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut data, b"IHDR", &header);
+    chunk(&mut data, b"IDAT", &zlib_stored(&raw));
+    chunk(&mut data, b"IEND", &[]);
+
+    std::fs::write(path, data)
+}
+
+fn chunk(data: &mut Vec<u8>, kind: &[u8; 4], body: &[u8]) {
+    data.extend((body.len() as u32).to_be_bytes());
+    let start = data.len();
+    data.extend(kind);
+    data.extend(body);
+    let crc = crc32(&data[start..]);
+    data.extend(crc.to_be_bytes());
+}
+
+fn zlib_stored(raw: &[u8]) -> Vec<u8> {
+    let mut data = vec![0x78, 0x01];
+
+    let mut blocks = raw.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        data.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        data.push(last as u8);
+        data.extend(len.to_le_bytes());
+        data.extend((!len).to_le_bytes());
+        data.extend(block);
+    }
+
+    data.extend(adler32(raw).to_be_bytes());
+    data
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xedb88320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
@@ -0,0 +1,98 @@
+use glam::{Vec2, Vec3, Vec4};
+use std::fmt::Write;
+use std::path::Path;
+
+pub struct Svg {
+    width: u32,
+    height: u32,
+    body: String,
+}
+
+impl Svg {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            body: String::new(),
+        }
+    }
+
+    // colors are premultiplied as on the canvas
+    pub fn background(&mut self, color: [f32; 4]) {
+        let color = Vec4::from(color);
+        let _ = writeln!(
+            self.body,
+            r#"<rect width="100%" height="100%" fill="{}" fill-opacity="{:.3}"/>"#,
+            hex(unpremultiply(color)),
+            color.w
+        );
+    }
+
+    pub fn circle(&mut self, center: Vec2, radius: f32, color: Vec3, opacity: f32) {
+        let _ = write!(
+            self.body,
+            r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}""#,
+            center.x,
+            center.y,
+            radius,
+            hex(color)
+        );
+        if opacity < 1.0 {
+            let _ = write!(self.body, r#" fill-opacity="{:.3}""#, opacity);
+        }
+        self.body.push_str("/>\n");
+    }
+
+    // straight alpha as BlendState::alpha takes it
+    pub fn polygon(&mut self, points: &[Vec2], color: Vec4) {
+        let _ = writeln!(
+            self.body,
+            r#"<polygon points="{}" fill="{}" fill-opacity="{:.3}"/>"#,
+            coordinates(points),
+            hex(color.truncate()),
+            color.w
+        );
+    }
+
+    pub fn polyline(&mut self, points: &[Vec2], color: Vec4) {
+        let _ = writeln!(
+            self.body,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-opacity="{:.3}" stroke-width="1"/>"#,
+            coordinates(points),
+            hex(color.truncate()),
+            color.w
+        );
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let text = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n{2}</svg>\n",
+            self.width, self.height, self.body
+        );
+        std::fs::write(path, text)
+    }
+}
+
+fn unpremultiply(color: Vec4) -> Vec3 {
+    if color.w > 0.0 {
+        color.truncate() / color.w
+    } else {
+        color.truncate()
+    }
+}
+
+fn hex(color: Vec3) -> String {
+    let [r, g, b] = color
+        .clamp(Vec3::ZERO, Vec3::ONE)
+        .to_array()
+        .map(|c| (255.0 * c).round() as u8);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn coordinates(points: &[Vec2]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{:.2},{:.2}", p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
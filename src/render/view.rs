@@ -0,0 +1,253 @@
+use super::{png, Image};
+use crate::analysis::compare::heat;
+use crate::analysis::harmony;
+use crate::color::difference::delta_e2000;
+use crate::color::measure::hue;
+use crate::color::naming::LUT_BITS;
+use crate::color::*;
+use crate::state::*;
+use glam::*;
+use std::io::Result;
+use std::path::Path;
+
+// the view mode of `state` over the whole image as ViewPs draws it
+pub fn render(state: &State, image: &Image) -> Vec<[u8; 3]> {
+    let (width, height) = (image.width as i32, image.height as i32);
+    let colors: Vec<Vec3> = image.pixels.iter().map(|&rgb| from_rgb8(rgb)).collect();
+
+    let shade: Box<dyn Fn(usize, Vec3) -> Vec3 + '_> = match state.view_mode {
+        ViewMode::Original => Box::new(|_, color| color),
+        ViewMode::Rgb(mask) => {
+            let mask = Vec3::new(
+                mask.at(0) as u32 as f32,
+                mask.at(1) as u32 as f32,
+                mask.at(2) as u32 as f32,
+            );
+            Box::new(move |_, color| mask * color)
+        }
+        ViewMode::Hue => Box::new(|_, color| hsl_to_rgb(hue(color), 0.8, 0.8)),
+        ViewMode::Saturation => {
+            let measure = state.saturation_measure;
+            Box::new(move |_, color| {
+                let saturation = measure.of(color);
+                let hue = (-120.0 + 180.0 * saturation) / 360.0;
+                hsv_to_rgb(hue, 0.8, saturation)
+            })
+        }
+        ViewMode::Brightness => {
+            let measure = state.brightness_measure;
+            Box::new(move |_, color| Vec3::splat(measure.of(color)))
+        }
+        ViewMode::Harmony => {
+            // fitted on the image as the executor does on the frame
+            let harmony = state.harmony.or_else(|| {
+                harmony::fit(
+                    &weighted_hue_histogram(&colors),
+                    state.harmony_mode.template(),
+                )
+            });
+            let (centers, half_widths, num) = harmony
+                .map(|harmony| harmony.shader_sectors())
+                .unwrap_or_default();
+
+            Box::new(move |_, color| {
+                if num == 0 {
+                    return color;
+                }
+
+                let in_harmony = (0..num as usize).any(|i| {
+                    let d = (hue(color) - centers[i]).rem_euclid(1.0);
+                    d.min(1.0 - d) <= half_widths[i]
+                });
+                if SaturationMeasure::HsvS.of(color) < 0.1 || in_harmony {
+                    Vec3::splat(0.5 * BrightnessMeasure::Luma.of(color))
+                } else {
+                    color
+                }
+            })
+        }
+        ViewMode::DeltaE => match state.target {
+            Some(target) => {
+                let target = rgb_to_lab(from_rgb8(target));
+                let formula = state.delta_e;
+                Box::new(move |_, color| {
+                    let t =
+                        (formula.between(target, rgb_to_lab(color)) / MAX_DELTA_E).clamp(0.0, 1.0);
+                    hsv_to_rgb((1.0 - t) * 2.0 / 3.0, 0.9, 0.9)
+                })
+            }
+            None => Box::new(|_, color| color),
+        },
+        ViewMode::Notan => {
+            let measure = state.brightness_measure;
+            let thresholds = state.shader_notan_thresholds();
+            let num = thresholds.iter().filter(|&&t| t <= 1.0).count().max(1);
+
+            Box::new(move |_, color| {
+                let brightness = measure.of(color);
+                let group = thresholds.iter().filter(|&&t| brightness >= t).count();
+                Vec3::splat(group as f32 / num as f32)
+            })
+        }
+        ViewMode::Posterize => {
+            let n = state.posterize_levels as f32;
+            let posterize = move |value: f32| (n * value).floor().min(n - 1.0) / (n - 1.0).max(1.0);
+
+            if state.posterize_lightness {
+                let measure = state.brightness_measure;
+                Box::new(move |_, color| {
+                    let brightness = measure.of(color);
+                    (color + posterize(brightness) - brightness).clamp(Vec3::ZERO, Vec3::ONE)
+                })
+            } else {
+                Box::new(move |_, color| color.to_array().map(posterize).into())
+            }
+        }
+        ViewMode::LocalContrast => {
+            let measure = state.brightness_measure;
+            let brightness: Vec<f32> = colors.iter().map(|&c| measure.of(c)).collect();
+
+            Box::new(move |index, _| {
+                let (x, y) = ((index as i32) % width, (index as i32) / width);
+                let b = |dx: i32, dy: i32| {
+                    let px = (x + dx).clamp(0, width - 1);
+                    let py = (y + dy).clamp(0, height - 1);
+                    brightness[(py * width + px) as usize]
+                };
+
+                let gx =
+                    (b(1, -1) + 2.0 * b(1, 0) + b(1, 1)) - (b(-1, -1) + 2.0 * b(-1, 0) + b(-1, 1));
+                let gy =
+                    (b(-1, 1) + 2.0 * b(0, 1) + b(1, 1)) - (b(-1, -1) + 2.0 * b(0, -1) + b(1, -1));
+                Vec3::splat((gx.hypot(gy) / 4.0).clamp(0.0, 1.0))
+            })
+        }
+        ViewMode::Palette | ViewMode::PaletteCompliance => match &state.palette {
+            Some(palette) => {
+                // the same lookup table the view pass reads
+                let lut = palette.lut();
+
+                if state.view_mode == ViewMode::Palette {
+                    Box::new(move |_, color| {
+                        let cell = (255.0 * color).as_uvec3() >> (8 - LUT_BITS);
+                        let index = cell.z << (2 * LUT_BITS) | cell.y << LUT_BITS | cell.x;
+                        from_rgb8(palette.names[lut[index as usize] as usize].rgb)
+                    })
+                } else {
+                    let (formula, tolerance) = (state.delta_e, state.palette_tolerance);
+                    Box::new(move |_, color| {
+                        let (_, delta_e) = palette::closest(palette, &lut, color, formula);
+                        if delta_e <= tolerance {
+                            color
+                        } else {
+                            Vec3::new(1.0, 0.0, 1.0)
+                        }
+                    })
+                }
+            }
+            None => Box::new(|_, color| color),
+        },
+        ViewMode::Compare => match &state.compare {
+            Some(reference) => Box::new(move |index, color| {
+                let (x, y) = ((index as i32) % width, (index as i32) / width);
+                if x < reference.width as i32 && y < reference.height as i32 {
+                    let rgb = reference.pixels[(y * reference.width as i32 + x) as usize];
+                    heat(delta_e2000(rgb_to_lab(color), rgb_to_lab(from_rgb8(rgb))))
+                } else {
+                    // beyond the compared image
+                    Vec3::splat(0.5 * BrightnessMeasure::Luma.of(color))
+                }
+            }),
+            None => Box::new(|_, color| color),
+        },
+        ViewMode::Squint => {
+            // the brightness of the blurred frame, as the view pass binds it
+            let blurred = blur(
+                &colors,
+                image.width as usize,
+                image.height as usize,
+                state.squint_radius,
+                state.squint_blur,
+            );
+            let measure = state.brightness_measure;
+            Box::new(move |index, _| Vec3::splat(measure.of(blurred[index])))
+        }
+        ViewMode::Selection => match state.selection {
+            Some(selection) => {
+                let (saturation, brightness) = (state.saturation_measure, state.brightness_measure);
+                let grey = state.selection_grey;
+                Box::new(move |_, color| {
+                    if selection.contains(color, saturation, brightness) {
+                        color
+                    } else if grey {
+                        Vec3::splat(BrightnessMeasure::Luma.of(color))
+                    } else {
+                        0.25 * color
+                    }
+                })
+            }
+            None => Box::new(|_, color| color),
+        },
+    };
+
+    colors
+        .iter()
+        .enumerate()
+        .map(|(index, &color)| {
+            let c = shade(index, color).clamp(Vec3::ZERO, Vec3::ONE) * 255.0;
+            [c.x, c.y, c.z].map(|v| v.round() as u8)
+        })
+        .collect()
+}
+
+// the view at the full resolution of the image
+pub fn save<P: AsRef<Path>>(state: &State, image: &Image, path: P) -> Result<()> {
+    let pixels: Vec<[u8; 4]> = render(state, image)
+        .into_iter()
+        .map(|[r, g, b]| [r, g, b, 0xff])
+        .collect();
+    png::save(path, image.width, image.height, &pixels)
+}
+
+// the separable blur of blur.hlsl, the edge repeated and the passes stored as 8 bit
+fn blur(colors: &[Vec3], width: usize, height: usize, radius: u32, kind: Blur) -> Vec<Vec3> {
+    let radius = radius as i32;
+    let sigma = (radius as f32 / 3.0).max(0.5);
+    let weights: Vec<f32> = (-radius..=radius)
+        .map(|offset| match kind {
+            Blur::Gaussian => (-((offset * offset) as f32) / (2.0 * sigma * sigma)).exp(),
+            Blur::Box => 1.0,
+        })
+        .collect();
+    let total = weights.iter().sum::<f32>();
+    let quantize = |color: Vec3| (255.0 * color).round() / 255.0;
+
+    let pass = |src: &[Vec3], step: (i32, i32)| -> Vec<Vec3> {
+        (0..width * height)
+            .map(|index| {
+                let (x, y) = ((index % width) as i32, (index / width) as i32);
+                let sum = (-radius..=radius)
+                    .zip(&weights)
+                    .map(|(i, &w)| {
+                        let px = (x + i * step.0).clamp(0, width as i32 - 1);
+                        let py = (y + i * step.1).clamp(0, height as i32 - 1);
+                        w * src[py as usize * width + px as usize]
+                    })
+                    .sum::<Vec3>();
+                quantize(sum / total)
+            })
+            .collect()
+    };
+
+    pass(&pass(colors, (1, 0)), (0, 1))
+}
+
+// matches CreateWeightedHueHistogram in histogram.hlsl
+fn weighted_hue_histogram(colors: &[Vec3]) -> Vec<u32> {
+    let mut histogram = vec![0; 256];
+    for &color in colors {
+        let saturation = SaturationMeasure::HsvS.of(color);
+        histogram[(255.0 * hue(color)) as usize] += (255.0 * saturation) as u32;
+    }
+    histogram
+}
@@ -1,7 +1,24 @@
-use glam::Quat;
+use glam::{Mat4, Quat, Vec2, Vec3, Vec4};
 use windows::Win32::Foundation::RECT;
 
+pub use crate::analysis::barcode::BarcodeColor;
+pub use crate::analysis::compare::Comparison;
+pub use crate::analysis::harmony::{Harmony, HarmonyTemplate};
+pub use crate::analysis::joint::JointAxes;
+pub use crate::analysis::probe::Probe;
+pub use crate::analysis::stats::Stats;
+pub use crate::camera::{Camera, CameraPreset};
+pub use crate::color::chromaticity::Diagram;
+pub use crate::color::difference::DeltaE;
+pub use crate::color::gamut::Gamut;
+pub use crate::color::measure::{BrightnessMeasure, SaturationMeasure};
+pub use crate::color::naming::{Dictionary, DictionaryKind, Naming};
+pub use crate::color::palette::Coverage;
+pub use crate::color::ycbcr::YcbcrMatrix;
+#[cfg(windows)]
 pub use crate::gui::*;
+use crate::render::Image;
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 
 #[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
@@ -13,6 +30,32 @@ pub enum ColorSpace {
     Yuv,
 }
 
+impl ColorSpace {
+    pub const ALL: [Self; 4] = [Self::Rgb, Self::Hsv, Self::Hsl, Self::Yuv];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Self::Rgb => "rgb",
+            Self::Hsv => "hsv",
+            Self::Hsl => "hsl",
+            Self::Yuv => "yuv",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|space| space.name() == name)
+    }
+
+    // looking along the lightness axis of the cloud, the grey diagonal of the
+    // RGB cube or the vertical axis of the others
+    pub fn lightness_view(&self) -> CameraPreset {
+        match *self {
+            Self::Rgb => CameraPreset::Diagonal,
+            Self::Hsv | Self::Hsl | Self::Yuv => CameraPreset::Top,
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Default, Debug)]
 pub struct ChannelMask(pub [bool; 3]);
 
@@ -26,6 +69,12 @@ impl ChannelMask {
     }
 }
 
+// delta E drawn in full red by the heat map
+pub const MAX_DELTA_E: f32 = 20.0;
+
+// value groups of the notan view are split by up to this many brightness thresholds
+pub const MAX_NOTAN_THRESHOLDS: usize = 4;
+
 #[derive(Clone, Copy, PartialEq, Default, Debug)]
 pub enum ViewMode {
     #[default]
@@ -34,6 +83,16 @@ pub enum ViewMode {
     Hue,
     Saturation,
     Brightness,
+    Harmony,
+    DeltaE,
+    Notan,
+    Posterize,
+    Squint,
+    LocalContrast,
+    Palette,
+    PaletteCompliance,
+    Selection,
+    Compare,
 }
 
 impl ViewMode {
@@ -43,6 +102,193 @@ impl ViewMode {
             _ => true,
         }
     }
+
+    // as `iromiru render --view-mode` and the automation API take it, the
+    // channels of rgb follow a colon as in rgb:rb
+    pub fn name(&self) -> String {
+        let name = match *self {
+            Self::Original => "original",
+            Self::Rgb(mask) => {
+                let channels = "rgb"
+                    .chars()
+                    .enumerate()
+                    .filter(|&(i, _)| mask.at(i))
+                    .map(|(_, c)| c)
+                    .collect::<String>();
+                return format!("rgb:{}", channels);
+            }
+            Self::Hue => "hue",
+            Self::Saturation => "saturation",
+            Self::Brightness => "brightness",
+            Self::Harmony => "harmony",
+            Self::DeltaE => "delta-e",
+            Self::Notan => "notan",
+            Self::Posterize => "posterize",
+            Self::Squint => "squint",
+            Self::LocalContrast => "contrast",
+            Self::Palette => "palette",
+            Self::PaletteCompliance => "compliance",
+            Self::Selection => "selection",
+            Self::Compare => "compare",
+        };
+        name.to_string()
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(channels) = name.strip_prefix("rgb:") {
+            return Some(Self::Rgb(ChannelMask::new(
+                channels.contains('r'),
+                channels.contains('g'),
+                channels.contains('b'),
+            )));
+        }
+
+        Some(match name {
+            "original" => Self::Original,
+            "rgb" => Self::Rgb(ChannelMask::new(true, true, true)),
+            "hue" => Self::Hue,
+            "saturation" => Self::Saturation,
+            "brightness" => Self::Brightness,
+            "harmony" => Self::Harmony,
+            "delta-e" => Self::DeltaE,
+            "notan" => Self::Notan,
+            "posterize" => Self::Posterize,
+            "squint" => Self::Squint,
+            "contrast" => Self::LocalContrast,
+            "palette" => Self::Palette,
+            "compliance" => Self::PaletteCompliance,
+            "selection" => Self::Selection,
+            "compare" => Self::Compare,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum HueShape {
+    #[default]
+    Linear,
+    Wheel,
+    Disc,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum Blur {
+    #[default]
+    Gaussian,
+    Box,
+}
+
+// colors picked on a histogram or in the color cloud, shown by the selection view
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Selection {
+    // bins lo..=hi of a hue, saturation or brightness histogram
+    Bins {
+        mode: HistogramMode,
+        lo: u32,
+        hi: u32,
+    },
+    // screen box of the cloud seen through `projection`, through all depths
+    Box {
+        color_space: ColorSpace,
+        projection: Mat4,
+        min: Vec2,
+        max: Vec2,
+    },
+    // view space sphere of the cloud seen with `rotation`
+    Sphere {
+        color_space: ColorSpace,
+        rotation: Quat,
+        center: Vec3,
+        radius: f32,
+    },
+}
+
+impl Selection {
+    // matches IsSelected of SelectCs in color_cloud.hlsl
+    pub fn contains(
+        &self,
+        rgb: Vec3,
+        saturation_measure: SaturationMeasure,
+        brightness_measure: BrightnessMeasure,
+    ) -> bool {
+        let projected = |color_space: ColorSpace, projection: Mat4| {
+            let clip = projection * crate::color::gamut::position(color_space, rgb).extend(1.0);
+            clip.truncate() / clip.w
+        };
+
+        match *self {
+            Self::Bins { mode, lo, hi } => {
+                let value = match mode {
+                    HistogramMode::Hue(_) => crate::color::measure::hue(rgb),
+                    HistogramMode::Saturation => saturation_measure.of(rgb),
+                    _ => brightness_measure.of(rgb),
+                };
+                let bin = (255.0 * value).floor();
+                lo as f32 <= bin && bin <= hi as f32
+            }
+            Self::Box {
+                color_space,
+                projection,
+                min,
+                max,
+            } => {
+                let position = projected(color_space, projection).truncate();
+                position.cmpge(min).all() && position.cmple(max).all()
+            }
+            Self::Sphere {
+                color_space,
+                rotation,
+                center,
+                radius,
+            } => {
+                let projection = Mat4::from_quat(rotation).inverse();
+                projected(color_space, projection).distance(center) <= radius
+            }
+        }
+    }
+}
+
+// how the pixel count of a color maps to its point size in the IroSphere
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum PointSize {
+    Linear,
+    #[default]
+    Sqrt,
+    Log,
+    Uniform,
+}
+
+impl PointSize {
+    pub const ALL: [Self; 4] = [Self::Linear, Self::Sqrt, Self::Log, Self::Uniform];
+}
+
+// thickness of the lightness slab of the slice view
+pub const CLIP_SLICE_THICKNESS: f32 = 0.04;
+
+// the part of the color cloud drawn, the shaders switch on the discriminant
+// of the mode drawn, Slice being a thin Lightness slab. Lightness and Slice cut
+// across the lightness axis of the color space, whatever the camera, while
+// Plane keeps the plane that faced the camera when it was chosen
+#[derive(Clone, Copy, Eq, PartialEq, Default, Hash, Debug)]
+pub enum CloudClip {
+    #[default]
+    Disable,
+    Lightness,
+    Hue,
+    Plane,
+    // the hue and chroma plane at one lightness, seen along the lightness axis
+    Slice,
+}
+
+impl CloudClip {
+    pub const ALL: [Self; 5] = [
+        Self::Disable,
+        Self::Lightness,
+        Self::Hue,
+        Self::Plane,
+        Self::Slice,
+    ];
 }
 
 #[derive(Clone, Copy, PartialEq, Default, Debug)]
@@ -50,9 +296,10 @@ pub enum HistogramMode {
     #[default]
     Disable,
     Rgb,
-    Hue,
+    Hue(HueShape),
     Saturation,
     Brightness,
+    Joint(JointAxes),
 }
 
 impl HistogramMode {
@@ -62,6 +309,40 @@ impl HistogramMode {
             _ => true,
         }
     }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Self::Disable => "disable",
+            Self::Rgb => "rgb",
+            Self::Hue(HueShape::Linear) => "hue",
+            Self::Hue(HueShape::Wheel) => "hue-wheel",
+            Self::Hue(HueShape::Disc) => "hue-disc",
+            Self::Saturation => "saturation",
+            Self::Brightness => "brightness",
+            Self::Joint(JointAxes::HueSaturation) => "joint-hue-saturation",
+            Self::Joint(JointAxes::HueBrightness) => "joint-hue-brightness",
+            Self::Joint(JointAxes::ChromaBrightness) => "joint-chroma-brightness",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let shapes = [HueShape::Linear, HueShape::Wheel, HueShape::Disc].map(Self::Hue);
+        let joints = JointAxes::ALL.map(Self::Joint);
+
+        [Self::Disable, Self::Rgb, Self::Saturation, Self::Brightness]
+            .into_iter()
+            .chain(shapes)
+            .chain(joints)
+            .find(|mode| mode.name() == name)
+    }
+
+    // a strip of 256 bins across the window, which a selection can brush
+    pub fn is_brushable(&self) -> bool {
+        matches!(
+            *self,
+            Self::Hue(HueShape::Linear) | Self::Saturation | Self::Brightness
+        )
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Default, Debug)]
@@ -85,22 +366,230 @@ impl ColorCloudMode {
             ColorCloudMode::Enable(color_space) => Some(color_space),
         }
     }
+
+    // disable or the color space
+    pub fn name(&self) -> &'static str {
+        self.color_space().map_or("disable", |space| space.name())
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "disable" => Some(Self::Disable),
+            _ => ColorSpace::from_name(name).map(Self::Enable),
+        }
+    }
+}
+
+// the CIE chromaticity diagram the colors of the frame are plotted on
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub enum ChromaticityMode {
+    #[default]
+    Disable,
+    Enable(Diagram),
+}
+
+impl ChromaticityMode {
+    pub fn is_enable(&self) -> bool {
+        !matches!(*self, Self::Disable)
+    }
+
+    pub fn diagram(&self) -> Option<Diagram> {
+        match *self {
+            Self::Disable => None,
+            Self::Enable(diagram) => Some(diagram),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub enum HarmonyMode {
+    #[default]
+    Disable,
+    Auto,
+    Fixed(HarmonyTemplate),
+}
+
+impl HarmonyMode {
+    pub fn is_enable(&self) -> bool {
+        !matches!(*self, Self::Disable)
+    }
+
+    pub fn template(&self) -> Option<HarmonyTemplate> {
+        match *self {
+            Self::Fixed(template) => Some(template),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub enum NamingMode {
+    #[default]
+    Disable,
+    Enable(DictionaryKind),
+}
+
+impl NamingMode {
+    pub fn is_enable(&self) -> bool {
+        !matches!(*self, Self::Disable)
+    }
+
+    pub fn dictionary(&self) -> Option<DictionaryKind> {
+        match *self {
+            Self::Disable => None,
+            Self::Enable(kind) => Some(kind),
+        }
+    }
 }
 
 #[derive(Clone, Default)]
 pub struct State {
     pub active: bool,
     pub rect: RECT,
+    pub cursor: Option<(i32, i32)>,
     pub view_mode: ViewMode,
     pub histogram_mode: HistogramMode,
+    pub histogram_log: bool,
+    pub saturation_measure: SaturationMeasure,
+    pub brightness_measure: BrightnessMeasure,
+    pub notan_thresholds: Vec<f32>,
+    pub posterize_levels: u32,
+    pub posterize_lightness: bool,
+    pub squint_radius: u32,
+    pub squint_blur: Blur,
+    pub squint_analysis: bool,
     pub color_cloud_mode: ColorCloudMode,
-    pub rotation: Quat,
+    pub cloud_bits: u32,
+    pub cloud_guides: bool,
+    // a wider gamut drawn around the sRGB hull
+    pub cloud_gamut: Option<Gamut>,
+    pub point_size: PointSize,
+    pub point_min_size: f32,
+    pub point_max_size: f32,
+    pub point_scale: f32,
+    pub point_opacity: bool,
+    pub clip: CloudClip,
+    // lightness from, to in 0 ~ 1
+    pub clip_lightness: (f32, f32),
+    // hue from, to in turns, wrapping around red
+    pub clip_hue: (f32, f32),
+    // the points behind the plane at `clip_depth` along `clip_normal` are kept
+    pub clip_normal: Vec3,
+    pub clip_depth: f32,
+    pub clip_slice: f32,
+    pub chromaticity_mode: ChromaticityMode,
+    pub harmony_mode: HarmonyMode,
+    pub naming_mode: NamingMode,
+    pub custom_dictionary: Option<Arc<Dictionary>>,
+    pub target: Option<[u8; 3]>,
+    pub delta_e: DeltaE,
+    pub palette: Option<Arc<Dictionary>>,
+    pub palette_tolerance: f32,
+    pub camera: Camera,
+    pub selection: Option<Selection>,
+    pub selection_grey: bool,
+    // a PNG the view of the next frame is exported to
+    pub view_export: Option<PathBuf>,
+    // a CSV or JSON file the statistics of each frame are recorded to
+    pub stats_recording: Option<PathBuf>,
+    pub stats_chart: bool,
+    // seconds shown by the strip chart
+    pub stats_window: f32,
+    // a screen area analyzed in place of the one under the window
+    pub capture_rect: Option<RECT>,
+    // the last frame is held and analyzed again instead of new ones
+    pub frozen: bool,
+    // the executor takes the next frame into `frame` and the statistics of it
+    // into `stats`, then clears these
+    pub frame_request: bool,
+    pub stats_request: bool,
+    // a Y4M or raw RGB file analyzed in place of the screen
+    pub video: Option<PathBuf>,
+    pub video_frame: usize,
+    pub ycbcr_matrix: YcbcrMatrix,
+    pub barcode_color: BarcodeColor,
+    // a PNG the barcode of the open video is exported to, with the shots as JSON
+    pub barcode_export: Option<PathBuf>,
+    // the image the frame is compared with, from its top left
+    pub compare: Option<Arc<Image>>,
+    // the next frame is taken as the compared image
+    pub compare_snapshot: bool,
+
+    pub harmony: Option<Harmony>,
+    pub probe: Option<Probe>,
+    pub naming: Option<Arc<Naming>>,
+    pub coverage: Option<Arc<Coverage>>,
+    // frames of the open video
+    pub video_len: usize,
+    pub comparison: Option<Comparison>,
+    pub frame: Option<Arc<Image>>,
+    pub stats: Option<Stats>,
 }
 
 impl State {
-    pub fn move_camera(&mut self, dx: f32, dy: f32) {
-        self.rotation *= Quat::from_rotation_x((180.0 * dy).to_radians());
-        self.rotation *= Quat::from_rotation_y((180.0 * dx).to_radians());
+    pub fn needs_harmony(&self) -> bool {
+        self.harmony_mode.is_enable() || self.view_mode == ViewMode::Harmony
+    }
+
+    pub fn needs_stats(&self) -> bool {
+        self.stats_recording.is_some() || self.stats_chart || self.stats_request
+    }
+
+    pub fn needs_snapshot(&self) -> bool {
+        self.view_export.is_some()
+            || self.compare_snapshot
+            || self.compare.is_some()
+            || self.frame_request
+            || self.frozen
+    }
+
+    pub fn needs_blur(&self) -> bool {
+        self.view_mode == ViewMode::Squint || self.squint_analysis
+    }
+
+    // the color cloud merges the colors into coarser bins
+    pub fn needs_color_bins(&self) -> bool {
+        self.color_cloud_mode.is_enable() && self.cloud_bits < 8
+    }
+
+    pub fn needs_color_count(&self) -> bool {
+        self.color_cloud_mode.is_enable() || self.naming_mode.is_enable() || self.palette.is_some()
+    }
+
+    // mode and parameters of IsClipped in color_cloud.hlsl
+    pub fn shader_clip(&self) -> (u32, Vec4) {
+        match self.clip {
+            CloudClip::Disable => (0, Vec4::ZERO),
+            CloudClip::Lightness => {
+                let (from, to) = self.clip_lightness;
+                (1, Vec4::new(from, to, 0.0, 0.0))
+            }
+            CloudClip::Slice => {
+                let half = 0.5 * CLIP_SLICE_THICKNESS;
+                (
+                    1,
+                    Vec4::new(self.clip_slice - half, self.clip_slice + half, 0.0, 0.0),
+                )
+            }
+            CloudClip::Hue => {
+                let (from, to) = self.clip_hue;
+                let width = (to - from).rem_euclid(1.0);
+                (
+                    2,
+                    Vec4::new(from, if width == 0.0 { 1.0 } else { width }, 0.0, 0.0),
+                )
+            }
+            CloudClip::Plane => (3, (-self.clip_normal).extend(-self.clip_depth)),
+        }
+    }
+
+    // unused thresholds are above any brightness
+    pub fn shader_notan_thresholds(&self) -> [f32; MAX_NOTAN_THRESHOLDS] {
+        let mut thresholds = [2.0; MAX_NOTAN_THRESHOLDS];
+        for (dst, &src) in thresholds.iter_mut().zip(&self.notan_thresholds) {
+            *dst = src;
+        }
+        thresholds
     }
 }
 
@@ -112,7 +601,7 @@ macro_rules! impl_accessor {
         #[allow(unused)]
         pub fn $getter(&self) -> $type {
             if let Ok(state) = self.0.read() {
-                state.$name
+                state.$name.clone()
             } else {
                 Default::default()
             }
@@ -131,7 +620,20 @@ impl RefState {
     pub fn new() -> Self {
         Self(Arc::new(RwLock::new(State {
             active: true,
-            rotation: Quat::IDENTITY,
+            notan_thresholds: vec![0.5],
+            posterize_levels: 4,
+            squint_radius: 8,
+            palette_tolerance: 5.0,
+            cloud_bits: 8,
+            cloud_guides: true,
+            point_min_size: 0.001,
+            point_max_size: 0.2,
+            point_scale: 1.0,
+            clip_lightness: (0.25, 0.75),
+            clip_hue: (0.0, 1.0 / 6.0),
+            clip_normal: Vec3::Z,
+            clip_slice: 0.5,
+            stats_window: 10.0,
             ..Default::default()
         })))
     }
@@ -146,17 +648,161 @@ impl RefState {
         set_histogram_mode
     );
 
+    impl_accessor!(histogram_log: bool, is_histogram_log, set_histogram_log);
+
+    impl_accessor!(
+        saturation_measure: SaturationMeasure,
+        saturation_measure,
+        set_saturation_measure
+    );
+
+    impl_accessor!(
+        brightness_measure: BrightnessMeasure,
+        brightness_measure,
+        set_brightness_measure
+    );
+
+    impl_accessor!(
+        notan_thresholds: Vec<f32>,
+        notan_thresholds,
+        set_notan_thresholds
+    );
+
+    impl_accessor!(posterize_levels: u32, posterize_levels, set_posterize_levels);
+
+    impl_accessor!(
+        posterize_lightness: bool,
+        is_posterize_lightness,
+        set_posterize_lightness
+    );
+
+    impl_accessor!(squint_radius: u32, squint_radius, set_squint_radius);
+
+    impl_accessor!(squint_blur: Blur, squint_blur, set_squint_blur);
+
+    impl_accessor!(squint_analysis: bool, is_squint_analysis, set_squint_analysis);
+
     impl_accessor!(
         color_cloud_mode: ColorCloudMode,
         color_cloud_mode,
         set_color_cloud_mode
     );
 
-    impl_accessor!(rotation: Quat, rotation, set_rotation);
+    impl_accessor!(cloud_bits: u32, cloud_bits, set_cloud_bits);
+
+    impl_accessor!(cloud_guides: bool, is_cloud_guides, set_cloud_guides);
+
+    impl_accessor!(cloud_gamut: Option<Gamut>, cloud_gamut, set_cloud_gamut);
+
+    impl_accessor!(point_size: PointSize, point_size, set_point_size);
+
+    impl_accessor!(point_min_size: f32, point_min_size, set_point_min_size);
+
+    impl_accessor!(point_max_size: f32, point_max_size, set_point_max_size);
+
+    impl_accessor!(point_scale: f32, point_scale, set_point_scale);
+
+    impl_accessor!(point_opacity: bool, is_point_opacity, set_point_opacity);
+
+    impl_accessor!(clip: CloudClip, clip, set_clip);
+
+    impl_accessor!(clip_lightness: (f32, f32), clip_lightness, set_clip_lightness);
+
+    impl_accessor!(clip_hue: (f32, f32), clip_hue, set_clip_hue);
+
+    impl_accessor!(clip_normal: Vec3, clip_normal, set_clip_normal);
+
+    impl_accessor!(clip_depth: f32, clip_depth, set_clip_depth);
+
+    impl_accessor!(clip_slice: f32, clip_slice, set_clip_slice);
+
+    impl_accessor!(
+        chromaticity_mode: ChromaticityMode,
+        chromaticity_mode,
+        set_chromaticity_mode
+    );
+
+    impl_accessor!(harmony_mode: HarmonyMode, harmony_mode, set_harmony_mode);
+
+    impl_accessor!(naming_mode: NamingMode, naming_mode, set_naming_mode);
+
+    impl_accessor!(
+        custom_dictionary: Option<Arc<Dictionary>>,
+        custom_dictionary,
+        set_custom_dictionary
+    );
+
+    impl_accessor!(target: Option<[u8; 3]>, target, set_target);
+
+    impl_accessor!(delta_e: DeltaE, delta_e, set_delta_e);
+
+    impl_accessor!(palette: Option<Arc<Dictionary>>, palette, set_palette);
+
+    impl_accessor!(palette_tolerance: f32, palette_tolerance, set_palette_tolerance);
+
+    impl_accessor!(camera: Camera, camera, set_camera);
+
+    impl_accessor!(selection: Option<Selection>, selection, set_selection);
+
+    impl_accessor!(selection_grey: bool, is_selection_grey, set_selection_grey);
+
+    impl_accessor!(view_export: Option<PathBuf>, view_export, set_view_export);
+
+    impl_accessor!(
+        stats_recording: Option<PathBuf>,
+        stats_recording,
+        set_stats_recording
+    );
+
+    impl_accessor!(stats_chart: bool, is_stats_chart, set_stats_chart);
+
+    impl_accessor!(stats_window: f32, stats_window, set_stats_window);
+
+    impl_accessor!(capture_rect: Option<RECT>, capture_rect, set_capture_rect);
+
+    impl_accessor!(frozen: bool, is_frozen, set_frozen);
+
+    impl_accessor!(frame_request: bool, is_frame_request, set_frame_request);
+
+    impl_accessor!(stats_request: bool, is_stats_request, set_stats_request);
+
+    impl_accessor!(video: Option<PathBuf>, video, set_video);
+
+    impl_accessor!(video_frame: usize, video_frame, set_video_frame);
+
+    impl_accessor!(ycbcr_matrix: YcbcrMatrix, ycbcr_matrix, set_ycbcr_matrix);
+
+    impl_accessor!(video_len: usize, video_len, set_video_len);
+
+    impl_accessor!(barcode_color: BarcodeColor, barcode_color, set_barcode_color);
+
+    impl_accessor!(
+        barcode_export: Option<PathBuf>,
+        barcode_export,
+        set_barcode_export
+    );
+
+    impl_accessor!(compare: Option<Arc<Image>>, compare, set_compare);
+
+    impl_accessor!(compare_snapshot: bool, is_compare_snapshot, set_compare_snapshot);
+
+    impl_accessor!(harmony: Option<Harmony>, harmony, set_harmony);
+
+    impl_accessor!(probe: Option<Probe>, probe, set_probe);
+
+    impl_accessor!(naming: Option<Arc<Naming>>, naming, set_naming);
+
+    impl_accessor!(coverage: Option<Arc<Coverage>>, coverage, set_coverage);
+
+    impl_accessor!(comparison: Option<Comparison>, comparison, set_comparison);
+
+    impl_accessor!(frame: Option<Arc<Image>>, frame, set_frame);
+
+    impl_accessor!(stats: Option<Stats>, stats, set_stats);
 
-    pub fn move_camera(&mut self, dx: f32, dy: f32) {
+    pub fn update_camera(&self, f: impl FnOnce(&mut Camera)) {
         if let Ok(mut state) = self.0.write() {
-            state.move_camera(dx, dy);
+            f(&mut state.camera);
         }
     }
 
@@ -0,0 +1,114 @@
+// frames of uncompressed video files, analyzed in place of the screen.
+// Nothing here touches Direct3D or Win32.
+
+mod raw;
+mod y4m;
+
+use crate::color::ycbcr::YcbcrMatrix;
+use crate::render::Image;
+use std::fs::File;
+use std::io::{BufReader, Read, Result};
+use std::path::Path;
+
+pub use self::raw::Raw;
+pub use self::y4m::Y4m;
+
+pub enum Video {
+    Y4m(Y4m),
+    Raw(Raw),
+}
+
+impl Video {
+    // a YUV4MPEG2 stream by its signature, otherwise a raw RGB dump sized by
+    // `size` or by a WxH in the file name such as clip_1920x1080.rgb
+    pub fn open<P: AsRef<Path>>(path: P, size: Option<(u32, u32)>) -> Result<Self> {
+        let path = path.as_ref();
+
+        let mut signature = [0; 9];
+        let is_y4m = BufReader::new(File::open(path)?)
+            .read_exact(&mut signature)
+            .is_ok()
+            && &signature == b"YUV4MPEG2";
+
+        if is_y4m {
+            return Ok(Self::Y4m(Y4m::open(path)?));
+        }
+
+        let size = size.or_else(|| size_in_name(path)).ok_or_else(|| {
+            invalid("the size of a raw RGB file is unknown, name it like clip_1920x1080.rgb")
+        })?;
+        Ok(Self::Raw(Raw::open(path, size.0, size.1)?))
+    }
+
+    pub fn width(&self) -> u32 {
+        match self {
+            Self::Y4m(y4m) => y4m.width(),
+            Self::Raw(raw) => raw.width(),
+        }
+    }
+
+    pub fn height(&self) -> u32 {
+        match self {
+            Self::Y4m(y4m) => y4m.height(),
+            Self::Raw(raw) => raw.height(),
+        }
+    }
+
+    // number of frames
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Y4m(y4m) => y4m.len(),
+            Self::Raw(raw) => raw.len(),
+        }
+    }
+
+    // frames per second when the file tells
+    pub fn frame_rate(&self) -> Option<f32> {
+        match self {
+            Self::Y4m(y4m) => y4m.frame_rate(),
+            Self::Raw(_) => None,
+        }
+    }
+
+    // `matrix` only applies to Y'CbCr frames
+    pub fn read(&mut self, index: usize, matrix: YcbcrMatrix) -> Result<Image> {
+        match self {
+            Self::Y4m(y4m) => y4m.read(index, matrix),
+            Self::Raw(raw) => raw.read(index),
+        }
+    }
+}
+
+// a still image of a PPM by its extension, otherwise a frame of a video
+pub fn load_frame<P: AsRef<Path>>(
+    path: P,
+    size: Option<(u32, u32)>,
+    index: usize,
+    matrix: YcbcrMatrix,
+) -> Result<Image> {
+    let path = path.as_ref();
+    let is_ppm = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("ppm"));
+
+    if is_ppm {
+        Image::load_ppm(path)
+    } else {
+        Video::open(path, size)?.read(index, matrix)
+    }
+}
+
+fn size_in_name(path: &Path) -> Option<(u32, u32)> {
+    let stem = path.file_stem()?.to_str()?;
+
+    stem.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter_map(|word| {
+            let (w, h) = word.split_once('x')?;
+            Some((w.parse().ok()?, h.parse().ok()?))
+        })
+        .rfind(|&(w, h)| w > 0 && h > 0)
+}
+
+fn invalid(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string())
+}
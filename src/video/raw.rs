@@ -0,0 +1,63 @@
+use super::invalid;
+use crate::render::Image;
+use std::fs::File;
+use std::io::{Read, Result, Seek, SeekFrom};
+use std::path::Path;
+
+// headerless frames of 8 bit RGB, one after another
+pub struct Raw {
+    file: File,
+    width: u32,
+    height: u32,
+    len: usize,
+}
+
+impl Raw {
+    pub fn open<P: AsRef<Path>>(path: P, width: u32, height: u32) -> Result<Self> {
+        let file = File::open(path)?;
+
+        let frame_size = 3 * width as u64 * height as u64;
+        // a partial frame at the end is ignored
+        let len = (file.metadata()?.len() / frame_size) as usize;
+        if len == 0 {
+            return Err(invalid("the raw RGB file is smaller than a frame"));
+        }
+
+        Ok(Self {
+            file,
+            width,
+            height,
+            len,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn read(&mut self, index: usize) -> Result<Image> {
+        if index >= self.len {
+            return Err(invalid("no such frame"));
+        }
+
+        let frame_size = 3 * self.width as usize * self.height as usize;
+        let mut data = vec![0; frame_size];
+        self.file
+            .seek(SeekFrom::Start(index as u64 * frame_size as u64))?;
+        self.file.read_exact(&mut data)?;
+
+        Ok(Image {
+            width: self.width,
+            height: self.height,
+            pixels: data.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+        })
+    }
+}
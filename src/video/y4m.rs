@@ -0,0 +1,457 @@
+use super::invalid;
+use crate::color::to_rgb8;
+use crate::color::ycbcr::{YcbcrMatrix, YcbcrRange};
+use crate::render::Image;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Result, Seek, SeekFrom};
+use std::path::Path;
+
+// longest header or FRAME line read
+const MAX_LINE: u64 = 4096;
+
+// how the chroma planes are subsampled and where their samples sit
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Subsampling {
+    // log2 of the luma samples per chroma sample
+    shift_x: u32,
+    shift_y: u32,
+    // on the left luma sample of each pair rather than between the two
+    cosited_x: bool,
+}
+
+impl Subsampling {
+    const C420: Self = Self::new(1, 1, false);
+    const C420_MPEG2: Self = Self::new(1, 1, true);
+    const C422: Self = Self::new(1, 0, true);
+    const C444: Self = Self::new(0, 0, false);
+
+    const fn new(shift_x: u32, shift_y: u32, cosited_x: bool) -> Self {
+        Self {
+            shift_x,
+            shift_y,
+            cosited_x,
+        }
+    }
+
+    fn chroma_size(&self, width: u32, height: u32) -> (u32, u32) {
+        (
+            width.div_ceil(1 << self.shift_x),
+            height.div_ceil(1 << self.shift_y),
+        )
+    }
+}
+
+// the C tag of the header, chroma planes of None for mono, and bits per sample
+fn parse_colorspace(tag: &str) -> Option<(Option<Subsampling>, u32)> {
+    Some(match tag {
+        // 420paldv sites Cb and Cr on alternate lines, taken as centered
+        "420jpeg" | "420" | "420paldv" => (Some(Subsampling::C420), 8),
+        "420mpeg2" => (Some(Subsampling::C420_MPEG2), 8),
+        "422" => (Some(Subsampling::C422), 8),
+        "444" => (Some(Subsampling::C444), 8),
+        "mono" => (None, 8),
+        "420p10" => (Some(Subsampling::C420), 10),
+        "422p10" => (Some(Subsampling::C422), 10),
+        "444p10" => (Some(Subsampling::C444), 10),
+        _ => return None,
+    })
+}
+
+// YUV4MPEG2 with 8 or 10 bit samples
+pub struct Y4m<R = File> {
+    reader: BufReader<R>,
+    width: u32,
+    height: u32,
+    frame_rate: Option<f32>,
+    subsampling: Option<Subsampling>,
+    bits: u32,
+    range: YcbcrRange,
+    // bytes of the planes of a frame
+    frame_size: usize,
+    // where the planes of each frame start
+    offsets: Vec<u64>,
+}
+
+impl Y4m {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(File::open(path)?)
+    }
+}
+
+impl<R: Read + Seek> Y4m<R> {
+    pub fn new(reader: R) -> Result<Self> {
+        let mut reader = BufReader::new(reader);
+
+        let header = read_line(&mut reader)?.ok_or_else(|| invalid("empty Y4M file"))?;
+        let mut fields = header.split_ascii_whitespace();
+        if fields.next() != Some("YUV4MPEG2") {
+            return Err(invalid("not a YUV4MPEG2 file"));
+        }
+
+        let (mut width, mut height) = (0, 0);
+        let mut frame_rate = None;
+        let (mut subsampling, mut bits) = (Some(Subsampling::C420), 8);
+        let mut range = YcbcrRange::Limited;
+
+        for field in fields {
+            let (tag, value) = field.split_at(1);
+            match tag {
+                "W" => width = value.parse().map_err(|_| invalid("bad Y4M width"))?,
+                "H" => height = value.parse().map_err(|_| invalid("bad Y4M height"))?,
+                "F" => {
+                    frame_rate = value
+                        .split_once(':')
+                        .and_then(|(n, d)| Some((n.parse::<f32>().ok()?, d.parse::<f32>().ok()?)))
+                        .filter(|&(_, d)| d > 0.0)
+                        .map(|(n, d)| n / d);
+                }
+                "C" => {
+                    (subsampling, bits) = parse_colorspace(value)
+                        .ok_or_else(|| invalid(&format!("unsupported Y4M colorspace {}", value)))?;
+                }
+                "X" if value.eq_ignore_ascii_case("COLORRANGE=FULL") => range = YcbcrRange::Full,
+                // interlacing, aspect ratio and other extensions
+                _ => (),
+            }
+        }
+
+        if width == 0 || height == 0 {
+            return Err(invalid("the Y4M header has no size"));
+        }
+        let frame_size =
+            frame_size(width, height, subsampling, bits).ok_or_else(|| invalid("Y4M too large"))?;
+
+        let mut y4m = Self {
+            reader,
+            width,
+            height,
+            frame_rate,
+            subsampling,
+            bits,
+            range,
+            frame_size,
+            offsets: vec![],
+        };
+        y4m.index()?;
+
+        if y4m.offsets.is_empty() {
+            return Err(invalid("the Y4M file has no frames"));
+        }
+
+        Ok(y4m)
+    }
+
+    // finds every frame up front, so seeking is a lookup
+    fn index(&mut self) -> Result<()> {
+        let frame_size = self.frame_size as u64;
+        let start = self.reader.stream_position()?;
+        let len = self.reader.seek(SeekFrom::End(0))?;
+        self.reader.seek(SeekFrom::Start(start))?;
+
+        while let Some(line) = read_line(&mut self.reader)? {
+            if !line.starts_with("FRAME") {
+                return Err(invalid("bad Y4M frame header"));
+            }
+
+            let offset = self.reader.stream_position()?;
+            // a truncated last frame is ignored
+            if len - offset < frame_size {
+                break;
+            }
+
+            self.offsets.push(offset);
+            self.reader.seek(SeekFrom::Start(offset + frame_size))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn frame_rate(&self) -> Option<f32> {
+        self.frame_rate
+    }
+
+    pub fn read(&mut self, index: usize, matrix: YcbcrMatrix) -> Result<Image> {
+        let offset = *self
+            .offsets
+            .get(index)
+            .ok_or_else(|| invalid("no such frame"))?;
+
+        let mut data = vec![0; self.frame_size];
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.reader.read_exact(&mut data)?;
+
+        Ok(self.convert(&data, matrix))
+    }
+
+    fn convert(&self, data: &[u8], matrix: YcbcrMatrix) -> Image {
+        let samples = to_samples(data, self.bits);
+        let (width, height) = (self.width, self.height);
+        let (luma, chroma) = samples.split_at(width as usize * height as usize);
+
+        let (cb, cr) = match self.subsampling {
+            Some(subsampling) => {
+                let (chroma_width, chroma_height) = subsampling.chroma_size(width, height);
+                let (cb, cr) = chroma.split_at(chroma_width as usize * chroma_height as usize);
+                (
+                    upsample(cb, chroma_width, chroma_height, width, height, subsampling),
+                    upsample(cr, chroma_width, chroma_height, width, height, subsampling),
+                )
+            }
+            None => {
+                let neutral = vec![(1 << (self.bits - 1)) as f32; luma.len()];
+                (neutral.clone(), neutral)
+            }
+        };
+
+        let pixels = luma
+            .iter()
+            .zip(cb.iter().zip(&cr))
+            .map(|(&y, (&cb, &cr))| {
+                let (y, cb, cr) = self.range.normalize(self.bits, y, cb, cr);
+                to_rgb8(matrix.to_rgb(y, cb, cr))
+            })
+            .collect();
+
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+// None when the size does not fit in memory
+fn frame_size(
+    width: u32,
+    height: u32,
+    subsampling: Option<Subsampling>,
+    bits: u32,
+) -> Option<usize> {
+    let luma = (width as usize).checked_mul(height as usize)?;
+    let chroma = match subsampling {
+        Some(subsampling) => {
+            let (width, height) = subsampling.chroma_size(width, height);
+            (width as usize)
+                .checked_mul(height as usize)?
+                .checked_mul(2)?
+        }
+        None => 0,
+    };
+    let bytes = if bits > 8 { 2 } else { 1 };
+
+    luma.checked_add(chroma)?.checked_mul(bytes)
+}
+
+// None at the end of the file
+fn read_line<R: Read>(reader: &mut BufReader<R>) -> Result<Option<String>> {
+    let mut line = vec![];
+    reader
+        .by_ref()
+        .take(MAX_LINE)
+        .read_until(b'\n', &mut line)?;
+
+    match line.pop() {
+        Some(b'\n') => Ok(Some(String::from_utf8_lossy(&line).into_owned())),
+        Some(_) => Err(invalid("unterminated Y4M header")),
+        None => Ok(None),
+    }
+}
+
+// 8 bit samples or 16 bit little endian words
+fn to_samples(data: &[u8], bits: u32) -> Vec<f32> {
+    if bits > 8 {
+        data.chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]) as f32)
+            .collect()
+    } else {
+        data.iter().map(|&c| c as f32).collect()
+    }
+}
+
+// bilinear interpolation of a chroma plane to the luma size, following the siting
+fn upsample(
+    plane: &[f32],
+    width: u32,
+    height: u32,
+    target_width: u32,
+    target_height: u32,
+    subsampling: Subsampling,
+) -> Vec<f32> {
+    // position of a luma sample in chroma samples, per axis
+    let source = |x: u32, shift: u32, cosited: bool, len: u32| -> (usize, usize, f32) {
+        let scale = (1 << shift) as f32;
+        let position = if cosited {
+            x as f32 / scale
+        } else {
+            (x as f32 + 0.5) / scale - 0.5
+        };
+
+        let position = position.clamp(0.0, (len - 1) as f32);
+        let i = position.floor() as u32;
+        (
+            i as usize,
+            (i + 1).min(len - 1) as usize,
+            position - i as f32,
+        )
+    };
+
+    let columns = (0..target_width)
+        .map(|x| source(x, subsampling.shift_x, subsampling.cosited_x, width))
+        .collect::<Vec<_>>();
+
+    let mut output = Vec::with_capacity(target_width as usize * target_height as usize);
+    for y in 0..target_height {
+        let (y0, y1, ty) = source(y, subsampling.shift_y, false, height);
+        let (row0, row1) = (y0 * width as usize, y1 * width as usize);
+
+        for &(x0, x1, tx) in &columns {
+            let top = plane[row0 + x0] + tx * (plane[row0 + x1] - plane[row0 + x0]);
+            let bottom = plane[row1 + x0] + tx * (plane[row1 + x1] - plane[row1 + x0]);
+            output.push(top + ty * (bottom - top));
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    // frames of one color, Y 100, Cb 128 and Cr 200
+    fn stream(header: &str, luma: usize, chroma: usize, frames: usize) -> Cursor<Vec<u8>> {
+        let mut data = format!("YUV4MPEG2 {}\n", header).into_bytes();
+        for i in 0..frames {
+            // the parameters of a frame are skipped
+            data.extend(if i == 0 { "FRAME Ip\n" } else { "FRAME\n" }.bytes());
+            data.extend([100].repeat(luma));
+            data.extend([128].repeat(chroma));
+            data.extend([200].repeat(chroma));
+        }
+        Cursor::new(data)
+    }
+
+    #[test]
+    fn reads_the_header_and_counts_the_frames() {
+        let y4m = Y4m::new(stream("W4 H2 F30000:1001 Ip A1:1 C420jpeg", 8, 2, 3)).unwrap();
+        assert_eq!((y4m.width(), y4m.height()), (4, 2));
+        assert_eq!(y4m.len(), 3);
+        assert!((y4m.frame_rate().unwrap() - 29.97).abs() < 1e-2);
+    }
+
+    #[test]
+    fn converts_each_subsampling() {
+        for (colorspace, chroma) in [("420jpeg", 2), ("420mpeg2", 2), ("444", 8)] {
+            let header = format!("W4 H2 F25:1 C{}", colorspace);
+            let mut y4m = Y4m::new(stream(&header, 8, chroma, 2)).unwrap();
+            assert_eq!(y4m.len(), 2);
+
+            let image = y4m.read(1, YcbcrMatrix::Bt709).unwrap();
+            assert_eq!((image.width, image.height), (4, 2));
+            assert!(
+                image.pixels.iter().all(|&rgb| rgb == [0xe3, 0x3b, 0x62]),
+                "{}: {:?}",
+                colorspace,
+                image.pixels
+            );
+        }
+    }
+
+    #[test]
+    fn ignores_a_truncated_last_frame() {
+        let mut data = stream("W4 H2 C444", 8, 8, 2).into_inner();
+        data.truncate(data.len() - 1);
+        assert_eq!(Y4m::new(Cursor::new(data)).unwrap().len(), 1);
+    }
+
+    // rows of the upsampled plane
+    fn rows(plane: Vec<f32>, width: usize) -> Vec<Vec<f32>> {
+        plane.chunks(width).map(<[f32]>::to_vec).collect()
+    }
+
+    #[test]
+    fn upsamples_centered_420() {
+        // chroma between the luma pairs, a quarter of the way to each neighbour
+        let horizontal = upsample(&[0.0, 100.0, 0.0, 100.0], 2, 2, 4, 4, Subsampling::C420);
+        assert_eq!(rows(horizontal, 4), vec![vec![0.0, 25.0, 75.0, 100.0]; 4]);
+
+        let vertical = upsample(&[0.0, 0.0, 100.0, 100.0], 2, 2, 4, 4, Subsampling::C420);
+        let columns = rows(vertical, 4)
+            .iter()
+            .map(|row| row[0])
+            .collect::<Vec<_>>();
+        assert_eq!(columns, [0.0, 25.0, 75.0, 100.0]);
+    }
+
+    #[test]
+    fn upsamples_cosited_420_and_422() {
+        // chroma on the left luma sample, halfway on the right one
+        let mpeg2 = upsample(
+            &[0.0, 100.0, 0.0, 100.0],
+            2,
+            2,
+            4,
+            4,
+            Subsampling::C420_MPEG2,
+        );
+        assert_eq!(rows(mpeg2, 4), vec![vec![0.0, 50.0, 100.0, 100.0]; 4]);
+
+        // full vertical resolution, each chroma row stays on its luma row
+        let c422 = upsample(&[0.0, 100.0, 40.0, 40.0], 2, 2, 4, 2, Subsampling::C422);
+        assert_eq!(
+            rows(c422, 4),
+            [[0.0, 50.0, 100.0, 100.0], [40.0, 40.0, 40.0, 40.0]]
+        );
+    }
+
+    #[test]
+    fn converts_a_10_bit_chroma_step() {
+        // 4x1 422p10, Y 400, Cb 512 and Cr stepping from neutral to 800
+        let mut data = b"YUV4MPEG2 W4 H1 C422p10\nFRAME\n".to_vec();
+        let samples = [400, 400, 400, 400, 512, 512, 512, 800];
+        data.extend(samples.iter().flat_map(|&s: &u16| s.to_le_bytes()));
+
+        let image = Y4m::new(Cursor::new(data))
+            .unwrap()
+            .read(0, YcbcrMatrix::Bt709)
+            .unwrap();
+
+        let expected = [512.0, 656.0, 800.0, 800.0].map(|cr| {
+            let (y, cb, cr) = YcbcrRange::Limited.normalize(10, 400.0, 512.0, cr);
+            to_rgb8(YcbcrMatrix::Bt709.to_rgb(y, cb, cr))
+        });
+        assert_eq!(image.pixels, expected);
+        assert_eq!(image.pixels[0], [98, 98, 98]);
+        assert_eq!(image.pixels[2], [0xe3, 0x3b, 0x62]);
+    }
+
+    #[test]
+    fn rejects_a_size_that_overflows() {
+        let header = b"YUV4MPEG2 W4294967295 H4294967295 C420jpeg\nFRAME\n";
+        let error = Y4m::new(Cursor::new(header.to_vec())).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+        let header = b"YUV4MPEG2 W4294967295 H1 C444p10\nFRAME\n";
+        assert!(Y4m::new(Cursor::new(header.to_vec())).is_err());
+    }
+
+    #[test]
+    fn rejects_a_bad_header() {
+        assert!(Y4m::new(Cursor::new(b"YUV4MPEG2 W4 C444\n".to_vec())).is_err());
+        assert!(Y4m::new(Cursor::new(b"YUV4MPEG2 W4 H2 C411\n".to_vec())).is_err());
+        assert!(Y4m::new(stream("W4 H2 C444", 8, 8, 0)).is_err());
+    }
+}